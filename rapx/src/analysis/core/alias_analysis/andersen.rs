@@ -0,0 +1,788 @@
+//! A flow-insensitive, context-insensitive and field-sensitive points-to analysis of the whole
+//! crate in the style of Andersen, which serves as a scalable alternative to MoP.
+//!
+//! The abstract objects are the places of the local variables, the objects allocated at the call
+//! sites of the functions without local bodies, and the objects pointed to by the arguments from
+//! the callers, each with its fields. Every assignment of MIR is an inclusion constraint between
+//! the points-to sets, and the constraints through dereferences are resolved as the points-to
+//! sets grow. The arguments and return values of the local functions are bound at the call sites.
+//!
+//! The result of a function is summarized as the aliases among its arguments and return value,
+//! as MoP does, where dereferences are transparent in the field paths. The objects coming from
+//! its callers are not counted, so that the summary only contains the aliases introduced by the
+//! function and its callees.
+
+use super::{
+    AAFact, AAPlace, AAResult, AAResultMap, AliasAnalysis,
+    default::{MopAAFact, MopAAResult, MopAAResultMap, MopConfig, types::is_not_drop},
+};
+use crate::analysis::Analysis;
+use rustc_data_structures::fx::{FxHashMap, FxHashSet, FxIndexSet};
+use rustc_hir::def_id::DefId;
+use rustc_middle::{
+    mir::{
+        BasicBlock, Body, Local, Location, Operand, Place, ProjectionElem, Rvalue, StatementKind,
+        TerminatorKind,
+    },
+    ty::{self, Instance, InstanceKind, Ty, TyCtxt, TypingEnv},
+};
+use rustc_span::sym;
+use std::collections::HashSet;
+
+type NodeId = usize;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Base {
+    /// A local variable of a function.
+    Local(DefId, Local),
+    /// A temporary introduced to lower nested dereferences.
+    Temp(DefId, usize),
+    /// The objects allocated by the call in the basic block.
+    Heap(DefId, BasicBlock),
+    /// The object pointed to by the argument place `local.path` of a function, passed from the
+    /// callers.
+    Param(DefId, Local, Vec<usize>),
+}
+
+impl Base {
+    /// The function where the object originates.
+    fn def_id(&self) -> DefId {
+        match self {
+            Base::Local(def_id, _)
+            | Base::Temp(def_id, _)
+            | Base::Heap(def_id, _)
+            | Base::Param(def_id, ..) => *def_id,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Loc {
+    base: Base,
+    fields: Vec<usize>,
+}
+
+/// A place of MIR, either an abstract object or the fields after dereferencing a pointer.
+#[derive(Clone)]
+enum Access {
+    Direct(NodeId),
+    Deref(NodeId, Vec<usize>),
+}
+
+impl Access {
+    fn field(self, graph: &mut PointsTo, field: usize) -> Access {
+        match self {
+            Access::Direct(id) => Access::Direct(graph.child(id, field)),
+            Access::Deref(ptr, mut fields) => {
+                fields.push(field);
+                Access::Deref(ptr, fields)
+            }
+        }
+    }
+}
+
+/// The constraint graph and its solution.
+#[derive(Default)]
+struct PointsTo {
+    field_depth: usize,
+    arg_counts: FxHashMap<DefId, usize>,
+    locs: Vec<Loc>,
+    ids: FxHashMap<Loc, NodeId>,
+    pts: Vec<FxIndexSet<NodeId>>,
+    /// The number of objects in `pts` that have been propagated.
+    done: Vec<usize>,
+    children: Vec<FxHashMap<usize, NodeId>>,
+    copy_to: Vec<Vec<NodeId>>,
+    copy_from: Vec<Vec<NodeId>>,
+    edges: FxHashSet<(NodeId, NodeId)>,
+    /// `dst ⊇ *ptr.fields`, indexed by `ptr`.
+    loads: Vec<Vec<(Vec<usize>, NodeId)>>,
+    /// `*ptr.fields ⊇ src`, indexed by `ptr`.
+    stores: Vec<Vec<(Vec<usize>, NodeId)>>,
+    /// `dst ∋ &(*ptr).fields`, indexed by `ptr`.
+    addrs: Vec<Vec<(Vec<usize>, NodeId)>>,
+    /// The fields created since their nodes were copied.
+    pending_fields: Vec<(NodeId, usize)>,
+    pending_copies: Vec<(NodeId, NodeId)>,
+    worklist: Vec<NodeId>,
+    temps: usize,
+}
+
+impl PointsTo {
+    /// Whether some nodes of the two sets are the same, point to each other, or point to a
+    /// common object.
+    fn is_aliased(&self, lhs: &FxHashSet<NodeId>, rhs: &FxHashSet<NodeId>) -> bool {
+        lhs.iter().any(|l| {
+            rhs.iter().any(|r| {
+                l == r
+                    || self.pts[*l].contains(r)
+                    || self.pts[*r].contains(l)
+                    || self.pts[*l]
+                        .iter()
+                        .any(|object| self.pts[*r].contains(object))
+            })
+        })
+    }
+
+    /// The argument place of a function that the location stands for, as seen by its callers.
+    fn arg_place(&self, loc: &Loc) -> Option<(DefId, Local, Vec<usize>)> {
+        match &loc.base {
+            Base::Local(def_id, local)
+                if local.as_usize() <= *self.arg_counts.get(def_id).unwrap_or(&0) =>
+            {
+                Some((*def_id, *local, loc.fields.clone()))
+            }
+            Base::Param(def_id, local, path) => {
+                Some((*def_id, *local, [path.as_slice(), &loc.fields].concat()))
+            }
+            _ => None,
+        }
+    }
+
+    fn node(&mut self, loc: Loc) -> NodeId {
+        if let Some(id) = self.ids.get(&loc) {
+            return *id;
+        }
+        let id = self.locs.len();
+        self.locs.push(loc.clone());
+        self.ids.insert(loc.clone(), id);
+        self.pts.push(FxIndexSet::default());
+        self.done.push(0);
+        self.children.push(FxHashMap::default());
+        self.copy_to.push(Vec::new());
+        self.copy_from.push(Vec::new());
+        self.loads.push(Vec::new());
+        self.stores.push(Vec::new());
+        self.addrs.push(Vec::new());
+        // The arguments point to the objects of the callers, which are unknown inside.
+        if let Some((def_id, local, path)) = self.arg_place(&loc)
+            && local.as_usize() > 0
+        {
+            let object = self.node(Loc {
+                base: Base::Param(def_id, local, path),
+                fields: Vec::new(),
+            });
+            self.add_pts(id, object);
+        }
+        id
+    }
+
+    fn local(&mut self, def_id: DefId, local: Local) -> NodeId {
+        self.node(Loc {
+            base: Base::Local(def_id, local),
+            fields: Vec::new(),
+        })
+    }
+
+    fn temp(&mut self, def_id: DefId) -> NodeId {
+        self.temps += 1;
+        self.node(Loc {
+            base: Base::Temp(def_id, self.temps),
+            fields: Vec::new(),
+        })
+    }
+
+    fn depth(&self, id: NodeId) -> usize {
+        match &self.locs[id].base {
+            Base::Param(_, _, path) => path.len() + self.locs[id].fields.len(),
+            _ => self.locs[id].fields.len(),
+        }
+    }
+
+    /// The node of the field, which is the node itself beyond the depth limit.
+    fn child(&mut self, id: NodeId, field: usize) -> NodeId {
+        if let Some(child) = self.children[id].get(&field) {
+            return *child;
+        }
+        if self.depth(id) >= self.field_depth {
+            return id;
+        }
+        let mut loc = self.locs[id].clone();
+        loc.fields.push(field);
+        let child = self.node(loc);
+        self.children[id].insert(field, child);
+        self.pending_fields.push((id, field));
+        child
+    }
+
+    /// The copies of aggregates are copies of their fields, including the new ones.
+    fn copy_field(&mut self, id: NodeId, field: usize) {
+        let child = self.children[id][&field];
+        for dst in self.copy_to[id].clone() {
+            let dst_child = self.child(dst, field);
+            self.pending_copies.push((dst_child, child));
+        }
+        for src in self.copy_from[id].clone() {
+            let src_child = self.child(src, field);
+            self.pending_copies.push((child, src_child));
+        }
+    }
+
+    fn child_path(&mut self, id: NodeId, fields: &[usize]) -> NodeId {
+        fields.iter().fold(id, |id, field| self.child(id, *field))
+    }
+
+    fn add_pts(&mut self, id: NodeId, object: NodeId) {
+        if self.pts[id].insert(object) {
+            self.worklist.push(id);
+        }
+    }
+
+    fn add_copy(&mut self, dst: NodeId, src: NodeId) {
+        if dst == src || !self.edges.insert((dst, src)) {
+            return;
+        }
+        self.copy_to[src].push(dst);
+        self.copy_from[dst].push(src);
+        for object in self.pts[src].clone() {
+            self.add_pts(dst, object);
+        }
+        for (field, src_child) in self.children[src].clone() {
+            let dst_child = self.child(dst, field);
+            self.pending_copies.push((dst_child, src_child));
+        }
+        for (field, dst_child) in self.children[dst].clone() {
+            let src_child = self.child(src, field);
+            self.pending_copies.push((dst_child, src_child));
+        }
+    }
+
+    fn propagate(&mut self, id: NodeId) {
+        let new: Vec<NodeId> = self.pts[id].iter().skip(self.done[id]).copied().collect();
+        self.done[id] = self.pts[id].len();
+        for object in new {
+            for dst in self.copy_to[id].clone() {
+                self.add_pts(dst, object);
+            }
+            for (fields, dst) in self.loads[id].clone() {
+                let src = self.child_path(object, &fields);
+                self.pending_copies.push((dst, src));
+            }
+            for (fields, src) in self.stores[id].clone() {
+                let dst = self.child_path(object, &fields);
+                self.pending_copies.push((dst, src));
+            }
+            for (fields, dst) in self.addrs[id].clone() {
+                let target = self.child_path(object, &fields);
+                self.add_pts(dst, target);
+            }
+        }
+    }
+
+    fn solve(&mut self) {
+        loop {
+            if let Some((id, field)) = self.pending_fields.pop() {
+                self.copy_field(id, field);
+            } else if let Some((dst, src)) = self.pending_copies.pop() {
+                self.add_copy(dst, src);
+            } else if let Some(id) = self.worklist.pop() {
+                self.propagate(id);
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn read(&mut self, def_id: DefId, access: Access) -> NodeId {
+        match access {
+            Access::Direct(id) => id,
+            Access::Deref(ptr, fields) => {
+                let temp = self.temp(def_id);
+                self.loads[ptr].push((fields, temp));
+                temp
+            }
+        }
+    }
+
+    fn address(&mut self, def_id: DefId, access: Access) -> NodeId {
+        let temp = self.temp(def_id);
+        match access {
+            Access::Direct(id) => self.add_pts(temp, id),
+            Access::Deref(ptr, fields) => self.addrs[ptr].push((fields, temp)),
+        }
+        temp
+    }
+
+    fn write(&mut self, access: Access, src: NodeId) {
+        match access {
+            Access::Direct(id) => self.pending_copies.push((id, src)),
+            Access::Deref(ptr, fields) => self.stores[ptr].push((fields, src)),
+        }
+    }
+}
+
+/// Pointers and their wrappers whose fields are transparent, so that `Box<T>` points to `T`.
+fn is_transparent<'tcx>(tcx: TyCtxt<'tcx>, ty: Ty<'tcx>) -> bool {
+    match ty.kind() {
+        ty::Adt(adt_def, _) => {
+            adt_def.is_box()
+                || tcx.is_diagnostic_item(sym::NonNull, adt_def.did())
+                || (tcx.crate_name(adt_def.did().krate) == sym::core
+                    && tcx.item_name(adt_def.did()).as_str() == "Unique")
+        }
+        _ => false,
+    }
+}
+
+/// The type of the argument place, whose dereferences are transparent.
+fn arg_place_ty<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    body: &Body<'tcx>,
+    local: usize,
+    path: &[usize],
+) -> Option<Ty<'tcx>> {
+    let mut ty = body.local_decls.get(Local::from_usize(local))?.ty;
+    for field in path {
+        loop {
+            ty = match ty.kind() {
+                ty::Ref(_, pointee, _) | ty::RawPtr(pointee, _) => *pointee,
+                ty::Adt(adt_def, args) if adt_def.is_box() => args.type_at(0),
+                _ => break,
+            };
+        }
+        ty = match ty.kind() {
+            ty::Adt(adt_def, args) if !adt_def.variants().is_empty() => adt_def
+                .variants()
+                .iter()
+                .next()?
+                .fields
+                .iter()
+                .nth(*field)?
+                .ty(tcx, args),
+            ty::Tuple(tys) => *tys.get(*field)?,
+            ty::Closure(_, args) => *args.as_closure().upvar_tys().get(*field)?,
+            _ => return None,
+        };
+    }
+    Some(ty)
+}
+
+pub struct AndersenAnalyzer<'tcx> {
+    pub tcx: TyCtxt<'tcx>,
+    pub config: MopConfig,
+    pub fn_map: FxHashMap<DefId, MopAAResult>,
+    /// The points-to sets solved by the last run, for the point-wise queries.
+    graph: PointsTo,
+}
+
+impl<'tcx> Analysis for AndersenAnalyzer<'tcx> {
+    fn name(&self) -> &'static str {
+        "Alias Analysis (Andersen)"
+    }
+
+    fn run(&mut self) {
+        rap_debug!("Start alias analysis via Andersen.");
+        let tcx = self.tcx;
+        let def_ids: Vec<DefId> = tcx
+            .mir_keys(())
+            .iter()
+            .filter(|local_def_id| tcx.hir_body_const_context(**local_def_id).is_none())
+            .map(|local_def_id| local_def_id.to_def_id())
+            .filter(|def_id| tcx.is_mir_available(*def_id))
+            .collect();
+        let mut graph = PointsTo {
+            field_depth: self.config.field_depth,
+            ..Default::default()
+        };
+        for def_id in &def_ids {
+            graph
+                .arg_counts
+                .insert(*def_id, tcx.optimized_mir(*def_id).arg_count);
+        }
+        let mut callees: FxHashMap<DefId, FxHashSet<DefId>> = FxHashMap::default();
+        for def_id in &def_ids {
+            let calls = self.add_constraints(&mut graph, *def_id);
+            callees.insert(*def_id, calls);
+        }
+        graph.solve();
+        rap_debug!("Solved the points-to sets of {} nodes.", graph.locs.len());
+
+        // The argument places of each function.
+        let mut places: FxHashMap<DefId, Vec<(usize, Vec<usize>, NodeId)>> = FxHashMap::default();
+        for (id, loc) in graph.locs.iter().enumerate() {
+            if let Some((def_id, local, path)) = graph.arg_place(loc) {
+                places
+                    .entry(def_id)
+                    .or_default()
+                    .push((local.as_usize(), path, id));
+            }
+        }
+        for def_id in def_ids {
+            let reachable = reachable_callees(&callees, def_id);
+            let places = places.remove(&def_id).unwrap_or_default();
+            let result = self.summarize(&graph, def_id, &reachable, &places);
+            self.fn_map.insert(def_id, result);
+        }
+        self.graph = graph;
+    }
+
+    fn reset(&mut self) {
+        self.fn_map.clear();
+        self.graph = PointsTo::default();
+    }
+}
+
+/// The analysis is flow-insensitive, so the point-wise queries ignore the location.
+impl<'tcx> AliasAnalysis<'tcx> for AndersenAnalyzer<'tcx> {
+    fn get_fn_alias(&self, def_id: DefId) -> Option<AAResult> {
+        self.fn_map.get(&def_id).cloned().map(Into::into)
+    }
+
+    fn get_all_fn_alias(&self) -> AAResultMap {
+        self.fn_map
+            .iter()
+            .map(|(k, v)| (*k, AAResult::from(v.clone())))
+            .collect()
+    }
+
+    fn may_alias(
+        &self,
+        def_id: DefId,
+        _location: Location,
+        place_a: Place<'tcx>,
+        place_b: Place<'tcx>,
+    ) -> bool {
+        let lhs = self.lookup(def_id, place_a);
+        let rhs = self.lookup(def_id, place_b);
+        self.graph.is_aliased(&lhs, &rhs)
+    }
+
+    fn alias_set(
+        &self,
+        def_id: DefId,
+        _location: Location,
+        place: Place<'tcx>,
+    ) -> HashSet<AAPlace> {
+        let ids = self.lookup(def_id, place);
+        let own = self.aa_place(def_id, place);
+        let mut result = HashSet::new();
+        for (id, loc) in self.graph.locs.iter().enumerate() {
+            let alias = match &loc.base {
+                Base::Local(base_def_id, local) if *base_def_id == def_id => {
+                    AAPlace::new(local.as_usize(), loc.fields.clone())
+                }
+                Base::Param(base_def_id, local, path) if *base_def_id == def_id => {
+                    AAPlace::new(local.as_usize(), [path.as_slice(), &loc.fields].concat())
+                }
+                _ => continue,
+            };
+            if alias != own && self.graph.is_aliased(&ids, &FxHashSet::from_iter([id])) {
+                result.insert(alias);
+            }
+        }
+        result
+    }
+}
+
+impl<'tcx> AndersenAnalyzer<'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>) -> Self {
+        Self::with_config(tcx, MopConfig::default())
+    }
+
+    /// Only the depth limit of fields in the config is used.
+    pub fn with_config(tcx: TyCtxt<'tcx>, config: MopConfig) -> Self {
+        Self {
+            tcx,
+            config,
+            fn_map: FxHashMap::default(),
+            graph: PointsTo::default(),
+        }
+    }
+
+    /// The nodes that the place of the function may stand for in the solved graph.
+    fn lookup(&self, def_id: DefId, place: Place<'tcx>) -> FxHashSet<NodeId> {
+        let graph = &self.graph;
+        let root = Loc {
+            base: Base::Local(def_id, place.local),
+            fields: Vec::new(),
+        };
+        let Some(root) = graph.ids.get(&root) else {
+            return FxHashSet::default();
+        };
+        let body = self.tcx.optimized_mir(def_id);
+        let mut ids = FxHashSet::from_iter([*root]);
+        for (base, elem) in place.iter_projections() {
+            ids = match elem {
+                ProjectionElem::Field(field, _) => {
+                    if is_transparent(self.tcx, base.ty(body, self.tcx).ty) {
+                        continue;
+                    }
+                    ids.iter()
+                        .filter_map(|id| graph.children[*id].get(&field.as_usize()).copied())
+                        .collect()
+                }
+                ProjectionElem::Deref => ids
+                    .iter()
+                    .flat_map(|id| graph.pts[*id].iter().copied())
+                    .collect(),
+                _ => ids,
+            };
+        }
+        ids
+    }
+
+    /// The place with the dereferences and transparent fields omitted.
+    fn aa_place(&self, def_id: DefId, place: Place<'tcx>) -> AAPlace {
+        let body = self.tcx.optimized_mir(def_id);
+        let fields = place
+            .iter_projections()
+            .filter_map(|(base, elem)| match elem {
+                ProjectionElem::Field(field, _)
+                    if !is_transparent(self.tcx, base.ty(body, self.tcx).ty) =>
+                {
+                    Some(field.as_usize())
+                }
+                _ => None,
+            })
+            .collect();
+        AAPlace::new(place.local.as_usize(), fields)
+    }
+
+    /// The summaries with the drop flags of the places, which can be used by SafeDrop in place
+    /// of those of MoP.
+    pub fn get_all_fn_alias_raw(&self) -> MopAAResultMap {
+        self.fn_map.clone()
+    }
+
+    fn lower_place(
+        &self,
+        graph: &mut PointsTo,
+        def_id: DefId,
+        body: &Body<'tcx>,
+        place: Place<'tcx>,
+    ) -> Access {
+        let mut access = Access::Direct(graph.local(def_id, place.local));
+        for (base, elem) in place.iter_projections() {
+            match elem {
+                ProjectionElem::Field(field, _) => {
+                    if is_transparent(self.tcx, base.ty(body, self.tcx).ty) {
+                        continue;
+                    }
+                    access = access.field(graph, field.as_usize());
+                }
+                ProjectionElem::Deref => {
+                    let ptr = graph.read(def_id, access);
+                    access = Access::Deref(ptr, Vec::new());
+                }
+                // The elements of arrays and the variants of enums are not distinguished.
+                _ => {}
+            }
+        }
+        access
+    }
+
+    fn read_operand(
+        &self,
+        graph: &mut PointsTo,
+        def_id: DefId,
+        body: &Body<'tcx>,
+        operand: &Operand<'tcx>,
+    ) -> Option<NodeId> {
+        match operand {
+            Operand::Copy(place) | Operand::Move(place) => {
+                let access = self.lower_place(graph, def_id, body, *place);
+                Some(graph.read(def_id, access))
+            }
+            _ => None,
+        }
+    }
+
+    /// Add the constraints of the function, and return the local functions it calls.
+    fn add_constraints(&self, graph: &mut PointsTo, def_id: DefId) -> FxHashSet<DefId> {
+        let tcx = self.tcx;
+        let body = tcx.optimized_mir(def_id);
+        let mut calls = FxHashSet::default();
+        for (bb, data) in body.basic_blocks.iter_enumerated() {
+            for statement in &data.statements {
+                let StatementKind::Assign(box (place, rvalue)) = &statement.kind else {
+                    continue;
+                };
+                let lhs = self.lower_place(graph, def_id, body, *place);
+                match rvalue {
+                    Rvalue::Use(operand)
+                    | Rvalue::Repeat(operand, _)
+                    | Rvalue::Cast(_, operand, _)
+                    | Rvalue::ShallowInitBox(operand, _)
+                    | Rvalue::WrapUnsafeBinder(operand, _) => {
+                        if let Some(src) = self.read_operand(graph, def_id, body, operand) {
+                            graph.write(lhs, src);
+                        }
+                    }
+                    Rvalue::CopyForDeref(rhs) => {
+                        let rhs = self.lower_place(graph, def_id, body, *rhs);
+                        let src = graph.read(def_id, rhs);
+                        graph.write(lhs, src);
+                    }
+                    Rvalue::Ref(_, _, rhs) | Rvalue::RawPtr(_, rhs) => {
+                        let rhs = self.lower_place(graph, def_id, body, *rhs);
+                        let src = graph.address(def_id, rhs);
+                        graph.write(lhs, src);
+                    }
+                    Rvalue::Aggregate(_, operands) => {
+                        let transparent = is_transparent(tcx, place.ty(body, tcx).ty);
+                        for (field, operand) in operands.iter_enumerated() {
+                            let Some(src) = self.read_operand(graph, def_id, body, operand) else {
+                                continue;
+                            };
+                            let dst = if transparent {
+                                lhs.clone()
+                            } else {
+                                lhs.clone().field(graph, field.as_usize())
+                            };
+                            graph.write(dst, src);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            let TerminatorKind::Call {
+                func,
+                args,
+                destination,
+                ..
+            } = &data.terminator().kind
+            else {
+                continue;
+            };
+            let dest = self.lower_place(graph, def_id, body, *destination);
+            let args: Vec<Option<NodeId>> = args
+                .iter()
+                .map(|arg| self.read_operand(graph, def_id, body, &arg.node))
+                .collect();
+            match self.resolve(def_id, func) {
+                Some(callee) if graph.arg_counts.contains_key(&callee) => {
+                    calls.insert(callee);
+                    let arg_count = graph.arg_counts[&callee];
+                    // The arguments of closures are tupled at the call sites.
+                    let spread = tcx.is_closure_like(callee) && args.len() == 2 && arg_count != 2;
+                    for (index, arg) in args.iter().enumerate() {
+                        let Some(arg) = *arg else {
+                            continue;
+                        };
+                        if spread && index == 1 {
+                            for field in 0..arg_count.saturating_sub(1) {
+                                let param = graph.local(callee, Local::from_usize(field + 2));
+                                let src = graph.child(arg, field);
+                                graph.pending_copies.push((param, src));
+                            }
+                        } else if index < arg_count {
+                            let param = graph.local(callee, Local::from_usize(index + 1));
+                            graph.pending_copies.push((param, arg));
+                        }
+                    }
+                    let ret = graph.local(callee, Local::from_usize(0));
+                    graph.write(dest, ret);
+                }
+                // The result of an unknown function is a new object holding the arguments. If
+                // it is a pointer, it may also be derived from the arguments, e.g., `as_ptr`.
+                _ => {
+                    let heap = graph.node(Loc {
+                        base: Base::Heap(def_id, bb),
+                        fields: Vec::new(),
+                    });
+                    let ptr = graph.temp(def_id);
+                    graph.add_pts(ptr, heap);
+                    let is_ptr = destination.ty(body, tcx).ty.is_any_ptr();
+                    for arg in args.into_iter().flatten() {
+                        graph.pending_copies.push((heap, arg));
+                        if is_ptr {
+                            graph.pending_copies.push((ptr, arg));
+                        }
+                    }
+                    graph.write(dest, ptr);
+                }
+            }
+        }
+        calls
+    }
+
+    /// The function called statically or resolved from the generic arguments.
+    fn resolve(&self, def_id: DefId, func: &Operand<'tcx>) -> Option<DefId> {
+        let (callee, args) = func.const_fn_def()?;
+        let typing_env = TypingEnv::post_analysis(self.tcx, def_id);
+        match Instance::try_resolve(self.tcx, typing_env, callee, args) {
+            Ok(Some(instance)) if matches!(instance.def, InstanceKind::Item(_)) => {
+                Some(instance.def_id())
+            }
+            _ => Some(callee),
+        }
+    }
+
+    /// Two argument places are aliases if one points to the other, or they point to a common
+    /// object of the function or its callees.
+    fn summarize(
+        &self,
+        graph: &PointsTo,
+        def_id: DefId,
+        reachable: &FxHashSet<DefId>,
+        places: &[(usize, Vec<usize>, NodeId)],
+    ) -> MopAAResult {
+        let tcx = self.tcx;
+        let body = tcx.optimized_mir(def_id);
+        let typing_env = TypingEnv::post_analysis(tcx, def_id);
+        let drop_flags = |local: usize, path: &[usize]| match arg_place_ty(tcx, body, local, path) {
+            Some(ty) => {
+                let need_drop = ty.needs_drop(tcx, typing_env);
+                (need_drop || !is_not_drop(tcx, ty), need_drop)
+            }
+            None => (true, false),
+        };
+        let counted = |object: &NodeId| {
+            let base = &graph.locs[*object].base;
+            base.def_id() == def_id
+                || (reachable.contains(&base.def_id()) && !matches!(base, Base::Param(..)))
+        };
+        let mut result = MopAAResult::new(body.arg_count);
+        for (i, (lhs_no, lhs_fields, lhs)) in places.iter().enumerate() {
+            for (rhs_no, rhs_fields, rhs) in &places[i + 1..] {
+                if lhs_no == rhs_no {
+                    continue;
+                }
+                let aliased = graph.pts[*lhs].contains(rhs)
+                    || graph.pts[*rhs].contains(lhs)
+                    || graph.pts[*lhs]
+                        .iter()
+                        .any(|object| counted(object) && graph.pts[*rhs].contains(object));
+                if !aliased {
+                    continue;
+                }
+                let (lhs_may_drop, lhs_need_drop) = drop_flags(*lhs_no, lhs_fields);
+                let (rhs_may_drop, rhs_need_drop) = drop_flags(*rhs_no, rhs_fields);
+                let mut fact = MopAAFact::new(
+                    *lhs_no,
+                    lhs_may_drop,
+                    lhs_need_drop,
+                    *rhs_no,
+                    rhs_may_drop,
+                    rhs_need_drop,
+                );
+                fact.fact = AAFact {
+                    lhs_no: *lhs_no,
+                    lhs_fields: lhs_fields.clone(),
+                    rhs_no: *rhs_no,
+                    rhs_fields: rhs_fields.clone(),
+                };
+                result.add_alias(fact);
+            }
+        }
+        result.sort_alias_index();
+        result
+    }
+}
+
+/// The local functions called by the function directly or indirectly.
+fn reachable_callees(
+    callees: &FxHashMap<DefId, FxHashSet<DefId>>,
+    def_id: DefId,
+) -> FxHashSet<DefId> {
+    let mut reachable = FxHashSet::default();
+    let mut stack = vec![def_id];
+    while let Some(caller) = stack.pop() {
+        for callee in callees.get(&caller).into_iter().flatten() {
+            if reachable.insert(*callee) {
+                stack.push(*callee);
+            }
+        }
+    }
+    reachable
+}
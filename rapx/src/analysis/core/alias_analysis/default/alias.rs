@@ -1,7 +1,13 @@
 use super::{
-    MopAAFact, MopAAResultMap, assign::*, block::Term, corner_case::*, graph::*, types::*, value::*,
+    MopAAFact, MopAAResult, MopAAResultMap, assign::*, block::Term, graph::*, types::*, value::*,
+};
+use crate::{
+    analysis::{
+        core::{alias_analysis::AAPlace, ownership_model},
+        graphs::scc::Scc,
+    },
+    utils::budget::Exhausted,
 };
-use crate::analysis::graphs::scc::Scc;
 use rustc_hir::def_id::DefId;
 use rustc_middle::{
     mir::{Operand, Place, ProjectionElem, TerminatorKind},
@@ -16,7 +22,12 @@ impl<'tcx> MopGraph<'tcx> {
             self.constants.insert(constant.local, constant.value);
         }
         let cur_block = self.blocks[bb_index].clone();
+        let mut probed = !self.is_probed(bb_index);
         for assign in cur_block.assignments {
+            if !probed && assign.index >= self.probe.as_ref().unwrap().location.statement_index {
+                self.record_probe();
+                probed = true;
+            }
             let mut lv_aliaset_idx = self.projection(false, assign.lv);
             let rv_aliaset_idx = self.projection(true, assign.rv);
             rap_debug!("{:?} = {:?}", lv_aliaset_idx, rv_aliaset_idx);
@@ -34,6 +45,43 @@ impl<'tcx> MopGraph<'tcx> {
                 self.merge_alias(lv_aliaset_idx, rv_aliaset_idx, 0);
             }
         }
+        if !probed {
+            self.record_probe();
+        }
+    }
+
+    /// Whether the program point of the probe is in the block.
+    fn is_probed(&self, bb_index: usize) -> bool {
+        self.probe
+            .as_ref()
+            .is_some_and(|probe| probe.location.block.as_usize() == bb_index)
+    }
+
+    /// Record the alias sets of the current path at the program point of the probe.
+    fn record_probe(&mut self) {
+        let state = (self.values.clone(), self.alias_set.clone());
+        if let Some(probe) = &mut self.probe {
+            probe.states.push(state);
+        }
+    }
+
+    /// The place of a value, where the fields are listed from the local down to the value.
+    pub fn value_place(&self, index: usize) -> AAPlace {
+        let mut fields = Vec::new();
+        let mut cur = index;
+        while let Some(father) = self
+            .values
+            .iter()
+            .find(|value| value.fields.get(&self.values[cur].field_id) == Some(&cur))
+        {
+            fields.push(self.values[cur].field_id);
+            cur = father.index;
+        }
+        fields.reverse();
+        AAPlace {
+            local: self.values[index].local,
+            fields,
+        }
     }
 
     /* Check the aliases introduced by the terminators (function call) of a scc block */
@@ -85,39 +133,41 @@ impl<'tcx> MopGraph<'tcx> {
                 }
                 if let &ty::FnDef(target_id, _) = constant.const_.ty().kind() {
                     if may_drop_flag > 0 {
-                        // This function does not introduce new aliases.
-                        if is_corner_case(target_id) {
+                        // The modeled functions only introduce the aliases in the model.
+                        if let Some(model) = ownership_model::lookup(self.tcx, target_id) {
+                            for assign in model.alias_facts() {
+                                self.merge(&assign, &merge_vec);
+                            }
                             return;
                         }
-                        if self.tcx.is_mir_available(target_id) {
-                            rap_debug!("target_id {:?}", target_id);
-                            if fn_map.contains_key(&target_id) {
-                                let assignments = fn_map.get(&target_id).unwrap();
-                                for assign in assignments.aliases().iter() {
-                                    if !assign.valuable() {
-                                        continue;
-                                    }
-                                    self.merge(assign, &merge_vec);
+                        // The summaries of dependencies are available without MIR.
+                        if let Some(assignments) = fn_map.get(&target_id) {
+                            for assign in assignments.aliases().iter() {
+                                if !assign.valuable() {
+                                    continue;
                                 }
-                            } else {
-                                /* Fixed-point iteration: this is not perfect */
-                                if recursion_set.contains(&target_id) {
-                                    return;
-                                }
-                                recursion_set.insert(target_id);
-                                let mut mop_graph = MopGraph::new(self.tcx, target_id);
-                                mop_graph.find_scc();
-                                mop_graph.check(0, fn_map, recursion_set);
-                                let ret_alias = mop_graph.ret_alias.clone();
-                                for assign in ret_alias.aliases().iter() {
-                                    if !assign.valuable() {
-                                        continue;
-                                    }
-                                    self.merge(assign, &merge_vec);
+                                self.merge(assign, &merge_vec);
+                            }
+                        } else if self.tcx.is_mir_available(target_id) {
+                            rap_debug!("target_id {:?}", target_id);
+                            /* Fixed-point iteration: this is not perfect */
+                            if recursion_set.contains(&target_id) {
+                                return;
+                            }
+                            recursion_set.insert(target_id);
+                            let mut mop_graph = MopGraph::new(self.tcx, target_id);
+                            mop_graph.config = self.config;
+                            mop_graph.find_scc();
+                            mop_graph.check(0, fn_map, recursion_set);
+                            let ret_alias = mop_graph.summary();
+                            for assign in ret_alias.aliases().iter() {
+                                if !assign.valuable() {
+                                    continue;
                                 }
-                                fn_map.insert(target_id, ret_alias);
-                                recursion_set.remove(&target_id);
+                                self.merge(assign, &merge_vec);
                             }
+                            fn_map.insert(target_id, ret_alias);
+                            recursion_set.remove(&target_id);
                         } else if self.values[lv].may_drop {
                             let mut right_set = Vec::new();
                             for rv in &merge_vec {
@@ -196,15 +246,7 @@ impl<'tcx> MopGraph<'tcx> {
             self.alias_set
         );
 
-        let max_field_depth = match std::env::var_os("MOP") {
-            Some(val) if val == "0" => 10,
-            Some(val) if val == "1" => 20,
-            Some(val) if val == "2" => 30,
-            Some(val) if val == "3" => 50,
-            _ => 15,
-        };
-
-        if depth > max_field_depth {
+        if depth > self.config.field_depth {
             return;
         }
 
@@ -270,6 +312,37 @@ impl<'tcx> MopGraph<'tcx> {
         self.merge_alias(lv, rv, 0);
     }
 
+    /// The alias summary of the function. If the budget of time or loop paths is exhausted, the
+    /// paths not visited may introduce more aliases, so every argument that may hold a pointer is
+    /// assumed to alias the return value and the other arguments. The visit limit is kept out, as
+    /// it is how MoP bounds the paths of every function.
+    pub fn summary(&self) -> MopAAResult {
+        let mut summary = self.ret_alias.clone();
+        if !matches!(
+            self.budget.exhausted(),
+            Some(Exhausted::Time(_) | Exhausted::SccPaths(_))
+        ) {
+            return summary;
+        }
+        let may_drop: Vec<&Value> = self.values[..=self.arg_size]
+            .iter()
+            .filter(|value| value.may_drop)
+            .collect();
+        for (i, lhs) in may_drop.iter().enumerate() {
+            for rhs in &may_drop[i + 1..] {
+                summary.add_alias(MopAAFact::new(
+                    lhs.local,
+                    lhs.may_drop,
+                    lhs.need_drop,
+                    rhs.local,
+                    rhs.may_drop,
+                    rhs.need_drop,
+                ));
+            }
+        }
+        summary
+    }
+
     //merge the result of current path to the final result.
     pub fn merge_results(&mut self, results_nodes: Vec<Value>) {
         for node in results_nodes.iter() {
@@ -16,6 +16,8 @@ pub struct Assignment<'tcx> {
     pub rv: Place<'tcx>,
     pub atype: AssignType,
     pub span: Span,
+    /// The index of the statement in its basic block.
+    pub index: usize,
 }
 
 impl<'tcx> Assignment<'tcx> {
@@ -24,12 +26,14 @@ impl<'tcx> Assignment<'tcx> {
         rv: Place<'tcx>,
         atype: AssignType,
         span: Span,
+        index: usize,
     ) -> Assignment<'tcx> {
         Assignment {
             lv,
             rv,
             atype,
             span,
+            index,
         }
     }
 }
@@ -1,13 +1,15 @@
-use super::{MopAAResult, assign::*, block::*, types::*, value::*};
+use super::{MopAAResult, MopConfig, assign::*, block::*, types::*, value::*};
 use crate::{
-    analysis::graphs::scc::{Scc, SccExit},
-    def_id::*,
-    utils::source::*,
+    analysis::{
+        core::{ownership_model, range_analysis::feasibility::PathFeasibility},
+        graphs::scc::{Scc, SccExit},
+    },
+    utils::{budget::FnBudget, source::*},
 };
 use rustc_data_structures::fx::{FxHashMap, FxHashSet};
 use rustc_middle::{
     mir::{
-        AggregateKind, BasicBlock, Const, Operand, Rvalue, StatementKind, TerminatorKind,
+        AggregateKind, BasicBlock, Const, Location, Operand, Rvalue, StatementKind, TerminatorKind,
         UnwindAction,
     },
     ty::{self, TyCtxt, TypingEnv},
@@ -35,10 +37,40 @@ pub struct MopGraph<'tcx> {
     pub discriminants: FxHashMap<usize, usize>,
     // a threhold to avoid path explosion.
     pub visit_times: usize,
+    pub config: MopConfig,
+    // the budget of time and paths to analyze the function.
+    pub budget: FnBudget,
     pub alias_set: Vec<usize>,
     // contains the return results for inter-procedure analysis.
     pub ret_alias: MopAAResult,
     pub terminators: Vec<TerminatorKind<'tcx>>,
+    // the program point whose alias sets are recorded on each path, used by the point-wise queries.
+    pub probe: Option<AliasProbe>,
+    // the components visited by the current path, whose feasibility is checked at the exits.
+    pub path: Vec<usize>,
+    // built on demand to check whether the path conditions of a result are satisfiable.
+    pub feasibility: Option<PathFeasibility>,
+}
+
+/// The values and the union-find of the alias sets on a path.
+pub type AliasState = (Vec<Value>, Vec<usize>);
+
+/// The alias sets at a program point, recorded on each path reaching it.
+#[derive(Debug, Clone)]
+pub struct AliasProbe {
+    /// The statement before which the alias sets are recorded.
+    pub location: Location,
+    /// The alias state of each path.
+    pub states: Vec<AliasState>,
+}
+
+impl AliasProbe {
+    pub fn new(location: Location) -> Self {
+        Self {
+            location,
+            states: Vec::new(),
+        }
+    }
 }
 
 impl<'tcx> MopGraph<'tcx> {
@@ -77,7 +109,7 @@ impl<'tcx> MopGraph<'tcx> {
             let mut cur_bb = Block::new(i, bb.is_cleanup);
 
             // handle general statements
-            for stmt in &bb.statements {
+            for (stmt_index, stmt) in bb.statements.iter().enumerate() {
                 let span = stmt.source_info.span;
                 match &stmt.kind {
                     StatementKind::Assign(box (place, rvalue)) => {
@@ -96,6 +128,7 @@ impl<'tcx> MopGraph<'tcx> {
                                                 rv_place,
                                                 AssignType::Copy,
                                                 span,
+                                                stmt_index,
                                             );
                                             cur_bb.assignments.push(assign);
                                         }
@@ -108,6 +141,7 @@ impl<'tcx> MopGraph<'tcx> {
                                                 rv_place,
                                                 AssignType::Move,
                                                 span,
+                                                stmt_index,
                                             );
                                             cur_bb.assignments.push(assign);
                                         }
@@ -148,8 +182,13 @@ impl<'tcx> MopGraph<'tcx> {
                             | Rvalue::CopyForDeref(rv_place) => {
                                 let rv_local = rv_place.local.as_usize();
                                 if values[lv_local].may_drop && values[rv_local].may_drop {
-                                    let assign =
-                                        Assignment::new(lv_place, rv_place, AssignType::Copy, span);
+                                    let assign = Assignment::new(
+                                        lv_place,
+                                        rv_place,
+                                        AssignType::Copy,
+                                        span,
+                                        stmt_index,
+                                    );
                                     cur_bb.assignments.push(assign);
                                 }
                             }
@@ -177,6 +216,7 @@ impl<'tcx> MopGraph<'tcx> {
                                                 rv_place,
                                                 AssignType::InitBox,
                                                 span,
+                                                stmt_index,
                                             );
                                             cur_bb.assignments.push(assign);
                                         }
@@ -193,6 +233,7 @@ impl<'tcx> MopGraph<'tcx> {
                                             rv_place,
                                             AssignType::Copy,
                                             span,
+                                            stmt_index,
                                         );
                                         cur_bb.assignments.push(assign);
                                     }
@@ -205,6 +246,7 @@ impl<'tcx> MopGraph<'tcx> {
                                             rv_place,
                                             AssignType::Move,
                                             span,
+                                            stmt_index,
                                         );
                                         cur_bb.assignments.push(assign);
                                     }
@@ -250,6 +292,7 @@ impl<'tcx> MopGraph<'tcx> {
                                                                 AssignType::Copy
                                                             },
                                                             span,
+                                                            stmt_index,
                                                         );
                                                         cur_bb.assignments.push(assign);
                                                         rap_debug!(
@@ -283,6 +326,7 @@ impl<'tcx> MopGraph<'tcx> {
                                                             rv_place,
                                                             AssignType::Copy,
                                                             span,
+                                                            stmt_index,
                                                         );
                                                         cur_bb.assignments.push(assign);
                                                     }
@@ -294,8 +338,13 @@ impl<'tcx> MopGraph<'tcx> {
                                 }
                             }
                             Rvalue::Discriminant(rv_place) => {
-                                let assign =
-                                    Assignment::new(lv_place, rv_place, AssignType::Variant, span);
+                                let assign = Assignment::new(
+                                    lv_place,
+                                    rv_place,
+                                    AssignType::Variant,
+                                    span,
+                                    stmt_index,
+                                );
                                 cur_bb.assignments.push(assign);
                                 discriminants.insert(lv_local, rv_place.local.as_usize());
                             }
@@ -361,13 +410,8 @@ impl<'tcx> MopGraph<'tcx> {
                 } => {
                     if let Operand::Constant(c) = func {
                         if let &ty::FnDef(id, ..) = c.ty().kind() {
-                            // for no_std crates without using alloc,
-                            // dealloc will be never found, thus call dealloc_opt here
-                            if id == drop()
-                                || id == drop_in_place()
-                                || id == manually_drop()
-                                || dealloc_opt().map(|f| f == id).unwrap_or(false)
-                            {
+                            // The functions that free their arguments, e.g., `drop_in_place`.
+                            if !ownership_model::freed_args(tcx, id).is_empty() {
                                 cur_bb.terminator = Term::Drop(terminator.clone());
                             } else {
                                 cur_bb.terminator = Term::Call(terminator.clone());
@@ -451,9 +495,28 @@ impl<'tcx> MopGraph<'tcx> {
             constants: FxHashMap::default(),
             ret_alias: MopAAResult::new(arg_size),
             visit_times: 0,
+            config: MopConfig::default(),
+            budget: FnBudget::new(),
             discriminants,
             terminators,
+            probe: None,
+            path: Vec::new(),
+            feasibility: None,
+        }
+    }
+
+    /// Whether some feasible path visits the basic blocks in order. Only the local functions are
+    /// checked, and the others are always taken as feasible.
+    pub fn is_feasible(&mut self, blocks: &[usize]) -> bool {
+        if !self.def_id.is_local() {
+            return true;
+        }
+        if self.feasibility.is_none() {
+            self.feasibility = Some(PathFeasibility::new(self));
         }
+        self.feasibility
+            .as_ref()
+            .is_none_or(|feasibility| feasibility.is_feasible(blocks))
     }
 
     pub fn dfs_on_spanning_tree(
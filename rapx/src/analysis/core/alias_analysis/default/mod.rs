@@ -1,25 +1,62 @@
 pub mod alias;
 pub mod assign;
 pub mod block;
-pub mod corner_case;
 pub mod graph;
 pub mod mop;
 pub mod types;
 pub mod value;
 
-use super::{AAFact, AAResult, AAResultMap, AliasAnalysis};
+use super::{AAFact, AAPlace, AAResult, AAResultMap, AliasAnalysis};
 use crate::{
-    analysis::{Analysis, graphs::scc::Scc},
+    analysis::{
+        Analysis,
+        core::{
+            ownership_model,
+            scheduler::{BottomUpScheduler, callee_summaries},
+            summary_cache::SummaryCache,
+        },
+        graphs::scc::Scc,
+    },
     def_id::*,
-    utils::source::*,
+    utils::{budget, source::*},
 };
-use graph::MopGraph;
+use graph::{AliasProbe, AliasState, MopGraph};
 use rustc_data_structures::fx::FxHashMap;
 use rustc_hir::def_id::DefId;
-use rustc_middle::ty::TyCtxt;
-use std::{collections::HashSet, convert::From, fmt};
+use rustc_middle::{
+    mir::{Location, Place},
+    ty::TyCtxt,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    cell::RefCell,
+    collections::HashSet,
+    convert::From,
+    fmt,
+    path::{Path, PathBuf},
+};
 
 pub const VISIT_LIMIT: usize = 1000;
+pub const FIELD_DEPTH: usize = 20;
+
+/// The thresholds of MoP that trade precision for efficiency; configured in `rapx.toml`.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MopConfig {
+    /// The depth limit of field-sensitive analysis.
+    pub field_depth: usize,
+    /// The number of visited paths of a function before the analysis gives up.
+    pub visit_limit: usize,
+}
+
+impl Default for MopConfig {
+    fn default() -> Self {
+        Self {
+            field_depth: FIELD_DEPTH,
+            visit_limit: VISIT_LIMIT,
+        }
+    }
+}
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub struct MopAAFact {
@@ -149,12 +186,92 @@ impl MopAAResult {
     }
 }
 
+/// The layout of `MopAAResult` in the summary files, with the aliases sorted for reproducible
+/// output.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MopAAResultJson {
+    pub arg_size: usize,
+    pub aliases: Vec<MopAAFactJson>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MopAAFactJson {
+    pub lhs_no: usize,
+    pub lhs_fields: Vec<usize>,
+    pub lhs_may_drop: bool,
+    pub lhs_need_drop: bool,
+    pub rhs_no: usize,
+    pub rhs_fields: Vec<usize>,
+    pub rhs_may_drop: bool,
+    pub rhs_need_drop: bool,
+}
+
+impl From<&MopAAResult> for MopAAResultJson {
+    fn from(result: &MopAAResult) -> Self {
+        let mut aliases: Vec<MopAAFactJson> = result
+            .aliases()
+            .iter()
+            .map(|alias| MopAAFactJson {
+                lhs_no: alias.lhs_no(),
+                lhs_fields: alias.lhs_fields().to_vec(),
+                lhs_may_drop: alias.lhs_may_drop,
+                lhs_need_drop: alias.lhs_need_drop,
+                rhs_no: alias.rhs_no(),
+                rhs_fields: alias.rhs_fields().to_vec(),
+                rhs_may_drop: alias.rhs_may_drop,
+                rhs_need_drop: alias.rhs_need_drop,
+            })
+            .collect();
+        aliases.sort_by(|a, b| {
+            (a.lhs_no, &a.lhs_fields, a.rhs_no, &a.rhs_fields).cmp(&(
+                b.lhs_no,
+                &b.lhs_fields,
+                b.rhs_no,
+                &b.rhs_fields,
+            ))
+        });
+        MopAAResultJson {
+            arg_size: result.arg_size(),
+            aliases,
+        }
+    }
+}
+
+impl From<MopAAResultJson> for MopAAResult {
+    fn from(json: MopAAResultJson) -> Self {
+        let mut result = MopAAResult::new(json.arg_size);
+        for alias in json.aliases {
+            let mut fact = MopAAFact::new(
+                alias.lhs_no,
+                alias.lhs_may_drop,
+                alias.lhs_need_drop,
+                alias.rhs_no,
+                alias.rhs_may_drop,
+                alias.rhs_need_drop,
+            );
+            fact.fact.lhs_fields = alias.lhs_fields;
+            fact.fact.rhs_fields = alias.rhs_fields;
+            result.add_alias(fact);
+        }
+        result
+    }
+}
+
 //struct to cache the results for analyzed functions.
 pub type MopAAResultMap = FxHashMap<DefId, MopAAResult>;
 
+/// The MoP graph of a function and the alias states probed at a program point.
+type ProbeResult<'tcx> = Option<(MopGraph<'tcx>, Vec<AliasState>)>;
+
 pub struct AliasAnalyzer<'tcx> {
     pub tcx: TyCtxt<'tcx>,
     pub fn_map: FxHashMap<DefId, MopAAResult>,
+    pub config: MopConfig,
+    /// The folder of the summary cache, if enabled.
+    pub cache_dir: Option<PathBuf>,
+    /// The probes of the point-wise queries, so that the paths of a function are enumerated
+    /// once per program point.
+    probes: RefCell<FxHashMap<(DefId, Location), ProbeResult<'tcx>>>,
 }
 
 impl<'tcx> Analysis for AliasAnalyzer<'tcx> {
@@ -164,10 +281,39 @@ impl<'tcx> Analysis for AliasAnalyzer<'tcx> {
 
     fn run(&mut self) {
         rap_debug!("Start alias analysis via MoP.");
+        let mut cache = self.cache_dir.as_ref().map(|dir| {
+            SummaryCache::<MopAAResultJson>::load(
+                self.tcx,
+                dir,
+                "alias",
+                &(
+                    self.config,
+                    budget::config(),
+                    ownership_model::user_entries(),
+                ),
+            )
+        });
         let mir_keys = self.tcx.mir_keys(());
+        // The cached summaries are available to the callers being analyzed.
+        let mut changed = Vec::new();
         for local_def_id in mir_keys {
-            self.query_mop(local_def_id.to_def_id());
+            let def_id = local_def_id.to_def_id();
+            match cache.as_ref().and_then(|cache| cache.get(def_id)) {
+                Some(summary) => {
+                    self.fn_map.insert(def_id, summary.clone().into());
+                }
+                None => changed.push(def_id),
+            }
         }
+        // Each component is analyzed with the summaries of its callees.
+        let (tcx, config) = (self.tcx, self.config);
+        BottomUpScheduler::new(tcx, changed).run(&mut self.fn_map, |scc, fn_map| {
+            let mut fn_map = callee_summaries(tcx, scc, fn_map);
+            for def_id in scc {
+                Self::query_mop(tcx, config, *def_id, &mut fn_map);
+            }
+            (fn_map.into_iter().collect(), ())
+        });
         // Meaning of output: 0 for ret value; 1,2,3,... for corresponding args.
         for (fn_id, fn_alias) in &mut self.fn_map {
             let fn_name = get_fn_name(self.tcx, *fn_id);
@@ -177,6 +323,15 @@ impl<'tcx> Analysis for AliasAnalyzer<'tcx> {
             }
         }
         self.handle_conor_cases();
+        if let Some(cache) = &mut cache {
+            for local_def_id in mir_keys {
+                let def_id = local_def_id.to_def_id();
+                if let Some(result) = self.fn_map.get(&def_id) {
+                    cache.insert(def_id, result.into());
+                }
+            }
+            cache.store();
+        }
     }
 
     fn reset(&mut self) {
@@ -184,7 +339,7 @@ impl<'tcx> Analysis for AliasAnalyzer<'tcx> {
     }
 }
 
-impl<'tcx> AliasAnalysis for AliasAnalyzer<'tcx> {
+impl<'tcx> AliasAnalysis<'tcx> for AliasAnalyzer<'tcx> {
     fn get_fn_alias(&self, def_id: DefId) -> Option<AAResult> {
         self.fn_map.get(&def_id).cloned().map(Into::into)
     }
@@ -195,16 +350,67 @@ impl<'tcx> AliasAnalysis for AliasAnalyzer<'tcx> {
             .map(|(k, v)| (*k, AAResult::from(v.clone())))
             .collect()
     }
+
+    fn may_alias(
+        &self,
+        def_id: DefId,
+        location: Location,
+        place_a: Place<'tcx>,
+        place_b: Place<'tcx>,
+    ) -> bool {
+        self.with_probe(def_id, location, |mop_graph, states| {
+            states.iter().any(|(values, alias_set)| {
+                mop_graph.values = values.clone();
+                mop_graph.alias_set = alias_set.clone();
+                let a = mop_graph.projection(false, place_a);
+                let b = mop_graph.projection(false, place_b);
+                mop_graph.union_is_same(a, b)
+            })
+        })
+        .unwrap_or(false)
+    }
+
+    fn alias_set(&self, def_id: DefId, location: Location, place: Place<'tcx>) -> HashSet<AAPlace> {
+        let mut result = HashSet::new();
+        self.with_probe(def_id, location, |mop_graph, states| {
+            for (values, alias_set) in states {
+                mop_graph.values = values.clone();
+                mop_graph.alias_set = alias_set.clone();
+                let e = mop_graph.projection(false, place);
+                let own = mop_graph.value_place(e);
+                for i in mop_graph.get_alias_set(e) {
+                    let alias = mop_graph.value_place(i);
+                    if alias != own {
+                        result.insert(alias);
+                    }
+                }
+            }
+        });
+        result
+    }
 }
 
 impl<'tcx> AliasAnalyzer<'tcx> {
     pub fn new(tcx: TyCtxt<'tcx>) -> Self {
+        Self::with_config(tcx, MopConfig::default())
+    }
+
+    pub fn with_config(tcx: TyCtxt<'tcx>, config: MopConfig) -> Self {
         Self {
             tcx,
             fn_map: FxHashMap::default(),
+            config,
+            cache_dir: None,
+            probes: RefCell::default(),
         }
     }
 
+    /// Reuse the summaries of the unchanged functions cached in the folder.
+    pub fn with_cache(mut self, cache_dir: Option<&Path>) -> Self {
+        self.cache_dir = cache_dir.map(Path::to_path_buf);
+        self
+    }
+
     fn handle_conor_cases(&mut self) {
         let cases = [
             copy_from_nonoverlapping(),
@@ -221,26 +427,61 @@ impl<'tcx> AliasAnalyzer<'tcx> {
         }
     }
 
-    fn query_mop(&mut self, def_id: DefId) {
-        let fn_name = get_fn_name(self.tcx, def_id);
+    fn query_mop(tcx: TyCtxt<'tcx>, config: MopConfig, def_id: DefId, fn_map: &mut MopAAResultMap) {
+        let fn_name = get_fn_name(tcx, def_id);
         rap_trace!("query_mop: {:?}", fn_name);
         /* filter const mir */
-        if let Some(_other) = self.tcx.hir_body_const_context(def_id.expect_local()) {
+        if let Some(_other) = tcx.hir_body_const_context(def_id.expect_local()) {
             return;
         }
 
-        if self.tcx.is_mir_available(def_id) {
-            let mut mop_graph = MopGraph::new(self.tcx, def_id);
+        if tcx.is_mir_available(def_id) {
+            let mut mop_graph = MopGraph::new(tcx, def_id);
+            mop_graph.config = config;
             mop_graph.find_scc();
             let mut recursion_set = HashSet::default();
-            mop_graph.check(0, &mut self.fn_map, &mut recursion_set);
-            if mop_graph.visit_times > VISIT_LIMIT {
-                rap_trace!("Over visited: {:?}", def_id);
-            }
-            self.fn_map.insert(def_id, mop_graph.ret_alias);
+            mop_graph.check(0, fn_map, &mut recursion_set);
+            mop_graph.budget.record(tcx, "alias", def_id);
+            fn_map.insert(def_id, mop_graph.summary());
         } else {
-            rap_trace!("Mir is not available at {}", self.tcx.def_path_str(def_id));
+            rap_trace!("Mir is not available at {}", tcx.def_path_str(def_id));
+        }
+    }
+
+    /// Call `f` with the MoP graph and the alias states probed at the location, which are
+    /// computed on the first query of the location.
+    fn with_probe<R>(
+        &self,
+        def_id: DefId,
+        location: Location,
+        f: impl FnOnce(&mut MopGraph<'tcx>, &[AliasState]) -> R,
+    ) -> Option<R> {
+        let mut probes = self.probes.borrow_mut();
+        let probe = probes
+            .entry((def_id, location))
+            .or_insert_with(|| self.probe(def_id, location));
+        let (mop_graph, states) = probe.as_mut()?;
+        Some(f(mop_graph, states))
+    }
+
+    /// Analyze the function again with the summaries of its callees, recording the alias sets
+    /// right before the statement at the location on each path reaching it.
+    fn probe(&self, def_id: DefId, location: Location) -> ProbeResult<'tcx> {
+        if def_id
+            .as_local()
+            .is_some_and(|local| self.tcx.hir_body_const_context(local).is_some())
+            || !self.tcx.is_mir_available(def_id)
+        {
+            return None;
         }
+        let mut mop_graph = MopGraph::new(self.tcx, def_id);
+        mop_graph.config = self.config;
+        mop_graph.probe = Some(AliasProbe::new(location));
+        mop_graph.find_scc();
+        let mut fn_map = callee_summaries(self.tcx, &[def_id], &self.fn_map);
+        mop_graph.check(0, &mut fn_map, &mut HashSet::default());
+        let states = mop_graph.probe.take()?.states;
+        Some((mop_graph, states))
     }
 
     pub fn get_all_fn_alias_raw(&mut self) -> MopAAResultMap {
@@ -11,6 +11,7 @@ use rustc_data_structures::fx::FxHashSet;
 use std::collections::HashSet;
 
 use super::{block::Term, graph::*, *};
+use crate::utils::budget::{self, Exhausted};
 
 impl<'tcx> MopGraph<'tcx> {
     pub fn split_check(
@@ -50,6 +51,15 @@ impl<'tcx> MopGraph<'tcx> {
         self.constants = backup_constant;
     }
 
+    /// Whether the budget of the function has run out, by the visited paths or the time.
+    pub fn is_over_budget(&mut self) -> bool {
+        if self.visit_times > self.config.visit_limit {
+            self.budget
+                .exhaust(Exhausted::Paths(self.config.visit_limit));
+        }
+        self.budget.is_exhausted()
+    }
+
     // the core function of the alias analysis algorithm.
     pub fn check(
         &mut self,
@@ -58,11 +68,12 @@ impl<'tcx> MopGraph<'tcx> {
         recursion_set: &mut HashSet<DefId>,
     ) {
         self.visit_times += 1;
-        if self.visit_times > VISIT_LIMIT {
+        if self.is_over_budget() {
             return;
         }
         let scc_idx = self.blocks[bb_idx].scc.enter;
         let cur_block = self.blocks[bb_idx].clone();
+        self.path.push(scc_idx);
 
         if bb_idx == scc_idx && !cur_block.scc.nodes.is_empty() {
             rap_debug!("check {:?} as a scc", bb_idx);
@@ -71,6 +82,7 @@ impl<'tcx> MopGraph<'tcx> {
             self.check_single_node(bb_idx, fn_map, recursion_set);
             self.handle_nexts(bb_idx, fn_map, None, None, recursion_set);
         }
+        self.path.pop();
     }
 
     pub fn check_scc(
@@ -150,7 +162,10 @@ impl<'tcx> MopGraph<'tcx> {
         self.alias_bb(self.blocks[bb_idx].scc.enter);
         self.alias_bbcall(self.blocks[bb_idx].scc.enter, fn_map, recursion_set);
         if cur_block.next.is_empty() {
-            self.merge_results(self.values.clone());
+            // The aliases found on a path whose conditions cannot hold are dropped.
+            if self.is_feasible(&self.path.clone()) {
+                self.merge_results(self.values.clone());
+            }
             return;
         }
     }
@@ -271,7 +286,7 @@ impl<'tcx> MopGraph<'tcx> {
             // Other cases in switchInt terminators
             if let Some(targets) = sw_targets {
                 for iter in targets.iter() {
-                    if self.visit_times > VISIT_LIMIT {
+                    if self.is_over_budget() {
                         continue;
                     }
                     let next = iter.1.as_usize();
@@ -304,7 +319,7 @@ impl<'tcx> MopGraph<'tcx> {
                 );
             } else {
                 for next in cur_block.next {
-                    if self.visit_times > VISIT_LIMIT {
+                    if self.is_over_budget() {
                         continue;
                     }
                     match exclusive_nodes {
@@ -334,6 +349,11 @@ impl<'tcx> MopGraph<'tcx> {
         visited: &mut FxHashSet<usize>, // Nodes visited in the context of this DFS; to avoid cycles.
         paths_in_scc: &mut Vec<(Vec<usize>, FxHashMap<usize, usize>)>, // All paths discovered in the SCC. First field: path; Second field: constants contriants.
     ) {
+        let scc_paths = budget::config().scc_paths;
+        if paths_in_scc.len() >= scc_paths {
+            self.budget.exhaust(Exhausted::SccPaths(scc_paths));
+            return;
+        }
         if scc.is_empty() {
             path.push(start);
             paths_in_scc.push((path.clone(), path_constants.clone()));
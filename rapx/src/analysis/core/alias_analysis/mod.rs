@@ -1,10 +1,13 @@
+pub mod andersen;
 pub mod default;
-use crate::utils::source::get_fn_name_byid;
+use crate::utils::{json::stable_def_path_tls, source::get_fn_name_byid};
 
 use super::super::Analysis;
 use rustc_data_structures::fx::FxHashMap;
 use rustc_hir::def_id::DefId;
+use rustc_middle::mir::{Location, Place};
 use rustc_span::def_id::LOCAL_CRATE;
+use serde::{Serialize, ser::SerializeStruct};
 use std::{collections::HashSet, fmt};
 
 /// The data structure to store aliases for a set of functions.
@@ -13,8 +16,29 @@ pub type AAResultMap = FxHashMap<DefId, AAResult>;
 /// This is a wrapper struct for displaying AAResultMap.
 pub struct AAResultMapWrapper(pub AAResultMap);
 
+/// The implementations of alias analysis, selected by `-alias=<backend>` for the alias analysis
+/// and its clients.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum AliasBackend {
+    /// The path-sensitive analysis of `default::AliasAnalyzer`.
+    #[default]
+    Mop,
+    /// The points-to analysis of `andersen::AndersenAnalyzer`.
+    Andersen,
+}
+
+impl AliasBackend {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "mop" => Some(AliasBackend::Mop),
+            "andersen" => Some(AliasBackend::Andersen),
+            _ => None,
+        }
+    }
+}
+
 /// This trait provides features related to alias analysis.
-pub trait AliasAnalysis: Analysis {
+pub trait AliasAnalysis<'tcx>: Analysis {
     /// Return the aliases among the function arguments and return value of a specific function.
     fn get_fn_alias(&self, def_id: DefId) -> Option<AAResult>;
     /// Return the aliases among the function arguments and return value for all functions.
@@ -28,6 +52,18 @@ pub trait AliasAnalysis: Analysis {
             .map(|(k, v)| (*k, v.clone()))
             .collect()
     }
+    /// Return whether the two places of a function may alias right before the statement at the
+    /// location, i.e., on any path reaching it.
+    fn may_alias(
+        &self,
+        def_id: DefId,
+        location: Location,
+        place_a: Place<'tcx>,
+        place_b: Place<'tcx>,
+    ) -> bool;
+    /// Return the places of a function that may alias the place right before the statement at
+    /// the location.
+    fn alias_set(&self, def_id: DefId, location: Location, place: Place<'tcx>) -> HashSet<AAPlace>;
 }
 
 /// To store the alias relationships among arguments and return values.
@@ -104,6 +140,78 @@ impl fmt::Display for AAResultMapWrapper {
     }
 }
 
+#[derive(Serialize)]
+struct AAResultJson<'a> {
+    function: String,
+    #[serde(flatten)]
+    result: &'a AAResult,
+}
+
+/// The JSON layout is a list of `{ "function", "arg_size", "aliases" }` sorted by def path.
+impl Serialize for AAResultMapWrapper {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut results: Vec<_> = self
+            .0
+            .iter()
+            .map(|(def_id, result)| AAResultJson {
+                function: stable_def_path_tls(*def_id),
+                result,
+            })
+            .collect();
+        results.sort_by(|a, b| a.function.cmp(&b.function));
+        results.serialize(serializer)
+    }
+}
+
+impl Serialize for AAResult {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut aliases: Vec<&AAFact> = self.alias_set.iter().collect();
+        aliases.sort_by_key(|fact| (fact.lhs_no, &fact.lhs_fields, fact.rhs_no, &fact.rhs_fields));
+        let mut state = serializer.serialize_struct("AAResult", 2)?;
+        state.serialize_field("arg_size", &self.arg_size)?;
+        state.serialize_field("aliases", &aliases)?;
+        state.end()
+    }
+}
+
+#[derive(Serialize)]
+struct AAPlaceJson<'a> {
+    local: usize,
+    fields: &'a [usize],
+}
+
+/// Each side of the fact is a field-sensitive place, e.g., `{ "local": 1, "fields": [0, 2] }`
+/// stands for `_1.0.2`.
+impl Serialize for AAFact {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_struct("AAFact", 2)?;
+        state.serialize_field(
+            "lhs",
+            &AAPlaceJson {
+                local: self.lhs_no,
+                fields: &self.lhs_fields,
+            },
+        )?;
+        state.serialize_field(
+            "rhs",
+            &AAPlaceJson {
+                local: self.rhs_no,
+                fields: &self.rhs_fields,
+            },
+        )?;
+        state.end()
+    }
+}
+
 /// AAFact is used to store the alias relationships between two places.
 /// The result is field-sensitive.
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
@@ -158,6 +266,26 @@ impl fmt::Display for AAFact {
     }
 }
 
+/// A field-sensitive place of a function, e.g., `1.0` for `(*_1).0`; the dereferences are
+/// transparent as in `AAFact`.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct AAPlace {
+    pub local: usize,
+    pub fields: Vec<usize>,
+}
+
+impl AAPlace {
+    pub fn new(local: usize, fields: Vec<usize>) -> Self {
+        Self { local, fields }
+    }
+}
+
+impl fmt::Display for AAPlace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", aa_place_desc_str(self.local, &self.fields, true))
+    }
+}
+
 fn aa_place_desc_str(no: usize, fields: &[usize], field_sensitive: bool) -> String {
     let mut result = String::new();
     result.push_str(&no.to_string());
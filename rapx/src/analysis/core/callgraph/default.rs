@@ -1,20 +1,32 @@
 use rustc_hir::{def::DefKind, def_id::DefId};
 use rustc_middle::{
-    mir::{self, Body},
-    ty::TyCtxt,
+    mir::{self, Body, Local, Operand},
+    ty::{self, Instance, InstanceKind, TyCtxt},
+};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use super::{
+    rta::ConstructedTypes,
+    visitor::{
+        CallGraphVisitor, bounded_instance, default_instance, monomorphize, resolve_fn_source,
+        typing_env,
+    },
 };
-use std::collections::HashSet;
-use std::{collections::HashMap, hash::Hash};
-
-use super::visitor::CallGraphVisitor;
 use crate::{
     Analysis,
-    analysis::core::callgraph::{CallGraph, CallGraphAnalysis},
+    analysis::core::{
+        callgraph::{CallGraph, CallGraphAnalysis, CallGraphConfig, InstanceCallGraph},
+        dataflow::{
+            default::DataFlowAnalyzer,
+            graph::{FnSource, Graph},
+        },
+    },
 };
 
 pub struct CallGraphAnalyzer<'tcx> {
     pub tcx: TyCtxt<'tcx>,
     pub graph: CallGraphInfo<'tcx>,
+    pub config: CallGraphConfig,
 }
 
 impl<'tcx> Analysis for CallGraphAnalyzer<'tcx> {
@@ -27,77 +39,245 @@ impl<'tcx> Analysis for CallGraphAnalyzer<'tcx> {
     }
 
     fn reset(&mut self) {
-        todo!();
+        self.graph = CallGraphInfo::new();
     }
 }
 
-impl<'tcx> CallGraphAnalysis for CallGraphAnalyzer<'tcx> {
+impl<'tcx> CallGraphAnalysis<'tcx> for CallGraphAnalyzer<'tcx> {
     fn get_callgraph(&mut self) -> CallGraph {
-        let fn_calls: HashMap<DefId, Vec<DefId>> = self
+        let mut fn_calls: HashMap<DefId, Vec<DefId>> = HashMap::new();
+        for (caller, callees) in &self.graph.fn_calls {
+            let callees_id = fn_calls
+                .entry(self.graph.functions[caller].get_def_id())
+                .or_default();
+            for (callee, _) in callees {
+                let callee_id = self.graph.functions[callee].get_def_id();
+                if !callees_id.contains(&callee_id) {
+                    callees_id.push(callee_id);
+                }
+            }
+        }
+        CallGraph::new(fn_calls)
+    }
+
+    fn get_instance_callgraph(&mut self) -> InstanceCallGraph<'tcx> {
+        let fn_calls = self
             .graph
             .fn_calls
-            .clone()
-            .into_iter()
+            .iter()
             .map(|(caller, callees)| {
-                let caller_id = self
-                    .graph
-                    .functions
-                    .get(&caller)
-                    .expect("Key must exist in functions map")
-                    .def_id;
-
-                let callees_id = callees
-                    .into_iter()
-                    .map(|(callee, _)| {
-                        self.graph
-                            .functions
-                            .get(&callee)
-                            .expect("Value must exist in functions map")
-                            .def_id
-                    })
-                    .collect::<Vec<_>>();
-                (caller_id, callees_id)
+                let mut callees_instance: Vec<Instance<'tcx>> = Vec::new();
+                for (callee, _) in callees {
+                    let callee_instance = self.graph.functions[callee].get_instance();
+                    if !callees_instance.contains(&callee_instance) {
+                        callees_instance.push(callee_instance);
+                    }
+                }
+                (
+                    self.graph.functions[caller].get_instance(),
+                    callees_instance,
+                )
             })
             .collect();
-        CallGraph { fn_calls }
+        InstanceCallGraph { fn_calls }
     }
 }
 
 impl<'tcx> CallGraphAnalyzer<'tcx> {
     pub fn new(tcx: TyCtxt<'tcx>) -> Self {
+        Self::with_config(tcx, CallGraphConfig::default())
+    }
+
+    pub fn with_config(tcx: TyCtxt<'tcx>, config: CallGraphConfig) -> Self {
         Self {
             tcx: tcx,
             graph: CallGraphInfo::new(),
+            config,
         }
     }
 
+    /// Start from the local functions and constants, and explore the instances reachable from
+    /// them. Generic functions are explored with their identity generic arguments if they are
+    /// not called with concrete ones.
     pub fn start(&mut self) {
+        let mut dataflow = DataFlowAnalyzer::new(self.tcx, false);
+        dataflow.build_graphs();
+        let constructed_types = self.config.rta.then(|| ConstructedTypes::collect(self.tcx));
+
+        let mut worklist: VecDeque<Instance<'tcx>> = VecDeque::new();
         for local_def_id in self.tcx.iter_local_def_id() {
             if self.tcx.hir_maybe_body_owned_by(local_def_id).is_some() {
                 let def_id = local_def_id.to_def_id();
-                if self.tcx.is_mir_available(def_id) {
-                    let def_kind = self.tcx.def_kind(def_id);
-
-                    let body: &Body<'_> = match def_kind {
-                        DefKind::Fn | DefKind::AssocFn => &self.tcx.optimized_mir(def_id),
-                        DefKind::Const
-                        | DefKind::Static { .. }
-                        | DefKind::AssocConst
-                        | DefKind::InlineConst
-                        | DefKind::AnonConst => {
-                            // NOTE: safer fallback for constants
-                            &self.tcx.mir_for_ctfe(def_id)
-                        }
-                        // These don't have MIR or shouldn't be visited
-                        _ => {
-                            rap_debug!("Skipping def_id {:?} with kind {:?}", def_id, def_kind);
-                            continue;
-                        }
-                    };
+                if self.tcx.is_closure_like(def_id) {
+                    // Closures are reached from the functions defining them.
+                    continue;
+                }
+                worklist.push_back(default_instance(self.tcx, def_id));
+            }
+        }
 
-                    let mut call_graph_visitor =
-                        CallGraphVisitor::new(self.tcx, def_id.into(), body, &mut self.graph);
-                    call_graph_visitor.visit();
+        let mut visited = HashSet::new();
+        loop {
+            while let Some(instance) = worklist.pop_front() {
+                if !visited.insert(instance) {
+                    continue;
+                }
+                let Some(body) = self.body_of(instance) else {
+                    continue;
+                };
+                let callees =
+                    CallGraphVisitor::with_instance(self.tcx, instance, body, &mut self.graph)
+                        .with_dataflow(dataflow.graphs.get(&instance.def_id()))
+                        .with_rta(constructed_types.as_ref())
+                        .visit();
+                worklist.extend(
+                    callees
+                        .into_iter()
+                        .filter(|callee| !visited.contains(callee)),
+                );
+            }
+            // Function pointers and closures passed as arguments are resolved at the call sites,
+            // which may find new instances to explore.
+            let callees = self.resolve_param_calls(&dataflow.graphs);
+            if callees.is_empty() {
+                break;
+            }
+            worklist.extend(callees);
+        }
+    }
+
+    /// The MIR of the local functions and constants; other instances are not explored.
+    fn body_of(&self, instance: Instance<'tcx>) -> Option<&'tcx Body<'tcx>> {
+        let InstanceKind::Item(def_id) = instance.def else {
+            return None;
+        };
+        if !def_id.is_local() || !self.tcx.is_mir_available(def_id) {
+            return None;
+        }
+        let def_kind = self.tcx.def_kind(def_id);
+        match def_kind {
+            DefKind::Fn | DefKind::AssocFn | DefKind::Closure => {
+                Some(self.tcx.optimized_mir(def_id))
+            }
+            DefKind::Const
+            | DefKind::Static { .. }
+            | DefKind::AssocConst
+            | DefKind::InlineConst
+            | DefKind::AnonConst => {
+                // NOTE: safer fallback for constants
+                Some(self.tcx.mir_for_ctfe(def_id))
+            }
+            // These don't have MIR or shouldn't be visited
+            _ => {
+                rap_debug!("Skipping def_id {:?} with kind {:?}", def_id, def_kind);
+                None
+            }
+        }
+    }
+
+    /// Add the calls of function pointers and closures received from parameters, and return the
+    /// new callees.
+    fn resolve_param_calls(&mut self, graphs: &HashMap<DefId, Graph>) -> Vec<Instance<'tcx>> {
+        let callers_map = self.graph.get_callers_map();
+        let mut new_callees = Vec::new();
+        for param_call in self.graph.param_calls.clone() {
+            let mut callees = HashSet::new();
+            let mut visited = HashSet::new();
+            self.collect_param_sources(
+                param_call.caller,
+                param_call.param,
+                graphs,
+                &callers_map,
+                &mut visited,
+                &mut callees,
+            );
+            for callee in callees {
+                let callee = bounded_instance(self.tcx, callee);
+                let callee_id = self.graph.add_node(self.tcx, callee);
+                if self
+                    .graph
+                    .has_edge(param_call.caller, callee_id, param_call.terminator)
+                {
+                    continue;
+                }
+                self.graph.add_funciton_call_edge(
+                    param_call.caller,
+                    callee_id,
+                    Some(param_call.terminator),
+                );
+                new_callees.push(callee);
+            }
+        }
+        new_callees
+    }
+
+    /// Find the functions and closures passed to the parameter by the callers, transitively
+    /// through their parameters.
+    fn collect_param_sources(
+        &self,
+        owner: usize,
+        param: Local,
+        graphs: &HashMap<DefId, Graph>,
+        callers_map: &HashMap<usize, Vec<(usize, Option<&'tcx mir::Terminator<'tcx>>)>>,
+        visited: &mut HashSet<(usize, Local)>,
+        callees: &mut HashSet<Instance<'tcx>>,
+    ) {
+        if !visited.insert((owner, param))
+            || self
+                .tcx
+                .is_closure_like(self.graph.functions[&owner].get_def_id())
+        {
+            // The arguments of closures are tupled at the call sites.
+            return;
+        }
+        let Some(callers) = callers_map.get(&owner) else {
+            return;
+        };
+        for (caller, terminator) in callers {
+            let Some(mir::TerminatorKind::Call { args, .. }) = terminator.map(|t| &t.kind) else {
+                continue;
+            };
+            let Some(arg) = args.get(param.as_usize() - 1) else {
+                continue;
+            };
+            let caller_instance = self.graph.functions[caller].get_instance();
+            let Some(body) = self.body_of(caller_instance) else {
+                continue;
+            };
+            match &arg.node {
+                Operand::Constant(constant) => {
+                    let ty = monomorphize(self.tcx, caller_instance, constant.const_.ty());
+                    if let ty::FnDef(def_id, args) = ty.kind() {
+                        let env = typing_env(self.tcx, caller_instance);
+                        let callee = Instance::try_resolve(self.tcx, env, *def_id, args)
+                            .ok()
+                            .flatten()
+                            .unwrap_or_else(|| Instance::new_raw(*def_id, args));
+                        callees.insert(callee);
+                    }
+                }
+                Operand::Copy(place) | Operand::Move(place) => {
+                    let Some(graph) = graphs.get(&caller_instance.def_id()) else {
+                        continue;
+                    };
+                    for source in graph.collect_fn_sources(place.local) {
+                        match source {
+                            FnSource::Param(param) => self.collect_param_sources(
+                                *caller,
+                                param,
+                                graphs,
+                                callers_map,
+                                visited,
+                                callees,
+                            ),
+                            source => callees.extend(resolve_fn_source(
+                                self.tcx,
+                                caller_instance,
+                                body,
+                                source,
+                            )),
+                        }
+                    }
                 }
             }
         }
@@ -109,21 +289,22 @@ impl<'tcx> CallGraphAnalyzer<'tcx> {
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
-pub struct Node {
-    def_id: DefId,
+pub struct Node<'tcx> {
+    instance: Instance<'tcx>,
     def_path: String,
 }
 
-impl Node {
-    pub fn new(def_id: DefId, def_path: &String) -> Self {
-        Self {
-            def_id: def_id,
-            def_path: def_path.clone(),
-        }
+impl<'tcx> Node<'tcx> {
+    pub fn new(instance: Instance<'tcx>, def_path: String) -> Self {
+        Self { instance, def_path }
     }
 
     pub fn get_def_id(&self) -> DefId {
-        self.def_id
+        self.instance.def_id()
+    }
+
+    pub fn get_instance(&self) -> Instance<'tcx> {
+        self.instance
     }
 
     pub fn get_def_path(&self) -> String {
@@ -131,10 +312,19 @@ impl Node {
     }
 }
 
+/// A call of a function pointer or closure received from a parameter of the caller.
+#[derive(Debug, Clone, Copy)]
+pub struct ParamCall<'tcx> {
+    pub caller: usize,
+    pub param: Local,
+    pub terminator: &'tcx mir::Terminator<'tcx>,
+}
+
 pub struct CallGraphInfo<'tcx> {
-    pub functions: HashMap<usize, Node>, // id -> node
+    pub functions: HashMap<usize, Node<'tcx>>, // id -> node
     pub fn_calls: HashMap<usize, Vec<(usize, Option<&'tcx mir::Terminator<'tcx>>)>>, // caller_id -> Vec<(callee_id, terminator)>
-    pub node_registry: HashMap<String, usize>,                                       // path -> id
+    pub node_registry: HashMap<Instance<'tcx>, usize>, // instance -> id
+    pub param_calls: Vec<ParamCall<'tcx>>,
 }
 
 impl<'tcx> CallGraphInfo<'tcx> {
@@ -143,6 +333,7 @@ impl<'tcx> CallGraphInfo<'tcx> {
             functions: HashMap::new(),
             fn_calls: HashMap::new(),
             node_registry: HashMap::new(),
+            param_calls: Vec::new(),
         }
     }
 
@@ -150,9 +341,15 @@ impl<'tcx> CallGraphInfo<'tcx> {
         self.functions.len()
     }
 
+    /// The def paths of the callees of all the instances of the function.
     pub fn get_callees_path(&self, caller_def_path: &String) -> Option<HashSet<String>> {
         let mut callees_path: HashSet<String> = HashSet::new();
-        if let Some(caller_id) = self.node_registry.get(caller_def_path) {
+        let mut found = false;
+        for (caller_id, caller_node) in &self.functions {
+            if caller_node.def_path != *caller_def_path {
+                continue;
+            }
+            found = true;
             if let Some(callees) = self.fn_calls.get(caller_id) {
                 for (id, _terminator) in callees {
                     if let Some(callee_node) = self.functions.get(id) {
@@ -160,20 +357,18 @@ impl<'tcx> CallGraphInfo<'tcx> {
                     }
                 }
             }
-            Some(callees_path)
-        } else {
-            None
         }
+        found.then_some(callees_path)
     }
 
     /// Add a node and return its id. If node already exists, only return its id.
-    pub fn add_node(&mut self, def_id: DefId, def_path: &String) -> usize {
-        if let Some(old_id) = self.node_registry.get(def_path) {
+    pub fn add_node(&mut self, tcx: TyCtxt<'tcx>, instance: Instance<'tcx>) -> usize {
+        if let Some(old_id) = self.node_registry.get(&instance) {
             *old_id
         } else {
             let new_id = self.node_registry.len();
-            let node = Node::new(def_id, def_path);
-            self.node_registry.insert(def_path.clone(), new_id);
+            let node = Node::new(instance, tcx.def_path_str(instance.def_id()));
+            self.node_registry.insert(instance, new_id);
             self.functions.insert(new_id, node);
             new_id
         }
@@ -189,9 +384,23 @@ impl<'tcx> CallGraphInfo<'tcx> {
         entry.push((callee_id, terminator_stmt));
     }
 
-    pub fn get_node_by_path(&self, def_path: &String) -> Option<usize> {
-        self.node_registry.get(def_path).copied()
+    pub fn has_edge(
+        &self,
+        caller_id: usize,
+        callee_id: usize,
+        terminator_stmt: &'tcx mir::Terminator<'tcx>,
+    ) -> bool {
+        self.fn_calls.get(&caller_id).is_some_and(|callees| {
+            callees.iter().any(|(id, terminator)| {
+                *id == callee_id && terminator.is_some_and(|t| std::ptr::eq(t, terminator_stmt))
+            })
+        })
     }
+
+    pub fn get_node(&self, instance: Instance<'tcx>) -> Option<usize> {
+        self.node_registry.get(&instance).copied()
+    }
+
     pub fn get_callers_map(
         &self,
     ) -> HashMap<usize, Vec<(usize, Option<&'tcx mir::Terminator<'tcx>>)>> {
@@ -215,24 +424,24 @@ impl<'tcx> CallGraphInfo<'tcx> {
             if let Some(caller_node) = self.functions.get(caller_id) {
                 for (callee_id, terminator) in callees {
                     if let Some(callee_node) = self.functions.get(callee_id) {
-                        let caller_def_path = caller_node.get_def_path();
-                        let callee_def_path = callee_node.get_def_path();
+                        let caller_instance = caller_node.get_instance();
+                        let callee_instance = callee_node.get_instance();
                         if let Some(terminator_stmt) = terminator {
                             rap_info!(
                                 "{}:{} -> {}:{} @ {:?}",
                                 caller_id,
-                                caller_def_path,
+                                caller_instance,
                                 *callee_id,
-                                callee_def_path,
+                                callee_instance,
                                 terminator_stmt.kind
                             );
                         } else {
                             rap_info!(
                                 " (Virtual) {}:{} -> {}:{}",
                                 caller_id,
-                                caller_def_path,
+                                caller_instance,
                                 *callee_id,
-                                callee_def_path,
+                                callee_instance,
                             );
                         }
                     }
@@ -241,6 +450,8 @@ impl<'tcx> CallGraphInfo<'tcx> {
         }
     }
 
+    /// The bottom-up order of the functions; each function appears once even if it has
+    /// several instances.
     pub fn get_reverse_post_order(&self) -> Vec<DefId> {
         let mut visited = HashSet::new();
         let mut post_order_ids = Vec::new(); // Will store the post-order traversal of `usize` IDs
@@ -253,14 +464,16 @@ impl<'tcx> CallGraphInfo<'tcx> {
         }
 
         // Map the ordered `usize` IDs back to `DefId`s for the analysis pipeline
+        let mut seen = HashSet::new();
         let mut analysis_order: Vec<DefId> = post_order_ids
             .into_iter()
             .map(|id| {
                 self.functions
                     .get(&id)
                     .expect("Node ID must exist in functions map")
-                    .def_id
+                    .get_def_id()
             })
+            .filter(|def_id| seen.insert(*def_id))
             .collect();
 
         // Reversing the post-order gives a topological sort (bottom-up)
@@ -1,15 +1,62 @@
 pub mod default;
+pub mod query;
+pub mod rta;
 pub mod visitor;
 
-use crate::Analysis;
+use crate::{Analysis, utils::json::stable_def_path};
+use query::CallGraphIndex;
 use rustc_hir::def_id::DefId;
-use rustc_middle::ty::TyCtxt;
-use std::{collections::HashMap, fmt};
+use rustc_middle::ty::{Instance, TyCtxt};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
 
 /// This is the data structure used to store function calls.
-/// It contains a HashMap that records the callees of all functions.
+/// It contains a HashMap that records the callees of all functions, and supports the queries
+/// in `query.rs`.
 pub struct CallGraph {
     pub fn_calls: HashMap<DefId, Vec<DefId>>, // caller_id -> Vec<(callee_id)>
+    index: CallGraphIndex,
+}
+
+/// The context-sensitive call graph, where each instance of a generic function is a node.
+/// A virtual call is represented by an edge to the `InstanceKind::Virtual` instance, which calls
+/// all the possible implementations.
+pub struct InstanceCallGraph<'tcx> {
+    pub fn_calls: HashMap<Instance<'tcx>, Vec<Instance<'tcx>>>, // caller -> Vec<callee>
+}
+
+impl<'tcx> fmt::Display for InstanceCallGraph<'tcx> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut fn_calls: Vec<_> = self
+            .fn_calls
+            .iter()
+            .map(|(caller, callees)| (caller.to_string(), callees))
+            .collect();
+        fn_calls.sort_by(|a, b| a.0.cmp(&b.0));
+        writeln!(f, "InstanceCallGraph:")?;
+        for (caller, callees) in fn_calls {
+            writeln!(f, "  {} calls:", caller)?;
+            for callee in callees {
+                writeln!(f, "    -> {}", callee)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The options in the `[callgraph]` table of `rapx.toml`.
+#[derive(Debug, Clone, Copy, Default, Hash, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CallGraphConfig {
+    /// Only keep the implementations whose self types are coerced into the trait objects in the
+    /// local crate as the targets of virtual calls (rapid type analysis).
+    pub rta: bool,
+    /// The number of calls from the function given by `-callgraph=<def path>` to the callers and
+    /// callees kept in the slice; unlimited if absent.
+    pub slice_depth: Option<usize>,
 }
 
 pub struct CallGraphDisplay<'a, 'tcx> {
@@ -32,8 +79,74 @@ impl<'a, 'tcx> fmt::Display for CallGraphDisplay<'a, 'tcx> {
     }
 }
 
+#[derive(Serialize)]
+struct CallGraphJson {
+    caller: String,
+    callees: Vec<String>,
+}
+
+/// The JSON layout is a list of `{ "caller", "callees" }` sorted by def path.
+impl<'a, 'tcx> Serialize for CallGraphDisplay<'a, 'tcx> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut results: Vec<_> = self
+            .graph
+            .fn_calls
+            .iter()
+            .map(|(caller, callees)| {
+                let mut callees: Vec<_> = callees
+                    .iter()
+                    .map(|callee| stable_def_path(self.tcx, *callee))
+                    .collect();
+                callees.sort();
+                CallGraphJson {
+                    caller: stable_def_path(self.tcx, *caller),
+                    callees,
+                }
+            })
+            .collect();
+        results.sort_by(|a, b| a.caller.cmp(&b.caller));
+        results.serialize(serializer)
+    }
+}
+
 /// This trait provides features related to call graph extraction and analysis.
-pub trait CallGraphAnalysis: Analysis {
+pub trait CallGraphAnalysis<'tcx>: Analysis {
     /// Return the call graph.
     fn get_callgraph(&mut self) -> CallGraph;
+    /// Return the call graph of the instances.
+    fn get_instance_callgraph(&mut self) -> InstanceCallGraph<'tcx>;
+
+    /// Return the functions that call the function directly or indirectly.
+    fn get_transitive_callers(&mut self, def_id: DefId) -> HashSet<DefId> {
+        self.get_callgraph().transitive_callers(def_id)
+    }
+
+    /// Return the functions called by the function directly or indirectly.
+    fn get_transitive_callees(&mut self, def_id: DefId) -> HashSet<DefId> {
+        self.get_callgraph().transitive_callees(def_id)
+    }
+
+    /// Return the shortest call path from any of the sources, e.g., the public APIs, to the
+    /// target.
+    fn get_call_path(&mut self, sources: &[DefId], target: DefId) -> Option<Vec<DefId>> {
+        self.get_callgraph().shortest_path(sources, target)
+    }
+
+    /// Return the groups of (mutually) recursive functions.
+    fn get_recursive_sccs(&mut self) -> Vec<Vec<DefId>> {
+        self.get_callgraph().recursive_sccs()
+    }
+
+    /// Return the functions with callees before callers, for summary-based analyses.
+    fn get_bottom_up_order(&mut self) -> Vec<DefId> {
+        self.get_callgraph().bottom_up_order()
+    }
+
+    /// Return the subgraph of the function with its callers and callees within `depth` calls.
+    fn get_callgraph_slice(&mut self, def_id: DefId, depth: Option<usize>) -> CallGraph {
+        self.get_callgraph().slice(def_id, depth)
+    }
 }
@@ -0,0 +1,235 @@
+use super::{CallGraph, CallGraphDisplay};
+use petgraph::{
+    Direction::{self, Incoming, Outgoing},
+    algo::tarjan_scc,
+    dot,
+    graph::{DiGraph, NodeIndex},
+};
+use rustc_hir::def_id::DefId;
+use rustc_middle::ty::TyCtxt;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// The call graph in petgraph, on which the queries run.
+pub(crate) struct CallGraphIndex {
+    graph: DiGraph<DefId, ()>,
+    node_index: HashMap<DefId, NodeIndex>,
+}
+
+impl CallGraphIndex {
+    /// Nodes are added in the order of `DefId`s, so that the query results are deterministic.
+    pub(crate) fn new(fn_calls: &HashMap<DefId, Vec<DefId>>) -> Self {
+        let mut def_ids: Vec<DefId> = fn_calls
+            .iter()
+            .flat_map(|(caller, callees)| std::iter::once(caller).chain(callees))
+            .copied()
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        def_ids.sort_by_key(|def_id| (def_id.krate, def_id.index));
+        let mut graph = DiGraph::new();
+        let node_index: HashMap<DefId, NodeIndex> = def_ids
+            .into_iter()
+            .map(|def_id| (def_id, graph.add_node(def_id)))
+            .collect();
+        let mut edges: Vec<_> = fn_calls
+            .iter()
+            .flat_map(|(caller, callees)| {
+                callees
+                    .iter()
+                    .map(|callee| (node_index[caller], node_index[callee]))
+            })
+            .collect();
+        edges.sort();
+        for (caller, callee) in edges {
+            graph.add_edge(caller, callee, ());
+        }
+        Self { graph, node_index }
+    }
+}
+
+impl CallGraph {
+    pub fn new(fn_calls: HashMap<DefId, Vec<DefId>>) -> Self {
+        let index = CallGraphIndex::new(&fn_calls);
+        Self { fn_calls, index }
+    }
+
+    pub fn contains(&self, def_id: DefId) -> bool {
+        self.index.node_index.contains_key(&def_id)
+    }
+
+    /// Find the function by its def path, e.g., `foo::bar` or `<Foo as Bar>::baz`.
+    pub fn find(&self, tcx: TyCtxt<'_>, def_path: &str) -> Option<DefId> {
+        self.index
+            .graph
+            .node_weights()
+            .copied()
+            .find(|def_id| tcx.def_path_str(*def_id) == def_path)
+    }
+
+    /// The functions that call the function directly or indirectly.
+    pub fn transitive_callers(&self, def_id: DefId) -> HashSet<DefId> {
+        self.reachable(def_id, Incoming, None)
+    }
+
+    /// The functions called by the function directly or indirectly.
+    pub fn transitive_callees(&self, def_id: DefId) -> HashSet<DefId> {
+        self.reachable(def_id, Outgoing, None)
+    }
+
+    /// The shortest call path from any of the sources to the target, including both ends.
+    pub fn shortest_path(&self, sources: &[DefId], target: DefId) -> Option<Vec<DefId>> {
+        let graph = &self.index.graph;
+        let target = *self.index.node_index.get(&target)?;
+        let mut prev: HashMap<NodeIndex, Option<NodeIndex>> = HashMap::new();
+        let mut queue = VecDeque::new();
+        for source in sources {
+            if let Some(&node) = self.index.node_index.get(source)
+                && prev.insert(node, None).is_none()
+            {
+                queue.push_back(node);
+            }
+        }
+        while let Some(node) = queue.pop_front() {
+            if node == target {
+                let mut path = vec![graph[node]];
+                let mut cur = node;
+                while let Some(Some(pred)) = prev.get(&cur) {
+                    path.push(graph[*pred]);
+                    cur = *pred;
+                }
+                path.reverse();
+                return Some(path);
+            }
+            for next in graph.neighbors_directed(node, Outgoing) {
+                if let std::collections::hash_map::Entry::Vacant(entry) = prev.entry(next) {
+                    entry.insert(Some(node));
+                    queue.push_back(next);
+                }
+            }
+        }
+        None
+    }
+
+    /// The strongly connected components in bottom-up order, i.e., callees before callers.
+    pub fn sccs(&self) -> Vec<Vec<DefId>> {
+        // Tarjan's algorithm finds the components in reverse topological order.
+        tarjan_scc(&self.index.graph)
+            .into_iter()
+            .map(|scc| {
+                let mut scc: Vec<DefId> = scc.into_iter().map(|n| self.index.graph[n]).collect();
+                scc.sort_by_key(|def_id| (def_id.krate, def_id.index));
+                scc
+            })
+            .collect()
+    }
+
+    /// The strongly connected components grouped into waves, where the callees of a component are
+    /// in the earlier waves, so that the components of a wave can be analyzed independently.
+    pub fn scc_waves(&self) -> Vec<Vec<Vec<DefId>>> {
+        let sccs = self.sccs();
+        let scc_of: HashMap<DefId, usize> = sccs
+            .iter()
+            .enumerate()
+            .flat_map(|(idx, scc)| scc.iter().map(move |def_id| (*def_id, idx)))
+            .collect();
+        let mut wave_of = Vec::with_capacity(sccs.len());
+        let mut waves: Vec<Vec<Vec<DefId>>> = Vec::new();
+        // The components are in bottom-up order, so the waves of the callees are known.
+        for (idx, scc) in sccs.into_iter().enumerate() {
+            let wave = scc
+                .iter()
+                .filter_map(|def_id| self.fn_calls.get(def_id))
+                .flatten()
+                .filter_map(|callee| scc_of.get(callee))
+                .filter(|callee_scc| **callee_scc != idx)
+                .map(|callee_scc| wave_of[*callee_scc] + 1)
+                .max()
+                .unwrap_or(0);
+            wave_of.push(wave);
+            if waves.len() <= wave {
+                waves.resize_with(wave + 1, Vec::new);
+            }
+            waves[wave].push(scc);
+        }
+        waves
+    }
+
+    /// The components of (mutually) recursive functions.
+    pub fn recursive_sccs(&self) -> Vec<Vec<DefId>> {
+        self.sccs()
+            .into_iter()
+            .filter(|scc| {
+                scc.len() > 1
+                    || self
+                        .fn_calls
+                        .get(&scc[0])
+                        .is_some_and(|c| c.contains(&scc[0]))
+            })
+            .collect()
+    }
+
+    /// Callees before callers; the functions in a recursion are ordered arbitrarily.
+    pub fn bottom_up_order(&self) -> Vec<DefId> {
+        self.sccs().into_iter().flatten().collect()
+    }
+
+    /// The subgraph of the function with its callers and callees within `depth` calls, or all of
+    /// them if `depth` is None.
+    pub fn slice(&self, def_id: DefId, depth: Option<usize>) -> CallGraph {
+        let mut nodes = self.reachable(def_id, Incoming, depth);
+        nodes.extend(self.reachable(def_id, Outgoing, depth));
+        if self.contains(def_id) {
+            nodes.insert(def_id);
+        }
+        let fn_calls = self
+            .fn_calls
+            .iter()
+            .filter(|(caller, _)| nodes.contains(caller))
+            .map(|(caller, callees)| {
+                let callees = callees
+                    .iter()
+                    .filter(|callee| nodes.contains(callee))
+                    .copied()
+                    .collect();
+                (*caller, callees)
+            })
+            .collect();
+        CallGraph::new(fn_calls)
+    }
+
+    /// Breadth-first search excluding the start node unless it is in a cycle.
+    fn reachable(&self, def_id: DefId, dir: Direction, depth: Option<usize>) -> HashSet<DefId> {
+        let graph = &self.index.graph;
+        let mut reached = HashSet::new();
+        let Some(&start) = self.index.node_index.get(&def_id) else {
+            return reached;
+        };
+        let mut queue = VecDeque::from([(start, 0)]);
+        while let Some((node, dist)) = queue.pop_front() {
+            if depth.is_some_and(|depth| dist >= depth) {
+                continue;
+            }
+            for next in graph.neighbors_directed(node, dir) {
+                if reached.insert(graph[next]) {
+                    queue.push_back((next, dist + 1));
+                }
+            }
+        }
+        reached
+    }
+}
+
+impl<'a, 'tcx> CallGraphDisplay<'a, 'tcx> {
+    /// The graph in the DOT format, where the nodes are labeled with def paths.
+    pub fn to_dot(&self) -> String {
+        let graph = self
+            .graph
+            .index
+            .graph
+            .map(|_, def_id| self.tcx.def_path_str(*def_id), |_, _| "");
+        format!(
+            "{}",
+            dot::Dot::with_config(&graph, &[dot::Config::EdgeNoLabel])
+        )
+    }
+}
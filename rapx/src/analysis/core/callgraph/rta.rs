@@ -0,0 +1,104 @@
+use rustc_hir::{def::DefKind, def_id::DefId};
+use rustc_middle::{
+    mir::{CastKind, Rvalue, StatementKind},
+    ty::{
+        self, Ty, TyCtxt,
+        adjustment::PointerCoercion,
+        fast_reject::{SimplifiedType, TreatParams, simplify_type},
+    },
+};
+use rustc_type_ir::elaborate::supertrait_def_ids;
+use std::collections::{HashMap, HashSet};
+
+/// The types coerced into trait objects in the local crate, which refine the implementors of a
+/// virtual call found by class hierarchy analysis (rapid type analysis).
+pub struct ConstructedTypes {
+    // trait -> the simplified types coerced into its objects; None if some type is unknown, e.g.,
+    // a type parameter.
+    types: HashMap<DefId, Option<HashSet<SimplifiedType>>>,
+}
+
+impl ConstructedTypes {
+    pub fn collect(tcx: TyCtxt<'_>) -> Self {
+        let mut constructed = ConstructedTypes {
+            types: HashMap::new(),
+        };
+        for local_def_id in tcx.mir_keys(()) {
+            let def_id = local_def_id.to_def_id();
+            if !matches!(
+                tcx.def_kind(def_id),
+                DefKind::Fn | DefKind::AssocFn | DefKind::Closure
+            ) || !tcx.is_mir_available(def_id)
+            {
+                continue;
+            }
+            let body = tcx.optimized_mir(def_id);
+            for data in body.basic_blocks.iter() {
+                for stmt in data.statements.iter() {
+                    let StatementKind::Assign(box (
+                        _,
+                        Rvalue::Cast(
+                            CastKind::PointerCoercion(PointerCoercion::Unsize, _),
+                            operand,
+                            target,
+                        ),
+                    )) = &stmt.kind
+                    else {
+                        continue;
+                    };
+                    let source = operand.ty(body, tcx);
+                    if let (Some(source), Some(target)) = (pointee(source), pointee(*target)) {
+                        constructed.add(tcx, source, target);
+                    }
+                }
+            }
+        }
+        constructed
+    }
+
+    fn add<'tcx>(&mut self, tcx: TyCtxt<'tcx>, source: Ty<'tcx>, target: Ty<'tcx>) {
+        let ty::Dynamic(preds, ..) = target.kind() else {
+            return;
+        };
+        let Some(principal) = preds.principal_def_id() else {
+            return;
+        };
+        let simplified = simplify_type(tcx, source, TreatParams::InstantiateWithInfer);
+        // The object can also be upcast to the supertraits.
+        for trait_def_id in supertrait_def_ids(tcx, principal) {
+            let entry = self
+                .types
+                .entry(trait_def_id)
+                .or_insert_with(|| Some(HashSet::new()));
+            match (entry.as_mut(), simplified) {
+                (Some(types), Some(simplified)) => {
+                    types.insert(simplified);
+                }
+                _ => *entry = None,
+            }
+        }
+    }
+
+    /// Whether the objects of the trait may be created from the self type of the impl. Traits
+    /// whose objects are never created in the local crate are kept as is, because the objects
+    /// may come from other crates.
+    pub fn may_construct(&self, tcx: TyCtxt<'_>, trait_def_id: DefId, impl_id: DefId) -> bool {
+        match self.types.get(&trait_def_id) {
+            Some(Some(types)) => {
+                let self_ty = tcx.type_of(impl_id).instantiate_identity();
+                simplify_type(tcx, self_ty, TreatParams::InstantiateWithInfer)
+                    .is_none_or(|simplified| types.contains(&simplified))
+            }
+            _ => true,
+        }
+    }
+}
+
+/// The pointee of references, raw pointers and smart pointers like `Box<T>`.
+fn pointee(ty: Ty<'_>) -> Option<Ty<'_>> {
+    match ty.kind() {
+        ty::Ref(_, pointee, _) | ty::RawPtr(pointee, _) => Some(*pointee),
+        ty::Adt(_, args) => args.types().next(),
+        _ => None,
+    }
+}
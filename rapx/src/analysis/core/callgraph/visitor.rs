@@ -1,214 +1,321 @@
-use super::default::CallGraphInfo;
-use regex::Regex;
+use super::{
+    default::{CallGraphInfo, ParamCall},
+    rta::ConstructedTypes,
+};
+use crate::analysis::core::dataflow::graph::{FnSource, Graph};
 use rustc_hir::def_id::DefId;
-use rustc_middle::mir;
-use rustc_middle::ty::{FnDef, Instance, InstanceKind, TyCtxt, TypingEnv};
+use rustc_middle::mir::{self, Local, Operand};
+use rustc_middle::ty::{
+    self, EarlyBinder, GenericArgs, GenericArgsRef, Instance, InstanceKind, Ty, TyCtxt,
+    TypeVisitableExt, TypingEnv,
+};
 use std::collections::HashSet;
 
+/// The limit of the type length of the generic arguments of the explored instances, like the
+/// `type_length_limit` of rustc.
+const INSTANCE_LENGTH_LIMIT: usize = 64;
+
 pub struct CallGraphVisitor<'b, 'tcx> {
     tcx: TyCtxt<'tcx>,
-    def_id: DefId,
+    instance: Instance<'tcx>,
     body: &'tcx mir::Body<'tcx>,
     call_graph_info: &'b mut CallGraphInfo<'tcx>,
+    // the dataflow graph of the body, used to find the targets of function pointers and closures
+    dataflow: Option<&'b Graph>,
+    constructed_types: Option<&'b ConstructedTypes>,
+    caller_id: usize,
+    callees: Vec<Instance<'tcx>>,
 }
 
 impl<'b, 'tcx> CallGraphVisitor<'b, 'tcx> {
+    /// Visit the body as a generic function, i.e., with the identity generic arguments.
     pub fn new(
         tcx: TyCtxt<'tcx>,
         def_id: DefId,
         body: &'tcx mir::Body<'tcx>,
         call_graph_info: &'b mut CallGraphInfo<'tcx>,
     ) -> Self {
+        let instance = Instance::new_raw(def_id, GenericArgs::identity_for_item(tcx, def_id));
+        Self::with_instance(tcx, instance, body, call_graph_info)
+    }
+
+    pub fn with_instance(
+        tcx: TyCtxt<'tcx>,
+        instance: Instance<'tcx>,
+        body: &'tcx mir::Body<'tcx>,
+        call_graph_info: &'b mut CallGraphInfo<'tcx>,
+    ) -> Self {
+        let caller_id = call_graph_info.add_node(tcx, instance);
         Self {
-            tcx: tcx,
-            def_id: def_id,
-            body: body,
-            call_graph_info: call_graph_info,
+            tcx,
+            instance,
+            body,
+            call_graph_info,
+            dataflow: None,
+            constructed_types: None,
+            caller_id,
+            callees: Vec::new(),
         }
     }
 
-    pub fn add_in_call_graph(
-        &mut self,
-        caller_def_path: &String,
-        callee_def_id: DefId,
-        callee_def_path: &String,
-        terminator: &'tcx mir::Terminator<'tcx>,
-    ) {
-        if let Some(caller_id) = self.call_graph_info.get_node_by_path(caller_def_path) {
-            if let Some(callee_id) = self.call_graph_info.get_node_by_path(callee_def_path) {
-                self.call_graph_info
-                    .add_funciton_call_edge(caller_id, callee_id, Some(terminator));
-            } else {
-                self.call_graph_info
-                    .add_node(callee_def_id, callee_def_path);
-                if let Some(callee_id) = self.call_graph_info.get_node_by_path(callee_def_path) {
-                    self.call_graph_info.add_funciton_call_edge(
-                        caller_id,
-                        callee_id,
-                        Some(terminator),
-                    );
-                }
-            }
+    pub fn with_dataflow(mut self, dataflow: Option<&'b Graph>) -> Self {
+        self.dataflow = dataflow;
+        self
+    }
+
+    /// Refine the implementors of virtual calls with rapid type analysis.
+    pub fn with_rta(mut self, constructed_types: Option<&'b ConstructedTypes>) -> Self {
+        self.constructed_types = constructed_types;
+        self
+    }
+
+    /// Add the calls in the body to the call graph and return the callee instances.
+    pub fn visit(mut self) -> Vec<Instance<'tcx>> {
+        for data in self.body.basic_blocks.iter() {
+            self.visit_terminator(data.terminator());
         }
+        self.callees
     }
 
-    pub fn visit(&mut self) {
-        let caller_path_str = self.tcx.def_path_str(self.def_id);
-        self.call_graph_info.add_node(self.def_id, &caller_path_str);
-        for (_, data) in self.body.basic_blocks.iter().enumerate() {
-            let terminator = data.terminator();
-            self.visit_terminator(&terminator);
+    fn visit_terminator(&mut self, terminator: &'tcx mir::Terminator<'tcx>) {
+        let mir::TerminatorKind::Call { func, args, .. } = &terminator.kind else {
+            return;
+        };
+        let func_ty = monomorphize(self.tcx, self.instance, func.ty(self.body, self.tcx));
+        match func_ty.kind() {
+            ty::FnDef(callee_def_id, callee_args) => {
+                let fn_operand = args.first().map(|arg| &arg.node);
+                self.visit_fn_def(*callee_def_id, callee_args, fn_operand, terminator);
+            }
+            ty::FnPtr(..) => self.visit_fn_operand(func, terminator),
+            _ => {}
         }
     }
 
-    fn add_to_call_graph(
+    fn visit_fn_def(
         &mut self,
         callee_def_id: DefId,
-        is_virtual: Option<bool>,
+        callee_args: GenericArgsRef<'tcx>,
+        fn_operand: Option<&Operand<'tcx>>,
         terminator: &'tcx mir::Terminator<'tcx>,
     ) {
-        let caller_def_path = self.tcx.def_path_str(self.def_id);
-        let mut callee_def_path = self.tcx.def_path_str(callee_def_id);
-
-        if let Some(true) = is_virtual {
-            // Handle dynamic dispatch for trait objects
-            let re = Regex::new(r"(?<dyn>\w+)::(?<func>\w+)").unwrap();
-            if let Some(caps) = re.captures(&callee_def_path) {
-                callee_def_path = format!("(dyn trait) <* as {}>::{}", &caps["dyn"], &caps["func"]);
-            };
-            self.handle_virtual_call(
-                &caller_def_path,
-                callee_def_id,
-                &callee_def_path,
-                terminator,
-            );
-        } else {
-            // let callee_location = self.tcx.def_span(callee_def_id);
-            if callee_def_id == self.def_id {
-                // Recursion
-                println!("Warning! Find a recursion function which may cause stackoverflow!")
+        let typing_env = typing_env(self.tcx, self.instance);
+        match Instance::try_resolve(self.tcx, typing_env, callee_def_id, callee_args) {
+            Ok(Some(instance)) => match instance.def {
+                // Calls of `dyn Fn*` objects are resolved by the dataflow.
+                InstanceKind::Virtual(def_id, _) if is_fn_trait_method(self.tcx, def_id) => {
+                    if let Some(fn_operand) = fn_operand {
+                        self.visit_fn_operand(fn_operand, terminator);
+                    }
+                }
+                InstanceKind::Virtual(..) => self.add_virtual_call(instance, terminator),
+                InstanceKind::CloneShim(def_id, _) if self.tcx.is_closure_like(def_id) => {}
+                _ => self.add_call(instance, Some(terminator)),
+            },
+            // The callee depends on the generic parameters of the caller.
+            _ => {
+                if is_fn_trait_method(self.tcx, callee_def_id) {
+                    if let Some(fn_operand) = fn_operand {
+                        self.visit_fn_operand(fn_operand, terminator);
+                    }
+                } else if self.tcx.trait_of_assoc(callee_def_id).is_some() {
+                    for candidate in implementors(self.tcx, callee_def_id, None) {
+                        self.add_call(candidate, Some(terminator));
+                    }
+                } else {
+                    // Although failing to get specific type, callee is still useful.
+                    self.add_call(
+                        Instance::new_raw(callee_def_id, callee_args),
+                        Some(terminator),
+                    );
+                }
             }
-            self.add_in_call_graph(
-                &caller_def_path,
-                callee_def_id,
-                &callee_def_path,
-                terminator,
-            );
         }
     }
 
-    fn visit_terminator(&mut self, terminator: &'tcx mir::Terminator<'tcx>) {
-        if let mir::TerminatorKind::Call { func, .. } = &terminator.kind {
-            if let mir::Operand::Constant(constant) = func {
-                if let FnDef(callee_def_id, callee_substs) = constant.const_.ty().kind() {
-                    let ty_env = TypingEnv::post_analysis(self.tcx, self.def_id);
-                    if let Ok(Some(instance)) =
-                        Instance::try_resolve(self.tcx, ty_env, *callee_def_id, callee_substs)
+    /// Find the functions and closures that the function pointer or the closure may point to.
+    fn visit_fn_operand(
+        &mut self,
+        fn_operand: &Operand<'tcx>,
+        terminator: &'tcx mir::Terminator<'tcx>,
+    ) {
+        let (Some(dataflow), Some(place)) = (self.dataflow, fn_operand.place()) else {
+            return;
+        };
+        for source in dataflow.collect_fn_sources(place.local) {
+            match source {
+                FnSource::Param(param) => self.call_graph_info.param_calls.push(ParamCall {
+                    caller: self.caller_id,
+                    param,
+                    terminator,
+                }),
+                source => {
+                    if let Some(callee) =
+                        resolve_fn_source(self.tcx, self.instance, self.body, source)
                     {
-                        let mut is_virtual = false;
-                        // Try to analysis the specific type of callee.
-                        let instance_def_id = match instance.def {
-                            InstanceKind::Item(def_id) => Some(def_id),
-                            InstanceKind::Intrinsic(def_id) => Some(def_id),
-                            InstanceKind::VTableShim(def_id) => Some(def_id),
-                            InstanceKind::ReifyShim(def_id, _) => Some(def_id),
-                            InstanceKind::FnPtrShim(def_id, _) => Some(def_id),
-                            InstanceKind::Virtual(def_id, _) => {
-                                is_virtual = true;
-                                Some(def_id)
-                            }
-                            InstanceKind::ClosureOnceShim { call_once, .. } => Some(call_once),
-                            InstanceKind::ConstructCoroutineInClosureShim {
-                                coroutine_closure_def_id,
-                                ..
-                            } => Some(coroutine_closure_def_id),
-                            InstanceKind::ThreadLocalShim(def_id) => Some(def_id),
-                            InstanceKind::DropGlue(def_id, _) => Some(def_id),
-                            InstanceKind::FnPtrAddrShim(def_id, _) => Some(def_id),
-                            InstanceKind::AsyncDropGlueCtorShim(def_id, _) => Some(def_id),
-                            InstanceKind::CloneShim(def_id, _) => {
-                                if !self.tcx.is_closure_like(def_id) {
-                                    // Not a closure
-                                    Some(def_id)
-                                } else {
-                                    None
-                                }
-                            }
-                            _ => todo!(),
-                        };
-                        if let Some(instance_def_id) = instance_def_id {
-                            self.add_to_call_graph(instance_def_id, Some(is_virtual), terminator);
-                        }
-                    } else {
-                        // Although failing to get specific type, callee is still useful.
-                        self.add_to_call_graph(*callee_def_id, None, terminator);
+                        self.add_call(callee, Some(terminator));
                     }
                 }
             }
         }
     }
 
-    fn handle_virtual_call(
+    fn add_call(
         &mut self,
-        caller_def_path: &String,
-        stub_def_id: DefId, // Callee is the dynamic call stub, i.e. the fn definition in trait
-        stub_def_path: &String,
-        terminator: &'tcx mir::Terminator<'tcx>,
+        callee: Instance<'tcx>,
+        terminator: Option<&'tcx mir::Terminator<'tcx>>,
     ) {
-        // Step 1: Add an edge from caller to the virtual function (stub);
-        let mut visited = false;
-        let stub_id = if let Some(id) = self.call_graph_info.get_node_by_path(stub_def_path) {
-            // Node exists, suggesting we have already analyzed this virtual function
-            visited = true;
-            id
-        } else {
-            self.call_graph_info.add_node(stub_def_id, stub_def_path)
-        };
-        let caller_id = self
-            .call_graph_info
-            .get_node_by_path(caller_def_path)
-            .unwrap(); // This must be Some since the caller must have been added to graph
+        if callee == self.instance {
+            // Recursion
+            println!("Warning! Find a recursion function which may cause stackoverflow!")
+        }
+        let callee = bounded_instance(self.tcx, callee);
+        let callee_id = self.call_graph_info.add_node(self.tcx, callee);
         self.call_graph_info
-            .add_funciton_call_edge(caller_id, stub_id, Some(terminator));
+            .add_funciton_call_edge(self.caller_id, callee_id, terminator);
+        self.callees.push(callee);
+    }
 
-        // If this function has already been analyzed, return;
+    /// Add an edge from the caller to the virtual function (stub), and edges from the stub to
+    /// all the implementors of the virtual function.
+    fn add_virtual_call(&mut self, stub: Instance<'tcx>, terminator: &'tcx mir::Terminator<'tcx>) {
+        // If the stub exists, we have already analyzed this virtual function.
+        let visited = self.call_graph_info.get_node(stub).is_some();
+        let stub_id = self.call_graph_info.add_node(self.tcx, stub);
+        self.call_graph_info
+            .add_funciton_call_edge(self.caller_id, stub_id, Some(terminator));
         if visited {
             return;
         }
-
-        // Step 2: Find all impls of the virtual function;
-        let mut candidates: HashSet<DefId> = HashSet::new();
-        if let Some(trait_def_id) = self.tcx.trait_of_assoc(stub_def_id) {
-            rap_debug!(
-                "[Callgraph] Virtual fn {:?} belongs to trait {:?}",
-                stub_def_id,
-                trait_def_id
-            );
-            for impl_id in self.tcx.all_impls(trait_def_id) {
-                let impl_map = self.tcx.impl_item_implementor_ids(impl_id);
-                if let Some(candidate_def_id) = impl_map.get(&stub_def_id) {
-                    candidates.insert(*candidate_def_id);
-                }
-            }
-        }
+        let candidates = implementors(self.tcx, stub.def_id(), self.constructed_types);
         rap_debug!(
             "[Callgraph] Implementors of {:?}: {:?}",
-            stub_def_id,
+            stub.def_id(),
             candidates
         );
-
-        // Step 3: For each implementor, add an edge from the stub to it.
-        for candidate_def_id in candidates {
-            let candidate_def_path = self.tcx.def_path_str(candidate_def_id);
-            let callee_id =
-                if let Some(id) = self.call_graph_info.get_node_by_path(&candidate_def_path) {
-                    id
-                } else {
-                    self.call_graph_info
-                        .add_node(candidate_def_id, &candidate_def_path)
-                };
+        for candidate in candidates {
+            let callee_id = self.call_graph_info.add_node(self.tcx, candidate);
             self.call_graph_info
                 .add_funciton_call_edge(stub_id, callee_id, None);
+            self.callees.push(candidate);
         }
     }
 }
+
+/// Find the implementations of the trait method with class hierarchy analysis, including the
+/// default body of the method if some impl does not override it.
+fn implementors<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    trait_fn: DefId,
+    constructed_types: Option<&ConstructedTypes>,
+) -> Vec<Instance<'tcx>> {
+    let Some(trait_def_id) = tcx.trait_of_assoc(trait_fn) else {
+        return Vec::new();
+    };
+    let mut candidates = HashSet::new();
+    let mut inherits_default = false;
+    for impl_id in tcx.all_impls(trait_def_id) {
+        if constructed_types.is_some_and(|types| !types.may_construct(tcx, trait_def_id, impl_id)) {
+            continue;
+        }
+        match tcx.impl_item_implementor_ids(impl_id).get(&trait_fn) {
+            Some(candidate) => {
+                candidates.insert(*candidate);
+            }
+            None => inherits_default = true,
+        }
+    }
+    if inherits_default && tcx.associated_item(trait_fn).defaultness(tcx).has_value() {
+        candidates.insert(trait_fn);
+    }
+    let mut candidates: Vec<_> = candidates
+        .into_iter()
+        .map(|def_id| default_instance(tcx, def_id))
+        .collect();
+    candidates.sort_by_key(|instance| tcx.def_path_str(instance.def_id()));
+    candidates
+}
+
+/// Resolve a function item or a closure found in the body of the instance.
+pub(crate) fn resolve_fn_source<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    instance: Instance<'tcx>,
+    body: &mir::Body<'tcx>,
+    source: FnSource,
+) -> Option<Instance<'tcx>> {
+    match source {
+        FnSource::FnItem(def_id) => Some(default_instance(tcx, def_id)),
+        FnSource::Closure(def_id, local) => closure_instance(tcx, instance, body, def_id, local),
+        FnSource::Param(_) => None,
+    }
+}
+
+fn closure_instance<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    instance: Instance<'tcx>,
+    body: &mir::Body<'tcx>,
+    def_id: DefId,
+    local: Local,
+) -> Option<Instance<'tcx>> {
+    let ty = monomorphize(tcx, instance, body.local_decls.get(local)?.ty);
+    match ty.kind() {
+        ty::Closure(closure_def_id, args) if *closure_def_id == def_id => {
+            Some(Instance::new_raw(def_id, args))
+        }
+        _ => None,
+    }
+}
+
+/// The instance of a function item used without generic arguments, e.g., as a candidate of a
+/// virtual call.
+pub(crate) fn default_instance(tcx: TyCtxt<'_>, def_id: DefId) -> Instance<'_> {
+    if tcx.generics_of(def_id).requires_monomorphization(tcx) {
+        Instance::new_raw(def_id, GenericArgs::identity_for_item(tcx, def_id))
+    } else {
+        Instance::mono(tcx, def_id)
+    }
+}
+
+/// The instance, or the identity instance of the function if its generic arguments are longer
+/// than `INSTANCE_LENGTH_LIMIT`. This stops polymorphic recursion, e.g., `f::<T>` calling
+/// `f::<Vec<T>>`, from being instantiated endlessly.
+pub(crate) fn bounded_instance<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    instance: Instance<'tcx>,
+) -> Instance<'tcx> {
+    let length: usize = instance.args.iter().map(|arg| arg.walk().count()).sum();
+    match instance.def {
+        InstanceKind::Item(def_id) if length > INSTANCE_LENGTH_LIMIT => {
+            default_instance(tcx, def_id)
+        }
+        _ => instance,
+    }
+}
+
+pub(crate) fn typing_env<'tcx>(tcx: TyCtxt<'tcx>, instance: Instance<'tcx>) -> TypingEnv<'tcx> {
+    if instance.args.has_param() {
+        TypingEnv::post_analysis(tcx, instance.def_id())
+    } else {
+        TypingEnv::fully_monomorphized()
+    }
+}
+
+/// Instantiate the type in the body of the instance with its generic arguments.
+pub(crate) fn monomorphize<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    instance: Instance<'tcx>,
+    ty: Ty<'tcx>,
+) -> Ty<'tcx> {
+    instance
+        .try_instantiate_mir_and_normalize_erasing_regions(
+            tcx,
+            typing_env(tcx, instance),
+            EarlyBinder::bind(ty),
+        )
+        .unwrap_or_else(|_| instance.instantiate_mir(tcx, EarlyBinder::bind(&ty)))
+}
+
+fn is_fn_trait_method(tcx: TyCtxt<'_>, def_id: DefId) -> bool {
+    tcx.trait_of_assoc(def_id)
+        .is_some_and(|trait_def_id| tcx.is_fn_trait(trait_def_id))
+}
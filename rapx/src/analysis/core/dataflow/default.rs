@@ -1,5 +1,6 @@
 use super::graph::*;
 use crate::analysis::core::dataflow::*;
+use rustc_data_structures::sync::par_map;
 
 pub struct DataFlowAnalyzer<'tcx> {
     pub tcx: TyCtxt<'tcx>,
@@ -78,14 +79,26 @@ impl<'tcx> DataFlowAnalyzer<'tcx> {
     }
 
     pub fn build_graphs(&mut self) {
-        for local_def_id in self.tcx.iter_local_def_id() {
-            let def_kind = self.tcx.def_kind(local_def_id);
-            if matches!(def_kind, DefKind::Fn) || matches!(def_kind, DefKind::AssocFn) {
-                if self.tcx.hir_maybe_body_owned_by(local_def_id).is_some() {
-                    let def_id = local_def_id.to_def_id();
-                    self.build_graph(def_id);
-                }
-            }
+        let def_ids: Vec<DefId> = self
+            .tcx
+            .iter_local_def_id()
+            .filter(|local_def_id| {
+                matches!(
+                    self.tcx.def_kind(*local_def_id),
+                    DefKind::Fn | DefKind::AssocFn
+                ) && self.tcx.hir_maybe_body_owned_by(*local_def_id).is_some()
+            })
+            .map(|local_def_id| local_def_id.to_def_id())
+            .collect();
+        // The graphs are intraprocedural, so the functions are independent of each other.
+        let tcx = self.tcx;
+        let graphs: Vec<HashMap<DefId, Graph>> = par_map(def_ids, |def_id| {
+            let mut analyzer = DataFlowAnalyzer::new(tcx, false);
+            analyzer.build_graph(def_id);
+            analyzer.graphs
+        });
+        for (def_id, graph) in graphs.into_iter().flatten() {
+            self.graphs.entry(def_id).or_insert(graph);
         }
     }
 
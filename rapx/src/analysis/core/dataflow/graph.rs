@@ -1,5 +1,5 @@
 use std::cell::Cell;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use rustc_hir::def_id::DefId;
 use rustc_index::IndexVec;
@@ -35,6 +35,19 @@ pub struct Graph {
     pub edges: GraphEdges,
     pub n_locals: usize,
     pub closures: HashSet<DefId>,
+    // the function items used as constants, e.g., `foo as fn()`, indexed by the constant nodes
+    pub fn_items: HashMap<Local, DefId>,
+}
+
+/// Where a function pointer or a closure may come from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FnSource {
+    /// A function item.
+    FnItem(DefId),
+    /// A closure constructed at the local.
+    Closure(DefId, Local),
+    /// A parameter of the function, which should be resolved at its call sites.
+    Param(Local),
 }
 
 impl From<Graph> for DataFlowGraph {
@@ -58,6 +71,7 @@ impl Graph {
             edges: GraphEdges::new(),
             n_locals,
             closures: HashSet::new(),
+            fn_items: HashMap::new(),
         }
     }
 
@@ -104,7 +118,10 @@ impl Graph {
                     Const::Unevaluated(_, ty) => ty.to_string(),
                     Const::Ty(ty, _) => ty.to_string(),
                 };
-                self.add_const_edge(src_desc, src_ty, dst, EdgeOp::Const);
+                let edge_idx = self.add_const_edge(src_desc, src_ty, dst, EdgeOp::Const);
+                if let TyKind::FnDef(def_id, _) = boxed_const_op.const_.ty().kind() {
+                    self.fn_items.insert(self.edges[edge_idx].src, *def_id);
+                }
             }
         }
     }
@@ -277,7 +294,7 @@ impl Graph {
                         }
                     }
                 }
-                Operand::Move(_) => {
+                Operand::Move(_) | Operand::Copy(_) => {
                     self.add_operand(func, dst); //the func is a place
                     for op in args.iter() {
                         //rustc version related
@@ -285,10 +302,6 @@ impl Graph {
                     }
                     self.nodes[dst].ops[seq] = NodeOp::CallOperand;
                 }
-                _ => {
-                    println!("{:?}", func);
-                    todo!();
-                }
             }
             self.nodes[dst].span = terminator.source_info.span;
             self.nodes[dst].seq = seq + 1;
@@ -454,6 +467,48 @@ impl Graph {
         }
     }
 
+    // Trace the value backwards through moves, casts, references and aggregates to find the
+    // function items, closures and parameters that a function pointer or closure may come from.
+    pub fn collect_fn_sources(&self, local: Local) -> HashSet<FnSource> {
+        let mut sources = HashSet::new();
+        let mut node_operator = |graph: &Graph, idx: Local| -> DFSStatus {
+            if let Some(def_id) = graph.fn_items.get(&idx) {
+                sources.insert(FnSource::FnItem(*def_id));
+                return DFSStatus::Stop;
+            }
+            if (1..=graph.argc).contains(&idx.as_usize()) {
+                sources.insert(FnSource::Param(idx));
+            }
+            let mut status = DFSStatus::Stop;
+            for op in graph.nodes[idx].ops.iter() {
+                match op {
+                    NodeOp::Aggregate(AggKind::Closure(def_id)) => {
+                        sources.insert(FnSource::Closure(*def_id, idx));
+                    }
+                    NodeOp::Nop
+                    | NodeOp::Use
+                    | NodeOp::Cast
+                    | NodeOp::Ref
+                    | NodeOp::CopyForDeref
+                    | NodeOp::Aggregate(_) => status = DFSStatus::Continue,
+                    // the return value of a call is not traced
+                    _ => {}
+                }
+            }
+            status
+        };
+        let mut seen = HashSet::new();
+        self.dfs(
+            local,
+            Direction::Upside,
+            &mut node_operator,
+            &mut Graph::always_true_edge_validator,
+            true,
+            &mut seen,
+        );
+        sources
+    }
+
     pub fn is_connected(&self, idx_1: Local, idx_2: Local) -> bool {
         let target = idx_2;
         let find = Cell::new(false);
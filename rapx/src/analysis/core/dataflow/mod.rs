@@ -10,7 +10,10 @@ use std::{
     process::Command,
 };
 
-use crate::{analysis::Analysis, utils::source::get_fn_name_byid};
+use crate::{
+    analysis::Analysis,
+    utils::{json::stable_def_path_tls, source::get_fn_name_byid},
+};
 
 use rustc_hir::{def::DefKind, def_id::DefId};
 use rustc_index::IndexVec;
@@ -19,6 +22,7 @@ use rustc_middle::{
     ty::TyCtxt,
 };
 use rustc_span::Span;
+use serde::Serialize;
 
 pub type Arg2Ret = IndexVec<Local, bool>;
 pub type Arg2RetMap = HashMap<DefId, IndexVec<Local, bool>>;
@@ -92,10 +96,43 @@ impl fmt::Display for Arg2RetWrapper {
     }
 }
 
+#[derive(Serialize)]
+struct Arg2RetJson {
+    function: String,
+    /// The arguments (by local index) that the return value depends on.
+    args: Vec<usize>,
+}
+
+/// The JSON layout is a list of `{ "function", "args" }` sorted by def path.
+impl Serialize for Arg2RetMapWrapper {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut results: Vec<_> = self
+            .0
+            .iter()
+            .map(|(def_id, arg2ret)| Arg2RetJson {
+                function: stable_def_path_tls(*def_id),
+                args: arg2ret
+                    .iter_enumerated()
+                    .filter(|(local, depends)| local.as_u32() > 0 && **depends)
+                    .map(|(local, _)| local.as_usize())
+                    .collect(),
+            })
+            .collect();
+        results.sort_by(|a, b| a.function.cmp(&b.function));
+        results.serialize(serializer)
+    }
+}
+
 impl fmt::Display for Arg2RetMapWrapper {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(f, "=== Print dataflow analysis results ===")?;
-        for (def_id, arg2ret) in &self.0 {
+        // Sorted by the def paths, as the order of the map varies between runs.
+        let mut results: Vec<_> = self.0.iter().collect();
+        results.sort_by_cached_key(|(def_id, _)| stable_def_path_tls(**def_id));
+        for (def_id, arg2ret) in results {
             let fn_name = get_fn_name_byid(def_id);
             writeln!(
                 f,
@@ -159,7 +196,10 @@ impl Debug for DataFlowGraphWrapper {
 impl Display for DataFlowGraphMapWrapper {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(f, "===Print dataflow analysis resuts===")?;
-        for (def_id, dfg) in &self.0 {
+        // Sorted by the def paths, as the order of the map varies between runs.
+        let mut results: Vec<_> = self.0.iter().collect();
+        results.sort_by_cached_key(|(def_id, _)| stable_def_path_tls(**def_id));
+        for (def_id, dfg) in results {
             let fn_name = get_fn_name_byid(def_id);
             writeln!(
                 f,
@@ -174,7 +214,10 @@ impl Display for DataFlowGraphMapWrapper {
 
 impl Debug for DataFlowGraphMapWrapper {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for (def_id, dfg) in &self.0 {
+        // Sorted by the def paths, as the order of the map varies between runs.
+        let mut results: Vec<_> = self.0.iter().collect();
+        results.sort_by_cached_key(|(def_id, _)| stable_def_path_tls(**def_id));
+        for (def_id, dfg) in results {
             writeln!(
                 f,
                 "DefId: {:?}\n{:?}",
@@ -3,5 +3,8 @@ pub mod api_dependency;
 pub mod callgraph;
 pub mod dataflow;
 pub mod ownedheap_analysis;
+pub mod ownership_model;
 pub mod range_analysis;
+pub mod scheduler;
 pub mod ssa_transform;
+pub mod summary_cache;
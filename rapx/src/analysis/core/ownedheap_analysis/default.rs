@@ -1,7 +1,8 @@
 use rustc_abi::VariantIdx;
 use rustc_middle::{
     mir::{
-        BasicBlock, BasicBlockData, Body, Local, LocalDecl, Operand, TerminatorKind,
+        BasicBlock, BasicBlockData, Body, Local, LocalDecl, Operand, RETURN_PLACE, SourceInfo,
+        TerminatorKind,
         visit::{TyContext, Visitor},
     },
     ty::{
@@ -9,7 +10,7 @@ use rustc_middle::{
         TypeSuperVisitable, TypeVisitable, TypeVisitor,
     },
 };
-use rustc_span::def_id::DefId;
+use rustc_span::{DUMMY_SP, def_id::DefId};
 use std::{collections::HashMap, ops::ControlFlow};
 
 use super::*;
@@ -34,10 +35,14 @@ impl<'tcx> Analysis for OwnedHeapAnalyzer<'tcx> {
     }
 }
 
-impl<'tcx> OwnedHeapAnalysis for OwnedHeapAnalyzer<'tcx> {
+impl<'tcx> OwnedHeapAnalysis<'tcx> for OwnedHeapAnalyzer<'tcx> {
     fn get_all_items(&self) -> OHAResultMap {
         self.adt_heap.clone()
     }
+
+    fn query(&mut self, ty: Ty<'tcx>) -> OwnedHeapTree {
+        self.query_tree(ty, &mut Vec::new())
+    }
 }
 
 // This function is aiming at resolving problems due to 'TyContext' not implementing 'Clone' trait,
@@ -129,16 +134,6 @@ impl<'tcx> OwnedHeapAnalyzer<'tcx> {
     //
     // Those 2 parts can accelerate heap-heap inference in the data-flow analysis.
     pub fn start(&mut self) {
-        #[inline(always)]
-        fn start_channel<M>(mut method: M, v_did: &Vec<DefId>)
-        where
-            M: FnMut(DefId) -> (),
-        {
-            for did in v_did {
-                method(*did);
-            }
-        }
-
         #[inline(always)]
         fn show_heap(ref_type_analysis: &mut OwnedHeapAnalyzer) {
             for elem in ref_type_analysis.adt_heap() {
@@ -170,15 +165,168 @@ impl<'tcx> OwnedHeapAnalyzer<'tcx> {
         }
 
         let dids: Vec<DefId> = self.adt_recorder.iter().map(|did| *did).collect();
-
-        start_channel(|did| self.extract_raw_generic(did), &dids);
-        start_channel(|did| self.extract_raw_generic_prop(did), &dids);
-        start_channel(|did| self.extract_phantom_unit(did), &dids);
-        start_channel(|did| self.extract_heap_prop(did), &dids);
+        self.extract(&dids);
 
         show_heap(self);
     }
 
+    // Each sub-phase goes through all the adts before the next one starts.
+    fn extract(&mut self, dids: &[DefId]) {
+        for did in dids {
+            self.extract_raw_generic(*did);
+        }
+        for did in dids {
+            self.extract_raw_generic_prop(*did);
+        }
+        for did in dids {
+            self.extract_phantom_unit(*did);
+        }
+        for did in dids {
+            self.extract_heap_prop(*did);
+        }
+    }
+
+    // Analyze the adts reachable from the type that are not met in the crate yet.
+    fn extract_on_demand(&mut self, ty: Ty<'tcx>) {
+        let ty_context = TyContext::LocalDecl {
+            local: RETURN_PLACE,
+            source_info: SourceInfo::outermost(DUMMY_SP),
+        };
+        self.visit_ty(ty, ty_context);
+        let dids: Vec<DefId> = self
+            .adt_recorder
+            .iter()
+            .filter(|did| !self.adt_heap.contains_key(did))
+            .copied()
+            .collect();
+        self.extract(&dids);
+    }
+
+    // The adts of the local crate are unfolded into their fields. The adts of other crates are
+    // summarized by the results above, where a generic parameter holding a heap owner makes the
+    // adt a heap owner, e.g., 'Option<String>'.
+    fn query_tree(&mut self, ty: Ty<'tcx>, stack: &mut Vec<Ty<'tcx>>) -> OwnedHeapTree {
+        let name = format!("{}", ty);
+        match ty.kind() {
+            TyKind::Bool
+            | TyKind::Char
+            | TyKind::Int(..)
+            | TyKind::Uint(..)
+            | TyKind::Float(..)
+            | TyKind::Str
+            | TyKind::Never
+            | TyKind::FnDef(..)
+            | TyKind::FnPtr(..)
+            | TyKind::RawPtr(..)
+            | TyKind::Ref(..) => OwnedHeapTree::leaf(name, OwnedHeap::False),
+            TyKind::Adt(adt_def, substs) => {
+                if !self.adt_heap.contains_key(&adt_def.did()) {
+                    self.extract_on_demand(ty);
+                }
+                let units = self
+                    .adt_heap
+                    .get(&adt_def.did())
+                    .cloned()
+                    .unwrap_or_default();
+                let is_owner = units.iter().any(|unit| unit.0 == OwnedHeap::True);
+                // A recursive adt owns heap only through its other fields.
+                if stack.contains(&ty) {
+                    let heap = if is_owner {
+                        OwnedHeap::True
+                    } else {
+                        OwnedHeap::False
+                    };
+                    return OwnedHeapTree::leaf(name, heap);
+                }
+                stack.push(ty);
+                let tree = if adt_def.did().is_local() {
+                    let mut children = Vec::new();
+                    for variant in adt_def.variants().iter() {
+                        for field in &variant.fields {
+                            let label = if adt_def.is_enum() {
+                                format!("{}.{}", variant.name, field.name)
+                            } else {
+                                field.name.to_string()
+                            };
+                            let field_ty = field.ty(self.tcx, substs);
+                            children.push((label, self.query_tree(field_ty, stack)));
+                        }
+                    }
+                    let mut tree = OwnedHeapTree::node(name, children);
+                    if is_owner {
+                        tree.heap = OwnedHeap::True;
+                    }
+                    tree
+                } else {
+                    let generics = self.tcx.generics_of(adt_def.did());
+                    let mut children = Vec::new();
+                    let mut heap = if is_owner {
+                        OwnedHeap::True
+                    } else {
+                        OwnedHeap::False
+                    };
+                    for (index, arg) in substs.iter().enumerate() {
+                        let Some(arg_ty) = arg.as_type() else {
+                            continue;
+                        };
+                        let child = self.query_tree(arg_ty, stack);
+                        let is_raw_param = units
+                            .iter()
+                            .any(|unit| unit.1.get(index).copied().unwrap_or(false));
+                        if is_raw_param && heap != OwnedHeap::True && child.heap != OwnedHeap::False
+                        {
+                            heap = child.heap;
+                        }
+                        let label = generics.param_at(index, self.tcx).name.to_string();
+                        children.push((label, child));
+                    }
+                    OwnedHeapTree {
+                        ty: name,
+                        heap,
+                        children,
+                    }
+                };
+                stack.pop();
+                tree
+            }
+            TyKind::Tuple(tys) => {
+                let children = tys
+                    .iter()
+                    .enumerate()
+                    .map(|(index, ty)| (index.to_string(), self.query_tree(ty, stack)))
+                    .collect();
+                OwnedHeapTree::node(name, children)
+            }
+            TyKind::Array(elem, ..) | TyKind::Slice(elem) => {
+                let children = vec![("[_]".to_owned(), self.query_tree(*elem, stack))];
+                OwnedHeapTree::node(name, children)
+            }
+            TyKind::Closure(def_id, substs) => {
+                let names: Vec<String> = match def_id.as_local() {
+                    Some(local_def_id) => self
+                        .tcx
+                        .closure_captures(local_def_id)
+                        .iter()
+                        .map(|captured| captured.to_string(self.tcx))
+                        .collect(),
+                    None => Vec::new(),
+                };
+                let upvars: Vec<Ty<'tcx>> = substs.as_closure().upvar_tys().iter().collect();
+                let children = upvars
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, ty)| {
+                        let label = names.get(index).cloned().unwrap_or(index.to_string());
+                        (label, self.query_tree(ty, stack))
+                    })
+                    .collect();
+                OwnedHeapTree::node(name, children)
+            }
+            // Trait objects, type parameters, projections and the like.
+            _ => OwnedHeapTree::leaf(name, OwnedHeap::Unknown),
+        }
+    }
+
     // Extract params in adt types, the 'param' means one generic parameter acting like 'T', 'A', etc...
     // In the sub-visitor RawGeneric, it will visit the given type recursively, and extract all params.
     //
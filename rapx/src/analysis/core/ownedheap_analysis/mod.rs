@@ -9,7 +9,11 @@ use std::{
     fmt::{self, Display},
 };
 
-use crate::{Analysis, utils::source::get_fn_name_byid};
+use crate::{
+    Analysis,
+    utils::{json::stable_def_path_tls, source::get_fn_name_byid},
+};
+use serde::Serialize;
 
 #[repr(u8)]
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
@@ -81,18 +85,117 @@ impl OHAResultMapWrapper {
         format!("{:?}, <{}>", heap, bit_str)
     }
 }
+#[derive(Serialize)]
+struct OHAVariantJson<'a> {
+    owned_heap: &'static str,
+    /// Whether each type parameter may hold data on heap.
+    params: &'a [bool],
+}
+
+#[derive(Serialize)]
+struct OHAResultJson<'a> {
+    #[serde(rename = "type")]
+    ty: String,
+    variants: Vec<OHAVariantJson<'a>>,
+}
+
+/// The JSON layout is a list of `{ "type", "variants": [{ "owned_heap", "params" }] }` sorted by
+/// def path, where `owned_heap` is one of `true`, `false` and `unknown`.
+impl Serialize for OHAResultMapWrapper {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut results: Vec<_> = self
+            .0
+            .iter()
+            .map(|(def_id, units)| OHAResultJson {
+                ty: stable_def_path_tls(*def_id),
+                variants: units
+                    .iter()
+                    .map(|(heap, params)| OHAVariantJson {
+                        owned_heap: match heap {
+                            OwnedHeap::False => "false",
+                            OwnedHeap::True => "true",
+                            OwnedHeap::Unknown => "unknown",
+                        },
+                        params,
+                    })
+                    .collect(),
+            })
+            .collect();
+        results.sort_by(|a, b| a.ty.cmp(&b.ty));
+        results.serialize(serializer)
+    }
+}
+
+/// The heap ownership of a type at a use site, together with that of its parts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OwnedHeapTree {
+    pub ty: String,
+    pub heap: OwnedHeap,
+    /// The parts labeled by the names of the fields (`Variant.field` for enums), the indices of
+    /// the tuple elements, `[_]` for the array elements, the names of the captured variables, or
+    /// the names of the type parameters of an ADT from other crates.
+    pub children: Vec<(String, OwnedHeapTree)>,
+}
+
+impl OwnedHeapTree {
+    pub fn leaf(ty: String, heap: OwnedHeap) -> Self {
+        Self {
+            ty,
+            heap,
+            children: Vec::new(),
+        }
+    }
+
+    /// A type owns heap if any part does, and is unknown if no part does but some part is unknown.
+    pub fn node(ty: String, children: Vec<(String, OwnedHeapTree)>) -> Self {
+        let heaps = children.iter().map(|(_, child)| child.heap);
+        let heap = heaps.fold(OwnedHeap::False, |heap, child| match (heap, child) {
+            (OwnedHeap::True, _) | (_, OwnedHeap::True) => OwnedHeap::True,
+            (OwnedHeap::Unknown, _) | (_, OwnedHeap::Unknown) => OwnedHeap::Unknown,
+            _ => OwnedHeap::False,
+        });
+        Self { ty, heap, children }
+    }
+}
+
+impl Display for OwnedHeapTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {:?}", self.ty, self.heap)?;
+        if self.children.is_empty() {
+            return Ok(());
+        }
+        let children = self
+            .children
+            .iter()
+            .map(|(label, child)| format!("{} = {}", label, child))
+            .collect::<Vec<_>>()
+            .join(", ");
+        write!(f, " {{ {} }}", children)
+    }
+}
+
 /// This trait provides features for owned heap analysis, which is used to determine if a type owns
 /// memory on heap. Owned heap should be automatically released by default.
-pub trait OwnedHeapAnalysis: Analysis {
+pub trait OwnedHeapAnalysis<'tcx>: Analysis {
     /// The function returns the result of owned heap analysis for all types.
     fn get_all_items(&self) -> OHAResultMap;
 
+    /// Return the heap ownership of any type, e.g., a tuple, an array, a closure or an ADT with
+    /// some generic parameters, and of each of its parts. The ADTs not met in the crate, e.g., of
+    /// other crates, are analyzed on demand. Trait objects and type parameters are `Unknown`.
+    fn query(&mut self, ty: Ty<'tcx>) -> OwnedHeapTree;
+
     /// If a type is a heap owner, the function returns Result<true>. If the specified type is
     /// illegal, the function returns Err.
-    fn is_heapowner<'tcx>(hares: OHAResultMap, ty: Ty<'tcx>) -> Result<bool, &'static str> {
+    fn is_heapowner(hares: OHAResultMap, ty: Ty<'tcx>) -> Result<bool, &'static str> {
         match ty.kind() {
             TyKind::Adt(adtdef, ..) => {
-                let heapinfo = hares.get(&adtdef.0.0.did).unwrap();
+                let heapinfo = hares
+                    .get(&adtdef.0.0.did)
+                    .ok_or("The ADT is not analyzed")?;
                 for item in heapinfo {
                     if item.0 == OwnedHeap::True {
                         return Ok(true);
@@ -106,10 +209,12 @@ pub trait OwnedHeapAnalysis: Analysis {
 
     /// A type might be a heap owner if it is not a heap owner directly but contains type
     /// parameters that may make the type become a heap owner after monomorphization.
-    fn maybe_heapowner<'tcx>(hares: OHAResultMap, ty: Ty<'tcx>) -> Result<bool, &'static str> {
+    fn maybe_heapowner(hares: OHAResultMap, ty: Ty<'tcx>) -> Result<bool, &'static str> {
         match ty.kind() {
             TyKind::Adt(adtdef, ..) => {
-                let heapinfo = hares.get(&adtdef.0.0.did).unwrap();
+                let heapinfo = hares
+                    .get(&adtdef.0.0.did)
+                    .ok_or("The ADT is not analyzed")?;
                 for item in heapinfo {
                     if item.0 == OwnedHeap::False && item.1.contains(&true) {
                         return Ok(true);
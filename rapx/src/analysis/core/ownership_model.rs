@@ -0,0 +1,244 @@
+//! A declarative model of the ownership effects of library functions.
+//!
+//! MoP and SafeDrop take the effects of a modeled function from the model instead of analyzing
+//! or guessing its body, e.g., that `ptr::drop_in_place` frees its argument. A curated model of
+//! `core`, `alloc` and `std` is shipped with RAPx, and more entries can be added with the
+//! `[[model]]` tables of `rapx.toml`, which take precedence over the shipped ones:
+//! ```toml
+//! [[model]]
+//! path = "my_crate::pool::Pool::release"
+//! frees = [2]
+//! ```
+//! The arguments are numbered from 1. The crates `std`, `core` and `alloc` are interchangeable
+//! in the paths, and `*` matches any sequence of characters. An entry of a trait method also
+//! applies to its implementations, e.g., `core::ops::Drop::drop`. A modeled function has exactly
+//! the effects listed in its entry, so an entry without effects means the function neither frees
+//! nor aliases its arguments.
+
+use crate::analysis::core::alias_analysis::default::MopAAFact;
+use regex::Regex;
+use rustc_data_structures::fx::FxHashMap;
+use rustc_hir::def_id::{DefId, LOCAL_CRATE};
+use rustc_middle::ty::{TyCtxt, print::with_no_trimmed_paths};
+use serde::Deserialize;
+use std::sync::{Mutex, OnceLock};
+
+static MODEL: OnceLock<OwnershipModel> = OnceLock::new();
+
+/// The entries shipped with RAPx, in the same format as `rapx.toml`.
+const LIBRARY_MODEL: &str = include_str!("../utils/assets/ownership_model.toml");
+
+/// The ownership effects of a function, as an entry of the model.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FnModel {
+    /// The def path pattern of the function.
+    pub path: String,
+    /// The arguments freed by the function.
+    #[serde(default)]
+    pub frees: Vec<usize>,
+    /// The arguments whose ownership is moved into the return value, e.g., `Box::from_raw`.
+    #[serde(default)]
+    pub moves: Vec<usize>,
+    /// The arguments borrowed by the return value, which keep their ownership.
+    #[serde(default)]
+    pub aliases: Vec<usize>,
+    /// The arguments that are never freed nor used, e.g., `mem::forget`.
+    #[serde(default)]
+    pub leaks: Vec<usize>,
+}
+
+impl FnModel {
+    /// The aliases between the return value and the arguments, in the form of MoP summaries.
+    pub fn alias_facts(&self) -> Vec<MopAAFact> {
+        let moves = self
+            .moves
+            .iter()
+            .map(|arg| MopAAFact::new(0, true, true, *arg, true, true));
+        let aliases = self
+            .aliases
+            .iter()
+            .map(|arg| MopAAFact::new(0, true, false, *arg, true, true));
+        moves.chain(aliases).collect()
+    }
+}
+
+#[derive(Deserialize)]
+struct ModelFile {
+    model: Vec<FnModel>,
+}
+
+struct OwnershipModel {
+    /// The entries of `rapx.toml` followed by the shipped ones.
+    entries: Vec<(FnModel, Regex)>,
+    user_entries: Vec<FnModel>,
+    /// The entry of each function looked up so far.
+    resolved: Mutex<FxHashMap<DefId, Option<usize>>>,
+}
+
+impl OwnershipModel {
+    fn new(user_entries: Vec<FnModel>) -> Self {
+        let library: ModelFile =
+            toml::from_str(LIBRARY_MODEL).expect("Failed to parse the ownership model");
+        let entries = user_entries
+            .iter()
+            .cloned()
+            .chain(library.model)
+            .filter_map(|entry| match path_pattern_to_regex(&entry.path) {
+                Ok(regex) => Some((entry, regex)),
+                Err(err) => {
+                    rap_warn!("Skip the model of `{}`: {}", entry.path, err);
+                    None
+                }
+            })
+            .collect();
+        Self {
+            entries,
+            user_entries,
+            resolved: Mutex::new(FxHashMap::default()),
+        }
+    }
+
+    fn find(&self, tcx: TyCtxt<'_>, def_id: DefId) -> Option<usize> {
+        if let Some(index) = self.resolved.lock().unwrap().get(&def_id) {
+            return *index;
+        }
+        // The implementations of a trait method are covered by the entry of the method.
+        let paths: Vec<String> = std::iter::once(def_id)
+            .chain(tcx.trait_item_of(def_id))
+            .map(|def_id| model_path(tcx, def_id))
+            .collect();
+        let index = self
+            .entries
+            .iter()
+            .position(|(_, regex)| paths.iter().any(|path| regex.is_match(path)));
+        self.resolved.lock().unwrap().insert(def_id, index);
+        index
+    }
+}
+
+/// Add the entries of `rapx.toml` to the model; must be called before any function is analyzed.
+pub fn init(user_entries: Vec<FnModel>) {
+    let _ = MODEL.set(OwnershipModel::new(user_entries));
+}
+
+fn model() -> &'static OwnershipModel {
+    MODEL.get_or_init(|| OwnershipModel::new(Vec::new()))
+}
+
+/// The entries of `rapx.toml`, which the cached summaries depend on.
+pub fn user_entries() -> &'static [FnModel] {
+    &model().user_entries
+}
+
+/// The entry of the function, if it is modeled.
+pub fn lookup(tcx: TyCtxt<'_>, def_id: DefId) -> Option<&'static FnModel> {
+    let model = model();
+    model.find(tcx, def_id).map(|index| &model.entries[index].0)
+}
+
+/// The arguments freed by the function according to the model.
+pub fn freed_args(tcx: TyCtxt<'_>, def_id: DefId) -> &'static [usize] {
+    lookup(tcx, def_id).map_or(&[], |entry| entry.frees.as_slice())
+}
+
+/// The def path of the function as matched by the patterns of the model.
+pub fn model_path(tcx: TyCtxt<'_>, def_id: DefId) -> String {
+    normalize_path(&def_path(tcx, def_id))
+}
+
+/// The def path as written in the source, prefixed by the name of the local crate, e.g.,
+/// `my_crate::pool::Pool::release`.
+fn def_path(tcx: TyCtxt<'_>, def_id: DefId) -> String {
+    let path = with_no_trimmed_paths!(tcx.def_path_str(def_id));
+    if def_id.is_local() {
+        format!("{}::{}", tcx.crate_name(LOCAL_CRATE), path)
+    } else {
+        path
+    }
+}
+
+/// `std::` and `alloc::` are written as `core::`, since the items of `core` and `alloc` are
+/// re-exported by `std` under the same modules.
+pub fn normalize_path(path: &str) -> String {
+    for krate in ["std::", "alloc::"] {
+        if let Some(rest) = path.strip_prefix(krate) {
+            return format!("core::{}", rest);
+        }
+    }
+    path.to_string()
+}
+
+/// The regex of a path pattern of the model, where `*` matches any sequence of characters.
+pub fn path_pattern_to_regex(pattern: &str) -> Result<Regex, regex::Error> {
+    glob_to_regex(&normalize_path(pattern), &[(r"\*", ".*")])
+}
+
+/// The regex matching the whole glob, with each escaped wildcard replaced in order by its regex,
+/// e.g., `\*` by `.*`.
+pub fn glob_to_regex(glob: &str, wildcards: &[(&str, &str)]) -> Result<Regex, regex::Error> {
+    let escaped = wildcards
+        .iter()
+        .fold(regex::escape(glob), |escaped, (wildcard, regex)| {
+            escaped.replace(wildcard, regex)
+        });
+    Regex::new(&format!("^{}$", escaped))
+}
+
+/// A table of path patterns, e.g., the APIs known by a detector, matched against the paths given
+/// by [`model_path`]. The patterns are compiled on the first match, so that the tables can be
+/// `static`:
+/// ```ignore
+/// static FORGET: PathPatterns<&str> = PathPatterns::new(&["core::mem::forget"]);
+/// assert!(FORGET.matches("core::mem::forget"));
+/// ```
+pub struct PathPatterns<T: 'static> {
+    entries: &'static [T],
+    pattern: fn(&T) -> &'static str,
+    regexes: OnceLock<Vec<Regex>>,
+}
+
+impl PathPatterns<&'static str> {
+    pub const fn new(patterns: &'static [&'static str]) -> Self {
+        Self::with_key(patterns, |pattern| pattern)
+    }
+}
+
+impl<V: 'static> PathPatterns<(&'static str, V)> {
+    /// The table of the patterns with their values, e.g., the kind of each API.
+    pub const fn keyed(entries: &'static [(&'static str, V)]) -> Self {
+        Self::with_key(entries, |(pattern, _)| pattern)
+    }
+}
+
+impl<T: 'static> PathPatterns<T> {
+    /// The table of the entries whose patterns are given by `pattern`.
+    pub const fn with_key(entries: &'static [T], pattern: fn(&T) -> &'static str) -> Self {
+        Self {
+            entries,
+            pattern,
+            regexes: OnceLock::new(),
+        }
+    }
+
+    /// The first entry whose pattern matches the path.
+    pub fn find(&self, path: &str) -> Option<&'static T> {
+        self.regexes
+            .get_or_init(|| {
+                self.entries
+                    .iter()
+                    .map(|entry| {
+                        path_pattern_to_regex((self.pattern)(entry))
+                            .expect("Invalid path pattern of a detector")
+                    })
+                    .collect()
+            })
+            .iter()
+            .position(|regex| regex.is_match(path))
+            .map(|index| &self.entries[index])
+    }
+
+    pub fn matches(&self, path: &str) -> bool {
+        self.find(path).is_some()
+    }
+}
@@ -17,14 +17,14 @@ use crate::{
         },
         graphs::scc::Scc,
     },
-    rap_debug, rap_info,
+    rap_debug, rap_info, rap_warn,
 };
 
-use rustc_data_structures::fx::FxHashMap;
+use rustc_data_structures::{fx::FxHashMap, sync::par_map};
 use rustc_hir::{def::DefKind, def_id::DefId};
 use rustc_middle::{
     mir::{Body, Place},
-    ty::TyCtxt,
+    ty::{self, Ty, TyCtxt},
 };
 use std::{
     cell::RefCell,
@@ -36,7 +36,10 @@ use std::{
     rc::Rc,
 };
 
-use super::{PathConstraint, PathConstraintMap, RAResult, RAResultMap, RAVecResultMap};
+use super::{
+    IntDomain, IntRange, PathConstraint, PathConstraintMap, RAResult, RAResultMap, RAVecResultMap,
+    TypedRAResultMap,
+};
 pub struct RangeAnalyzer<'tcx, T: IntervalArithmetic + ConstConvert + Debug> {
     pub tcx: TyCtxt<'tcx>,
     pub debug: bool,
@@ -107,6 +110,30 @@ where
     }
 }
 
+/// The bodies analyzed: functions, methods and closures with MIR.
+fn analyzed_bodies(tcx: TyCtxt<'_>) -> Vec<DefId> {
+    tcx.iter_local_def_id()
+        .filter(|local_def_id| {
+            matches!(
+                tcx.def_kind(*local_def_id),
+                DefKind::Fn | DefKind::AssocFn | DefKind::Closure
+            )
+        })
+        .map(|local_def_id| local_def_id.to_def_id())
+        .filter(|def_id| tcx.is_mir_available(*def_id))
+        .collect()
+}
+
+/// Write the constraint graph of the function to `cg_dot/<function>_cg.dot` for debugging.
+fn dump_constraintgraph(tcx: TyCtxt<'_>, def_id: DefId, dot: &str) -> std::io::Result<()> {
+    let dir_path = PathBuf::from("cg_dot");
+    fs::create_dir_all(&dir_path)?;
+    let output_path = dir_path.join(format!("{}_cg.dot", tcx.def_path_str(def_id)));
+    File::create(&output_path)?.write_all(dot.as_bytes())?;
+    rap_debug!("Write the constraint graph to {}", output_path.display());
+    Ok(())
+}
+
 impl<'tcx, T> RangeAnalyzer<'tcx, T>
 where
     T: IntervalArithmetic + ConstConvert + Debug,
@@ -161,26 +188,17 @@ where
         cg.build_nuutila(false);
         // cg.rap_print_vars();
         // cg.rap_print_final_vars();
-        let dot_output = cg.to_dot();
+        if self.debug
+            && let Err(err) = dump_constraintgraph(self.tcx, def_id, &cg.to_dot())
+        {
+            rap_warn!("Failed to dump the constraint graph: {}", err);
+        }
         let vars_map = cg.get_vars().clone();
 
         self.cg_map.insert(def_id, Rc::new(RefCell::new(cg)));
         let mut vec = Vec::new();
         vec.push(RefCell::new(vars_map));
         self.vars_map.insert(def_id, vec);
-        let function_name = self.tcx.def_path_str(def_id);
-
-        let dir_path = PathBuf::from("cg_dot");
-        fs::create_dir_all(dir_path.clone()).unwrap();
-        let safe_filename = format!("{}_cg.dot", function_name);
-        let output_path = dir_path.join(format!("{}", safe_filename));
-
-        let mut file = File::create(&output_path).expect("cannot create file");
-        file.write_all(dot_output.as_bytes())
-            .expect("Could not write to file");
-
-        println!("Successfully generated graph.dot");
-        // println!("Run 'dot -Tpng -o graph.png graph.dot' to generate the image.");
     }
 
     fn only_caller_range_analysis(&mut self) {
@@ -190,35 +208,31 @@ where
         // PHASE 1: Build all ConstraintGraphs and the complete CallGraph first.
         // ====================================================================
         rap_debug!("PHASE 1: Building all ConstraintGraphs and the CallGraph...");
-        for local_def_id in self.tcx.iter_local_def_id() {
-            if matches!(self.tcx.def_kind(local_def_id), DefKind::Fn) {
-                let def_id = local_def_id.to_def_id();
-
-                if self.tcx.is_mir_available(def_id) {
-                    let mut body = self.tcx.optimized_mir(def_id).clone();
-                    let body_mut_ref = unsafe { &mut *(&mut body as *mut Body<'tcx>) };
-                    // Run SSA/ESSA passes
-                    let mut passrunner = PassRunner::new(self.tcx);
-                    passrunner.run_pass(body_mut_ref, ssa_def_id, essa_def_id);
-                    self.body_map.insert(def_id, body);
-                    // Print the MIR after SSA/ESSA passes
-                    rap_debug!("{:#?}", body_mut_ref.local_decls);
-                    if self.debug {
-                        print_diff(self.tcx, body_mut_ref, def_id.into());
-                        print_mir_graph(self.tcx, body_mut_ref, def_id.into());
-                    }
-
-                    self.ssa_places_mapping
-                        .insert(def_id, passrunner.places_map.clone());
-                    // rap_debug!("ssa_places_mapping: {:?}", self.ssa_places_mapping);
-                    // Build and store the constraint graph
-                    self.build_constraintgraph(body_mut_ref, def_id);
-                    // Visit for call graph construction
-                    let mut call_graph_visitor =
-                        CallGraphVisitor::new(self.tcx, def_id, body_mut_ref, &mut self.callgraph);
-                    call_graph_visitor.visit();
-                }
+        let def_ids = analyzed_bodies(self.tcx);
+        // Run SSA/ESSA passes in parallel, as they transform the functions independently.
+        let tcx = self.tcx;
+        let ssa_bodies: Vec<_> = par_map(def_ids, |def_id| {
+            let mut body = tcx.optimized_mir(def_id).clone();
+            let mut passrunner = PassRunner::new(tcx);
+            passrunner.run_pass(&mut body, ssa_def_id, essa_def_id);
+            (def_id, body, passrunner.places_map)
+        });
+        for (def_id, mut body, places_map) in ssa_bodies {
+            let body_mut_ref = unsafe { &mut *(&mut body as *mut Body<'tcx>) };
+            self.body_map.insert(def_id, body);
+            // Print the MIR after SSA/ESSA passes
+            rap_debug!("{:#?}", body_mut_ref.local_decls);
+            if self.debug {
+                print_diff(self.tcx, body_mut_ref, def_id);
+                print_mir_graph(self.tcx, body_mut_ref, def_id);
             }
+
+            self.ssa_places_mapping.insert(def_id, places_map);
+            // rap_debug!("ssa_places_mapping: {:?}", self.ssa_places_mapping);
+            // Build and store the constraint graph
+            self.build_constraintgraph(body_mut_ref, def_id);
+            // Visit for call graph construction
+            CallGraphVisitor::new(self.tcx, def_id, body_mut_ref, &mut self.callgraph).visit();
         }
         rap_debug!("PHASE 1 Complete. CallGraph built.");
         // self.callgraph.print_call_graph(); // Optional: for debugging
@@ -307,31 +321,7 @@ where
         def_id: DefId,
     ) -> Option<PathConstraint<'tcx>> {
         if self.tcx.is_mir_available(def_id) {
-            let mut body = self.tcx.optimized_mir(def_id).clone();
-            let body_mut_ref = unsafe { &mut *(&mut body as *mut Body<'tcx>) };
-
-            let mut cg: ConstraintGraph<'tcx, T> =
-                ConstraintGraph::new_without_ssa(body_mut_ref, self.tcx, def_id);
-            let mut graph = MopGraph::new(self.tcx, def_id);
-            graph.find_scc();
-            let paths: Vec<Vec<usize>> = graph.get_all_branch_sub_blocks_paths();
-            let result = cg.start_analyze_path_constraints(body_mut_ref, &paths);
-            rap_debug!(
-                "Paths for function {}: {:?}",
-                self.tcx.def_path_str(def_id),
-                paths
-            );
-            let switchbbs = cg.switchbbs.clone();
-            rap_debug!(
-                "Switch basicblocks for function {}: {:?}",
-                self.tcx.def_path_str(def_id),
-                switchbbs
-            );
-            rap_debug!(
-                "Path Constraints Analysis Result for function {}: {:?}",
-                self.tcx.def_path_str(def_id),
-                result
-            );
+            let result = Self::analyze_path_constraints(self.tcx, def_id);
             self.path_constraints.insert(def_id, result.clone());
             Some(result)
         } else {
@@ -339,42 +329,166 @@ where
         }
     }
     pub fn start_path_constraints_analysis(&mut self) {
-        for local_def_id in self.tcx.iter_local_def_id() {
-            if matches!(self.tcx.def_kind(local_def_id), DefKind::Fn) {
-                let def_id = local_def_id.to_def_id();
-
-                if self.tcx.is_mir_available(def_id) {
-                    let mut body = self.tcx.optimized_mir(def_id).clone();
-                    let body_mut_ref = unsafe { &mut *(&mut body as *mut Body<'tcx>) };
-
-                    let mut cg: ConstraintGraph<'tcx, T> =
-                        ConstraintGraph::new_without_ssa(body_mut_ref, self.tcx, def_id);
-                    let mut graph = MopGraph::new(self.tcx, def_id);
-                    graph.find_scc();
-                    // rap_info!("child_scc: {:?}\n", graph.child_scc);
-                    // rap_info!("scc_indices: {:?}\n", graph.scc_indices);
-                    // rap_info!("blocks: {:?}\n", graph.blocks);
-                    let paths: Vec<Vec<usize>> = graph.get_all_branch_sub_blocks_paths();
-                    let result = cg.start_analyze_path_constraints(body_mut_ref, &paths);
-                    rap_debug!(
-                        "Paths for function {}: {:?}",
-                        self.tcx.def_path_str(def_id),
-                        paths
-                    );
-                    let switchbbs = cg.switchbbs.clone();
-                    rap_debug!(
-                        "Switch basicblocks for function {}: {:?}",
-                        self.tcx.def_path_str(def_id),
-                        switchbbs
-                    );
-                    rap_debug!(
-                        "Path Constraints Analysis Result for function {}: {:?}",
-                        self.tcx.def_path_str(def_id),
-                        result
-                    );
-                    self.path_constraints.insert(def_id, result);
+        let def_ids = analyzed_bodies(self.tcx);
+        // The path constraints are intraprocedural, so the functions are analyzed in parallel.
+        let tcx = self.tcx;
+        let results: Vec<_> = par_map(def_ids, |def_id| {
+            (def_id, Self::analyze_path_constraints(tcx, def_id))
+        });
+        self.path_constraints.extend(results);
+    }
+    fn analyze_path_constraints(tcx: TyCtxt<'tcx>, def_id: DefId) -> PathConstraint<'tcx> {
+        let mut graph = MopGraph::new(tcx, def_id);
+        graph.find_scc();
+        // rap_info!("child_scc: {:?}\n", graph.child_scc);
+        // rap_info!("scc_indices: {:?}\n", graph.scc_indices);
+        // rap_info!("blocks: {:?}\n", graph.blocks);
+        let paths: Vec<Vec<usize>> = graph.get_all_branch_sub_blocks_paths();
+        rap_debug!(
+            "Paths for function {}: {:?}",
+            tcx.def_path_str(def_id),
+            paths
+        );
+        Self::path_constraints_of(tcx, def_id, &paths)
+    }
+
+    /// Extract the constraints of the given paths of the function, each a sequence of basic
+    /// blocks, e.g., the paths enumerated by other analyses.
+    pub fn path_constraints_of(
+        tcx: TyCtxt<'tcx>,
+        def_id: DefId,
+        paths: &[Vec<usize>],
+    ) -> PathConstraint<'tcx> {
+        let mut body = tcx.optimized_mir(def_id).clone();
+        let body_mut_ref = unsafe { &mut *(&mut body as *mut Body<'tcx>) };
+
+        let mut cg: ConstraintGraph<'tcx, T> =
+            ConstraintGraph::new_without_ssa(body_mut_ref, tcx, def_id);
+        let result = cg.start_analyze_path_constraints(body_mut_ref, paths);
+        let switchbbs = cg.switchbbs.clone();
+        rap_debug!(
+            "Switch basicblocks for function {}: {:?}",
+            tcx.def_path_str(def_id),
+            switchbbs
+        );
+        rap_debug!(
+            "Path Constraints Analysis Result for function {}: {:?}",
+            tcx.def_path_str(def_id),
+            result
+        );
+        result
+    }
+}
+
+/// Range analysis in the native interval domain of each integer type, so that, e.g., the `u64`
+/// values above `i64::MAX` and the `u128` values are modeled exactly and the wrap-arounds happen at
+/// the right bounds. The crate is analyzed once per domain met in its bodies, and each place keeps
+/// the result of the domain of its type. The places of other types, e.g., `bool`, default to the
+/// `i64` domain, and the `i128` places have no domain and are left out.
+pub struct TypedRangeAnalyzer<'tcx> {
+    pub tcx: TyCtxt<'tcx>,
+    pub debug: bool,
+    pub final_vars: TypedRAResultMap<'tcx>,
+    /// The bodies after the SSA/ESSA passes, whose places the ranges are of.
+    pub body_map: FxHashMap<DefId, Body<'tcx>>,
+}
+
+impl<'tcx> Analysis for TypedRangeAnalyzer<'tcx> {
+    fn name(&self) -> &'static str {
+        "Typed Range Analysis"
+    }
+
+    fn run(&mut self) {
+        for domain in self.domains() {
+            match domain {
+                IntDomain::I8 => self.run_domain::<i8>(domain, IntRange::I8),
+                IntDomain::I16 => self.run_domain::<i16>(domain, IntRange::I16),
+                IntDomain::I32 => self.run_domain::<i32>(domain, IntRange::I32),
+                IntDomain::I64 => self.run_domain::<i64>(domain, IntRange::I64),
+                IntDomain::Isize => self.run_domain::<isize>(domain, IntRange::Isize),
+                IntDomain::U8 => self.run_domain::<u8>(domain, IntRange::U8),
+                IntDomain::U16 => self.run_domain::<u16>(domain, IntRange::U16),
+                IntDomain::U32 => self.run_domain::<u32>(domain, IntRange::U32),
+                IntDomain::U64 => self.run_domain::<u64>(domain, IntRange::U64),
+                IntDomain::U128 => self.run_domain::<u128>(domain, IntRange::U128),
+                IntDomain::Usize => self.run_domain::<usize>(domain, IntRange::Usize),
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.final_vars.clear();
+        self.body_map.clear();
+    }
+}
+
+impl<'tcx> TypedRangeAnalyzer<'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>, debug: bool) -> Self {
+        Self {
+            tcx,
+            debug,
+            final_vars: FxHashMap::default(),
+            body_map: FxHashMap::default(),
+        }
+    }
+
+    pub fn get_all_fn_ranges(&self) -> TypedRAResultMap<'tcx> {
+        self.final_vars.clone()
+    }
+
+    /// The domains of the integers in the locals of the analyzed bodies, including those nested
+    /// in tuples, e.g., the results of the overflow-checked operations.
+    fn domains(&self) -> Vec<IntDomain> {
+        let mut domains: Vec<IntDomain> = analyzed_bodies(self.tcx)
+            .into_iter()
+            .flat_map(|def_id| {
+                self.tcx
+                    .optimized_mir(def_id)
+                    .local_decls
+                    .iter()
+                    .flat_map(|decl| decl.ty.walk())
+                    .filter_map(|arg| arg.as_type().and_then(IntDomain::of))
+                    .collect::<Vec<_>>()
+            })
+            .chain([IntDomain::I64])
+            .collect();
+        domains.sort();
+        domains.dedup();
+        domains
+    }
+
+    /// The domain of a place of the type; an overflow-checked result, e.g., `(u8, bool)`, is in
+    /// the domain of its value.
+    fn place_domain(ty: Ty<'tcx>) -> Option<IntDomain> {
+        match ty.kind() {
+            ty::Int(_) | ty::Uint(_) => IntDomain::of(ty),
+            ty::Tuple(tys) if tys.len() == 2 && tys[0].is_integral() && tys[1].is_bool() => {
+                IntDomain::of(tys[0])
+            }
+            _ => Some(IntDomain::I64),
+        }
+    }
+
+    fn run_domain<T>(&mut self, domain: IntDomain, wrap: fn(Range<T>) -> IntRange)
+    where
+        T: IntervalArithmetic + ConstConvert + Debug,
+    {
+        rap_debug!("Range analysis in the {:?} domain", domain);
+        let mut analyzer = RangeAnalyzer::<T>::new(self.tcx, self.debug);
+        analyzer.only_caller_range_analysis();
+        for (def_id, ranges) in analyzer.final_vars {
+            let local_decls = &analyzer.body_map[&def_id].local_decls;
+            let result = self.final_vars.entry(def_id).or_default();
+            for (place, range) in ranges {
+                if Self::place_domain(place.ty(local_decls, self.tcx).ty) == Some(domain) {
+                    result.insert(place, wrap(range));
                 }
             }
         }
+        // The passes are the same in all domains, but a domain may analyze bodies the others
+        // do not reach.
+        for (def_id, body) in analyzer.body_map {
+            self.body_map.entry(def_id).or_insert(body);
+        }
     }
 }
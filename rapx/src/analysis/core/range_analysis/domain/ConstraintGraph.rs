@@ -75,6 +75,9 @@ pub struct ConstraintGraph<'tcx, T: IntervalArithmetic + ConstConvert + Debug> {
     pub arg_count: usize,
     pub rerurn_places: HashSet<&'tcx Place<'tcx>>,
     pub switchbbs: HashMap<BasicBlock, (Place<'tcx>, Place<'tcx>)>,
+    // The branches of each switch comparing two places, as the same place may be compared by
+    // several switches.
+    pub switch_branchmap: HashMap<BasicBlock, ValueBranchMap<'tcx, T>>,
     pub const_func_place: HashMap<&'tcx Place<'tcx>, usize>,
     pub func_without_mir: HashMap<DefId, String>,
     pub unique_adt_path: HashMap<String, usize>,
@@ -127,6 +130,7 @@ where
             arg_count: 0,
             rerurn_places: HashSet::new(),
             switchbbs: HashMap::new(),
+            switch_branchmap: HashMap::new(),
             const_func_place: HashMap::new(),
             func_without_mir: HashMap::new(),
             unique_adt_path: unique_adt_path,
@@ -166,6 +170,7 @@ where
             arg_count: 0,
             rerurn_places: HashSet::new(),
             switchbbs: HashMap::new(),
+            switch_branchmap: HashMap::new(),
             const_func_place: HashMap::new(),
             func_without_mir: HashMap::new(),
             unique_adt_path: unique_adt_path,
@@ -751,6 +756,7 @@ where
                             ValueBranchMap::new(p1, &target_vec[0], &target_vec[1], SFOp1, STOp1);
                         let vbm_2 =
                             ValueBranchMap::new(p2, &target_vec[0], &target_vec[1], SFOp2, STOp2);
+                        self.switch_branchmap.insert(block, vbm_1.clone());
                         self.values_branchmap.insert(&p1, vbm_1);
                         self.values_branchmap.insert(&p2, vbm_2);
                         self.switchbbs.insert(block, (*p1, *p2));
@@ -813,7 +819,7 @@ where
                         if let StatementKind::Assign(box (lhs, Rvalue::Use(OP1))) =
                             &stmt_original.kind
                         {
-                            if lhs.clone() == op1.place().unwrap() {
+                            if op1.place() == Some(*lhs) {
                                 return_op1 = OP1;
                             }
                         }
@@ -823,7 +829,7 @@ where
                             if let StatementKind::Assign(box (lhs, Rvalue::Use(OP2))) =
                                 &stmt_original.kind
                             {
-                                if lhs.clone() == op2.place().unwrap() {
+                                if op2.place() == Some(*lhs) {
                                     return_op2 = OP2;
                                 }
                             }
@@ -837,6 +843,7 @@ where
         None
     }
 
+    /// The range of the variable compared with the constant in the given branch.
     fn apply_comparison<U: IntervalArithmetic>(
         &self,
         constant: U,
@@ -844,47 +851,23 @@ where
         is_true_branch: bool,
         const_in_left: bool,
     ) -> Range<U> {
-        match cmp_op {
-            BinOp::Lt => {
-                if is_true_branch ^ const_in_left {
-                    Range::new(U::min_value(), constant.sub(U::one()), RangeType::Unknown)
-                } else {
-                    Range::new(constant, U::max_value(), RangeType::Unknown)
-                }
-            }
-
-            BinOp::Le => {
-                if is_true_branch ^ const_in_left {
-                    Range::new(U::min_value(), constant, RangeType::Unknown)
-                } else {
-                    Range::new(constant.add(U::one()), U::max_value(), RangeType::Unknown)
-                }
-            }
-
-            BinOp::Gt => {
-                if is_true_branch ^ const_in_left {
-                    Range::new(U::min_value(), constant, RangeType::Unknown)
-                } else {
-                    Range::new(constant.add(U::one()), U::max_value(), RangeType::Unknown)
-                }
-            }
-
-            BinOp::Ge => {
-                if is_true_branch ^ const_in_left {
-                    Range::new(U::min_value(), constant, RangeType::Unknown)
-                } else {
-                    Range::new(constant, U::max_value().sub(U::one()), RangeType::Unknown)
-                }
-            }
-
-            BinOp::Eq => {
-                if is_true_branch ^ const_in_left {
-                    Range::new(U::min_value(), constant, RangeType::Unknown)
-                } else {
-                    Range::new(constant, U::max_value(), RangeType::Unknown)
-                }
-            }
-
+        // `c < x` is `x > c`, so the variable is always in the left from now on.
+        let cmp_op = if const_in_left {
+            Self::reverse_binop(cmp_op).unwrap_or(cmp_op)
+        } else {
+            cmp_op
+        };
+        let below = |bound: U| Range::new(U::min_value(), bound, RangeType::Unknown);
+        let above = |bound: U| Range::new(bound, U::max_value(), RangeType::Unknown);
+        let pred = constant.checked_sub(&U::one()).unwrap_or(U::min_value());
+        let succ = constant.checked_add(&U::one()).unwrap_or(U::max_value());
+        match (cmp_op, is_true_branch) {
+            (BinOp::Lt, true) | (BinOp::Ge, false) => below(pred),
+            (BinOp::Lt, false) | (BinOp::Ge, true) => above(constant),
+            (BinOp::Le, true) | (BinOp::Gt, false) => below(constant),
+            (BinOp::Le, false) | (BinOp::Gt, true) => above(succ),
+            (BinOp::Eq, true) => Range::new(constant.clone(), constant, RangeType::Unknown),
+            (BinOp::Eq, false) => Range::new(U::min_value(), U::max_value(), RangeType::Unknown),
             _ => Range::new(constant.clone(), constant.clone(), RangeType::Empty),
         }
     }
@@ -1118,6 +1101,8 @@ where
             );
             // This handles cases where the call is not a direct one,
             // such as calling a function pointer stored in a variable.
+            // The callee is unknown, so the result is left unconstrained.
+            return;
         }
         let mut constant_count = 0 as usize;
         let arg_count = args.len();
@@ -1505,6 +1490,12 @@ where
             }
             Operand::Constant(_) => None,
         };
+        if source1_place.is_none() {
+            // The operations are evaluated from the place of the first operand, so those of a
+            // constant, e.g., `1 << n`, are left unconstrained.
+            rap_trace!("binary_op with a constant first operand{:?}\n", inst);
+            return;
+        }
 
         match op2 {
             Operand::Copy(place) | Operand::Move(place) => {
@@ -1935,6 +1926,16 @@ where
                 self.fix_intersects(&component);
 
                 let variable: &Place<'tcx> = *component.iter().next().unwrap();
+                // An ESSA sink is evaluated when propagated from its source, maybe before its
+                // symbolic bound is fixed, so it is evaluated again now that the bound is.
+                if let Some(&op) = self.defmap.get(variable)
+                    && let BasicOpKind::Essa(essaop) = &mut self.oprs[op]
+                    && essaop.is_unresolved()
+                {
+                    let new_range = essaop.eval(&self.vars);
+                    essaop.mark_resolved();
+                    self.vars.get_mut(variable).unwrap().set_range(new_range);
+                }
                 let varnode = self.vars.get_mut(variable).unwrap();
                 if varnode.get_range().is_unknown() {
                     varnode.set_default();
@@ -2134,6 +2135,10 @@ where
         result
     }
 
+    /// The comparisons of the places along each path. The places are not in SSA form, so the
+    /// constraints of a place are dropped once a block on the path assigns to it. The places
+    /// assigned in the loops, whose blocks are not on the paths, or borrowed mutably, i.e.,
+    /// possibly assigned anywhere, are not constrained at all.
     pub fn analyze_path_constraints(
         &self,
         body: &'tcx Body<'tcx>,
@@ -2142,6 +2147,16 @@ where
         let mut all_path_results: HashMap<Vec<usize>, Vec<(Place<'tcx>, Place<'tcx>, BinOp)>> =
             HashMap::with_capacity(all_paths_indices.len());
 
+        let mut mutated = MutatedLocals::default();
+        mutated.visit_body(body);
+        let on_paths: HashSet<usize> = all_paths_indices.iter().flatten().copied().collect();
+        let mut unconstrained = mutated.borrowed.clone();
+        for (bb, locals) in &mutated.assigned {
+            if !on_paths.contains(&bb.as_usize()) {
+                unconstrained.extend(locals);
+            }
+        }
+
         for path_indices in all_paths_indices {
             let mut current_path_constraints: Vec<(Place<'tcx>, Place<'tcx>, BinOp)> = Vec::new();
 
@@ -2153,6 +2168,12 @@ where
             for window in path_bbs.windows(2) {
                 let current_bb = window[0];
 
+                if let Some(assigned) = mutated.assigned.get(&current_bb) {
+                    current_path_constraints.retain(|(place_1, place_2, _)| {
+                        !assigned.contains(&place_1.local) && !assigned.contains(&place_2.local)
+                    });
+                }
+
                 if self.switchbbs.contains_key(&current_bb) {
                     let next_bb = window[1];
                     let current_bb_data = &body[current_bb];
@@ -2164,7 +2185,12 @@ where
                     {
                         let (constraint_place_1, constraint_place_2) =
                             self.switchbbs.get(&current_bb).unwrap();
-                        if let Some(vbm) = self.values_branchmap.get(constraint_place_1) {
+                        if unconstrained.contains(&constraint_place_1.local)
+                            || unconstrained.contains(&constraint_place_2.local)
+                        {
+                            continue;
+                        }
+                        if let Some(vbm) = self.switch_branchmap.get(&current_bb) {
                             let relevant_interval_opt = if next_bb == *vbm.get_bb_true() {
                                 Some(vbm.get_itv_t())
                             } else if next_bb == *vbm.get_bb_false() {
@@ -2196,6 +2222,35 @@ where
         all_path_results
     }
 }
+
+/// The locals assigned in each basic block, and those borrowed mutably.
+#[derive(Default)]
+struct MutatedLocals {
+    assigned: HashMap<BasicBlock, HashSet<Local>>,
+    borrowed: HashSet<Local>,
+}
+
+impl<'tcx> Visitor<'tcx> for MutatedLocals {
+    fn visit_place(&mut self, place: &Place<'tcx>, context: PlaceContext, location: Location) {
+        match context {
+            PlaceContext::MutatingUse(
+                visit::MutatingUseContext::Borrow | visit::MutatingUseContext::RawBorrow,
+            ) => {
+                self.borrowed.insert(place.local);
+            }
+            PlaceContext::MutatingUse(_)
+            | PlaceContext::NonUse(
+                visit::NonUseContext::StorageLive | visit::NonUseContext::StorageDead,
+            ) => {
+                self.assigned
+                    .entry(location.block)
+                    .or_default()
+                    .insert(place.local);
+            }
+            _ => {}
+        }
+    }
+}
 #[derive(Debug)]
 pub struct Nuutila<'tcx, T: IntervalArithmetic + ConstConvert + Debug> {
     pub variables: &'tcx VarNodes<'tcx, T>,
@@ -9,7 +9,10 @@ use rust_intervals::NothingBetween;
 use crate::analysis::core::range_analysis::domain::ConstraintGraph::ConstraintGraph;
 use crate::analysis::core::range_analysis::{Range, RangeType};
 use crate::{rap_debug, rap_trace};
-use num_traits::{Bounded, CheckedAdd, CheckedSub, One, ToPrimitive, Zero, ops};
+use num_traits::{
+    Bounded, CheckedAdd, CheckedMul, CheckedSub, One, ToPrimitive, WrappingAdd, WrappingMul,
+    WrappingSub, Zero, ops,
+};
 use rustc_abi::Size;
 use rustc_data_structures::fx::FxHashMap;
 use rustc_hir::def_id::DefId;
@@ -32,52 +35,28 @@ pub trait ConstConvert: Sized {
     fn from_const(c: &Const) -> Option<Self>;
 }
 
-impl ConstConvert for u32 {
-    fn from_const(c: &Const) -> Option<Self> {
-        if let Some(scalar) = c.try_to_scalar_int() {
-            Some(scalar.to_bits(scalar.size()) as u32)
-        } else {
-            None
-        }
-    }
-}
-impl ConstConvert for usize {
-    fn from_const(c: &Const) -> Option<Self> {
-        if let Some(scalar) = c.try_to_scalar_int() {
-            Some(scalar.to_bits(scalar.size()) as usize)
-        } else {
-            None
-        }
-    }
-}
-impl ConstConvert for i32 {
-    fn from_const(c: &Const) -> Option<Self> {
-        if let Some(scalar) = c.try_to_scalar_int() {
-            Some(scalar.to_bits(scalar.size()) as i32)
-        } else {
-            None
-        }
-    }
+/// Constants are read at the width of their own type and sign-extended if the type is signed, so
+/// that, e.g., `-1i8` is `-1` in the `i64` domain.
+macro_rules! impl_const_convert {
+    ($($t:ty),*) => {
+        $(
+            impl ConstConvert for $t {
+                fn from_const(c: &Const) -> Option<Self> {
+                    let scalar = c.try_to_scalar_int()?;
+                    if c.ty().is_signed() {
+                        Some(scalar.to_int(scalar.size()) as $t)
+                    } else {
+                        Some(scalar.to_bits(scalar.size()) as $t)
+                    }
+                }
+            }
+        )*
+    };
 }
+impl_const_convert!(
+    u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize
+);
 
-impl ConstConvert for i64 {
-    fn from_const(c: &Const) -> Option<Self> {
-        if let Some(scalar) = c.try_to_scalar_int() {
-            Some(scalar.to_bits(scalar.size()) as i64)
-        } else {
-            None
-        }
-    }
-}
-impl ConstConvert for i128 {
-    fn from_const(c: &Const) -> Option<Self> {
-        if let Some(scalar) = c.try_to_scalar_int() {
-            Some(scalar.to_bits(scalar.size()) as i128)
-        } else {
-            None
-        }
-    }
-}
 pub trait IntervalArithmetic:
     PartialOrd
     + Clone
@@ -87,6 +66,10 @@ pub trait IntervalArithmetic:
     + One
     + CheckedAdd
     + CheckedSub
+    + CheckedMul
+    + WrappingAdd
+    + WrappingSub
+    + WrappingMul
     + Add<Output = Self>
     + Sub<Output = Self>
     + Mul<Output = Self>
@@ -97,9 +80,18 @@ pub trait IntervalArithmetic:
 {
 }
 
-impl IntervalArithmetic for i32 {}
+// `i128` has no interval domain, as `rust_intervals` does not support it.
+impl IntervalArithmetic for u8 {}
+impl IntervalArithmetic for u16 {}
+impl IntervalArithmetic for u32 {}
+impl IntervalArithmetic for u64 {}
+impl IntervalArithmetic for u128 {}
 impl IntervalArithmetic for usize {}
+impl IntervalArithmetic for i8 {}
+impl IntervalArithmetic for i16 {}
+impl IntervalArithmetic for i32 {}
 impl IntervalArithmetic for i64 {}
+impl IntervalArithmetic for isize {}
 use rustc_middle::ty::Ty;
 
 #[derive(Debug, Clone)]
@@ -193,15 +185,12 @@ impl<'tcx> SymbExpr<'tcx> {
                 let r_range = rhs.eval(vars);
 
                 match op {
-                    BinOp::Add | BinOp::AddUnchecked | BinOp::AddWithOverflow => {
-                        l_range.add(&r_range)
-                    }
-                    BinOp::Sub | BinOp::SubUnchecked | BinOp::SubWithOverflow => {
-                        l_range.sub(&r_range)
-                    }
-                    BinOp::Mul | BinOp::MulUnchecked | BinOp::MulWithOverflow => {
-                        l_range.mul(&r_range)
-                    }
+                    BinOp::Add => l_range.wrapping_add(&r_range),
+                    BinOp::Sub => l_range.wrapping_sub(&r_range),
+                    BinOp::Mul => l_range.wrapping_mul(&r_range),
+                    BinOp::AddUnchecked | BinOp::AddWithOverflow => l_range.add(&r_range),
+                    BinOp::SubUnchecked | BinOp::SubWithOverflow => l_range.sub(&r_range),
+                    BinOp::MulUnchecked | BinOp::MulWithOverflow => l_range.mul(&r_range),
 
                     _ => Range::new(T::min_value(), T::max_value(), RangeType::Regular),
                 }
@@ -406,16 +395,14 @@ impl<'tcx, T: IntervalArithmetic + ConstConvert + Debug> SymbInterval<'tcx, T> {
         self.symbound
     }
 
-    pub fn sym_fix_intersects(
-        &self,
-        bound: &VarNode<'tcx, T>,
-        sink: &VarNode<'tcx, T>,
-    ) -> Range<T> {
+    /// The range of the sink given the range of the bound. The other side is left open, as the
+    /// sink may not be evaluated yet, and the range of the source is intersected with it later.
+    pub fn sym_fix_intersects(&self, bound: &VarNode<'tcx, T>) -> Range<T> {
         let l = bound.get_range().get_lower().clone();
         let u = bound.get_range().get_upper().clone();
 
-        let lower = sink.get_range().get_lower().clone();
-        let upper = sink.get_range().get_upper().clone();
+        let lower = T::min_value();
+        let upper = T::max_value();
 
         match self.predicate {
             BinOp::Eq => Range::new(l, u, RangeType::Regular),
@@ -617,7 +604,7 @@ impl<'tcx, T: IntervalArithmetic + ConstConvert + Debug> BasicOpKind<'tcx, T> {
         let intersect = self.get_intersect_mut();
 
         if let IntervalType::Symb(symbi) = intersect {
-            let range = symbi.sym_fix_intersects(v, sink);
+            let range = symbi.sym_fix_intersects(v);
             rap_trace!(
                 "from {:?} to {:?} fix_intersects: {:} -> {:?}\n",
                 v.get_value().clone(),
@@ -670,18 +657,12 @@ impl<'tcx, T: IntervalArithmetic + ConstConvert + Debug> BasicOpKind<'tcx, T> {
     pub fn get_sources(&self) -> Vec<&'tcx Place<'tcx>> {
         match self {
             BasicOpKind::Unary(op) => vec![op.source],
-            BasicOpKind::Binary(op) => {
-                let mut sources = vec![];
-                sources.push(op.source1.unwrap());
-                if let Some(source2) = op.source2 {
-                    sources.push(source2);
-                }
-                sources
-            }
+            BasicOpKind::Binary(op) => op.source1.into_iter().chain(op.source2).collect(),
             BasicOpKind::Essa(op) => vec![op.source],
             BasicOpKind::ControlDep(op) => vec![op.source],
             BasicOpKind::Phi(op) => op.sources.clone(),
-            BasicOpKind::Use(op) => vec![op.source.unwrap()],
+            // The constants have no source.
+            BasicOpKind::Use(op) => op.source.into_iter().collect(),
             BasicOpKind::Call(op) => op.sources.clone(),
             BasicOpKind::Ref(op) => vec![op.source],
             BasicOpKind::Aggregate(_) => vec![],
@@ -726,12 +707,50 @@ impl<'tcx, T: IntervalArithmetic + ConstConvert + Debug> CallOp<'tcx, T> {
     pub fn eval(&self, caller_vars: &VarNodes<'tcx, T>) -> Range<T> {
         return Range::default(T::min_value());
     }
+
+    /// The range of an argument in the caller.
+    fn arg_range(&self, index: usize, caller_vars: &VarNodes<'tcx, T>) -> Option<Range<T>> {
+        match self.args.get(index)? {
+            Operand::Copy(place) | Operand::Move(place) => {
+                caller_vars.get(place).map(|node| node.get_range().clone())
+            }
+            Operand::Constant(c) => {
+                let value = Self::convert_const(&c.const_)?;
+                Some(Range::new(value, value, RangeType::Regular))
+            }
+        }
+    }
+
+    /// The result of the `wrapping_*` methods of the integers, e.g.,
+    /// `core::num::<impl u8>::wrapping_add`, which wraps around at the bounds of the domain.
+    fn eval_wrapping(&self, caller_vars: &VarNodes<'tcx, T>) -> Option<Range<T>> {
+        let (owner, name) = self.fun_path.rsplit_once("::")?;
+        if !owner.contains("num::<impl ") {
+            return None;
+        }
+        let lhs = self.arg_range(0, caller_vars)?;
+        let rhs = self.arg_range(1, caller_vars)?;
+        match name {
+            "wrapping_add" => Some(lhs.wrapping_add(&rhs)),
+            "wrapping_sub" => Some(lhs.wrapping_sub(&rhs)),
+            "wrapping_mul" => Some(lhs.wrapping_mul(&rhs)),
+            _ => None,
+        }
+    }
     pub fn eval_call(
         &self,
         caller_vars: &VarNodes<'tcx, T>,
         cg_map: &FxHashMap<DefId, Rc<RefCell<ConstraintGraph<'tcx, T>>>>,
         vars_map: &mut FxHashMap<DefId, Vec<RefCell<VarNodes<'tcx, T>>>>,
     ) -> Range<T> {
+        if let Some(result) = self.eval_wrapping(caller_vars) {
+            rap_trace!(
+                "Wrapping call detected on place {:?}: {:?}",
+                self.sink,
+                result
+            );
+            return result;
+        }
         match self.fun_path.as_str() {
             "std::iter::IntoIterator::into_iter" => match self.args.first() {
                 Some(Operand::Copy(place)) | Some(Operand::Move(place)) => {
@@ -774,7 +793,10 @@ impl<'tcx, T: IntervalArithmetic + ConstConvert + Debug> CallOp<'tcx, T> {
                 match self.args.last() {
                     Some(Operand::Copy(place)) | Some(Operand::Move(place)) => {
                         let range = caller_vars[place].get_range().clone();
-                        let len = range.get_upper().clone() - range.get_lower().clone();
+                        let len = range
+                            .get_upper()
+                            .checked_sub(&range.get_lower())
+                            .unwrap_or(T::max_value());
                         result = Range::new(len.clone(), len.clone(), RangeType::Regular);
                     }
                     Some(Operand::Constant(c)) => {}
@@ -909,13 +931,8 @@ impl<'tcx, T: IntervalArithmetic + ConstConvert + Debug> CallOp<'tcx, T> {
                     rap_debug!(" final return range {:?} ", return_range);
                     return return_range;
                 }
-                let Some(callee_varnodes_vec) = vars_map.get_mut(&self.def_id) else {
-                    panic!(
-                        "No variable map entry for this function {:?}, skipping Nuutila\n",
-                        self.def_id
-                    );
-                };
-                callee_cg.reset_vars(callee_varnodes_vec);
+                // The return value is not modeled, e.g., a cast, while the ranges of the callee
+                // are kept as those of the functions returning one.
             } else {
                 // Recursive call detected or graph is already borrowed.
                 // Conservatively return a full range.
@@ -1211,15 +1228,31 @@ impl<'tcx, T: IntervalArithmetic + ConstConvert + Debug> BinaryOp<'tcx, T> {
         match &self.inst.kind {
             StatementKind::Assign(box (place, rvalue)) => match rvalue {
                 Rvalue::BinaryOp(binop, _) => match binop {
-                    BinOp::Add | BinOp::AddUnchecked | BinOp::AddWithOverflow => {
+                    // Without overflow checks, e.g., after `wrapping_add` is inlined, the result
+                    // wraps around.
+                    BinOp::Add => {
+                        result = op1.wrapping_add(&op2);
+                    }
+
+                    BinOp::Sub => {
+                        result = op1.wrapping_sub(&op2);
+                    }
+
+                    BinOp::Mul => {
+                        result = op1.wrapping_mul(&op2);
+                    }
+
+                    // The checked results only flow on when they do not overflow, and the
+                    // unchecked ones never overflow.
+                    BinOp::AddUnchecked | BinOp::AddWithOverflow => {
                         result = op1.add(&op2);
                     }
 
-                    BinOp::SubUnchecked | BinOp::SubWithOverflow | BinOp::Sub => {
+                    BinOp::SubUnchecked | BinOp::SubWithOverflow => {
                         result = op1.sub(&op2);
                     }
 
-                    BinOp::MulUnchecked | BinOp::MulWithOverflow | BinOp::Mul => {
+                    BinOp::MulUnchecked | BinOp::MulWithOverflow => {
                         result = op1.mul(&op2);
                     }
 
@@ -131,45 +131,61 @@ where
         self.rtype = RangeType::Regular;
         self.range = Interval::new_closed_closed(T::min_value(), T::max_value());
     }
-    pub fn add(&self, other: &Range<T>) -> Range<T> {
-        let a = self
-            .get_lower()
-            .clone()
-            .checked_add(&other.get_lower().clone())
-            .unwrap_or(T::max_value());
-
-        let b = self
-            .get_upper()
-            .clone()
-            .checked_add(&other.get_upper().clone())
-            .unwrap_or(T::max_value());
+    /// The full range of the domain.
+    pub fn full() -> Range<T> {
+        Range::new(T::min_value(), T::max_value(), RangeType::Regular)
+    }
+
+    /// A bound of an overflow-checked operation, saturated at the end of the domain it overflows.
+    fn saturate(bound: Option<T>, upward: bool) -> T {
+        match bound {
+            Some(bound) => bound,
+            None if upward => T::max_value(),
+            None => T::min_value(),
+        }
+    }
 
+    /// The sum on the non-overflowing paths, e.g., after the assertion of an `AddWithOverflow`.
+    pub fn add(&self, other: &Range<T>) -> Range<T> {
+        let a = Self::saturate(
+            self.get_lower().checked_add(&other.get_lower()),
+            other.get_lower() > T::zero(),
+        );
+        let b = Self::saturate(
+            self.get_upper().checked_add(&other.get_upper()),
+            other.get_upper() > T::zero(),
+        );
         Range::new(a, b, RangeType::Regular)
     }
 
+    /// The difference on the non-overflowing paths.
     pub fn sub(&self, other: &Range<T>) -> Range<T> {
-        let a = self
-            .get_lower()
-            .clone()
-            .checked_sub(&other.get_upper().clone())
-            .unwrap_or(T::min_value());
-
-        let b = self
-            .get_upper()
-            .clone()
-            .checked_sub(&other.get_lower().clone())
-            .unwrap_or(T::max_value());
-
+        let a = Self::saturate(
+            self.get_lower().checked_sub(&other.get_upper()),
+            other.get_upper() < T::zero(),
+        );
+        let b = Self::saturate(
+            self.get_upper().checked_sub(&other.get_lower()),
+            other.get_lower() < T::zero(),
+        );
         Range::new(a, b, RangeType::Regular)
     }
 
+    /// The product on the non-overflowing paths; it is not bounded if any product of the bounds
+    /// overflows.
     pub fn mul(&self, other: &Range<T>) -> Range<T> {
-        let candidates = vec![
-            self.get_lower().clone() * other.get_lower().clone(),
-            self.get_lower().clone() * other.get_upper().clone(),
-            self.get_upper().clone() * other.get_lower().clone(),
-            self.get_upper().clone() * other.get_upper().clone(),
-        ];
+        let candidates: Option<Vec<T>> = [
+            (self.get_lower(), other.get_lower()),
+            (self.get_lower(), other.get_upper()),
+            (self.get_upper(), other.get_lower()),
+            (self.get_upper(), other.get_upper()),
+        ]
+        .iter()
+        .map(|(a, b)| a.checked_mul(b))
+        .collect();
+        let Some(candidates) = candidates else {
+            return Range::full();
+        };
         let min = candidates
             .iter()
             .cloned()
@@ -183,6 +199,102 @@ where
         Range::new(min, max, RangeType::Regular)
     }
 
+    /// Whether the range is inferred, i.e., known and narrower than the whole domain.
+    pub fn is_bounded(&self) -> bool {
+        self.is_regular()
+            && !(self.get_lower() == T::min_value() && self.get_upper() == T::max_value())
+    }
+
+    /// Whether the range is inverted, e.g., `[3, 0]`, which the narrowing gives to the places of
+    /// infeasible branches.
+    pub fn is_inverted(&self) -> bool {
+        self.is_regular() && self.get_lower() > self.get_upper()
+    }
+
+    /// Whether the operation on values of the ranges may overflow the domain.
+    pub fn may_overflow(&self, op: BinOp, other: &Range<T>) -> bool {
+        let (l1, u1) = (self.get_lower(), self.get_upper());
+        let (l2, u2) = (other.get_lower(), other.get_upper());
+        match op {
+            BinOp::Add | BinOp::AddWithOverflow | BinOp::AddUnchecked => {
+                l1.checked_add(&l2).is_none() || u1.checked_add(&u2).is_none()
+            }
+            BinOp::Sub | BinOp::SubWithOverflow | BinOp::SubUnchecked => {
+                l1.checked_sub(&u2).is_none() || u1.checked_sub(&l2).is_none()
+            }
+            BinOp::Mul | BinOp::MulWithOverflow | BinOp::MulUnchecked => {
+                [(l1, l2), (l1, u2), (u1, l2), (u1, u2)]
+                    .iter()
+                    .any(|(a, b)| a.checked_mul(b).is_none())
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether shifting the values of the range left by `amount` bits loses any set bit.
+    pub fn may_shl_overflow(&self, amount: u32) -> bool {
+        let is_zero = self.get_lower() == T::zero() && self.get_upper() == T::zero();
+        match num_traits::checked_pow(T::one() + T::one(), amount as usize) {
+            Some(factor) => {
+                self.get_lower().checked_mul(&factor).is_none()
+                    || self.get_upper().checked_mul(&factor).is_none()
+            }
+            None => !is_zero,
+        }
+    }
+
+    /// The wrapped result of an operation, given its checked bounds. The bounds stay ordered if
+    /// neither overflows, or if both overflow in the same direction, as the results are then at
+    /// most one wrap apart.
+    fn wrap(bounds: (Option<T>, Option<T>), wrapped: (T, T), same_direction: bool) -> Range<T> {
+        match bounds {
+            (Some(lower), Some(upper)) => Range::new(lower, upper, RangeType::Regular),
+            (None, None) if same_direction => Range::new(wrapped.0, wrapped.1, RangeType::Regular),
+            _ => Range::full(),
+        }
+    }
+
+    /// The sum with wrap-around at the bounds of the domain, e.g., `wrapping_add` or `Add` without
+    /// overflow checks.
+    pub fn wrapping_add(&self, other: &Range<T>) -> Range<T> {
+        let (l1, u1) = (self.get_lower(), self.get_upper());
+        let (l2, u2) = (other.get_lower(), other.get_upper());
+        Self::wrap(
+            (l1.checked_add(&l2), u1.checked_add(&u2)),
+            (l1.wrapping_add(&l2), u1.wrapping_add(&u2)),
+            (l2 > T::zero()) == (u2 > T::zero()),
+        )
+    }
+
+    /// The difference with wrap-around at the bounds of the domain.
+    pub fn wrapping_sub(&self, other: &Range<T>) -> Range<T> {
+        let (l1, u1) = (self.get_lower(), self.get_upper());
+        let (l2, u2) = (other.get_lower(), other.get_upper());
+        Self::wrap(
+            (l1.checked_sub(&u2), u1.checked_sub(&l2)),
+            (l1.wrapping_sub(&u2), u1.wrapping_sub(&l2)),
+            (l2 < T::zero()) == (u2 < T::zero()),
+        )
+    }
+
+    /// The product with wrap-around at the bounds of the domain; it is not bounded once a product
+    /// of the bounds overflows.
+    pub fn wrapping_mul(&self, other: &Range<T>) -> Range<T> {
+        let overflows = [
+            (self.get_lower(), other.get_lower()),
+            (self.get_lower(), other.get_upper()),
+            (self.get_upper(), other.get_lower()),
+            (self.get_upper(), other.get_upper()),
+        ]
+        .iter()
+        .any(|(a, b)| a.checked_mul(b).is_none());
+        if overflows {
+            Range::full()
+        } else {
+            self.mul(other)
+        }
+    }
+
     pub fn intersectwith(&self, other: &Range<T>) -> Range<T> {
         if self.is_unknown() {
             return Range::new(
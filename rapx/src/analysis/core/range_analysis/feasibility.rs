@@ -0,0 +1,142 @@
+//! Path feasibility based on the path constraints of range analysis.
+//!
+//! The paths of a function are enumerated over its strongly-connected components as MoP does, and
+//! the constraints of each path, e.g., `_1 < _2` for the branch taken at a `switchInt`, are checked
+//! by Z3. MoP and SafeDrop ask whether the blocks where they find an alias or a bug are visited in
+//! order by some feasible path, so that the results only found on infeasible paths are dropped.
+
+use super::{PathConstraint, default::RangeAnalyzer};
+use crate::{
+    analysis::core::alias_analysis::default::{block::Term, graph::MopGraph},
+    utils::budget,
+};
+use rustc_middle::mir::{BinOp, Place};
+use std::collections::HashMap;
+use z3::ast::{Ast, Bool, Int};
+
+#[derive(Debug, Clone, Default)]
+pub struct PathFeasibility {
+    /// The enter node of the component that each basic block belongs to.
+    enters: Vec<usize>,
+    /// The enumerated paths with whether their constraints are satisfiable; empty if the function
+    /// has no branches or too many paths to enumerate.
+    paths: Vec<(Vec<usize>, bool)>,
+}
+
+impl PathFeasibility {
+    /// Check the paths of the function whose components have been found in the graph, within the
+    /// path budget of `rapx.toml`.
+    pub fn new(graph: &MopGraph<'_>) -> Self {
+        let mut feasibility = PathFeasibility {
+            enters: graph.blocks.iter().map(|block| block.scc.enter).collect(),
+            paths: Vec::new(),
+        };
+        let has_branch = graph
+            .blocks
+            .iter()
+            .any(|block| matches!(block.terminator, Term::Switch(_)));
+        if !has_branch || graph.blocks.is_empty() {
+            return feasibility;
+        }
+        let Some(paths) = enumerate_paths(graph, budget::config().paths) else {
+            rap_debug!(
+                "Too many paths to check the feasibility: {:?}",
+                graph.def_id
+            );
+            return feasibility;
+        };
+        let constraints: PathConstraint<'_> =
+            RangeAnalyzer::<i64>::path_constraints_of(graph.tcx, graph.def_id, &paths);
+        feasibility.paths = paths
+            .into_iter()
+            .map(|path| {
+                let feasible = constraints.get(&path).is_none_or(|c| is_satisfiable(c));
+                (path, feasible)
+            })
+            .collect();
+        feasibility
+    }
+
+    /// Whether some feasible path visits the basic blocks in order. The blocks not visited by any
+    /// enumerated path are assumed to be feasible.
+    pub fn is_feasible(&self, blocks: &[usize]) -> bool {
+        if self.paths.iter().all(|(_, feasible)| *feasible) {
+            return true;
+        }
+        let blocks: Vec<usize> = blocks
+            .iter()
+            .filter_map(|block| self.enters.get(*block).copied())
+            .collect();
+        let mut visiting = self
+            .paths
+            .iter()
+            .filter(|(path, _)| is_subsequence(&blocks, path))
+            .peekable();
+        visiting.peek().is_none() || visiting.any(|(_, feasible)| *feasible)
+    }
+}
+
+/// The paths from the entry to the exits over the components, each as the sequence of their
+/// enter nodes; None if there are more paths than the limit.
+fn enumerate_paths(graph: &MopGraph<'_>, limit: usize) -> Option<Vec<Vec<usize>>> {
+    let mut paths = Vec::new();
+    let mut stack = vec![(graph.blocks[0].scc.enter, 0)];
+    let mut path = Vec::new();
+    while let Some((node, depth)) = stack.pop() {
+        path.truncate(depth);
+        path.push(node);
+        let next = &graph.blocks[node].next;
+        if next.is_empty() {
+            if paths.len() >= limit {
+                return None;
+            }
+            paths.push(path.clone());
+            continue;
+        }
+        for child in next {
+            let child = graph.blocks[*child].scc.enter;
+            // The back edges are ignored, as they are within the components.
+            if !path.contains(&child) {
+                stack.push((child, depth + 1));
+            }
+        }
+    }
+    Some(paths)
+}
+
+fn is_subsequence(blocks: &[usize], path: &[usize]) -> bool {
+    let mut path = path.iter();
+    blocks.iter().all(|block| path.any(|node| node == block))
+}
+
+/// Whether the comparisons between the places may hold at the same time, where the places are
+/// taken as unbounded integers. The solver may give up within the timeout, which counts as
+/// satisfiable.
+fn is_satisfiable<'tcx>(constraints: &[(Place<'tcx>, Place<'tcx>, BinOp)]) -> bool {
+    if constraints.is_empty() {
+        return true;
+    }
+    let mut cfg = z3::Config::new();
+    cfg.set_timeout_msec(budget::config().solver_timeout);
+    let ctx = z3::Context::new(&cfg);
+    let solver = z3::Solver::new(&ctx);
+    let mut vars: HashMap<Place<'tcx>, Int<'_>> = HashMap::new();
+    for (lhs, rhs, op) in constraints {
+        let [lhs, rhs] = [lhs, rhs].map(|place| {
+            vars.entry(*place)
+                .or_insert_with(|| Int::new_const(&ctx, format!("{:?}", place)))
+                .clone()
+        });
+        let cond: Bool<'_> = match op {
+            BinOp::Eq => lhs._eq(&rhs),
+            BinOp::Ne => lhs._eq(&rhs).not(),
+            BinOp::Lt => lhs.lt(&rhs),
+            BinOp::Le => lhs.le(&rhs),
+            BinOp::Gt => lhs.gt(&rhs),
+            BinOp::Ge => lhs.ge(&rhs),
+            _ => continue,
+        };
+        solver.assert(&cond);
+    }
+    solver.check() != z3::SatResult::Unsat
+}
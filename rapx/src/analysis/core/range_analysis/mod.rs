@@ -3,19 +3,25 @@
 #![allow(dead_code)]
 pub mod default;
 pub mod domain;
+pub mod feasibility;
 use crate::{
     analysis::{
         Analysis,
         core::range_analysis::domain::domain::{ConstConvert, IntervalArithmetic},
     },
-    utils::source::get_fn_name_byid,
+    utils::{json::stable_def_path_tls, source::get_fn_name_byid},
 };
+use num_traits::ToPrimitive;
 use once_cell::sync::Lazy;
 // use intervals::Closed;
 use rust_intervals::Interval;
 use rustc_data_structures::fx::FxHashMap;
 use rustc_hir::def_id::DefId;
-use rustc_middle::mir::{BinOp, Place};
+use rustc_middle::{
+    mir::{BinOp, Const, Place, ProjectionElem},
+    ty::{self, IntTy, Ty, UintTy},
+};
+use serde::{Serialize, ser::SerializeStruct};
 
 use std;
 use std::{
@@ -26,12 +32,16 @@ pub type RAResult<'tcx, T> = HashMap<Place<'tcx>, Range<T>>;
 pub type RAResultMap<'tcx, T> = FxHashMap<DefId, HashMap<Place<'tcx>, Range<T>>>;
 pub type RAVecResultMap<'tcx, T> = FxHashMap<DefId, Vec<HashMap<Place<'tcx>, Range<T>>>>;
 
+pub type TypedRAResult<'tcx> = HashMap<Place<'tcx>, IntRange>;
+pub type TypedRAResultMap<'tcx> = FxHashMap<DefId, HashMap<Place<'tcx>, IntRange>>;
+
 pub type PathConstraint<'tcx> = HashMap<Vec<usize>, Vec<(Place<'tcx>, Place<'tcx>, BinOp)>>;
 pub type PathConstraintMap<'tcx> =
     FxHashMap<DefId, HashMap<Vec<usize>, Vec<(Place<'tcx>, Place<'tcx>, BinOp)>>>;
 pub struct RAResultWrapper<'tcx, T: IntervalArithmetic>(pub RAResult<'tcx, T>);
 pub struct RAResultMapWrapper<'tcx, T: IntervalArithmetic>(pub RAResultMap<'tcx, T>);
 pub struct RAVecResultMapWrapper<'tcx, T: IntervalArithmetic>(pub RAVecResultMap<'tcx, T>);
+pub struct TypedRAResultMapWrapper<'tcx>(pub TypedRAResultMap<'tcx>);
 pub struct PathConstraintWrapper<'tcx>(pub PathConstraint<'tcx>);
 pub struct PathConstraintMapWrapper<'tcx>(pub PathConstraintMap<'tcx>);
 
@@ -87,7 +97,10 @@ where
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(f, "=== Print range analysis resuts ===")?;
-        for (def_id, ra_result) in &self.0 {
+        // Sorted by the def paths, as the order of the map varies between runs.
+        let mut results: Vec<_> = self.0.iter().collect();
+        results.sort_by_cached_key(|(def_id, _)| stable_def_path_tls(**def_id));
+        for (def_id, ra_result) in results {
             let fn_name = get_fn_name_byid(def_id);
             writeln!(f, "Function: {:?} =>", fn_name)?;
 
@@ -101,6 +114,206 @@ where
         Ok(())
     }
 }
+/// The interval domain of an integer type at its native width. `i128` has none, as
+/// `rust_intervals` does not support it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum IntDomain {
+    I8,
+    I16,
+    I32,
+    I64,
+    Isize,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    Usize,
+}
+
+impl IntDomain {
+    pub fn of(ty: Ty<'_>) -> Option<Self> {
+        match ty.kind() {
+            ty::Int(IntTy::I8) => Some(Self::I8),
+            ty::Int(IntTy::I16) => Some(Self::I16),
+            ty::Int(IntTy::I32) => Some(Self::I32),
+            ty::Int(IntTy::I64) => Some(Self::I64),
+            ty::Int(IntTy::Isize) => Some(Self::Isize),
+            ty::Uint(UintTy::U8) => Some(Self::U8),
+            ty::Uint(UintTy::U16) => Some(Self::U16),
+            ty::Uint(UintTy::U32) => Some(Self::U32),
+            ty::Uint(UintTy::U64) => Some(Self::U64),
+            ty::Uint(UintTy::U128) => Some(Self::U128),
+            ty::Uint(UintTy::Usize) => Some(Self::Usize),
+            _ => None,
+        }
+    }
+}
+
+/// The range of an integer place in the domain of its type.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum IntRange {
+    I8(Range<i8>),
+    I16(Range<i16>),
+    I32(Range<i32>),
+    I64(Range<i64>),
+    Isize(Range<isize>),
+    U8(Range<u8>),
+    U16(Range<u16>),
+    U32(Range<u32>),
+    U64(Range<u64>),
+    U128(Range<u128>),
+    Usize(Range<usize>),
+}
+
+macro_rules! with_int_range {
+    ($range:expr, $r:ident => $body:expr) => {
+        match $range {
+            IntRange::I8($r) => $body,
+            IntRange::I16($r) => $body,
+            IntRange::I32($r) => $body,
+            IntRange::I64($r) => $body,
+            IntRange::Isize($r) => $body,
+            IntRange::U8($r) => $body,
+            IntRange::U16($r) => $body,
+            IntRange::U32($r) => $body,
+            IntRange::U64($r) => $body,
+            IntRange::U128($r) => $body,
+            IntRange::Usize($r) => $body,
+        }
+    };
+}
+
+macro_rules! with_int_ranges {
+    ($lhs:expr, $rhs:expr, $a:ident, $b:ident => $body:expr, _ => $otherwise:expr) => {
+        match ($lhs, $rhs) {
+            (IntRange::I8($a), IntRange::I8($b)) => $body,
+            (IntRange::I16($a), IntRange::I16($b)) => $body,
+            (IntRange::I32($a), IntRange::I32($b)) => $body,
+            (IntRange::I64($a), IntRange::I64($b)) => $body,
+            (IntRange::Isize($a), IntRange::Isize($b)) => $body,
+            (IntRange::U8($a), IntRange::U8($b)) => $body,
+            (IntRange::U16($a), IntRange::U16($b)) => $body,
+            (IntRange::U32($a), IntRange::U32($b)) => $body,
+            (IntRange::U64($a), IntRange::U64($b)) => $body,
+            (IntRange::U128($a), IntRange::U128($b)) => $body,
+            (IntRange::Usize($a), IntRange::Usize($b)) => $body,
+            _ => $otherwise,
+        }
+    };
+}
+
+impl IntDomain {
+    /// Whether the value is representable in the domain.
+    pub fn contains<N: ToPrimitive>(self, n: N) -> bool {
+        match self {
+            Self::I8 => n.to_i8().is_some(),
+            Self::I16 => n.to_i16().is_some(),
+            Self::I32 => n.to_i32().is_some(),
+            Self::I64 => n.to_i64().is_some(),
+            Self::Isize => n.to_isize().is_some(),
+            Self::U8 => n.to_u8().is_some(),
+            Self::U16 => n.to_u16().is_some(),
+            Self::U32 => n.to_u32().is_some(),
+            Self::U64 => n.to_u64().is_some(),
+            Self::U128 => n.to_u128().is_some(),
+            Self::Usize => n.to_usize().is_some(),
+        }
+    }
+}
+
+impl IntRange {
+    /// The range of a constant in the domain.
+    pub fn from_const(domain: IntDomain, c: &Const<'_>) -> Option<Self> {
+        fn exact<T: IntervalArithmetic + ConstConvert>(c: &Const<'_>) -> Option<Range<T>> {
+            let value = T::from_const(c)?;
+            Some(Range::new(value, value, RangeType::Regular))
+        }
+        Some(match domain {
+            IntDomain::I8 => IntRange::I8(exact(c)?),
+            IntDomain::I16 => IntRange::I16(exact(c)?),
+            IntDomain::I32 => IntRange::I32(exact(c)?),
+            IntDomain::I64 => IntRange::I64(exact(c)?),
+            IntDomain::Isize => IntRange::Isize(exact(c)?),
+            IntDomain::U8 => IntRange::U8(exact(c)?),
+            IntDomain::U16 => IntRange::U16(exact(c)?),
+            IntDomain::U32 => IntRange::U32(exact(c)?),
+            IntDomain::U64 => IntRange::U64(exact(c)?),
+            IntDomain::U128 => IntRange::U128(exact(c)?),
+            IntDomain::Usize => IntRange::Usize(exact(c)?),
+        })
+    }
+
+    /// Whether the range is inferred, i.e., known and narrower than the whole domain.
+    pub fn is_bounded(&self) -> bool {
+        with_int_range!(self, range => range.is_bounded())
+    }
+
+    /// Whether the range is inverted, i.e., the place is unreachable.
+    pub fn is_inverted(&self) -> bool {
+        with_int_range!(self, range => range.is_inverted())
+    }
+
+    /// Whether the operation may overflow; the operands are in the same domain.
+    pub fn may_overflow(&self, op: BinOp, rhs: &IntRange) -> bool {
+        with_int_ranges!(self, rhs, a, b => a.may_overflow(op, b), _ => false)
+    }
+
+    /// Whether shifting the values left by the amounts may overflow the `bits` of the type, or
+    /// lose any set bit.
+    pub fn may_shl_overflow(&self, amount: &IntRange, bits: u64) -> bool {
+        let (lower, upper) = with_int_range!(amount, range => (range.get_lower().to_u32(), range.get_upper().to_u32()));
+        match (lower, upper) {
+            (Some(_), Some(upper)) if u64::from(upper) < bits => {
+                with_int_range!(self, range => range.may_shl_overflow(upper))
+            }
+            _ => true,
+        }
+    }
+
+    /// Whether all the values are representable in the domain.
+    pub fn fits(&self, domain: IntDomain) -> bool {
+        with_int_range!(self, range => {
+            domain.contains(range.get_lower()) && domain.contains(range.get_upper())
+        })
+    }
+}
+
+impl Display for IntRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        with_int_range!(self, range => write!(f, "{}", range))
+    }
+}
+
+impl Serialize for IntRange {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        with_int_range!(self, range => range.serialize(serializer))
+    }
+}
+
+impl<'tcx> Display for TypedRAResultMapWrapper<'tcx> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "=== Print range analysis resuts ===")?;
+        let mut results: Vec<_> = self.0.iter().collect();
+        results.sort_by_cached_key(|(def_id, _)| stable_def_path_tls(**def_id));
+        for (def_id, ra_result) in results {
+            let fn_name = get_fn_name_byid(def_id);
+            writeln!(f, "Function: {:?} =>", fn_name)?;
+
+            let mut sorted: Vec<_> = ra_result.iter().collect();
+            sorted.sort_by_key(|(place, _)| place.local.as_usize());
+
+            for (place, range) in sorted {
+                writeln!(f, "  {:?} => {}", place, range)?;
+            }
+        }
+        Ok(())
+    }
+}
+
 impl<'tcx, T> Display for RAVecResultMapWrapper<'tcx, T>
 where
     DefId: Debug,
@@ -109,7 +322,10 @@ where
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(f, "=== Print range analysis resuts ===")?;
-        for (def_id, vec_of_maps) in &self.0 {
+        // Sorted by the def paths, as the order of the map varies between runs.
+        let mut results: Vec<_> = self.0.iter().collect();
+        results.sort_by_cached_key(|(def_id, _)| stable_def_path_tls(**def_id));
+        for (def_id, vec_of_maps) in results {
             let fn_name = get_fn_name_byid(def_id);
             writeln!(f, "Function: {:?} =>", fn_name)?;
 
@@ -134,7 +350,9 @@ where
     BinOp: Debug,
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for (path, constraints) in &self.0 {
+        let mut pc: Vec<_> = self.0.iter().collect();
+        pc.sort_by(|a, b| a.0.cmp(b.0));
+        for (path, constraints) in pc {
             writeln!(f, "Path {:?}:", path)?;
             for (p1, p2, op) in constraints {
                 writeln!(f, "    Constraint:{:?} {:?} {:?}", p1, op, p2)?;
@@ -152,9 +370,14 @@ where
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(f, "=== Print paths and constraints ===")?;
-        for (def_id, pc) in &self.0 {
+        // Sorted by the def paths, as the order of the map varies between runs.
+        let mut results: Vec<_> = self.0.iter().collect();
+        results.sort_by_cached_key(|(def_id, _)| stable_def_path_tls(**def_id));
+        for (def_id, pc) in results {
             let fn_name = get_fn_name_byid(def_id);
             writeln!(f, "Function: {:?}:", fn_name)?;
+            let mut pc: Vec<_> = pc.iter().collect();
+            pc.sort_by(|a, b| a.0.cmp(b.0));
             for (path, constraints) in pc {
                 writeln!(f, "  Path {:?}:", path)?;
                 for (p1, p2, op) in constraints {
@@ -165,6 +388,208 @@ where
         Ok(())
     }
 }
+#[derive(Serialize)]
+struct RAResultJson<'a, R: Serialize> {
+    function: String,
+    ranges: Vec<RAPlaceRangeJson<'a, R>>,
+}
+
+#[derive(Serialize)]
+struct RAPlaceRangeJson<'a, R: Serialize> {
+    place: PlaceJson,
+    range: &'a R,
+}
+
+/// The JSON layout is a list of `{ "function", "ranges": [{ "place", "range" }] }` sorted by def
+/// path and by place.
+fn serialize_ranges<'tcx, R, S>(
+    results: &FxHashMap<DefId, HashMap<Place<'tcx>, R>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    R: Serialize,
+    S: serde::Serializer,
+{
+    let mut results: Vec<_> = results
+        .iter()
+        .map(|(def_id, ra_result)| {
+            let mut ranges: Vec<_> = ra_result
+                .iter()
+                .map(|(place, range)| RAPlaceRangeJson {
+                    place: PlaceJson::from(place),
+                    range,
+                })
+                .collect();
+            ranges.sort_by(|a, b| a.place.cmp(&b.place));
+            RAResultJson {
+                function: stable_def_path_tls(*def_id),
+                ranges,
+            }
+        })
+        .collect();
+    results.sort_by(|a, b| a.function.cmp(&b.function));
+    results.serialize(serializer)
+}
+
+impl<'tcx, T> Serialize for RAResultMapWrapper<'tcx, T>
+where
+    T: IntervalArithmetic,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serialize_ranges(&self.0, serializer)
+    }
+}
+
+impl<'tcx> Serialize for TypedRAResultMapWrapper<'tcx> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serialize_ranges(&self.0, serializer)
+    }
+}
+
+#[derive(Serialize)]
+struct PathConstraintJson {
+    function: String,
+    paths: Vec<PathJson>,
+}
+
+#[derive(Serialize)]
+struct PathJson {
+    /// The basic blocks along the path.
+    path: Vec<usize>,
+    constraints: Vec<ConstraintJson>,
+}
+
+#[derive(Serialize)]
+struct ConstraintJson {
+    lhs: PlaceJson,
+    op: String,
+    rhs: PlaceJson,
+}
+
+/// The JSON layout is a list of `{ "function", "paths": [{ "path", "constraints" }] }` sorted by
+/// def path and by path; each constraint is `{ "lhs", "op", "rhs" }`, e.g., `op` is `Lt`.
+impl<'tcx> Serialize for PathConstraintMapWrapper<'tcx> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut results: Vec<_> = self
+            .0
+            .iter()
+            .map(|(def_id, pc)| {
+                let mut paths: Vec<_> = pc
+                    .iter()
+                    .map(|(path, constraints)| PathJson {
+                        path: path.clone(),
+                        constraints: constraints
+                            .iter()
+                            .map(|(lhs, rhs, op)| ConstraintJson {
+                                lhs: PlaceJson::from(lhs),
+                                op: format!("{:?}", op),
+                                rhs: PlaceJson::from(rhs),
+                            })
+                            .collect(),
+                    })
+                    .collect();
+                paths.sort_by(|a, b| a.path.cmp(&b.path));
+                PathConstraintJson {
+                    function: stable_def_path_tls(*def_id),
+                    paths,
+                }
+            })
+            .collect();
+        results.sort_by(|a, b| a.function.cmp(&b.function));
+        results.serialize(serializer)
+    }
+}
+
+/// A MIR place in JSON, e.g., `{ "local": 1, "projection": ["deref", "field(0)"] }` for `(*_1).0`.
+#[derive(Serialize, PartialEq, Eq, PartialOrd, Ord)]
+struct PlaceJson {
+    local: usize,
+    projection: Vec<String>,
+}
+
+impl<'tcx> From<&Place<'tcx>> for PlaceJson {
+    fn from(place: &Place<'tcx>) -> Self {
+        let projection = place
+            .projection
+            .iter()
+            .map(|elem| match elem {
+                ProjectionElem::Deref => "deref".to_string(),
+                ProjectionElem::Field(field, _) => format!("field({})", field.as_usize()),
+                ProjectionElem::Index(local) => format!("index(_{})", local.as_usize()),
+                ProjectionElem::ConstantIndex {
+                    offset, from_end, ..
+                } => {
+                    if from_end {
+                        format!("index(-{})", offset)
+                    } else {
+                        format!("index({})", offset)
+                    }
+                }
+                ProjectionElem::Subslice { from, to, from_end } => {
+                    if from_end {
+                        format!("subslice({}..-{})", from, to)
+                    } else {
+                        format!("subslice({}..{})", from, to)
+                    }
+                }
+                ProjectionElem::Downcast(_, variant) => {
+                    format!("downcast({})", variant.as_usize())
+                }
+                ProjectionElem::OpaqueCast(_) => "opaque_cast".to_string(),
+                ProjectionElem::UnwrapUnsafeBinder(_) => "unwrap_unsafe_binder".to_string(),
+            })
+            .collect();
+        PlaceJson {
+            local: place.local.as_usize(),
+            projection,
+        }
+    }
+}
+
+/// The JSON form of an interval bound: a number, or `"-inf"`/`"+inf"` for the minimum/maximum
+/// value of the domain.
+fn bound_to_json<T: IntervalArithmetic>(bound: Option<&T>) -> serde_json::Value {
+    match bound {
+        None => serde_json::Value::Null,
+        Some(b) if *b == T::min_value() => serde_json::Value::from("-inf"),
+        Some(b) if *b == T::max_value() => serde_json::Value::from("+inf"),
+        Some(b) => {
+            let num = format!("{:?}", b);
+            match num.parse::<serde_json::Number>() {
+                Ok(num) => serde_json::Value::Number(num),
+                Err(_) => serde_json::Value::String(num),
+            }
+        }
+    }
+}
+
+/// The JSON layout is `{ "type", "lower", "upper" }`, where `type` is one of `Unknown`,
+/// `Regular` and `Empty`.
+impl<T> Serialize for Range<T>
+where
+    T: IntervalArithmetic,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_struct("Range", 3)?;
+        state.serialize_field("type", &self.rtype.to_string())?;
+        state.serialize_field("lower", &bound_to_json(self.range.lower()))?;
+        state.serialize_field("upper", &bound_to_json(self.range.upper()))?;
+        state.end()
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
 pub enum RangeType {
     Unknown,
@@ -0,0 +1,119 @@
+//! Bottom-up scheduling of per-function analyses on the thread pool of rustc.
+//!
+//! The strongly connected components of the call graph are grouped into waves, where the callees
+//! of a component are in the earlier waves. The components of a wave are analyzed in parallel
+//! with the summaries of the earlier waves, and their results are merged in the order of the
+//! components, so that the output does not depend on the number of threads. The functions are
+//! analyzed on one thread unless `-jobs=N` is given, which rapx forwards to rustc as `-Zthreads=N`.
+
+use crate::analysis::{
+    Analysis,
+    core::callgraph::{CallGraphAnalysis, default::CallGraphAnalyzer},
+};
+use rustc_data_structures::{
+    fx::{FxHashMap, FxHashSet},
+    sync::{DynSend, DynSync, par_map},
+};
+use rustc_hir::def_id::DefId;
+use rustc_middle::{mir::TerminatorKind, ty::TyCtxt};
+
+pub struct BottomUpScheduler {
+    waves: Vec<Vec<Vec<DefId>>>,
+}
+
+impl BottomUpScheduler {
+    /// Schedule the functions; those not in the call graph are put in the first wave.
+    pub fn new(tcx: TyCtxt<'_>, def_ids: impl IntoIterator<Item = DefId>) -> Self {
+        let def_ids: FxHashSet<DefId> = def_ids.into_iter().collect();
+        let mut analyzer = CallGraphAnalyzer::new(tcx);
+        analyzer.run();
+        let mut waves: Vec<Vec<Vec<DefId>>> = analyzer
+            .get_callgraph()
+            .scc_waves()
+            .into_iter()
+            .map(|wave| {
+                wave.into_iter()
+                    .map(|scc| {
+                        scc.into_iter()
+                            .filter(|def_id| def_ids.contains(def_id))
+                            .collect::<Vec<_>>()
+                    })
+                    .filter(|scc| !scc.is_empty())
+                    .collect::<Vec<_>>()
+            })
+            .filter(|wave| !wave.is_empty())
+            .collect();
+        let scheduled: FxHashSet<DefId> = waves.iter().flatten().flatten().copied().collect();
+        let mut rest: Vec<DefId> = def_ids
+            .into_iter()
+            .filter(|def_id| !scheduled.contains(def_id))
+            .collect();
+        rest.sort_by_key(|def_id| (def_id.krate, def_id.index));
+        if waves.is_empty() {
+            waves.push(Vec::new());
+        }
+        waves[0].extend(rest.into_iter().map(|def_id| vec![def_id]));
+        Self { waves }
+    }
+
+    /// Analyze the components wave by wave. `analyze` gets the summaries of the earlier waves and
+    /// returns the summaries computed for the component, together with its other results. The
+    /// summaries of the component members replace the existing ones, while those of the other
+    /// functions summarized on demand are only added if absent.
+    pub fn run<S, R, F>(&self, summaries: &mut FxHashMap<DefId, S>, analyze: F) -> Vec<R>
+    where
+        S: DynSend + DynSync,
+        R: DynSend,
+        F: Fn(&[DefId], &FxHashMap<DefId, S>) -> (Vec<(DefId, S)>, R) + DynSend + DynSync,
+    {
+        let mut results = Vec::new();
+        for wave in &self.waves {
+            let shared = &*summaries;
+            let outputs: Vec<_> = par_map(wave, |scc| (scc, analyze(scc, shared)));
+            for (scc, (new_summaries, result)) in outputs {
+                for (def_id, summary) in new_summaries {
+                    if scc.contains(&def_id) {
+                        summaries.insert(def_id, summary);
+                    } else {
+                        summaries.entry(def_id).or_insert(summary);
+                    }
+                }
+                results.push(result);
+            }
+        }
+        results
+    }
+}
+
+/// The functions called in the body by their `FnDef`s, whose summaries are looked up when the
+/// function is analyzed.
+pub fn direct_callees(tcx: TyCtxt<'_>, def_id: DefId) -> FxHashSet<DefId> {
+    if def_id
+        .as_local()
+        .is_some_and(|local_def_id| tcx.hir_body_const_context(local_def_id).is_some())
+        || !tcx.is_mir_available(def_id)
+    {
+        return FxHashSet::default();
+    }
+    let body = tcx.optimized_mir(def_id);
+    body.basic_blocks
+        .iter()
+        .filter_map(|data| match &data.terminator().kind {
+            TerminatorKind::Call { func, .. } => func.const_fn_def().map(|(callee, _)| callee),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Copy the summaries of the functions called by the component, so that the component can be
+/// analyzed with its own map of summaries.
+pub fn callee_summaries<S: Clone>(
+    tcx: TyCtxt<'_>,
+    scc: &[DefId],
+    summaries: &FxHashMap<DefId, S>,
+) -> FxHashMap<DefId, S> {
+    scc.iter()
+        .flat_map(|def_id| direct_callees(tcx, *def_id))
+        .filter_map(|callee| Some((callee, summaries.get(&callee)?.clone())))
+        .collect()
+}
@@ -149,7 +149,7 @@ impl<'tcx> Replacer<'tcx> {
                         if let StatementKind::Assign(box (lhs, Rvalue::Use(OP1))) =
                             &stmt_original.kind
                         {
-                            if lhs.clone() == op1.place().unwrap() {
+                            if op1.place() == Some(*lhs) {
                                 return_op1 = OP1;
                             }
                         }
@@ -159,7 +159,7 @@ impl<'tcx> Replacer<'tcx> {
                             if let StatementKind::Assign(box (lhs, Rvalue::Use(OP2))) =
                                 &stmt_original.kind
                             {
-                                if lhs.clone() == op2.place().unwrap() {
+                                if op2.place() == Some(*lhs) {
                                     return_op2 = OP2;
                                 }
                             }
@@ -352,18 +352,26 @@ impl<'tcx> Replacer<'tcx> {
                     (None, Some(_)) | (Some(_), None) => {
                         let mut operand: IndexVec<_, _> = IndexVec::with_capacity(3);
 
-                        let place = match op1 {
+                        // The variable comes first, e.g., `10 < x` is recorded as `x > 10`.
+                        let const_in_left = const_op1.is_some();
+                        let (variable, constant) = if const_in_left {
+                            (op2.clone(), op1.clone())
+                        } else {
+                            (op1.clone(), op2.clone())
+                        };
+                        let place = match variable {
                             Operand::Copy(p) | Operand::Move(p) => Place::from(p),
                             _ => panic!("Expected a place"),
                         };
-                        operand.push(op1.clone());
-                        operand.push(op2.clone());
+                        operand.push(variable);
+                        operand.push(constant);
                         let rvalue;
-                        if value == 0 {
-                            operand.push(flip_cmp_operand.clone());
-                        } else {
-                            operand.push(cmp_operand.clone());
-                        }
+                        operand.push(match (value == 0, const_in_left) {
+                            (true, false) => flip_cmp_operand.clone(),
+                            (false, false) => cmp_operand.clone(),
+                            (true, true) => flip_reverse_cmp_operand.clone(),
+                            (false, true) => reverse_cmp_operand.clone(),
+                        });
                         let ADT = AggregateKind::Adt(
                             self.ssatransformer.essa_def_id.clone(),
                             rustc_abi::VariantIdx::from_u32(0),
@@ -476,7 +484,7 @@ impl<'tcx> Replacer<'tcx> {
                     if let Some((op1, op2, cmp_op)) =
                         self.extract_condition(switch_place, switch_block_data)
                     {
-                        if op2.constant().is_none() {
+                        if op1.constant().is_none() && op2.constant().is_none() {
                             let essa_statement = body.basic_blocks.as_mut()[succ_bb]
                                 .statements
                                 .get_mut(0)
@@ -517,7 +525,10 @@ impl<'tcx> Replacer<'tcx> {
                                 StatementKind::Assign(box (place, rvalue)) => {
                                     if let Rvalue::Aggregate(_, operands) = rvalue {
                                         let loc: usize = 0;
-                                        operands[FieldIdx::from_usize(loc)] = op1.clone();
+                                        // The variable comes first, as in `essa_assign_statement`.
+                                        let variable =
+                                            if op1.constant().is_some() { op2 } else { op1 };
+                                        operands[FieldIdx::from_usize(loc)] = variable.clone();
                                     }
                                 }
                                 _ => {}
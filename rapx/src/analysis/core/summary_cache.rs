@@ -0,0 +1,189 @@
+//! Per-function summaries cached on disk across runs, enabled by `-cache=<dir>`.
+//!
+//! The summaries of an analysis are stored in `{dir}/{crate}-{stable crate id}-{analysis}.json`.
+//! Each function is identified by its `DefPathHash` and stamped with the hash of its MIR body,
+//! which also covers the crate hashes of the dependencies it calls. A summary is reused only if
+//! neither the function nor any function it calls directly or indirectly has changed.
+
+use crate::{
+    analysis::{
+        Analysis,
+        core::callgraph::{CallGraphAnalysis, default::CallGraphAnalyzer},
+    },
+    utils::{
+        fs::rap_write_atomically,
+        json::{def_id_of_hash_pair, def_path_hash_pair},
+    },
+};
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_hir::def_id::{DefId, LOCAL_CRATE};
+use rustc_middle::{
+    mir::{TerminatorKind, pretty::MirWriter},
+    ty::{self, TyCtxt},
+};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    io,
+    path::{Path, PathBuf},
+};
+
+/// Bump this version whenever the layout of the cache file is changed incompatibly.
+pub const CACHE_VERSION: u32 = 2;
+
+#[derive(Serialize, Deserialize)]
+struct CacheFile<T> {
+    version: u32,
+    /// The summaries depend on the implementation of the analyses.
+    rapx_version: String,
+    functions: Vec<CacheEntry<T>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheEntry<T> {
+    def_path_hash: (u64, u64),
+    /// Only for human readers; the function is identified by `def_path_hash`.
+    function: String,
+    body_hash: u64,
+    summary: T,
+}
+
+pub struct SummaryCache<'tcx, T> {
+    tcx: TyCtxt<'tcx>,
+    path: PathBuf,
+    summaries: FxHashMap<DefId, T>,
+}
+
+impl<'tcx, T: Serialize + DeserializeOwned> SummaryCache<'tcx, T> {
+    /// Load the cached summaries of the analysis with the configuration, except those of the
+    /// changed functions and their transitive callers.
+    pub fn load(tcx: TyCtxt<'tcx>, dir: &Path, analysis: &str, config: &impl Hash) -> Self {
+        let mut hasher = DefaultHasher::new();
+        config.hash(&mut hasher);
+        let path = dir.join(format!(
+            "{}-{:016x}-{}-{:016x}.json",
+            tcx.crate_name(LOCAL_CRATE),
+            tcx.stable_crate_id(LOCAL_CRATE).as_u64(),
+            analysis,
+            hasher.finish()
+        ));
+        let mut cache = SummaryCache {
+            tcx,
+            path,
+            summaries: FxHashMap::default(),
+        };
+        let Ok(content) = std::fs::read(&cache.path) else {
+            return cache;
+        };
+        let file: CacheFile<T> = match serde_json::from_slice(&content) {
+            Ok(file) => file,
+            Err(err) => {
+                rap_warn!("Failed to read the cache {}: {}", cache.path.display(), err);
+                return cache;
+            }
+        };
+        if file.version != CACHE_VERSION || file.rapx_version != env!("CARGO_PKG_VERSION") {
+            return cache;
+        }
+        let mut changed = Vec::new();
+        for entry in file.functions {
+            let Some(def_id) =
+                def_id_of_hash_pair(tcx, entry.def_path_hash).filter(|def_id| def_id.is_local())
+            else {
+                continue;
+            };
+            if body_hash(tcx, def_id) == Some(entry.body_hash) {
+                cache.summaries.insert(def_id, entry.summary);
+            } else {
+                changed.push(def_id);
+            }
+        }
+        // A new function needs no invalidation, as its callers are changed to call it.
+        if !changed.is_empty() {
+            let mut analyzer = CallGraphAnalyzer::new(tcx);
+            analyzer.run();
+            let callgraph = analyzer.get_callgraph();
+            for def_id in &changed {
+                for caller in callgraph.transitive_callers(*def_id) {
+                    cache.summaries.remove(&caller);
+                }
+            }
+        }
+        rap_info!(
+            "Reuse the cached {} summaries of {} functions; {} functions changed.",
+            analysis,
+            cache.summaries.len(),
+            changed.len()
+        );
+        cache
+    }
+
+    pub fn get(&self, def_id: DefId) -> Option<&T> {
+        self.summaries.get(&def_id)
+    }
+
+    pub fn insert(&mut self, def_id: DefId, summary: T) {
+        self.summaries.insert(def_id, summary);
+    }
+
+    /// Write the summaries, which replace those of the previous run.
+    pub fn store(&self) {
+        let mut functions: Vec<CacheEntry<&T>> = self
+            .summaries
+            .iter()
+            .filter_map(|(def_id, summary)| {
+                Some(CacheEntry {
+                    def_path_hash: def_path_hash_pair(self.tcx, *def_id),
+                    function: self.tcx.def_path_str(*def_id),
+                    body_hash: body_hash(self.tcx, *def_id)?,
+                    summary,
+                })
+            })
+            .collect();
+        functions.sort_by_key(|entry| entry.def_path_hash);
+        let file = CacheFile {
+            version: CACHE_VERSION,
+            rapx_version: env!("CARGO_PKG_VERSION").to_owned(),
+            functions,
+        };
+        match serde_json::to_vec(&file)
+            .map_err(io::Error::from)
+            .and_then(|content| rap_write_atomically(&self.path, &content))
+        {
+            Ok(()) => rap_debug!("Write the cache {}", self.path.display()),
+            Err(err) => rap_warn!("Failed to write the cache {}: {}", self.path.display(), err),
+        }
+    }
+}
+
+/// The hash of the MIR body of the local function and the crate hashes of the dependencies it
+/// calls; `None` if the function has no body to analyze.
+pub fn body_hash(tcx: TyCtxt<'_>, def_id: DefId) -> Option<u64> {
+    let local_def_id = def_id.as_local()?;
+    if tcx.hir_body_const_context(local_def_id).is_some() || !tcx.is_mir_available(def_id) {
+        return None;
+    }
+    let body = tcx.optimized_mir(def_id);
+    let mut mir = Vec::new();
+    MirWriter::new(tcx).write_mir_fn(body, &mut mir).ok()?;
+    let mut hasher = DefaultHasher::new();
+    mir.hash(&mut hasher);
+    // The crate numbers differ between sessions, so the crate hashes are sorted instead.
+    let mut crate_hashes: Vec<u128> = body
+        .basic_blocks
+        .iter()
+        .filter_map(|data| match &data.terminator().kind {
+            TerminatorKind::Call { func, .. } => match func.ty(body, tcx).kind() {
+                ty::FnDef(callee, _) if !callee.is_local() => Some(callee.krate),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect::<FxHashSet<_>>()
+        .into_iter()
+        .map(|krate| tcx.crate_hash(krate).as_u128())
+        .collect();
+    crate_hashes.sort();
+    crate_hashes.hash(&mut hasher);
+    Some(hasher.finish())
+}
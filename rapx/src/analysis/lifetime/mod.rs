@@ -0,0 +1,90 @@
+pub mod unbounded;
+
+use crate::{
+    analysis::{
+        upg::hir_visitor::ContainsUnsafe,
+        utils::fn_info::{check_safety, get_rawptr_deref, get_unsafe_callees},
+    },
+    utils::{
+        diagnostics::{Finding, FindingKind},
+        log::{span_to_filename, span_to_line_number},
+    },
+};
+use rustc_hir::{Safety, def::DefKind};
+use rustc_middle::ty::TyCtxt;
+use rustc_span::def_id::DefId;
+use unbounded::{SourceKind, UnboundedAnalysis, UnboundedReturn};
+
+/// Detect the safe public functions returning references whose lifetimes are not tied to the
+/// inputs, while the references are derived from raw pointers or produced by `transmute`.
+pub struct UnboundedLifetimeCheck<'tcx> {
+    pub tcx: TyCtxt<'tcx>,
+}
+
+impl<'tcx> UnboundedLifetimeCheck<'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>) -> Self {
+        Self { tcx }
+    }
+
+    pub fn start(&self) {
+        for local_def_id in self.tcx.mir_keys(()) {
+            let def_id = local_def_id.to_def_id();
+            if !matches!(self.tcx.def_kind(def_id), DefKind::Fn | DefKind::AssocFn)
+                || !self.tcx.is_mir_available(def_id)
+                || !self.tcx.visibility(def_id).is_public()
+                || check_safety(self.tcx, def_id) == Safety::Unsafe
+            {
+                continue;
+            }
+            // Only the functions with unsafe code can produce such references.
+            let Some(body_id) = self.tcx.hir_maybe_body_owned_by(*local_def_id) else {
+                continue;
+            };
+            let (_, block_unsafe) = ContainsUnsafe::contains_unsafe(self.tcx, body_id.id());
+            if !block_unsafe
+                && get_rawptr_deref(self.tcx, def_id).is_empty()
+                && get_unsafe_callees(self.tcx, def_id).is_empty()
+            {
+                continue;
+            }
+            if let Some(unbounded) = UnboundedAnalysis::new(self.tcx, def_id).run() {
+                self.report(def_id, &unbounded);
+            }
+        }
+    }
+
+    fn report(&self, def_id: DefId, unbounded: &UnboundedReturn) {
+        let fn_span = self.tcx.optimized_mir(def_id).span;
+        let source = &unbounded.source;
+        let produced = match &source.kind {
+            SourceKind::RawDeref => "derived from a raw pointer dereference".to_owned(),
+            SourceKind::RawToRef(api) => format!("derived from a raw pointer by `{}`", api),
+            SourceKind::Transmute => "produced by `transmute`".to_owned(),
+        };
+        let regions = unbounded
+            .regions
+            .iter()
+            .map(|region| format!("`{}`", region))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let detail = format!(
+            "Unbounded lifetime: Location in file {} line {}.\n    | MIR detail: the returned reference is {} at bb{}, but its lifetime {} is not tied to any input.",
+            span_to_filename(source.span),
+            span_to_line_number(source.span),
+            produced,
+            source.bb,
+            regions,
+        );
+        Finding::new(
+            FindingKind::UnboundedLifetime,
+            def_id,
+            "Unbounded lifetime detected.",
+        )
+        .primary(source.span, detail.as_str())
+        .related(
+            self.tcx.def_span(def_id),
+            "the lifetime of the return value is chosen by the caller",
+        )
+        .report(fn_span);
+    }
+}
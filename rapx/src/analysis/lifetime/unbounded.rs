@@ -0,0 +1,250 @@
+//! The references returned with lifetimes unbounded by the inputs.
+//!
+//! A lifetime in the return type is unbounded if it is `'static` or a lifetime parameter not
+//! appearing in, nor outlived by a lifetime of, the input types, so the caller can choose it
+//! freely. Such a lifetime is sound only if the reference is derived from data living long
+//! enough, which is not the case for the references derived from raw pointers, e.g., by `&*ptr`
+//! or `slice::from_raw_parts`, or produced by `transmute`.
+
+use crate::analysis::{
+    core::ownership_model::{PathPatterns, model_path},
+    safedrop::provenance::is_pass_through,
+};
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_middle::{
+    mir::{
+        Body, CastKind, Local, Operand, Place, ProjectionElem, RETURN_PLACE, Rvalue, StatementKind,
+        TerminatorKind,
+    },
+    ty::{self, Region, Ty, TyCtxt},
+};
+use rustc_span::{Span, def_id::DefId};
+
+/// The APIs converting raw pointers into references.
+const RAW_TO_REF_APIS: &[&str] = &[
+    "core::slice::from_raw_parts*",
+    "core::ptr::*_ptr::<impl *>::as_ref*",
+    "core::ptr::*_ptr::<impl *>::as_mut*",
+    "core::ptr::NonNull::<*>::as_ref",
+    "core::ptr::NonNull::<*>::as_mut",
+    "core::ptr::NonNull::<*>::as_uninit_*",
+];
+
+fn is_raw_to_ref(path: &str) -> bool {
+    static PATTERNS: PathPatterns<&str> = PathPatterns::new(RAW_TO_REF_APIS);
+    PATTERNS.matches(path)
+}
+
+/// How the returned reference is produced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SourceKind {
+    /// `&*ptr` or `&mut *ptr` of a raw pointer.
+    RawDeref,
+    /// An API converting a raw pointer into a reference, e.g., `slice::from_raw_parts`.
+    RawToRef(String),
+    Transmute,
+}
+
+#[derive(Debug, Clone)]
+pub struct Source {
+    pub kind: SourceKind,
+    pub bb: usize,
+    pub span: Span,
+}
+
+/// A reference returned with unbounded lifetimes.
+#[derive(Debug, Clone)]
+pub struct UnboundedReturn {
+    /// The unbounded lifetimes in the return type, e.g., `'a` or `'static`.
+    pub regions: Vec<String>,
+    pub source: Source,
+}
+
+pub struct UnboundedAnalysis<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    def_id: DefId,
+    body: &'tcx Body<'tcx>,
+}
+
+impl<'tcx> UnboundedAnalysis<'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>, def_id: DefId) -> Self {
+        Self {
+            tcx,
+            def_id,
+            body: tcx.optimized_mir(def_id),
+        }
+    }
+
+    pub fn run(&self) -> Option<UnboundedReturn> {
+        let regions = self.unbounded_regions();
+        if regions.is_empty() {
+            return None;
+        }
+        let source = self.return_source()?;
+        Some(UnboundedReturn {
+            regions: regions.iter().map(|region| region.to_string()).collect(),
+            source,
+        })
+    }
+
+    /// The lifetimes in the return type that are neither in the input types nor outlived by
+    /// those in the input types.
+    fn unbounded_regions(&self) -> Vec<Region<'tcx>> {
+        let sig = self
+            .tcx
+            .fn_sig(self.def_id)
+            .instantiate_identity()
+            .skip_binder();
+        let mut bounded: FxHashSet<Region<'tcx>> =
+            sig.inputs().iter().flat_map(|ty| regions(*ty)).collect();
+        let predicates = self
+            .tcx
+            .predicates_of(self.def_id)
+            .instantiate_identity(self.tcx)
+            .predicates;
+        let outlives: Vec<(Region<'tcx>, Region<'tcx>)> = predicates
+            .iter()
+            .filter_map(|clause| {
+                let ty::OutlivesPredicate(long, short) =
+                    clause.as_region_outlives_clause()?.skip_binder();
+                Some((long, short))
+            })
+            .collect();
+        loop {
+            let len = bounded.len();
+            for (long, short) in &outlives {
+                if bounded.contains(long) {
+                    bounded.insert(*short);
+                }
+            }
+            if bounded.len() == len {
+                break;
+            }
+        }
+        let mut unbounded = Vec::new();
+        for region in regions(sig.output()) {
+            let is_param = match region.kind() {
+                ty::ReStatic | ty::ReEarlyParam(_) => true,
+                ty::ReBound(kind, _) => kind == ty::BoundVarIndexKind::Bound(ty::INNERMOST),
+                _ => false,
+            };
+            if is_param && !bounded.contains(&region) && !unbounded.contains(&region) {
+                unbounded.push(region);
+            }
+        }
+        unbounded
+    }
+
+    /// The raw pointer dereference or `transmute` that the return value is derived from.
+    fn return_source(&self) -> Option<Source> {
+        let mut sources: FxHashMap<Local, Source> = FxHashMap::default();
+        loop {
+            let len = sources.len();
+            for (bb, data) in self.body.basic_blocks.iter_enumerated() {
+                for stmt in &data.statements {
+                    let StatementKind::Assign(box (place, rvalue)) = &stmt.kind else {
+                        continue;
+                    };
+                    if sources.contains_key(&place.local) {
+                        continue;
+                    }
+                    let source = match rvalue {
+                        Rvalue::Ref(_, _, borrowed) if self.has_raw_deref(borrowed) => {
+                            Some(Source {
+                                kind: SourceKind::RawDeref,
+                                bb: bb.as_usize(),
+                                span: stmt.source_info.span,
+                            })
+                        }
+                        Rvalue::Cast(CastKind::Transmute, _, ty) if has_regions(*ty) => {
+                            Some(Source {
+                                kind: SourceKind::Transmute,
+                                bb: bb.as_usize(),
+                                span: stmt.source_info.span,
+                            })
+                        }
+                        _ => rvalue_places(rvalue)
+                            .into_iter()
+                            .find_map(|used| sources.get(&used.local).cloned()),
+                    };
+                    if let Some(source) = source {
+                        sources.insert(place.local, source);
+                    }
+                }
+                let TerminatorKind::Call {
+                    func,
+                    args,
+                    destination,
+                    ..
+                } = &data.terminator().kind
+                else {
+                    continue;
+                };
+                let Some((callee, _)) = func.const_fn_def() else {
+                    continue;
+                };
+                if sources.contains_key(&destination.local) {
+                    continue;
+                }
+                let path = model_path(self.tcx, callee);
+                let span = data.terminator().source_info.span;
+                let source = if path.starts_with("core::intrinsics::transmute") {
+                    Some(Source {
+                        kind: SourceKind::Transmute,
+                        bb: bb.as_usize(),
+                        span,
+                    })
+                } else if is_raw_to_ref(&path) {
+                    Some(Source {
+                        kind: SourceKind::RawToRef(self.tcx.def_path_str(callee)),
+                        bb: bb.as_usize(),
+                        span,
+                    })
+                } else if is_pass_through(&path) {
+                    args.first()
+                        .and_then(|arg| arg.node.place())
+                        .and_then(|arg| sources.get(&arg.local).cloned())
+                } else {
+                    None
+                };
+                if let Some(source) = source {
+                    sources.insert(destination.local, source);
+                }
+            }
+            if sources.len() == len {
+                break;
+            }
+        }
+        sources.remove(&RETURN_PLACE)
+    }
+
+    fn has_raw_deref(&self, place: &Place<'tcx>) -> bool {
+        place.iter_projections().any(|(base, elem)| {
+            elem == ProjectionElem::Deref && base.ty(self.body, self.tcx).ty.is_raw_ptr()
+        })
+    }
+}
+
+fn regions<'tcx>(ty: Ty<'tcx>) -> impl Iterator<Item = Region<'tcx>> {
+    ty.walk().filter_map(|arg| arg.as_region())
+}
+
+fn has_regions(ty: Ty<'_>) -> bool {
+    regions(ty).next().is_some()
+}
+
+/// The places whose values flow into the result of the rvalue.
+fn rvalue_places<'a, 'tcx>(rvalue: &'a Rvalue<'tcx>) -> Vec<&'a Place<'tcx>> {
+    let operand_place = |op: &'a Operand<'tcx>| match op {
+        Operand::Copy(place) | Operand::Move(place) => Some(place),
+        _ => None,
+    };
+    match rvalue {
+        Rvalue::Use(op) | Rvalue::Cast(_, op, _) => operand_place(op).into_iter().collect(),
+        Rvalue::Ref(_, _, place) | Rvalue::RawPtr(_, place) | Rvalue::CopyForDeref(place) => {
+            vec![place]
+        }
+        Rvalue::Aggregate(_, ops) => ops.iter().filter_map(operand_place).collect(),
+        _ => Vec::new(),
+    }
+}
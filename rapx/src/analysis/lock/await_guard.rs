@@ -0,0 +1,65 @@
+//! The lock guards held across `.await`.
+//!
+//! The locals live across a suspension point of an `async` body are saved in the coroutine,
+//! whose layout lists their types. A saved `MutexGuard` or `RwLock*Guard` keeps the lock while
+//! the task is suspended, which blocks the other tasks on the lock and, since the guards are not
+//! `Send`, keeps the future from being spawned on a multi-threaded executor.
+
+use crate::analysis::core::ownership_model::{PathPatterns, model_path};
+use rustc_hir::{CoroutineDesugaring, CoroutineKind};
+use rustc_middle::ty::{self, TyCtxt};
+use rustc_span::{Span, def_id::DefId};
+
+/// The guards holding locks.
+const GUARD_TYPES: &[&str] = &[
+    "core::sync::*MutexGuard",
+    "core::sync::*RwLockReadGuard",
+    "core::sync::*RwLockWriteGuard",
+    "lock_api::*Guard",
+];
+
+fn is_guard(path: &str) -> bool {
+    static PATTERNS: PathPatterns<&str> = PathPatterns::new(GUARD_TYPES);
+    PATTERNS.matches(path)
+}
+
+/// A guard saved across a suspension point.
+#[derive(Debug, Clone)]
+pub struct GuardAcrossAwait {
+    /// The `async` function or block.
+    pub def_id: DefId,
+    pub guard_ty: String,
+    /// The local holding the guard.
+    pub span: Span,
+}
+
+/// The guards saved across the suspension points of the `async` body.
+pub fn guards_across_await(tcx: TyCtxt<'_>, def_id: DefId) -> Vec<GuardAcrossAwait> {
+    if !matches!(
+        tcx.coroutine_kind(def_id),
+        Some(CoroutineKind::Desugared(CoroutineDesugaring::Async, _))
+    ) {
+        return Vec::new();
+    }
+    let Some(layout) = tcx.mir_coroutine_witnesses(def_id) else {
+        return Vec::new();
+    };
+    let mut guards = Vec::new();
+    for saved in layout.field_tys.iter() {
+        let guard = saved.ty.walk().find_map(|arg| {
+            let ty = arg.as_type()?;
+            let ty::Adt(adt_def, _) = ty.kind() else {
+                return None;
+            };
+            is_guard(&model_path(tcx, adt_def.did())).then(|| ty.to_string())
+        });
+        if let Some(guard_ty) = guard {
+            guards.push(GuardAcrossAwait {
+                def_id: tcx.typeck_root_def_id(def_id),
+                guard_ty,
+                span: saved.source_info.span,
+            });
+        }
+    }
+    guards
+}
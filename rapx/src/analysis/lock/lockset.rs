@@ -0,0 +1,612 @@
+//! The locks held at each point of a function.
+//!
+//! A lock is identified by the access path of the receiver of `lock`, `read` or `write`, e.g.,
+//! `(*_1).0` for `self.balance.lock()`, which is resolved through the references, the
+//! dereferences of smart pointers and the getters summarized by the alias analysis. The guard
+//! returned by the call, unwrapped by `unwrap` or `expect`, holds the lock until it is dropped,
+//! either explicitly or at the end of its scope. The locks held on all the paths reaching a
+//! point form its lock set; locking a lock in the lock set again is a double lock. The functions
+//! are summarized bottom-up by the locks they acquire on their arguments and statics, so that a
+//! call is checked as if the locks were acquired at the call site.
+//!
+//! The locks of different functions are compared by their classes, i.e., the field of the type or
+//! the static declaring them, e.g., `Account.balance`. Acquiring a lock of a class while holding
+//! one of another class gives an edge of the lock order.
+
+use crate::analysis::{
+    core::{
+        alias_analysis::AAResultMap,
+        ownership_model::{PathPatterns, model_path},
+    },
+    safedrop::provenance::is_pass_through,
+};
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_middle::{
+    mir::{
+        BasicBlock, Body, Local, Operand, Place, ProjectionElem, Rvalue, StatementKind,
+        TerminatorKind,
+    },
+    ty::{self, Instance, Ty, TyCtxt, TypingEnv},
+};
+use rustc_span::{Span, def_id::DefId, sym};
+use std::collections::VecDeque;
+
+/// The calls returning lock guards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LockApi {
+    /// `Mutex::lock` or `RwLock::write`.
+    Exclusive,
+    /// `RwLock::read` or `ReentrantMutex::lock`, which can be held together on one thread.
+    Shared,
+}
+
+const LOCK_APIS: &[(&str, LockApi)] = &[
+    ("*::Mutex::<*>::lock", LockApi::Exclusive),
+    ("*::RwLock::<*>::write", LockApi::Exclusive),
+    ("*::RwLock::<*>::read", LockApi::Shared),
+    ("*::RwLock::<*>::upgradable_read", LockApi::Shared),
+    ("*::ReentrantMutex::<*>::lock", LockApi::Shared),
+];
+
+/// The calls returning the guard in their first argument, besides `unwrap` and `expect`.
+const GUARD_PASS_THROUGH: &[&str] = &[
+    "core::result::Result::<*>::unwrap_or_else",
+    "core::result::Result::<*>::ok",
+];
+
+/// The calls returning a reference to the same lock as their first argument.
+const LOCK_PASS_THROUGH: &[&str] = &[
+    "core::ops::Deref::deref",
+    "core::convert::AsRef::as_ref",
+    "core::borrow::Borrow::borrow",
+];
+
+fn lock_api(path: &str) -> Option<LockApi> {
+    static APIS: PathPatterns<(&str, LockApi)> = PathPatterns::keyed(LOCK_APIS);
+    APIS.find(path).map(|(_, api)| *api)
+}
+
+fn is_guard_pass_through(path: &str) -> bool {
+    static PATTERNS: PathPatterns<&str> = PathPatterns::new(GUARD_PASS_THROUGH);
+    is_pass_through(path) || PATTERNS.matches(path)
+}
+
+fn is_lock_pass_through(path: &str) -> bool {
+    static PATTERNS: PathPatterns<&str> = PathPatterns::new(LOCK_PASS_THROUGH);
+    PATTERNS.matches(path)
+}
+
+/// The variable an access path starts from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LockRoot {
+    /// The argument of the function, numbered from 1.
+    Arg(usize),
+    Static(DefId),
+    Local(usize),
+}
+
+/// The access path of a lock, where the dereferences are transparent.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LockPlace {
+    pub root: LockRoot,
+    pub fields: Vec<usize>,
+}
+
+/// A lock acquired by a function or its callees, in the summary of the function.
+#[derive(Debug, Clone)]
+pub struct Acquire {
+    pub place: LockPlace,
+    pub class: Option<String>,
+    pub exclusive: bool,
+    /// The call of `lock`, `read` or `write`.
+    pub span: Span,
+}
+
+/// A lock held by a guard.
+#[derive(Debug, Clone)]
+struct Held {
+    guard: Local,
+    place: LockPlace,
+    class: Option<String>,
+    exclusive: bool,
+    span: Span,
+}
+
+/// Locking a lock already held by the function.
+#[derive(Debug, Clone)]
+pub struct DoubleLock {
+    /// Where the lock is acquired first.
+    pub held_span: Span,
+    /// Where the lock is acquired again, either directly or in the callee.
+    pub bb: usize,
+    pub span: Span,
+    pub callee: Option<String>,
+    /// The call of `lock`, `read` or `write` in the callee.
+    pub callee_span: Option<Span>,
+}
+
+/// Acquiring a lock of the class `to` while holding one of the class `from`.
+#[derive(Debug, Clone)]
+pub struct LockEdge {
+    pub from: String,
+    pub to: String,
+    pub def_id: DefId,
+    pub from_span: Span,
+    pub to_span: Span,
+}
+
+#[derive(Debug, Default)]
+pub struct LockSetResult {
+    pub double_locks: Vec<DoubleLock>,
+    pub edges: Vec<LockEdge>,
+}
+
+type LockSet = Vec<Held>;
+
+/// The definition of a local used to resolve access paths.
+#[derive(Debug, Clone)]
+enum Def<'tcx> {
+    /// A reference to or a copy of the place, with the fields appended.
+    Place(Place<'tcx>, Vec<usize>),
+    Static(DefId),
+    /// Defined more than once or by an unknown value.
+    Opaque,
+}
+
+pub struct LockSetAnalysis<'tcx, 'a> {
+    tcx: TyCtxt<'tcx>,
+    def_id: DefId,
+    typing_env: TypingEnv<'tcx>,
+    body: &'tcx Body<'tcx>,
+    alias: &'a AAResultMap,
+    summaries: &'a FxHashMap<DefId, Vec<Acquire>>,
+    defs: FxHashMap<Local, Def<'tcx>>,
+}
+
+impl<'tcx, 'a> LockSetAnalysis<'tcx, 'a> {
+    pub fn new(
+        tcx: TyCtxt<'tcx>,
+        def_id: DefId,
+        alias: &'a AAResultMap,
+        summaries: &'a FxHashMap<DefId, Vec<Acquire>>,
+    ) -> Self {
+        let mut analysis = Self {
+            tcx,
+            def_id,
+            typing_env: TypingEnv::post_analysis(tcx, def_id),
+            body: tcx.optimized_mir(def_id),
+            alias,
+            summaries,
+            defs: FxHashMap::default(),
+        };
+        analysis.collect_defs();
+        analysis
+    }
+
+    /// Check the function, and summarize the locks it acquires on its arguments and statics.
+    pub fn run(&self) -> (Vec<Acquire>, LockSetResult) {
+        let entries = self.propagate();
+        let mut result = LockSetResult::default();
+        let mut acquires = Vec::new();
+        for (bb, entry) in entries.iter_enumerated() {
+            let Some(mut state) = entry.clone() else {
+                continue;
+            };
+            self.transfer(bb, &mut state, Some(&mut result), &mut acquires);
+        }
+        let mut seen = FxHashSet::default();
+        acquires.retain(|acquire: &Acquire| {
+            !matches!(acquire.place.root, LockRoot::Local(_))
+                && seen.insert((acquire.place.clone(), acquire.exclusive))
+        });
+        (acquires, result)
+    }
+
+    /// The lock sets at the entries of the blocks, where a lock is held at a join point only if
+    /// it is held on all the incoming edges. The cleanup blocks are not visited.
+    fn propagate(&self) -> rustc_index::IndexVec<BasicBlock, Option<LockSet>> {
+        let blocks = &self.body.basic_blocks;
+        let mut entries: rustc_index::IndexVec<BasicBlock, Option<LockSet>> =
+            rustc_index::IndexVec::from_elem_n(None, blocks.len());
+        entries[BasicBlock::from_usize(0)] = Some(Vec::new());
+        let mut worklist: VecDeque<BasicBlock> = VecDeque::from([BasicBlock::from_usize(0)]);
+        while let Some(bb) = worklist.pop_front() {
+            let Some(mut state) = entries[bb].clone() else {
+                continue;
+            };
+            self.transfer(bb, &mut state, None, &mut Vec::new());
+            for succ in blocks[bb].terminator().successors() {
+                if blocks[succ].is_cleanup {
+                    continue;
+                }
+                let joined = match &entries[succ] {
+                    None => state.clone(),
+                    Some(old) => old
+                        .iter()
+                        .filter(|held| {
+                            state
+                                .iter()
+                                .any(|h| h.guard == held.guard && h.place == held.place)
+                        })
+                        .cloned()
+                        .collect(),
+                };
+                let changed = match &entries[succ] {
+                    None => true,
+                    Some(old) => old.len() != joined.len(),
+                };
+                if changed {
+                    entries[succ] = Some(joined);
+                    worklist.push_back(succ);
+                }
+            }
+        }
+        entries
+    }
+
+    /// Apply the effects of the block to the lock set. The double locks and lock order edges are
+    /// collected if `result` is given.
+    fn transfer(
+        &self,
+        bb: BasicBlock,
+        state: &mut LockSet,
+        mut result: Option<&mut LockSetResult>,
+        acquires: &mut Vec<Acquire>,
+    ) {
+        let data = &self.body.basic_blocks[bb];
+        for stmt in &data.statements {
+            let StatementKind::Assign(box (place, rvalue)) = &stmt.kind else {
+                continue;
+            };
+            match rvalue {
+                // The guard is moved to another local.
+                Rvalue::Use(Operand::Move(moved)) if moved.projection.is_empty() => {
+                    for held in state.iter_mut().filter(|held| held.guard == moved.local) {
+                        held.guard = place.local;
+                    }
+                    if !place.projection.is_empty() {
+                        state.retain(|held| held.guard != place.local);
+                    }
+                }
+                // The guard is moved into an aggregate, which is no longer tracked.
+                Rvalue::Aggregate(_, operands) => {
+                    for operand in operands {
+                        if let Operand::Move(moved) = operand {
+                            state.retain(|held| held.guard != moved.local);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        let terminator = data.terminator();
+        match &terminator.kind {
+            TerminatorKind::Drop { place, .. } => {
+                if place.projection.is_empty() {
+                    state.retain(|held| held.guard != place.local);
+                }
+            }
+            TerminatorKind::Call {
+                func,
+                args,
+                destination,
+                ..
+            } => {
+                let args: Vec<&Operand<'tcx>> = args.iter().map(|arg| &arg.node).collect();
+                let span = terminator.source_info.span;
+                let Some((callee, generic_args)) = func.const_fn_def() else {
+                    self.release_moved(state, &args);
+                    return;
+                };
+                let path = model_path(self.tcx, callee);
+                if let Some(api) = lock_api(&path) {
+                    let Some(place) = args.first().and_then(|arg| self.resolve_operand(arg)) else {
+                        return;
+                    };
+                    let acquire = Acquire {
+                        class: self.class_of(&place),
+                        place,
+                        exclusive: api == LockApi::Exclusive,
+                        span,
+                    };
+                    if let Some(result) = result.as_deref_mut() {
+                        self.check_acquire(state, &acquire, bb, span, None, result);
+                    }
+                    state.push(Held {
+                        guard: destination.local,
+                        place: acquire.place.clone(),
+                        class: acquire.class.clone(),
+                        exclusive: acquire.exclusive,
+                        span,
+                    });
+                    acquires.push(acquire);
+                    return;
+                }
+                if is_guard_pass_through(&path)
+                    && let Some(Operand::Move(moved)) = args.first()
+                    && state.iter().any(|held| held.guard == moved.local)
+                {
+                    for held in state.iter_mut().filter(|held| held.guard == moved.local) {
+                        held.guard = destination.local;
+                    }
+                    return;
+                }
+                self.release_moved(state, &args);
+                let callee = self.resolve_callee(callee, generic_args);
+                let Some(summary) = self.summaries.get(&callee) else {
+                    return;
+                };
+                let name = self.tcx.def_path_str(callee);
+                for callee_acquire in summary {
+                    let Some(acquire) = self.map_acquire(callee_acquire, &args) else {
+                        continue;
+                    };
+                    if let Some(result) = result.as_deref_mut() {
+                        self.check_acquire(
+                            state,
+                            &acquire,
+                            bb,
+                            span,
+                            Some((name.clone(), callee_acquire.span)),
+                            result,
+                        );
+                    }
+                    acquires.push(Acquire { span, ..acquire });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// The guards moved into a call, e.g., `drop(guard)`, are released.
+    fn release_moved(&self, state: &mut LockSet, args: &[&Operand<'tcx>]) {
+        for arg in args {
+            if let Operand::Move(moved) = arg
+                && moved.projection.is_empty()
+            {
+                state.retain(|held| held.guard != moved.local);
+            }
+        }
+    }
+
+    fn check_acquire(
+        &self,
+        state: &LockSet,
+        acquire: &Acquire,
+        bb: BasicBlock,
+        span: Span,
+        callee: Option<(String, Span)>,
+        result: &mut LockSetResult,
+    ) {
+        for held in state {
+            if held.place == acquire.place && (held.exclusive || acquire.exclusive) {
+                result.double_locks.push(DoubleLock {
+                    held_span: held.span,
+                    bb: bb.as_usize(),
+                    span,
+                    callee: callee.as_ref().map(|(name, _)| name.clone()),
+                    callee_span: callee.as_ref().map(|(_, span)| *span),
+                });
+            }
+            if let (Some(from), Some(to)) = (&held.class, &acquire.class)
+                && from != to
+            {
+                result.edges.push(LockEdge {
+                    from: from.clone(),
+                    to: to.clone(),
+                    def_id: self.def_id,
+                    from_span: held.span,
+                    to_span: span,
+                });
+            }
+        }
+    }
+
+    /// The lock acquired by the callee on its argument or a static, as seen by the caller.
+    fn map_acquire(&self, acquire: &Acquire, args: &[&Operand<'tcx>]) -> Option<Acquire> {
+        let place = match acquire.place.root {
+            LockRoot::Arg(index) => {
+                let mut place = self.resolve_operand(args.get(index - 1)?)?;
+                place.fields.extend(&acquire.place.fields);
+                place
+            }
+            LockRoot::Static(_) => acquire.place.clone(),
+            LockRoot::Local(_) => return None,
+        };
+        Some(Acquire {
+            class: self.class_of(&place).or_else(|| acquire.class.clone()),
+            place,
+            exclusive: acquire.exclusive,
+            span: acquire.span,
+        })
+    }
+
+    fn resolve_callee(&self, callee: DefId, generic_args: ty::GenericArgsRef<'tcx>) -> DefId {
+        match Instance::try_resolve(self.tcx, self.typing_env, callee, generic_args) {
+            Ok(Some(instance)) => instance.def_id(),
+            _ => callee,
+        }
+    }
+
+    fn collect_defs(&mut self) {
+        let mut defs: FxHashMap<Local, Def<'tcx>> = FxHashMap::default();
+        let mut define = |local: Local, def: Def<'tcx>| {
+            let def = if defs.contains_key(&local) {
+                Def::Opaque
+            } else {
+                def
+            };
+            defs.insert(local, def);
+        };
+        for data in self.body.basic_blocks.iter() {
+            for stmt in &data.statements {
+                let StatementKind::Assign(box (place, rvalue)) = &stmt.kind else {
+                    continue;
+                };
+                if !place.projection.is_empty() {
+                    continue;
+                }
+                let def = match rvalue {
+                    Rvalue::Ref(_, _, used)
+                    | Rvalue::RawPtr(_, used)
+                    | Rvalue::CopyForDeref(used)
+                    | Rvalue::Use(Operand::Copy(used) | Operand::Move(used))
+                    | Rvalue::Cast(_, Operand::Copy(used) | Operand::Move(used), _) => {
+                        Def::Place(*used, Vec::new())
+                    }
+                    Rvalue::Use(Operand::Constant(constant)) => {
+                        match constant.check_static_ptr(self.tcx) {
+                            Some(def_id) => Def::Static(def_id),
+                            None => Def::Opaque,
+                        }
+                    }
+                    _ => Def::Opaque,
+                };
+                define(place.local, def);
+            }
+            let TerminatorKind::Call {
+                func,
+                args,
+                destination,
+                ..
+            } = &data.terminator().kind
+            else {
+                continue;
+            };
+            let def = match func.const_fn_def() {
+                Some((callee, generic_args)) => self.call_def(callee, generic_args, args),
+                None => Def::Opaque,
+            };
+            define(destination.local, def);
+        }
+        self.defs = defs;
+    }
+
+    /// The place the result of the call refers to, if it is the same lock as an argument.
+    fn call_def(
+        &self,
+        callee: DefId,
+        generic_args: ty::GenericArgsRef<'tcx>,
+        args: &[rustc_span::source_map::Spanned<Operand<'tcx>>],
+    ) -> Def<'tcx> {
+        let path = model_path(self.tcx, callee);
+        let first = args.first().and_then(|arg| arg.node.place());
+        let is_shared_ptr_clone = path == "core::clone::Clone::clone"
+            && generic_args
+                .types()
+                .next()
+                .and_then(|ty| ty.ty_adt_def())
+                .is_some_and(|adt_def| {
+                    self.tcx.is_diagnostic_item(sym::Arc, adt_def.did())
+                        || self.tcx.is_diagnostic_item(sym::Rc, adt_def.did())
+                });
+        if let Some(first) = first
+            && (is_lock_pass_through(&path) || is_shared_ptr_clone || is_pass_through(&path))
+        {
+            return Def::Place(first, Vec::new());
+        }
+        // The getters returning a reference to a field of an argument, e.g., `&self.lock`.
+        let callee = self.resolve_callee(callee, generic_args);
+        if let Some(alias) = self.alias.get(&callee) {
+            for fact in alias.aliases() {
+                if fact.lhs_no() == 0
+                    && fact.lhs_fields().is_empty()
+                    && let Some(arg) = args.get(fact.rhs_no().wrapping_sub(1))
+                    && let Some(place) = arg.node.place()
+                {
+                    return Def::Place(place, fact.rhs_fields().to_vec());
+                }
+            }
+        }
+        Def::Opaque
+    }
+
+    fn resolve_operand(&self, operand: &Operand<'tcx>) -> Option<LockPlace> {
+        self.resolve(operand.place()?, 0)
+    }
+
+    /// The access path of the place, following the definitions of the locals.
+    fn resolve(&self, place: Place<'tcx>, depth: usize) -> Option<LockPlace> {
+        const MAX_DEPTH: usize = 32;
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        let mut fields = Vec::new();
+        for elem in place.projection {
+            match elem {
+                ProjectionElem::Field(field, _) => fields.push(field.as_usize()),
+                ProjectionElem::Deref | ProjectionElem::Downcast(..) => {}
+                _ => return None,
+            }
+        }
+        let local = place.local;
+        let mut base = match self.defs.get(&local) {
+            _ if local.as_usize() >= 1 && local.as_usize() <= self.body.arg_count => LockPlace {
+                root: LockRoot::Arg(local.as_usize()),
+                fields: Vec::new(),
+            },
+            Some(Def::Place(used, extra)) => {
+                let mut base = self.resolve(*used, depth + 1)?;
+                base.fields.extend(extra);
+                base
+            }
+            Some(Def::Static(def_id)) => LockPlace {
+                root: LockRoot::Static(*def_id),
+                fields: Vec::new(),
+            },
+            Some(Def::Opaque) | None => LockPlace {
+                root: LockRoot::Local(local.as_usize()),
+                fields: Vec::new(),
+            },
+        };
+        base.fields.extend(fields);
+        Some(base)
+    }
+
+    /// The class of the lock, i.e., the field of the type or the static declaring it, e.g.,
+    /// `Account.balance`.
+    fn class_of(&self, place: &LockPlace) -> Option<String> {
+        let (mut name, mut ty) = match place.root {
+            LockRoot::Static(def_id) => (
+                self.tcx.def_path_str(def_id),
+                self.tcx.type_of(def_id).instantiate_identity(),
+            ),
+            _ if place.fields.is_empty() => return None,
+            LockRoot::Arg(local) | LockRoot::Local(local) => {
+                let ty = peel(self.tcx, self.body.local_decls[Local::from_usize(local)].ty);
+                let ty::Adt(adt_def, _) = ty.kind() else {
+                    return None;
+                };
+                (self.tcx.def_path_str(adt_def.did()), ty)
+            }
+        };
+        for field in &place.fields {
+            let (field_name, field_ty) = match ty.kind() {
+                ty::Adt(adt_def, args) if adt_def.is_struct() => {
+                    let field = adt_def.non_enum_variant().fields.iter().nth(*field)?;
+                    (field.name.to_string(), field.ty(self.tcx, args))
+                }
+                ty::Tuple(tys) => (field.to_string(), tys.get(*field).copied()?),
+                _ => return None,
+            };
+            name = format!("{}.{}", name, field_name);
+            ty = peel(self.tcx, field_ty);
+        }
+        Some(name)
+    }
+}
+
+/// The type behind the references and smart pointers, e.g., `Mutex<T>` for `&Arc<Mutex<T>>`.
+fn peel<'tcx>(tcx: TyCtxt<'tcx>, ty: Ty<'tcx>) -> Ty<'tcx> {
+    let ty = ty.peel_refs();
+    let ty::Adt(adt_def, args) = ty.kind() else {
+        return ty;
+    };
+    let is_smart_pointer = adt_def.is_box()
+        || tcx.is_diagnostic_item(sym::Arc, adt_def.did())
+        || tcx.is_diagnostic_item(sym::Rc, adt_def.did());
+    match args.types().next() {
+        Some(inner) if is_smart_pointer => peel(tcx, inner),
+        _ => ty,
+    }
+}
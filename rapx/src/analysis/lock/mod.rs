@@ -0,0 +1,203 @@
+pub mod await_guard;
+pub mod lockset;
+
+use crate::{
+    analysis::{
+        Analysis,
+        core::{
+            alias_analysis::{
+                AliasAnalysis, AliasBackend, andersen::AndersenAnalyzer, default::AliasAnalyzer,
+            },
+            scheduler::{BottomUpScheduler, callee_summaries},
+        },
+    },
+    utils::{
+        diagnostics::{Finding, FindingKind},
+        log::{span_to_filename, span_to_line_number},
+        source::get_name,
+    },
+};
+use await_guard::{GuardAcrossAwait, guards_across_await};
+use lockset::{DoubleLock, LockEdge, LockSetAnalysis, LockSetResult};
+use rustc_data_structures::fx::FxHashMap;
+use rustc_hir::def::DefKind;
+use rustc_middle::ty::TyCtxt;
+use rustc_span::{def_id::DefId, symbol::Symbol};
+
+/// Detect the locks acquired again while held, the locks acquired in conflicting orders by
+/// different functions, and the lock guards held across `.await`.
+pub struct LockCheck<'tcx> {
+    pub tcx: TyCtxt<'tcx>,
+    /// The alias analysis that resolves the getters of the locks; Andersen by default, as MoP
+    /// explores every path of the lock internals, e.g., those of `RwLock`.
+    pub alias_backend: AliasBackend,
+}
+
+impl<'tcx> LockCheck<'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>) -> Self {
+        Self {
+            tcx,
+            alias_backend: AliasBackend::Andersen,
+        }
+    }
+
+    /// Summarize the aliases of the local functions with the backend.
+    pub fn with_alias_backend(mut self, alias_backend: AliasBackend) -> Self {
+        self.alias_backend = alias_backend;
+        self
+    }
+
+    pub fn start(&self) {
+        let tcx = self.tcx;
+        let alias = &match self.alias_backend {
+            AliasBackend::Mop => {
+                let mut analyzer = AliasAnalyzer::new(tcx);
+                analyzer.run();
+                analyzer.get_all_fn_alias()
+            }
+            AliasBackend::Andersen => {
+                let mut analyzer = AndersenAnalyzer::new(tcx);
+                analyzer.run();
+                analyzer.get_all_fn_alias()
+            }
+        };
+        let def_ids: Vec<DefId> = tcx
+            .mir_keys(())
+            .iter()
+            .map(|local_def_id| local_def_id.to_def_id())
+            .filter(|def_id| {
+                matches!(
+                    tcx.def_kind(*def_id),
+                    DefKind::Fn | DefKind::AssocFn | DefKind::Closure
+                ) && tcx.is_mir_available(*def_id)
+            })
+            .collect();
+
+        let mut summaries = FxHashMap::default();
+        let results = BottomUpScheduler::new(tcx, def_ids.iter().copied()).run(
+            &mut summaries,
+            |scc, summaries| {
+                let callees = callee_summaries(tcx, scc, summaries);
+                let mut new_summaries = Vec::new();
+                let mut results = Vec::new();
+                for def_id in scc {
+                    let (acquires, result) =
+                        LockSetAnalysis::new(tcx, *def_id, alias, &callees).run();
+                    new_summaries.push((*def_id, acquires));
+                    results.push((*def_id, result));
+                }
+                (new_summaries, results)
+            },
+        );
+        let results: Vec<(DefId, LockSetResult)> = results.into_iter().flatten().collect();
+        for (def_id, result) in &results {
+            for double_lock in &result.double_locks {
+                self.report_double_lock(*def_id, double_lock);
+            }
+        }
+        self.report_inversions(results.iter().flat_map(|(_, result)| &result.edges));
+
+        for def_id in &def_ids {
+            for guard in guards_across_await(tcx, *def_id) {
+                self.report_guard(&guard);
+            }
+        }
+    }
+
+    fn fn_name(&self, def_id: DefId) -> Symbol {
+        get_name(self.tcx, def_id).unwrap_or_else(|| Symbol::intern("no symbol"))
+    }
+
+    fn report_double_lock(&self, def_id: DefId, double_lock: &DoubleLock) {
+        let fn_span = self.tcx.optimized_mir(def_id).span;
+        let acquired = match &double_lock.callee {
+            Some(callee) => format!("acquired again in `{}`", callee),
+            None => "acquired again".to_owned(),
+        };
+        let detail = format!(
+            "Double lock: Location in file {} line {}.\n    | MIR detail: the lock held since line {} is {} at bb{}.",
+            span_to_filename(double_lock.span),
+            span_to_line_number(double_lock.span),
+            span_to_line_number(double_lock.held_span),
+            acquired,
+            double_lock.bb,
+        );
+        let mut finding = Finding::new(FindingKind::DoubleLock, def_id, "Double lock detected.")
+            .primary(double_lock.span, detail.as_str())
+            .related(double_lock.held_span, "the lock is first acquired here");
+        if let Some(callee_span) = double_lock.callee_span {
+            finding = finding.related(callee_span, "the lock is acquired again in the callee");
+        }
+        finding.report(fn_span);
+    }
+
+    /// Report the pairs of lock classes acquired in both orders, once per pair.
+    fn report_inversions<'a>(&self, edges: impl Iterator<Item = &'a LockEdge>) {
+        let mut orders: FxHashMap<(&str, &str), &LockEdge> = FxHashMap::default();
+        for edge in edges {
+            orders
+                .entry((edge.from.as_str(), edge.to.as_str()))
+                .or_insert(edge);
+        }
+        let mut pairs: Vec<(&LockEdge, &LockEdge)> = orders
+            .iter()
+            .filter(|((from, to), _)| from < to)
+            .filter_map(|((from, to), edge)| Some((*edge, *orders.get(&(*to, *from))?)))
+            .collect();
+        pairs.sort_by(|(a, _), (b, _)| (&a.from, &a.to).cmp(&(&b.from, &b.to)));
+        for (forward, backward) in pairs {
+            self.report_inversion(forward, backward);
+        }
+    }
+
+    fn report_inversion(&self, forward: &LockEdge, backward: &LockEdge) {
+        let detail = format!(
+            "Lock order inversion: Location in file {} line {}.\n    | MIR detail: `{}` is acquired while holding `{}`, but `{}` is acquired while holding `{}` in file {} line {}.",
+            span_to_filename(forward.to_span),
+            span_to_line_number(forward.to_span),
+            forward.to,
+            forward.from,
+            backward.to,
+            backward.from,
+            span_to_filename(backward.to_span),
+            span_to_line_number(backward.to_span),
+        );
+        let fn_span = self.tcx.optimized_mir(forward.def_id).span;
+        Finding::new(
+            FindingKind::LockOrderInversion,
+            forward.def_id,
+            "Lock order inversion detected.",
+        )
+        .location(format!("{} -> {}", forward.from, forward.to))
+        .primary(forward.to_span, detail.as_str())
+        .related(forward.from_span, "the first lock is acquired here")
+        .related(
+            backward.to_span,
+            "the locks are acquired in the other order here",
+        )
+        .subject(format!(
+            "functions {:?} and {:?}",
+            self.fn_name(forward.def_id),
+            self.fn_name(backward.def_id)
+        ))
+        .report(fn_span);
+    }
+
+    fn report_guard(&self, guard: &GuardAcrossAwait) {
+        let fn_span = self.tcx.optimized_mir(guard.def_id).span;
+        let detail = format!(
+            "Guard across await: Location in file {} line {}.\n    | MIR detail: the guard of type `{}` is held across a suspension point.",
+            span_to_filename(guard.span),
+            span_to_line_number(guard.span),
+            guard.guard_ty,
+        );
+        Finding::new(
+            FindingKind::GuardAcrossAwait,
+            guard.def_id,
+            "Guard held across await detected.",
+        )
+        .location(guard.guard_ty.clone())
+        .primary(guard.span, detail.as_str())
+        .report(fn_span);
+    }
+}
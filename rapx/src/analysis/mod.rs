@@ -1,11 +1,18 @@
 pub mod core;
 pub mod graphs;
+pub mod lifetime;
+pub mod lock;
 pub mod opt;
+pub mod overflow;
+pub mod panic_safety;
+pub mod rc_cycle;
 pub mod rcanary;
 pub mod safedrop;
 pub mod scan;
+pub mod send_sync;
 pub mod senryx;
 pub mod test;
+pub mod uninit;
 pub mod upg;
 pub mod utils;
 
@@ -8,6 +8,7 @@ use crate::{
         core::dataflow::{graph::*, *},
         utils::def_path::DefPath,
     },
+    utils::diagnostics::{Finding, FindingKind},
     utils::log::{relative_pos_range, span_to_filename, span_to_line_number, span_to_source_code},
 };
 use annotate_snippets::{Level, Renderer, Snippet};
@@ -82,6 +83,17 @@ impl OptCheck for BoundsExtendCheck {
 }
 
 fn report_extend_bug(graph: &Graph, span: Span) {
+    let is_new = Finding::new(
+        FindingKind::BoundsChecking,
+        graph.def_id,
+        "Unnecessary bound checkings detected",
+    )
+    .primary(span, "Checked here.")
+    .emit();
+    if !is_new {
+        return;
+    }
+
     let code_source = span_to_source_code(graph.span);
     let filename = span_to_filename(graph.span);
     let snippet = Snippet::source(&code_source)
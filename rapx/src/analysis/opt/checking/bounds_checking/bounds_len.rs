@@ -12,6 +12,7 @@ use crate::{
         core::dataflow::{graph::*, *},
         utils::def_path::DefPath,
     },
+    utils::diagnostics::{Finding, FindingKind},
     utils::log::{relative_pos_range, span_to_filename, span_to_line_number, span_to_source_code},
 };
 use annotate_snippets::{Level, Renderer, Snippet};
@@ -285,6 +286,19 @@ fn find_downside_index_node(graph: &Graph, node_idx: Local) -> Vec<Local> {
 
 fn report_upperbound_bug(graph: &Graph, upperbound_node_idx: Local, index_record: &Vec<Local>) {
     let upperbound_span = graph.nodes[upperbound_node_idx].span;
+    let mut finding = Finding::new(
+        FindingKind::BoundsChecking,
+        graph.def_id,
+        "Unnecessary bounds checkings detected",
+    )
+    .primary(upperbound_span, "Index is upperbounded.");
+    for node_idx in index_record {
+        finding = finding.related(graph.nodes[*node_idx].span, "Checked here.");
+    }
+    if !finding.emit() {
+        return;
+    }
+
     let code_source = span_to_source_code(graph.span);
     let filename = span_to_filename(upperbound_span);
     let mut snippet = Snippet::source(&code_source)
@@ -1,12 +1,13 @@
 use once_cell::sync::OnceCell;
 
-use rustc_hir::{Expr, ExprKind, intravisit};
+use rustc_hir::{Expr, ExprKind, def_id::DefId, intravisit};
 use rustc_middle::ty::TyCtxt;
 use rustc_middle::ty::TypeckResults;
 use rustc_span::Span;
 
 use crate::analysis::core::dataflow::graph::Graph;
 use crate::analysis::utils::def_path::DefPath;
+use crate::utils::diagnostics::{Finding, FindingKind};
 use crate::utils::log::{
     relative_pos_range, span_to_filename, span_to_first_line, span_to_line_number,
     span_to_source_code, span_to_trimmed_span,
@@ -109,9 +110,9 @@ impl OptCheck for BoundsLoopPushCheck {
         }
     }
 
-    fn report(&self, _: &Graph) {
+    fn report(&self, graph: &Graph) {
         for (loop_span, push_record) in self.record.iter() {
-            report_loop_push_bug(*loop_span, push_record);
+            report_loop_push_bug(graph.def_id, *loop_span, push_record);
         }
     }
 
@@ -120,7 +121,20 @@ impl OptCheck for BoundsLoopPushCheck {
     }
 }
 
-fn report_loop_push_bug(loop_span: Span, push_record: &Vec<Span>) {
+fn report_loop_push_bug(def_id: DefId, loop_span: Span, push_record: &Vec<Span>) {
+    let mut finding = Finding::new(
+        FindingKind::BoundsChecking,
+        def_id,
+        "Unnecessary bounds checkings detected",
+    )
+    .primary(loop_span, "A loop operation.");
+    for push_span in push_record {
+        finding = finding.related(*push_span, "Push happens here.");
+    }
+    if !finding.emit() {
+        return;
+    }
+
     let code_source = span_to_source_code(loop_span);
     let filename = span_to_filename(loop_span);
     let mut snippet = Snippet::source(&code_source)
@@ -10,6 +10,7 @@ use crate::{
         core::dataflow::{graph::*, *},
         opt::OptCheck,
     },
+    utils::diagnostics::{Finding, FindingKind},
     utils::log::{relative_pos_range, span_to_filename, span_to_line_number, span_to_source_code},
 };
 use annotate_snippets::{Level, Renderer, Snippet};
@@ -62,6 +63,17 @@ impl OptCheck for EncodingCheck {
 }
 
 fn report_encoding_bug(graph: &Graph, span: Span) {
+    let is_new = Finding::new(
+        FindingKind::EncodingChecking,
+        graph.def_id,
+        "Unnecessary encoding checkings detected",
+    )
+    .primary(span, "Checked here.")
+    .emit();
+    if !is_new {
+        return;
+    }
+
     let code_source = span_to_source_code(graph.span);
     let filename = span_to_filename(graph.span);
     let snippet = Snippet::source(&code_source)
@@ -1,3 +1,4 @@
+use crate::utils::diagnostics::{Finding, FindingKind};
 use annotate_snippets::{Level, Renderer, Snippet};
 
 use once_cell::sync::OnceCell;
@@ -84,6 +85,17 @@ impl OptCheck for StringLowercaseCheck {
 }
 
 fn report_string_ascii_bug(graph: &Graph, contains_span: Span) {
+    let is_new = Finding::new(
+        FindingKind::EncodingChecking,
+        graph.def_id,
+        "Unnecessary encoding checkings detected.",
+    )
+    .primary(contains_span, "Checked here.")
+    .emit();
+    if !is_new {
+        return;
+    }
+
     let code_source = span_to_source_code(graph.span);
     let filename = span_to_filename(graph.span);
     let snippet = Snippet::source(&code_source)
@@ -11,6 +11,7 @@ use crate::{
         core::dataflow::{graph::*, *},
         utils::def_path::DefPath,
     },
+    utils::diagnostics::{Finding, FindingKind},
     utils::log::{relative_pos_range, span_to_filename, span_to_line_number, span_to_source_code},
 };
 use annotate_snippets::{Level, Renderer, Snippet};
@@ -109,6 +110,19 @@ impl OptCheck for StringPushCheck {
 }
 
 fn report_string_push_bug(graph: &Graph, spans: &Vec<Span>) {
+    let mut finding = Finding::new(
+        FindingKind::EncodingChecking,
+        graph.def_id,
+        "Unnecessary encoding checkings detected",
+    )
+    .primary(graph.span, "Unnecessary encoding checkings detected");
+    for span in spans.iter() {
+        finding = finding.related(*span, "Checked here.");
+    }
+    if !finding.emit() {
+        return;
+    }
+
     let code_source = span_to_source_code(graph.span);
     let filename = span_to_filename(graph.span);
     let mut snippet = Snippet::source(&code_source)
@@ -8,6 +8,7 @@ use crate::{
         opt::OptCheck,
         utils::def_path::DefPath,
     },
+    utils::diagnostics::{Finding, FindingKind},
     utils::log::{relative_pos_range, span_to_filename, span_to_line_number, span_to_source_code},
 };
 use rustc_hir::def_id::DefId;
@@ -83,6 +84,17 @@ impl OptCheck for LocalSetCheck {
 }
 
 fn report_local_set(graph: &Graph, span: Span) {
+    let is_new = Finding::new(
+        FindingKind::Initialization,
+        graph.def_id,
+        "Unnecessary data collection initialization detected",
+    )
+    .primary(span, "Initialization happens here")
+    .emit();
+    if !is_new {
+        return;
+    }
+
     let code_source = span_to_source_code(graph.span);
     let filename = span_to_filename(span);
     let snippet = Snippet::source(&code_source)
@@ -12,6 +12,7 @@ use crate::{
         opt::OptCheck,
         utils::def_path::DefPath,
     },
+    utils::diagnostics::{Finding, FindingKind},
     utils::log::{relative_pos_range, span_to_filename, span_to_line_number, span_to_source_code},
 };
 
@@ -67,6 +68,17 @@ impl OptCheck for VecInitCheck {
 }
 
 fn report_vec_init(graph: &Graph, span: Span) {
+    let is_new = Finding::new(
+        FindingKind::Initialization,
+        graph.def_id,
+        "Unnecessary data collection initialization detected",
+    )
+    .primary(span, "Initialization happens here")
+    .emit();
+    if !is_new {
+        return;
+    }
+
     let code_source = span_to_source_code(graph.span);
     let filename = span_to_filename(span);
     let snippet = Snippet::source(&code_source)
@@ -8,6 +8,7 @@ use crate::{
         opt::OptCheck,
         utils::def_path::DefPath,
     },
+    utils::diagnostics::{Finding, FindingKind},
     utils::log::{relative_pos_range, span_to_filename, span_to_line_number, span_to_source_code},
 };
 use annotate_snippets::{Level, Renderer, Snippet};
@@ -93,6 +94,17 @@ impl OptCheck for FlattenCollectCheck {
 }
 
 fn report_flatten_collect(graph: &Graph, span: Span) {
+    let is_new = Finding::new(
+        FindingKind::Reallocation,
+        graph.def_id,
+        "Data collection inefficiency detected",
+    )
+    .primary(span, "Flatten then collect.")
+    .emit();
+    if !is_new {
+        return;
+    }
+
     let code_source = span_to_source_code(graph.span);
     let filename = span_to_filename(span);
     let snippet: Snippet<'_> = Snippet::source(&code_source)
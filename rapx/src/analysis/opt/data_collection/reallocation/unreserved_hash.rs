@@ -6,6 +6,7 @@ use crate::{
         opt::OptCheck,
         utils::def_path::DefPath,
     },
+    utils::diagnostics::{Finding, FindingKind},
     utils::log::{relative_pos_range, span_to_filename, span_to_line_number, span_to_source_code},
 };
 use once_cell::sync::OnceCell;
@@ -113,6 +114,18 @@ impl OptCheck for UnreservedHashCheck {
 }
 
 fn report_unreserved_hash_bug(graph: &Graph, hash_span: Span, insert_span: Span) {
+    let is_new = Finding::new(
+        FindingKind::Reallocation,
+        graph.def_id,
+        "Improper data collection detected",
+    )
+    .primary(hash_span, "Space unreserved.")
+    .related(insert_span, "Insertion happens here.")
+    .emit();
+    if !is_new {
+        return;
+    }
+
     let code_source = span_to_source_code(graph.span);
     let filename = span_to_filename(hash_span);
     let snippet: Snippet<'_> = Snippet::source(&code_source)
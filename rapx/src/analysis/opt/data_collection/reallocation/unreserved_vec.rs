@@ -6,6 +6,7 @@ use crate::{
         opt::OptCheck,
         utils::def_path::DefPath,
     },
+    utils::diagnostics::{Finding, FindingKind},
     utils::log::{relative_pos_range, span_to_filename, span_to_line_number, span_to_source_code},
 };
 use once_cell::sync::OnceCell;
@@ -193,6 +194,17 @@ impl OptCheck for UnreservedVecCheck {
 }
 
 fn report_unreserved_vec_bug(graph: &Graph, span: Span) {
+    let is_new = Finding::new(
+        FindingKind::Reallocation,
+        graph.def_id,
+        "Improper data collection detected",
+    )
+    .primary(span, "Space unreserved.")
+    .emit();
+    if !is_new {
+        return;
+    }
+
     let code_source = span_to_source_code(graph.span);
     let filename = span_to_filename(span);
     let snippet: Snippet<'_> = Snippet::source(&code_source)
@@ -8,6 +8,7 @@ use crate::{
         opt::OptCheck,
         utils::def_path::DefPath,
     },
+    utils::diagnostics::{Finding, FindingKind},
     utils::log::{relative_pos_range, span_to_filename, span_to_line_number, span_to_source_code},
 };
 use rustc_hir::def_id::DefId;
@@ -80,6 +81,17 @@ impl OptCheck for ParticipantCheck {
 }
 
 fn report_participant(graph: &Graph, span: Span) {
+    let is_new = Finding::new(
+        FindingKind::Suboptimal,
+        graph.def_id,
+        "Suboptimal data collection detected",
+    )
+    .primary(span, "Data collection created here")
+    .emit();
+    if !is_new {
+        return;
+    }
+
     let code_source = span_to_source_code(graph.span);
     let filename = span_to_filename(span);
     let snippet = Snippet::source(&code_source)
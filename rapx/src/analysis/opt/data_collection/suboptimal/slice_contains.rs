@@ -4,6 +4,7 @@ use once_cell::sync::OnceCell;
 
 use crate::{
     analysis::{core::dataflow::graph::*, opt::OptCheck, utils::def_path::DefPath},
+    utils::diagnostics::{Finding, FindingKind},
     utils::log::{relative_pos_range, span_to_filename, span_to_line_number, span_to_source_code},
 };
 use rustc_hir::{Expr, ExprKind, intravisit};
@@ -79,6 +80,17 @@ impl OptCheck for SliceContainsCheck {
 }
 
 fn report_slice_contains_bug(graph: &Graph, contains_span: Span) {
+    let is_new = Finding::new(
+        FindingKind::Suboptimal,
+        graph.def_id,
+        "Improper data collection detected",
+    )
+    .primary(contains_span, "Slice contains happens here.")
+    .emit();
+    if !is_new {
+        return;
+    }
+
     let code_source = span_to_source_code(graph.span);
     let filename = span_to_filename(graph.span);
     let snippet = Snippet::source(&code_source)
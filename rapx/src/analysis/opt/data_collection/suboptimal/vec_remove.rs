@@ -8,6 +8,7 @@ use crate::{
         opt::OptCheck,
         utils::def_path::DefPath,
     },
+    utils::diagnostics::{Finding, FindingKind},
     utils::log::{relative_pos_range, span_to_filename, span_to_line_number, span_to_source_code},
 };
 use rustc_middle::ty::TyCtxt;
@@ -88,6 +89,17 @@ impl OptCheck for VecRemoveCheck {
 }
 
 fn report_vec_remove_bug(graph: &Graph, span: Span) {
+    let is_new = Finding::new(
+        FindingKind::Suboptimal,
+        graph.def_id,
+        "Improper data collection detected",
+    )
+    .primary(span, "Vec increasement / decreasement happens here.")
+    .emit();
+    if !is_new {
+        return;
+    }
+
     let code_source = span_to_source_code(graph.span);
     let filename = span_to_filename(graph.span);
     let snippet = Snippet::source(&code_source)
@@ -1,6 +1,6 @@
 use once_cell::sync::OnceCell;
 
-use rustc_hir::{Expr, ExprKind, intravisit};
+use rustc_hir::{Expr, ExprKind, def_id::DefId, intravisit};
 use rustc_middle::ty::TyCtxt;
 use rustc_middle::ty::TypeckResults;
 use rustc_span::Span;
@@ -8,6 +8,7 @@ use rustc_span::Span;
 use crate::analysis::core::dataflow::graph::Graph;
 use crate::analysis::opt::OptCheck;
 use crate::analysis::utils::def_path::DefPath;
+use crate::utils::diagnostics::{Finding, FindingKind};
 use crate::utils::log::{
     relative_pos_range, span_to_filename, span_to_line_number, span_to_source_code,
 };
@@ -91,7 +92,12 @@ impl OptCheck for NextIteratorCheck {
     }
 
     fn report(&self, graph: &Graph) {
-        report_next_iterator_bug(&self.next_record, &self.chain_record, graph.span);
+        report_next_iterator_bug(
+            graph.def_id,
+            &self.next_record,
+            &self.chain_record,
+            graph.span,
+        );
     }
 
     fn cnt(&self) -> usize {
@@ -99,7 +105,25 @@ impl OptCheck for NextIteratorCheck {
     }
 }
 
-fn report_next_iterator_bug(next_record: &Vec<Span>, chain_record: &Vec<Span>, graph_span: Span) {
+fn report_next_iterator_bug(
+    def_id: DefId,
+    next_record: &Vec<Span>,
+    chain_record: &Vec<Span>,
+    graph_span: Span,
+) {
+    let mut finding = Finding::new(
+        FindingKind::Iterator,
+        def_id,
+        "Inefficient iterators detected",
+    )
+    .primary(graph_span, "Inefficient iterators detected");
+    for span in next_record.iter().chain(chain_record) {
+        finding = finding.related(*span, "Use chunk iterators.");
+    }
+    if !finding.emit() {
+        return;
+    }
+
     let code_source = span_to_source_code(graph_span);
     let filename = span_to_filename(graph_span);
     let mut snippet = Snippet::source(&code_source)
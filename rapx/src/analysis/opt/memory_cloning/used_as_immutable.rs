@@ -4,6 +4,7 @@ use crate::{
         opt::OptCheck,
         utils::def_path::DefPath,
     },
+    utils::diagnostics::{Finding, FindingKind},
     utils::log::{relative_pos_range, span_to_filename, span_to_line_number, span_to_source_code},
 };
 use annotate_snippets::{Level, Renderer, Snippet};
@@ -155,6 +156,18 @@ impl OptCheck for UsedAsImmutableCheck {
 }
 
 fn report_used_as_immutable(graph: &Graph, clone_span: Span, use_span: Span) {
+    let is_new = Finding::new(
+        FindingKind::Cloning,
+        graph.def_id,
+        "Unnecessary memory cloning detected",
+    )
+    .primary(clone_span, "Cloning happens here.")
+    .related(use_span, "Used here")
+    .emit();
+    if !is_new {
+        return;
+    }
+
     let code_source = span_to_source_code(graph.span);
     let filename = span_to_filename(clone_span);
     let snippet = Snippet::source(&code_source)
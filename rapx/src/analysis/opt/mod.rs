@@ -17,6 +17,7 @@ use memory_cloning::used_as_immutable::UsedAsImmutableCheck;
 
 use lazy_static::lazy_static;
 use rustc_span::symbol::Symbol;
+use serde::Deserialize;
 use std::sync::Mutex;
 
 lazy_static! {
@@ -24,6 +25,19 @@ lazy_static! {
     pub static ref LEVEL: Mutex<usize> = Mutex::new(0);
 }
 
+/// How many of the detected inefficiencies are reported.
+#[derive(Debug, Clone, Copy, Default, Hash, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OptLevel {
+    /// Only print the statistics, i.e., `-opt=report`.
+    Report = 0,
+    /// Report the inefficiencies with high confidence, i.e., `-opt`.
+    #[default]
+    Default = 1,
+    /// Report all the inefficiencies, i.e., `-opt=all`.
+    All = 2,
+}
+
 pub struct Opt<'tcx> {
     pub tcx: TyCtxt<'tcx>,
     pub level: usize,
@@ -0,0 +1,267 @@
+//! The arithmetic hazards of a body after the SSA/ESSA passes, judged by the ranges inferred for
+//! its operands.
+//!
+//! Only the operands with bounded ranges are judged, e.g., constants or variables assigned in
+//! branches, since an unknown argument may make any operation overflow. The overflow-checked
+//! operations count as well: they panic in debug builds, but wrap around in release builds.
+
+use crate::analysis::core::{
+    ownership_model::{PathPatterns, model_path},
+    range_analysis::{IntDomain, IntRange, TypedRAResult},
+};
+use rustc_data_structures::fx::FxHashSet;
+use rustc_middle::{
+    mir::{
+        BinOp, Body, CastKind, Local, Location, Operand, Place, PlaceElem, PlaceRef,
+        ProjectionElem, Rvalue, StatementKind, TerminatorKind,
+        visit::{PlaceContext, Visitor},
+    },
+    ty::{self, Ty, TyCtxt},
+};
+use rustc_span::Span;
+
+/// The APIs taking a length, a count or an index.
+const LENGTH_APIS: &[&str] = &[
+    "core::ops::Index::index",
+    "core::ops::IndexMut::index_mut",
+    "core::slice::<impl [*]>::get*",
+    "core::slice::<impl [*]>::split_at*",
+    "core::slice::<impl [*]>::swap",
+    "core::slice::from_raw_parts*",
+    "core::vec::Vec::<*>::with_capacity",
+    "core::vec::Vec::<*>::set_len",
+    "core::vec::Vec::<*>::truncate",
+    "core::vec::Vec::<*>::reserve*",
+    "core::vec::Vec::<*>::resize",
+    "core::vec::Vec::<*>::insert",
+    "core::vec::Vec::<*>::remove",
+    "core::vec::Vec::<*>::swap_remove",
+    "core::vec::from_elem",
+    "core::ptr::copy*",
+    "core::intrinsics::copy*",
+    "core::ptr::write_bytes",
+    "core::ptr::const_ptr::<impl *const *>::add",
+    "core::ptr::mut_ptr::<impl *mut *>::add",
+];
+
+fn is_length_api(path: &str) -> bool {
+    static PATTERNS: PathPatterns<&str> = PathPatterns::new(LENGTH_APIS);
+    PATTERNS.matches(path)
+}
+
+#[derive(Debug, Clone)]
+pub enum HazardKind {
+    /// An addition, subtraction, multiplication or left shift that may overflow.
+    Overflow(BinOp),
+    /// An `as` cast that may truncate the value or change its sign, with the target type.
+    Cast(String),
+    /// A `usize` subtraction that may underflow, with where the result is used as an index or a
+    /// length, e.g., `an index` or the path of the API.
+    Underflow { sink: String, sink_span: Span },
+}
+
+#[derive(Debug, Clone)]
+pub struct Hazard {
+    pub kind: HazardKind,
+    /// The type of the operands.
+    pub ty: String,
+    pub bb: usize,
+    pub span: Span,
+    /// The operands and their ranges, as the evidence.
+    pub operands: Vec<(String, IntRange)>,
+}
+
+pub struct HazardVisitor<'a, 'tcx> {
+    tcx: TyCtxt<'tcx>,
+    body: &'a Body<'tcx>,
+    ranges: &'a TypedRAResult<'tcx>,
+}
+
+impl<'a, 'tcx> HazardVisitor<'a, 'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>, body: &'a Body<'tcx>, ranges: &'a TypedRAResult<'tcx>) -> Self {
+        Self { tcx, body, ranges }
+    }
+
+    pub fn run(&self) -> Vec<Hazard> {
+        let mut hazards = Vec::new();
+        for (bb, data) in self.body.basic_blocks.iter_enumerated() {
+            for statement in &data.statements {
+                let StatementKind::Assign(box (dest, rvalue)) = &statement.kind else {
+                    continue;
+                };
+                let hazard = match rvalue {
+                    Rvalue::BinaryOp(op, box (lhs, rhs)) => self.check_binary(*dest, *op, lhs, rhs),
+                    Rvalue::Cast(CastKind::IntToInt, operand, to) => self.check_cast(operand, *to),
+                    _ => None,
+                };
+                if let Some(kind) = hazard {
+                    hazards.push(Hazard {
+                        kind,
+                        ty: String::new(),
+                        bb: bb.as_usize(),
+                        span: statement.source_info.span,
+                        operands: Vec::new(),
+                    });
+                    let hazard = hazards.last_mut().unwrap();
+                    let operands: Vec<&Operand<'tcx>> = match rvalue {
+                        Rvalue::BinaryOp(_, box (lhs, rhs)) => vec![lhs, rhs],
+                        Rvalue::Cast(_, operand, _) => vec![operand],
+                        _ => Vec::new(),
+                    };
+                    hazard.ty = operands[0].ty(self.body, self.tcx).to_string();
+                    hazard.operands = operands
+                        .into_iter()
+                        .filter_map(|operand| Some((name(operand), self.range(operand)?)))
+                        .collect();
+                }
+            }
+        }
+        hazards
+    }
+
+    /// The range of an integer operand, if it is bounded. The operands with inverted ranges are
+    /// in infeasible branches, so they are not judged either.
+    fn range(&self, operand: &Operand<'tcx>) -> Option<IntRange> {
+        let domain = IntDomain::of(operand.ty(self.body, self.tcx))?;
+        let range = match operand {
+            Operand::Copy(place) | Operand::Move(place) => self.ranges.get(place).cloned(),
+            Operand::Constant(c) => IntRange::from_const(domain, &c.const_),
+        }?;
+        (range.is_bounded() && !range.is_inverted()).then_some(range)
+    }
+
+    fn check_binary(
+        &self,
+        dest: Place<'tcx>,
+        op: BinOp,
+        lhs: &Operand<'tcx>,
+        rhs: &Operand<'tcx>,
+    ) -> Option<HazardKind> {
+        let (lhs_range, rhs_range) = (self.range(lhs)?, self.range(rhs)?);
+        let ty = lhs.ty(self.body, self.tcx);
+        match op {
+            BinOp::Sub | BinOp::SubWithOverflow if lhs_range.may_overflow(op, &rhs_range) => {
+                if ty.is_usize()
+                    && let Some((sink, sink_span)) = self.length_use(dest.local)
+                {
+                    Some(HazardKind::Underflow { sink, sink_span })
+                } else {
+                    Some(HazardKind::Overflow(op))
+                }
+            }
+            BinOp::Add | BinOp::AddWithOverflow | BinOp::Mul | BinOp::MulWithOverflow
+                if lhs_range.may_overflow(op, &rhs_range) =>
+            {
+                Some(HazardKind::Overflow(op))
+            }
+            BinOp::Shl => {
+                let bits = ty.primitive_size(self.tcx).bits();
+                lhs_range
+                    .may_shl_overflow(&rhs_range, bits)
+                    .then_some(HazardKind::Overflow(op))
+            }
+            _ => None,
+        }
+    }
+
+    fn check_cast(&self, operand: &Operand<'tcx>, to: Ty<'tcx>) -> Option<HazardKind> {
+        let to_domain = IntDomain::of(to)?;
+        let range = self.range(operand)?;
+        (!range.fits(to_domain)).then(|| HazardKind::Cast(to.to_string()))
+    }
+
+    /// Where the result of the operation is used as an index or a length, through copies and the
+    /// SSA/ESSA statements.
+    fn length_use(&self, result: Local) -> Option<(String, Span)> {
+        let mut derived = FxHashSet::from_iter([result]);
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for data in self.body.basic_blocks.iter() {
+                for statement in &data.statements {
+                    let StatementKind::Assign(box (dest, rvalue)) = &statement.kind else {
+                        continue;
+                    };
+                    if !dest.projection.is_empty()
+                        || derived.contains(&dest.local)
+                        || !self.body.local_decls[dest.local].ty.is_integral()
+                    {
+                        continue;
+                    }
+                    let reads_derived = match rvalue {
+                        Rvalue::Use(operand) => reads(operand, &derived),
+                        Rvalue::Aggregate(_, operands) => {
+                            operands.iter().any(|operand| reads(operand, &derived))
+                        }
+                        _ => false,
+                    };
+                    if reads_derived {
+                        derived.insert(dest.local);
+                        changed = true;
+                    }
+                }
+            }
+        }
+
+        let mut indexes = IndexUses {
+            derived: &derived,
+            span: None,
+            body: self.body,
+        };
+        indexes.visit_body(self.body);
+        if let Some(span) = indexes.span {
+            return Some(("an index".to_owned(), span));
+        }
+        for data in self.body.basic_blocks.iter() {
+            let TerminatorKind::Call { func, args, .. } = &data.terminator().kind else {
+                continue;
+            };
+            let ty::FnDef(callee, _) = func.ty(self.body, self.tcx).kind() else {
+                continue;
+            };
+            let path = model_path(self.tcx, *callee);
+            if is_length_api(&path) && args.iter().any(|arg| reads(&arg.node, &derived)) {
+                return Some((format!("`{}`", path), data.terminator().source_info.span));
+            }
+        }
+        None
+    }
+}
+
+fn reads(operand: &Operand<'_>, locals: &FxHashSet<Local>) -> bool {
+    operand
+        .place()
+        .is_some_and(|place| locals.contains(&place.local))
+}
+
+fn name(operand: &Operand<'_>) -> String {
+    match operand {
+        Operand::Copy(place) | Operand::Move(place) => format!("{:?}", place),
+        Operand::Constant(c) => format!("{:?}", c),
+    }
+}
+
+/// The first place indexed by one of the locals.
+struct IndexUses<'a, 'tcx> {
+    derived: &'a FxHashSet<Local>,
+    span: Option<Span>,
+    body: &'a Body<'tcx>,
+}
+
+impl<'a, 'tcx> Visitor<'tcx> for IndexUses<'a, 'tcx> {
+    fn visit_projection_elem(
+        &mut self,
+        place_ref: PlaceRef<'tcx>,
+        elem: PlaceElem<'tcx>,
+        context: PlaceContext,
+        location: Location,
+    ) {
+        if let ProjectionElem::Index(local) = elem
+            && self.derived.contains(&local)
+            && self.span.is_none()
+        {
+            self.span = Some(self.body.source_info(location).span);
+        }
+        self.super_projection_elem(place_ref, elem, context, location);
+    }
+}
@@ -0,0 +1,93 @@
+pub mod hazard;
+
+use crate::{
+    analysis::{Analysis, core::range_analysis::default::TypedRangeAnalyzer},
+    utils::{
+        diagnostics::{Finding, FindingKind},
+        log::{span_to_filename, span_to_line_number},
+    },
+};
+use hazard::{Hazard, HazardKind, HazardVisitor};
+use rustc_middle::ty::TyCtxt;
+use rustc_span::def_id::DefId;
+
+/// Detect the integer overflows, the truncating or sign-changing `as` casts and the `usize`
+/// subtractions underflowing before being used as an index or a length. The operations are judged
+/// by the ranges of their operands inferred by the range analysis in the domain of each integer
+/// type, which are reported as the evidence.
+pub struct OverflowCheck<'tcx> {
+    pub tcx: TyCtxt<'tcx>,
+}
+
+impl<'tcx> OverflowCheck<'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>) -> Self {
+        Self { tcx }
+    }
+
+    pub fn start(&self) {
+        let mut analyzer = TypedRangeAnalyzer::new(self.tcx, false);
+        analyzer.run();
+        let mut def_ids: Vec<DefId> = analyzer.body_map.keys().copied().collect();
+        def_ids.sort_by_key(|def_id| self.tcx.def_path_str(*def_id));
+        for def_id in def_ids {
+            let Some(ranges) = analyzer.final_vars.get(&def_id) else {
+                continue;
+            };
+            let body = &analyzer.body_map[&def_id];
+            for hazard in HazardVisitor::new(self.tcx, body, ranges).run() {
+                self.report(def_id, &hazard);
+            }
+        }
+    }
+
+    fn report(&self, def_id: DefId, hazard: &Hazard) {
+        let fn_span = self.tcx.optimized_mir(def_id).span;
+        let evidence = hazard
+            .operands
+            .iter()
+            .map(|(operand, range)| format!("`{}` in {}", operand, range))
+            .collect::<Vec<_>>()
+            .join(" and ");
+        let (kind, title, name, detail) = match &hazard.kind {
+            HazardKind::Overflow(op) => (
+                FindingKind::IntOverflow,
+                "Integer overflow detected.",
+                "Integer overflow",
+                format!(
+                    "the `{:?}` of `{}` may overflow with {}.",
+                    op, hazard.ty, evidence
+                ),
+            ),
+            HazardKind::Cast(to) => (
+                FindingKind::TruncatingCast,
+                "Truncating cast detected.",
+                "Truncating cast",
+                format!(
+                    "the cast from `{}` to `{}` may truncate the value or change its sign with {}.",
+                    hazard.ty, to, evidence
+                ),
+            ),
+            HazardKind::Underflow { sink, .. } => (
+                FindingKind::UsizeUnderflow,
+                "Usize underflow detected.",
+                "Usize underflow",
+                format!(
+                    "the subtraction may underflow with {}, and its result is used as {}.",
+                    evidence, sink
+                ),
+            ),
+        };
+        let detail = format!(
+            "{}: Location in file {} line {}.\n    | MIR detail: {}",
+            name,
+            span_to_filename(hazard.span),
+            span_to_line_number(hazard.span),
+            detail,
+        );
+        let mut finding = Finding::new(kind, def_id, title).primary(hazard.span, detail.as_str());
+        if let HazardKind::Underflow { sink_span, .. } = &hazard.kind {
+            finding = finding.related(*sink_span, "the result is used here");
+        }
+        finding.report(fn_span);
+    }
+}
@@ -0,0 +1,333 @@
+//! The invariants temporarily broken by unsafe code in a function, and the calls to user-provided
+//! code that may panic before they are restored.
+//!
+//! An invariant is broken by duplicating the ownership of a value, e.g., with `ptr::read`, or by
+//! `Vec::set_len` exposing elements that are not initialized yet, which is assumed unless a write
+//! into the buffer of the vector dominates it. If a closure, a function pointer or a trait method
+//! of a generic type is called afterwards, it may panic before the invariant is restored, e.g., by
+//! `ptr::write` or `mem::forget`, and the unwinding then drops the duplicate twice or drops the
+//! uninitialized elements.
+
+use crate::analysis::core::ownership_model::{PathPatterns, model_path};
+use rustc_data_structures::{
+    fx::{FxHashMap, FxHashSet},
+    graph::dominators::Dominators,
+};
+use rustc_middle::{
+    mir::{BasicBlock, Body, Local, Operand, Rvalue, StatementKind, TerminatorKind},
+    ty::{self, GenericArgsRef, Ty, TyCtxt, TypingEnv},
+};
+use rustc_span::{Span, def_id::DefId};
+use std::collections::VecDeque;
+
+/// How an unsafe API breaks the invariant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bypass {
+    /// The ownership of the value is duplicated.
+    Duplicate,
+    /// The length of the vector covers the elements not initialized yet.
+    SetLen,
+}
+
+const BYPASS_APIS: &[(&str, Bypass)] = &[
+    ("core::ptr::read*", Bypass::Duplicate),
+    ("core::ptr::*_ptr::<impl *>::read*", Bypass::Duplicate),
+    ("core::ptr::NonNull::<*>::read*", Bypass::Duplicate),
+    (
+        "core::mem::MaybeUninit::<*>::assume_init_read",
+        Bypass::Duplicate,
+    ),
+    ("core::ptr::copy*", Bypass::Duplicate),
+    ("core::intrinsics::copy*", Bypass::Duplicate),
+    ("core::ptr::*_ptr::<impl *>::copy_*", Bypass::Duplicate),
+    ("core::vec::Vec::<*>::set_len", Bypass::SetLen),
+];
+
+/// The APIs after which the duplicated values are no longer dropped twice.
+const RESTORE_DUPLICATE_APIS: &[&str] = &[
+    "core::ptr::write*",
+    "core::ptr::mut_ptr::<impl *mut *>::write*",
+    "core::ptr::NonNull::<*>::write*",
+    "core::ptr::copy*",
+    "core::intrinsics::copy*",
+    "core::ptr::*_ptr::<impl *>::copy_*",
+    "core::mem::forget",
+    "core::mem::ManuallyDrop::<*>::new",
+    "core::vec::Vec::<*>::set_len",
+];
+
+/// The APIs writing to the memory that their first argument points to.
+const WRITE_APIS: &[&str] = &[
+    "core::ptr::write*",
+    "core::ptr::mut_ptr::<impl *mut *>::write*",
+    "core::ptr::NonNull::<*>::write*",
+];
+
+/// The APIs after which the length of the vector is set again.
+const RESTORE_SET_LEN_APIS: &[&str] = &["core::vec::Vec::<*>::set_len"];
+
+fn bypass_api(path: &str) -> Option<Bypass> {
+    static APIS: PathPatterns<(&str, Bypass)> = PathPatterns::keyed(BYPASS_APIS);
+    APIS.find(path).map(|(_, bypass)| *bypass)
+}
+
+impl Bypass {
+    fn is_restored_by(&self, path: &str) -> bool {
+        static DUPLICATE: PathPatterns<&str> = PathPatterns::new(RESTORE_DUPLICATE_APIS);
+        static SET_LEN: PathPatterns<&str> = PathPatterns::new(RESTORE_SET_LEN_APIS);
+        match self {
+            Bypass::Duplicate => DUPLICATE.matches(path),
+            Bypass::SetLen => SET_LEN.matches(path),
+        }
+    }
+}
+
+/// A call that breaks the invariant.
+#[derive(Debug, Clone)]
+pub struct BrokenInvariant<'tcx> {
+    pub bypass: Bypass,
+    /// The path of the unsafe API.
+    pub api: String,
+    /// The type of the duplicated values or the vector elements.
+    pub ty: Ty<'tcx>,
+    pub bb: usize,
+    pub span: Span,
+}
+
+/// A call to user-provided code that may panic while the invariant is broken.
+#[derive(Debug, Clone)]
+pub struct PanicSafetyBug<'tcx> {
+    pub broken: BrokenInvariant<'tcx>,
+    /// The description of the callee.
+    pub callee: String,
+    pub bb: usize,
+    pub span: Span,
+}
+
+pub struct InvariantAnalysis<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    typing_env: TypingEnv<'tcx>,
+    body: &'tcx Body<'tcx>,
+    pub bugs: Vec<PanicSafetyBug<'tcx>>,
+}
+
+impl<'tcx> InvariantAnalysis<'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>, def_id: DefId) -> Self {
+        Self {
+            tcx,
+            typing_env: TypingEnv::post_analysis(tcx, def_id),
+            body: tcx.optimized_mir(def_id),
+            bugs: Vec::new(),
+        }
+    }
+
+    pub fn run(&mut self) {
+        let guards = self.guards();
+        let dominators = self.body.basic_blocks.dominators();
+        let roots = self.roots();
+        for (bb, data) in self.body.basic_blocks.iter_enumerated() {
+            if data.is_cleanup {
+                continue;
+            }
+            let Some(broken) = self.broken_invariant(bb, dominators, &roots) else {
+                continue;
+            };
+            // The elements of a vector whose length is set to zero beforehand are leaked
+            // rather than dropped during unwinding.
+            if guards
+                .iter()
+                .any(|guard| *guard != bb && dominators.dominates(*guard, bb))
+            {
+                continue;
+            }
+            if let Some(bug) = self.panicking_call(broken) {
+                self.bugs.push(bug);
+            }
+        }
+    }
+
+    /// The blocks calling `Vec::set_len(0)`.
+    fn guards(&self) -> Vec<BasicBlock> {
+        self.body
+            .basic_blocks
+            .iter_enumerated()
+            .filter(|(bb, _)| {
+                let Some((path, _, args)) = self.callee(*bb) else {
+                    return false;
+                };
+                bypass_api(&path) == Some(Bypass::SetLen)
+                    && args.get(1).and_then(|arg| self.const_usize(arg)) == Some(0)
+            })
+            .map(|(bb, _)| bb)
+            .collect()
+    }
+
+    fn broken_invariant(
+        &self,
+        bb: BasicBlock,
+        dominators: &Dominators<BasicBlock>,
+        roots: &FxHashMap<Local, Local>,
+    ) -> Option<BrokenInvariant<'tcx>> {
+        let (path, generic_args, args) = self.callee(bb)?;
+        let bypass = bypass_api(&path)?;
+        if bypass == Bypass::SetLen
+            && (args.get(1).and_then(|arg| self.const_usize(arg)) == Some(0)
+                || self.is_written_before(bb, args.first()?, dominators, roots))
+        {
+            return None;
+        }
+        let ty = generic_args.types().next()?;
+        if !ty.needs_drop(self.tcx, self.typing_env) {
+            return None;
+        }
+        Some(BrokenInvariant {
+            bypass,
+            api: path,
+            ty,
+            bb: bb.as_usize(),
+            span: self.body.basic_blocks[bb].terminator().source_info.span,
+        })
+    }
+
+    /// Whether a write through a pointer into the vector dominates the call of `set_len` on it,
+    /// e.g., `ptr::write(v.as_mut_ptr().add(len), x)` before `v.set_len(len + 1)`.
+    fn is_written_before(
+        &self,
+        set_len: BasicBlock,
+        vec: &Operand<'tcx>,
+        dominators: &Dominators<BasicBlock>,
+        roots: &FxHashMap<Local, Local>,
+    ) -> bool {
+        static WRITES: PathPatterns<&str> = PathPatterns::new(WRITE_APIS);
+        let Some(vec) = vec.place().map(|place| root(roots, place.local)) else {
+            return false;
+        };
+        self.body.basic_blocks.indices().any(|bb| {
+            bb != set_len
+                && dominators.dominates(bb, set_len)
+                && self.callee(bb).is_some_and(|(path, _, args)| {
+                    WRITES.matches(&path)
+                        && args
+                            .first()
+                            .and_then(|arg| arg.place())
+                            .is_some_and(|place| root(roots, place.local) == vec)
+                })
+        })
+    }
+
+    /// The local that each reference or pointer is derived from, through borrows, copies, casts
+    /// and calls returning a raw pointer from their first argument, e.g., `Vec::as_mut_ptr` and
+    /// `add`. The fields are not distinguished from their base.
+    fn roots(&self) -> FxHashMap<Local, Local> {
+        let mut roots = FxHashMap::default();
+        for bb in self.body.basic_blocks.reverse_postorder() {
+            let data = &self.body.basic_blocks[*bb];
+            for stmt in &data.statements {
+                let StatementKind::Assign(box (place, rvalue)) = &stmt.kind else {
+                    continue;
+                };
+                let Some(lv) = place.as_local() else {
+                    continue;
+                };
+                let rv = match rvalue {
+                    Rvalue::Use(op) | Rvalue::Cast(_, op, _) => op.place().map(|place| place.local),
+                    Rvalue::Ref(_, _, place) | Rvalue::RawPtr(_, place) => Some(place.local),
+                    _ => None,
+                };
+                if let Some(rv) = rv {
+                    roots.insert(lv, root(&roots, rv));
+                }
+            }
+            if let TerminatorKind::Call {
+                args, destination, ..
+            } = &data.terminator().kind
+                && let Some(lv) = destination.as_local()
+                && self.body.local_decls[lv].ty.is_raw_ptr()
+                && let Some(rv) = args.first().and_then(|arg| arg.node.place())
+            {
+                roots.insert(lv, root(&roots, rv.local));
+            }
+        }
+        roots
+    }
+
+    /// The first call to user-provided code reachable from the broken invariant before it is
+    /// restored, ignoring the unwinding paths.
+    fn panicking_call(&self, broken: BrokenInvariant<'tcx>) -> Option<PanicSafetyBug<'tcx>> {
+        let start = BasicBlock::from_usize(broken.bb);
+        let mut visited = FxHashSet::default();
+        let mut worklist: VecDeque<BasicBlock> = self.successors(start).collect();
+        while let Some(bb) = worklist.pop_front() {
+            if !visited.insert(bb) {
+                continue;
+            }
+            if let Some(callee) = self.user_callee(bb) {
+                let terminator = self.body.basic_blocks[bb].terminator();
+                return Some(PanicSafetyBug {
+                    broken,
+                    callee,
+                    bb: bb.as_usize(),
+                    span: terminator.source_info.span,
+                });
+            }
+            if let Some((path, _, _)) = self.callee(bb)
+                && broken.bypass.is_restored_by(&path)
+            {
+                continue;
+            }
+            worklist.extend(self.successors(bb));
+        }
+        None
+    }
+
+    fn successors(&self, bb: BasicBlock) -> impl Iterator<Item = BasicBlock> + '_ {
+        self.body.basic_blocks[bb]
+            .terminator()
+            .successors()
+            .filter(|succ| !self.body.basic_blocks[*succ].is_cleanup)
+    }
+
+    fn callee(
+        &self,
+        bb: BasicBlock,
+    ) -> Option<(String, GenericArgsRef<'tcx>, Vec<&'tcx Operand<'tcx>>)> {
+        let TerminatorKind::Call { func, args, .. } = &self.body.basic_blocks[bb].terminator().kind
+        else {
+            return None;
+        };
+        let (callee, generic_args) = func.const_fn_def()?;
+        let args = args.iter().map(|arg| &arg.node).collect();
+        Some((model_path(self.tcx, callee), generic_args, args))
+    }
+
+    /// The description of the callee if it is user-provided, i.e., a function pointer, a closure
+    /// or trait object passed in, or a trait method of a generic type.
+    fn user_callee(&self, bb: BasicBlock) -> Option<String> {
+        let TerminatorKind::Call { func, .. } = &self.body.basic_blocks[bb].terminator().kind
+        else {
+            return None;
+        };
+        let Some((callee, generic_args)) = func.const_fn_def() else {
+            return Some(format!("the function pointer `{:?}`", func));
+        };
+        self.tcx.trait_of_assoc(callee)?;
+        let self_ty = generic_args.types().next()?.peel_refs();
+        match self_ty.kind() {
+            ty::Param(_) | ty::Dynamic(..) | ty::Alias(..) => Some(format!(
+                "`{}`",
+                self.tcx.def_path_str_with_args(callee, generic_args)
+            )),
+            _ => None,
+        }
+    }
+
+    fn const_usize(&self, op: &Operand<'tcx>) -> Option<u64> {
+        op.constant()?
+            .const_
+            .try_eval_target_usize(self.tcx, self.typing_env)
+    }
+}
+
+fn root(roots: &FxHashMap<Local, Local>, local: Local) -> Local {
+    roots.get(&local).copied().unwrap_or(local)
+}
@@ -0,0 +1,71 @@
+pub mod invariant;
+
+use crate::utils::{
+    diagnostics::{Finding, FindingKind},
+    log::{span_to_filename, span_to_line_number},
+};
+use invariant::{Bypass, InvariantAnalysis, PanicSafetyBug};
+use rustc_middle::ty::TyCtxt;
+use rustc_span::def_id::DefId;
+
+/// Detect the panic safety bugs, i.e., the unsafe code that temporarily breaks an invariant,
+/// e.g., with `ptr::read` or `Vec::set_len`, and calls user-provided code that may panic before
+/// the invariant is restored.
+pub struct PanicSafetyCheck<'tcx> {
+    pub tcx: TyCtxt<'tcx>,
+}
+
+impl<'tcx> PanicSafetyCheck<'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>) -> Self {
+        Self { tcx }
+    }
+
+    pub fn start(&self) {
+        for local_def_id in self.tcx.mir_keys(()) {
+            let def_id = local_def_id.to_def_id();
+            if self.tcx.hir_body_const_context(*local_def_id).is_some()
+                || !self.tcx.is_mir_available(def_id)
+            {
+                continue;
+            }
+            let mut analysis = InvariantAnalysis::new(self.tcx, def_id);
+            analysis.run();
+            if !analysis.bugs.is_empty() {
+                self.report(def_id, &analysis.bugs);
+            }
+        }
+    }
+
+    fn report(&self, def_id: DefId, bugs: &[PanicSafetyBug<'tcx>]) {
+        let fn_span = self.tcx.optimized_mir(def_id).span;
+        for bug in bugs {
+            let broken = &bug.broken;
+            let consequence = match broken.bypass {
+                Bypass::Duplicate => format!(
+                    "`{}` at bb{} duplicates the ownership of `{}`, which may be dropped twice",
+                    broken.api, broken.bb, broken.ty
+                ),
+                Bypass::SetLen => format!(
+                    "`{}` at bb{} exposes the elements of `{}` that may be uninitialized, which may be dropped",
+                    broken.api, broken.bb, broken.ty
+                ),
+            };
+            let detail = format!(
+                "Panic safety: Location in file {} line {}.\n    | MIR detail: {} if {} at bb{} panics.",
+                span_to_filename(bug.span),
+                span_to_line_number(bug.span),
+                consequence,
+                bug.callee,
+                bug.bb,
+            );
+            Finding::new(
+                FindingKind::PanicSafety,
+                def_id,
+                "Panic safety bug detected.",
+            )
+            .primary(bug.span, detail.as_str())
+            .related(broken.span, "the invariant is broken here")
+            .report(fn_span);
+        }
+    }
+}
@@ -0,0 +1,369 @@
+//! The strong references stored into the objects managed by `Rc` and `Arc` in a function.
+//!
+//! Each object is named by the local that receives it from `Rc::new`, or by the argument it comes
+//! from. The analysis follows, flow-insensitively, the objects each local holds an `Rc` of and the
+//! objects each local points into, e.g., the result of `Rc::deref` or of `RefCell::borrow_mut` on
+//! it. Storing a value holding an `Rc` of `b` into an object `a`, by an assignment through a
+//! reference into `a` or by passing both to a call such as `Vec::push`, adds an edge `a -> b`.
+//! A cycle of edges is a strong cycle that is never freed, unless a `Weak` replaces one of them.
+
+use super::strong_graph::{StrongGraph, is_shared_ptr, may_hold_strong, shared_pointee};
+use crate::analysis::core::ownership_model::{PathPatterns, model_path};
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_middle::{
+    mir::{Body, Local, Operand, Place, ProjectionElem, Rvalue, StatementKind, TerminatorKind},
+    ty::{Ty, TyCtxt},
+};
+use rustc_span::{Span, def_id::DefId};
+
+/// The functions creating a new object managed by an `Rc` or `Arc` from their arguments.
+const NEW_APIS: &[&str] = &[
+    "core::rc::Rc::<*>::new",
+    "core::rc::Rc::<*>::pin",
+    "core::sync::Arc::<*>::new",
+    "core::sync::Arc::<*>::pin",
+];
+
+/// The functions returning a new strong reference to the object of their first argument.
+const CLONE_APIS: &[&str] = &["core::clone::Clone::clone", "core::rc::Rc::<*>::clone"];
+
+fn is_new(path: &str) -> bool {
+    static PATTERNS: PathPatterns<&str> = PathPatterns::new(NEW_APIS);
+    PATTERNS.matches(path)
+}
+
+fn is_clone(path: &str) -> bool {
+    static PATTERNS: PathPatterns<&str> = PathPatterns::new(CLONE_APIS);
+    PATTERNS.matches(path)
+}
+
+/// A strong reference stored into an object.
+#[derive(Debug, Clone, Copy)]
+pub struct StrongEdge {
+    pub from: Local,
+    pub to: Local,
+    /// The assignment or the call storing the reference.
+    pub span: Span,
+    /// The `Rc::clone` producing the reference, if any.
+    pub clone_span: Option<Span>,
+}
+
+/// A cycle of strong references built in a function.
+#[derive(Debug, Clone)]
+pub struct StrongCycle {
+    pub def_id: DefId,
+    /// The type of the objects, e.g., `Rc<RefCell<Node>>`.
+    pub ty: String,
+    /// The edge stored last, which closes the cycle.
+    pub closing: StrongEdge,
+    pub edges: Vec<StrongEdge>,
+}
+
+pub struct HandleFlow<'tcx, 'a> {
+    tcx: TyCtxt<'tcx>,
+    def_id: DefId,
+    body: &'tcx Body<'tcx>,
+    graph: &'a StrongGraph,
+    /// The objects of which each local holds a strong reference, with the clone producing it.
+    holds: FxHashMap<Local, FxHashMap<Local, Option<Span>>>,
+    /// The objects each local points into.
+    points_into: FxHashMap<Local, FxHashSet<Local>>,
+    edges: FxHashMap<(Local, Local), StrongEdge>,
+}
+
+impl<'tcx, 'a> HandleFlow<'tcx, 'a> {
+    pub fn new(tcx: TyCtxt<'tcx>, def_id: DefId, graph: &'a StrongGraph) -> Self {
+        Self {
+            tcx,
+            def_id,
+            body: tcx.optimized_mir(def_id),
+            graph,
+            holds: FxHashMap::default(),
+            points_into: FxHashMap::default(),
+            edges: FxHashMap::default(),
+        }
+    }
+
+    pub fn run(mut self) -> Vec<StrongCycle> {
+        for arg in self.body.args_iter() {
+            let ty = self.body.local_decls[arg].ty;
+            if shared_pointee(self.tcx, ty).is_some() {
+                self.holds.entry(arg).or_default().insert(arg, None);
+            } else if ty.is_ref() {
+                self.points_into.entry(arg).or_default().insert(arg);
+            }
+        }
+        loop {
+            let size = self.size();
+            self.visit_body();
+            if self.size() == size {
+                break;
+            }
+        }
+        self.cycles()
+    }
+
+    fn size(&self) -> usize {
+        self.holds
+            .values()
+            .map(|objects| objects.len())
+            .sum::<usize>()
+            + self
+                .points_into
+                .values()
+                .map(|objects| objects.len())
+                .sum::<usize>()
+            + self.edges.len()
+    }
+
+    fn ty(&self, local: Local) -> Ty<'tcx> {
+        self.body.local_decls[local].ty
+    }
+
+    fn hold(&mut self, local: Local, objects: Vec<(Local, Option<Span>)>) {
+        let holds = self.holds.entry(local).or_default();
+        for (object, clone_span) in objects {
+            holds.entry(object).or_insert(clone_span);
+        }
+    }
+
+    fn point_into(&mut self, local: Local, objects: Vec<Local>) {
+        self.points_into.entry(local).or_default().extend(objects);
+    }
+
+    fn store(&mut self, into: Vec<Local>, values: Vec<(Local, Option<Span>)>, span: Span) {
+        for from in into {
+            for (to, clone_span) in values.iter() {
+                self.edges.entry((from, *to)).or_insert(StrongEdge {
+                    from,
+                    to: *to,
+                    span,
+                    clone_span: *clone_span,
+                });
+            }
+        }
+    }
+
+    /// The objects held by the value of the place. A place read through a reference into an
+    /// object holds what the object holds.
+    fn held_by(&self, place: &Place<'tcx>) -> Vec<(Local, Option<Span>)> {
+        let mut held: Vec<(Local, Option<Span>)> = self
+            .holds
+            .get(&place.local)
+            .into_iter()
+            .flatten()
+            .map(|(object, clone_span)| (*object, *clone_span))
+            .collect();
+        if place.projection.contains(&ProjectionElem::Deref) {
+            for object in self.pointed_by(place) {
+                held.extend(
+                    self.edges
+                        .values()
+                        .filter(|edge| edge.from == object)
+                        .map(|edge| (edge.to, edge.clone_span)),
+                );
+            }
+        }
+        held
+    }
+
+    fn pointed_by(&self, place: &Place<'tcx>) -> Vec<Local> {
+        self.points_into
+            .get(&place.local)
+            .into_iter()
+            .flatten()
+            .copied()
+            .collect()
+    }
+
+    fn operand_holds(&self, operand: &Operand<'tcx>) -> Vec<(Local, Option<Span>)> {
+        operand
+            .place()
+            .map(|place| self.held_by(&place))
+            .unwrap_or_default()
+    }
+
+    fn visit_body(&mut self) {
+        for data in self.body.basic_blocks.iter() {
+            if data.is_cleanup {
+                continue;
+            }
+            for stmt in &data.statements {
+                let StatementKind::Assign(box (place, rvalue)) = &stmt.kind else {
+                    continue;
+                };
+                self.visit_assign(place, rvalue, stmt.source_info.span);
+            }
+            let terminator = data.terminator();
+            if let TerminatorKind::Call {
+                func,
+                args,
+                destination,
+                ..
+            } = &terminator.kind
+            {
+                let args: Vec<&Operand<'tcx>> = args.iter().map(|arg| &arg.node).collect();
+                self.visit_call(func, &args, destination, terminator.source_info.span);
+            }
+        }
+    }
+
+    fn visit_assign(&mut self, place: &Place<'tcx>, rvalue: &Rvalue<'tcx>, span: Span) {
+        let mut held = Vec::new();
+        let mut pointed = Vec::new();
+        match rvalue {
+            Rvalue::Use(operand) | Rvalue::Cast(_, operand, _) => {
+                held = self.operand_holds(operand);
+                if let Some(source) = operand.place() {
+                    pointed = self.pointed_by(&source);
+                }
+            }
+            Rvalue::Aggregate(_, operands) => {
+                for operand in operands {
+                    held.extend(self.operand_holds(operand));
+                }
+            }
+            Rvalue::Ref(_, _, source)
+            | Rvalue::RawPtr(_, source)
+            | Rvalue::CopyForDeref(source) => {
+                held = self.held_by(source);
+                pointed = self.pointed_by(source);
+            }
+            _ => {}
+        }
+        if place.projection.contains(&ProjectionElem::Deref) {
+            if may_hold_strong(self.tcx, place.ty(&self.body.local_decls, self.tcx).ty) {
+                self.store(self.pointed_by(place), held, span);
+            }
+        } else if place.projection.is_empty() {
+            self.hold(place.local, held);
+            self.point_into(place.local, pointed);
+        }
+    }
+
+    fn visit_call(
+        &mut self,
+        func: &Operand<'tcx>,
+        args: &[&Operand<'tcx>],
+        destination: &Place<'tcx>,
+        span: Span,
+    ) {
+        let Some((callee, _)) = func.const_fn_def() else {
+            return;
+        };
+        if !destination.projection.is_empty() {
+            return;
+        }
+        let dest = destination.local;
+        let path = model_path(self.tcx, callee);
+        let moved_holds: Vec<(Local, Option<Span>)> = args
+            .iter()
+            .filter(|arg| matches!(arg, Operand::Move(_)) && !arg.ty(self.body, self.tcx).is_ref())
+            .flat_map(|arg| self.operand_holds(arg))
+            .collect();
+        let self_is_shared = args
+            .first()
+            .is_some_and(|arg| shared_pointee(self.tcx, arg.ty(self.body, self.tcx)).is_some());
+
+        if is_new(&path) {
+            self.hold(dest, vec![(dest, None)]);
+            self.store(vec![dest], moved_holds, span);
+            return;
+        }
+        if self_is_shared {
+            let first = self.operand_holds(args[0]);
+            if is_clone(&path) {
+                self.hold(
+                    dest,
+                    first
+                        .into_iter()
+                        .map(|(object, _)| (object, Some(span)))
+                        .collect(),
+                );
+            } else if !is_shared_ptr(self.tcx, self.ty(dest)) {
+                // The result is the object itself or a reference into it, e.g., `Rc::deref`.
+                self.point_into(dest, first.into_iter().map(|(object, _)| object).collect());
+            }
+            return;
+        }
+
+        let pointed: Vec<Local> = args
+            .iter()
+            .filter_map(|arg| arg.place())
+            .flat_map(|place| self.pointed_by(&place))
+            .collect();
+        // A strong reference moved into a call with a reference into an object, e.g., `push`.
+        self.store(pointed.clone(), moved_holds, span);
+        if may_hold_strong(self.tcx, self.ty(dest)) {
+            let held = args
+                .iter()
+                .flat_map(|arg| self.operand_holds(arg))
+                .collect();
+            self.hold(dest, held);
+        }
+        self.point_into(dest, pointed);
+    }
+
+    /// The cycles of edges whose objects may form strong cycles by their types, reported at the
+    /// edge stored last.
+    fn cycles(&self) -> Vec<StrongCycle> {
+        let mut edges: Vec<StrongEdge> = self.edges.values().copied().collect();
+        edges.sort_by_key(|edge| (edge.from, edge.to));
+        let mut reported: FxHashSet<Local> = FxHashSet::default();
+        let mut cycles = Vec::new();
+        for edge in edges.iter() {
+            if reported.contains(&edge.from) {
+                continue;
+            }
+            let Some(path) = self.path(&edges, edge.to, edge.from) else {
+                continue;
+            };
+            let mut cycle: Vec<StrongEdge> = path;
+            cycle.push(*edge);
+            let Some(pointee) = shared_pointee(self.tcx, self.ty(edge.from)) else {
+                continue;
+            };
+            if !self.graph.may_form_cycle(self.tcx, pointee)
+                || cycle.iter().all(|edge| edge.clone_span.is_none())
+            {
+                continue;
+            }
+            reported.extend(cycle.iter().map(|edge| edge.from));
+            let closing = *cycle.iter().max_by_key(|edge| edge.span.hi()).unwrap();
+            cycles.push(StrongCycle {
+                def_id: self.def_id,
+                ty: self.ty(edge.from).peel_refs().to_string(),
+                closing,
+                edges: cycle,
+            });
+        }
+        cycles
+    }
+
+    /// The edges of a path between two objects, empty if they are the same.
+    fn path(&self, edges: &[StrongEdge], from: Local, to: Local) -> Option<Vec<StrongEdge>> {
+        let mut pred: FxHashMap<Local, StrongEdge> = FxHashMap::default();
+        let mut stack = vec![from];
+        let mut visited = FxHashSet::from_iter([from]);
+        while let Some(object) = stack.pop() {
+            if object == to {
+                let mut path = Vec::new();
+                let mut object = to;
+                while object != from {
+                    let edge = pred[&object];
+                    path.push(edge);
+                    object = edge.from;
+                }
+                path.reverse();
+                return Some(path);
+            }
+            for edge in edges.iter().filter(|edge| edge.from == object) {
+                if visited.insert(edge.to) {
+                    pred.insert(edge.to, *edge);
+                    stack.push(edge.to);
+                }
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,64 @@
+pub mod handle_flow;
+pub mod strong_graph;
+
+use crate::utils::{
+    diagnostics::{Finding, FindingKind},
+    log::{span_to_filename, span_to_line_number},
+};
+use handle_flow::{HandleFlow, StrongCycle};
+use rustc_hir::def::DefKind;
+use rustc_middle::ty::TyCtxt;
+use strong_graph::StrongGraph;
+
+/// Detect the strong reference cycles of `Rc` and `Arc`, which are never freed. The types that may
+/// form such cycles are found first, and the functions cloning and storing their references into
+/// each other are then checked on MIR.
+pub struct RcCycleCheck<'tcx> {
+    pub tcx: TyCtxt<'tcx>,
+}
+
+impl<'tcx> RcCycleCheck<'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>) -> Self {
+        Self { tcx }
+    }
+
+    pub fn start(&self) {
+        let graph = StrongGraph::new(self.tcx);
+        for local_def_id in self.tcx.mir_keys(()) {
+            let def_id = local_def_id.to_def_id();
+            if !matches!(
+                self.tcx.def_kind(def_id),
+                DefKind::Fn | DefKind::AssocFn | DefKind::Closure
+            ) || !self.tcx.is_mir_available(def_id)
+            {
+                continue;
+            }
+            for cycle in HandleFlow::new(self.tcx, def_id, &graph).run() {
+                self.report(&cycle);
+            }
+        }
+    }
+
+    fn report(&self, cycle: &StrongCycle) {
+        let fn_span = self.tcx.optimized_mir(cycle.def_id).span;
+        let closing = &cycle.closing;
+        let detail = format!(
+            "Reference cycle: Location in file {} line {}.\n    | MIR detail: the strong reference stored here closes a cycle of length {} between the objects of type `{}` without a `Weak`.",
+            span_to_filename(closing.span),
+            span_to_line_number(closing.span),
+            cycle.edges.len(),
+            cycle.ty,
+        );
+        let mut finding = Finding::new(
+            FindingKind::RcCycle,
+            cycle.def_id,
+            "Reference cycle detected.",
+        )
+        .location(cycle.ty.clone())
+        .primary(closing.span, detail.as_str());
+        for clone_span in cycle.edges.iter().filter_map(|edge| edge.clone_span) {
+            finding = finding.related(clone_span, "the strong reference is cloned here");
+        }
+        finding.report(fn_span);
+    }
+}
@@ -0,0 +1,216 @@
+//! The types that may form strong reference cycles.
+//!
+//! A local ADT points to another one strongly if one of its fields owns an `Rc` or `Arc` of it,
+//! possibly inside containers such as `Vec`, `Option` or `RefCell`. A `Weak` breaks the chain, and
+//! so do references and raw pointers, which do not own their pointees. A trait object or a
+//! closure may capture any `Rc`, so it points to all the types held by an `Rc` in the crate. The
+//! ADTs owned by value are also followed, e.g., a `Child` inside a `Parent`, and a type may form a
+//! strong cycle if it lies on a cycle of the graph going through at least one `Rc`.
+
+use crate::analysis::{Analysis, core::ownedheap_analysis::default::OwnedHeapAnalyzer};
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_middle::ty::{self, Ty, TyCtxt};
+use rustc_span::{def_id::DefId, sym};
+
+/// What a type owns, as far as strong references are concerned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Target {
+    /// A local ADT, held by value or behind an `Rc`.
+    Adt(DefId, bool),
+    /// A trait object or a closure, which may capture any `Rc`.
+    Erased,
+}
+
+pub fn is_shared_ptr(tcx: TyCtxt<'_>, ty: Ty<'_>) -> bool {
+    ty.ty_adt_def().is_some_and(|adt_def| {
+        tcx.is_diagnostic_item(sym::Rc, adt_def.did())
+            || tcx.is_diagnostic_item(sym::Arc, adt_def.did())
+    })
+}
+
+fn is_weak_ptr(tcx: TyCtxt<'_>, ty: Ty<'_>) -> bool {
+    ty.ty_adt_def().is_some_and(|adt_def| {
+        tcx.is_diagnostic_item(sym::RcWeak, adt_def.did())
+            || tcx.is_diagnostic_item(sym::ArcWeak, adt_def.did())
+    })
+}
+
+/// The pointee of an `Rc` or `Arc`, behind the references.
+pub fn shared_pointee<'tcx>(tcx: TyCtxt<'tcx>, ty: Ty<'tcx>) -> Option<Ty<'tcx>> {
+    let ty = ty.peel_refs();
+    if !is_shared_ptr(tcx, ty) {
+        return None;
+    }
+    let ty::Adt(_, args) = ty.kind() else {
+        return None;
+    };
+    args.types().next()
+}
+
+/// Collect the targets owned by the type, without entering the local ADTs.
+pub fn targets<'tcx>(tcx: TyCtxt<'tcx>, ty: Ty<'tcx>, shared: bool, targets: &mut Vec<Target>) {
+    match ty.kind() {
+        ty::Adt(adt_def, args) => {
+            if is_weak_ptr(tcx, ty) || adt_def.is_phantom_data() {
+                return;
+            }
+            if is_shared_ptr(tcx, ty) {
+                if let Some(pointee) = args.types().next() {
+                    self::targets(tcx, pointee, true, targets);
+                }
+            } else if adt_def.did().is_local() {
+                targets.push(Target::Adt(adt_def.did(), shared));
+            } else {
+                for arg in args.types() {
+                    self::targets(tcx, arg, shared, targets);
+                }
+            }
+        }
+        ty::Array(ty, _) | ty::Slice(ty) => self::targets(tcx, *ty, shared, targets),
+        ty::Tuple(tys) => {
+            for ty in tys.iter() {
+                self::targets(tcx, ty, shared, targets);
+            }
+        }
+        ty::Closure(_, args) => {
+            for ty in args.as_closure().upvar_tys() {
+                self::targets(tcx, ty, shared, targets);
+            }
+        }
+        ty::Dynamic(..) => targets.push(Target::Erased),
+        _ => {}
+    }
+}
+
+/// Whether a value of the type may hold an `Rc` or `Arc`, either itself, behind a reference or in
+/// the fields of the ADTs it owns.
+pub fn may_hold_strong<'tcx>(tcx: TyCtxt<'tcx>, ty: Ty<'tcx>) -> bool {
+    fn visit<'tcx>(tcx: TyCtxt<'tcx>, ty: Ty<'tcx>, visited: &mut FxHashSet<DefId>) -> bool {
+        let ty = ty.peel_refs();
+        if is_shared_ptr(tcx, ty) {
+            return true;
+        }
+        let mut owned = Vec::new();
+        targets(tcx, ty, false, &mut owned);
+        owned.iter().any(|target| match target {
+            Target::Erased | Target::Adt(_, true) => true,
+            Target::Adt(did, false) => {
+                visited.insert(*did)
+                    && tcx
+                        .adt_def(*did)
+                        .all_fields()
+                        .any(|field| visit(tcx, tcx.type_of(field.did).skip_binder(), visited))
+            }
+        })
+    }
+    visit(tcx, ty, &mut FxHashSet::default())
+}
+
+/// The graph of the local ADTs pointing to each other strongly.
+pub struct StrongGraph {
+    /// The successors of each type, and whether the edge goes through an `Rc`.
+    edges: FxHashMap<DefId, FxHashSet<(DefId, bool)>>,
+    cyclic: FxHashSet<DefId>,
+}
+
+impl StrongGraph {
+    /// Build the graph from the ADTs, with their generic arguments, met by the owned heap
+    /// analysis in the crate.
+    pub fn new(tcx: TyCtxt<'_>) -> Self {
+        let mut heap = OwnedHeapAnalyzer::new(tcx);
+        heap.run();
+
+        let mut owned: FxHashMap<DefId, Vec<Target>> = FxHashMap::default();
+        let mut pointees: FxHashSet<DefId> = FxHashSet::default();
+        for ty in heap.ty_map().keys() {
+            let ty::Adt(adt_def, args) = ty.kind() else {
+                continue;
+            };
+            let mut fields = Vec::new();
+            if is_shared_ptr(tcx, *ty) {
+                targets(tcx, *ty, false, &mut fields);
+            } else {
+                for field in adt_def.all_fields() {
+                    targets(tcx, field.ty(tcx, args), false, &mut fields);
+                }
+            }
+            pointees.extend(fields.iter().filter_map(|target| match target {
+                Target::Adt(did, true) => Some(*did),
+                _ => None,
+            }));
+            if adt_def.did().is_local() {
+                owned.entry(adt_def.did()).or_default().extend(fields);
+            }
+        }
+
+        let mut edges: FxHashMap<DefId, FxHashSet<(DefId, bool)>> = FxHashMap::default();
+        for (did, targets) in owned {
+            let succs = edges.entry(did).or_default();
+            for target in targets {
+                match target {
+                    Target::Adt(to, shared) => {
+                        succs.insert((to, shared));
+                    }
+                    Target::Erased => succs.extend(pointees.iter().map(|to| (*to, true))),
+                }
+            }
+        }
+        let mut graph = Self {
+            edges,
+            cyclic: FxHashSet::default(),
+        };
+        graph.cyclic = graph.find_cyclic();
+        rap_debug!("types forming strong cycles: {:?}", graph.cyclic);
+        graph
+    }
+
+    fn reachable(&self, from: DefId) -> FxHashSet<DefId> {
+        let mut reachable = FxHashSet::from_iter([from]);
+        let mut stack = vec![from];
+        while let Some(did) = stack.pop() {
+            for (succ, _) in self.edges.get(&did).into_iter().flatten() {
+                if reachable.insert(*succ) {
+                    stack.push(*succ);
+                }
+            }
+        }
+        reachable
+    }
+
+    /// The types on a cycle through an `Rc` edge `from -> to`, i.e., reaching `from` and reached
+    /// from `to`.
+    fn find_cyclic(&self) -> FxHashSet<DefId> {
+        let reachable: FxHashMap<DefId, FxHashSet<DefId>> = self
+            .edges
+            .keys()
+            .map(|did| (*did, self.reachable(*did)))
+            .collect();
+        let mut cyclic = FxHashSet::default();
+        for (from, succs) in &self.edges {
+            for (to, shared) in succs {
+                let Some(from_to) = reachable.get(to) else {
+                    continue;
+                };
+                if !*shared || !from_to.contains(from) {
+                    continue;
+                }
+                cyclic.extend(
+                    from_to
+                        .iter()
+                        .filter(|did| reachable.get(did).is_some_and(|r| r.contains(from))),
+                );
+            }
+        }
+        cyclic
+    }
+
+    /// Whether the pointee of an `Rc` may be part of a strong cycle.
+    pub fn may_form_cycle<'tcx>(&self, tcx: TyCtxt<'tcx>, pointee: Ty<'tcx>) -> bool {
+        let mut owned = Vec::new();
+        targets(tcx, pointee, false, &mut owned);
+        owned.iter().any(|target| match target {
+            Target::Adt(did, _) => self.cyclic.contains(did),
+            Target::Erased => true,
+        })
+    }
+}
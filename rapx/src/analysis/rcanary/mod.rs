@@ -4,7 +4,9 @@ use crate::analysis::{
     Analysis,
     core::ownedheap_analysis::{OHAResultMap, OwnedHeapAnalysis, default::OwnedHeapAnalyzer},
 };
-use ranalyzer::{FlowAnalysis, IcxSliceFroBlock, IntraFlowContext, MirGraph};
+use ranalyzer::{
+    FlowAnalysis, IcxSliceFroBlock, IntraFlowContext, MirGraph, inter_visitor::OwnershipSummaryMap,
+};
 use rustc_middle::ty::TyCtxt;
 use std::collections::HashMap;
 
@@ -14,6 +16,7 @@ pub struct rCanary<'tcx> {
     tcx: TyCtxt<'tcx>,
     adt_owner: OHAResultMap,
     mir_graph: MirGraph,
+    ownership_summaries: OwnershipSummaryMap,
 }
 
 impl<'tcx> rCanary<'tcx> {
@@ -22,6 +25,7 @@ impl<'tcx> rCanary<'tcx> {
             tcx,
             adt_owner: adt_owner,
             mir_graph: HashMap::default(),
+            ownership_summaries: OwnershipSummaryMap::default(),
         }
     }
 
@@ -53,6 +57,14 @@ impl<'tcx> rCanary<'tcx> {
     pub fn mir_graph_mut(&mut self) -> &mut MirGraph {
         &mut self.mir_graph
     }
+
+    pub fn ownership_summaries(&self) -> &OwnershipSummaryMap {
+        &self.ownership_summaries
+    }
+
+    pub fn ownership_summaries_mut(&mut self) -> &mut OwnershipSummaryMap {
+        &mut self.ownership_summaries
+    }
 }
 
 pub trait Tcx<'tcx, 'o, 'a> {
@@ -10,7 +10,10 @@ use rustc_middle::{
 use rustc_span::def_id::DefId;
 
 use super::{IcxMut, IcxSliceMut, Rcx, RcxMut, rCanary};
-use crate::analysis::core::ownedheap_analysis::{OHAResultMap, OwnedHeap, default::TyWithIndex};
+use crate::{
+    analysis::core::ownedheap_analysis::{OHAResultMap, OwnedHeap, default::TyWithIndex},
+    utils::budget::FnBudget,
+};
 use ownership::{IntraVar, Taint};
 
 use std::{
@@ -107,8 +110,10 @@ impl<'tcx, 'a> FlowAnalysis<'tcx, 'a> {
         // this phase determines the final order of all basic blocks for us to visit
         // Note: we will not visit the clean-up blocks (unwinding)
         self.order();
+        // this phase summarizes the ownership of the arguments of each function bottom-up
+        self.inter_run();
         // this phase will generate the Intra procedural visitor for us to visit the block
-        // note that the inter procedural part is inside in this function but cod in module inter_visitor
+        // note that the summaries of the callees are applied at the call sites
         self.intra_run();
     }
 }
@@ -170,6 +175,9 @@ struct IntraFlowAnalysis<'tcx, 'ctx, 'a> {
     pub graph: &'a Graph,
     taint_flag: bool,
     taint_source: Vec<Terminator<'tcx>>,
+    // whether a leak of the function has been reported at a return
+    leak_reported: bool,
+    budget: FnBudget,
 }
 
 impl<'tcx, 'ctx, 'a> IntraFlowAnalysis<'tcx, 'ctx, 'a> {
@@ -192,6 +200,8 @@ impl<'tcx, 'ctx, 'a> IntraFlowAnalysis<'tcx, 'ctx, 'a> {
             graph,
             taint_flag: false,
             taint_source: Vec::default(),
+            leak_reported: false,
+            budget: FnBudget::new(),
         }
     }
 
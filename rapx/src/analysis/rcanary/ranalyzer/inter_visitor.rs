@@ -1,29 +1,332 @@
+//! The interprocedural phase of rCanary.
+//!
+//! Each function is summarized by what it does with the ownership of its arguments: an owned
+//! argument may be dropped or moved on (consumed), moved into the return value, converted into a
+//! raw pointer returned to the caller, or leaked, i.e., forgotten or converted into a raw pointer
+//! that is never freed. The summaries are computed bottom-up over the call graph, so that the
+//! fate of an argument passed on to a callee follows the summary of the callee. The intra
+//! procedural visitor applies them at the call sites, where the heap of a leaked argument stays
+//! owned by the caller and is reported at the returns.
+
 use super::super::RcxMut;
 use super::FlowAnalysis;
-use rustc_data_structures::graph;
-use rustc_middle::ty::InstanceKind::Item;
+use crate::analysis::{
+    core::{
+        ownership_model::{PathPatterns, model_path},
+        scheduler::{BottomUpScheduler, callee_summaries},
+    },
+    safedrop::provenance::is_pass_through,
+};
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_middle::{
+    mir::{
+        Body, Local, Operand, Place, ProjectionElem, RETURN_PLACE, Rvalue, StatementKind,
+        TerminatorKind,
+    },
+    ty::{GenericArgsRef, Instance, InstanceKind::Item, TyCtxt, TypingEnv},
+};
+use rustc_span::def_id::DefId;
+
+/// What a function does with the ownership of an argument.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParamFate {
+    /// Dropped or moved on, or not owned at all.
+    #[default]
+    Consumed,
+    /// Moved into the return value.
+    Returned,
+    /// Converted into a raw pointer in the return value, which the caller must free.
+    ReturnedRaw,
+    /// Forgotten or converted into a raw pointer that is never freed.
+    Leaked,
+}
+
+/// The fates of the arguments of a function, from the first one.
+pub type OwnershipSummary = Vec<ParamFate>;
+pub type OwnershipSummaryMap = FxHashMap<DefId, OwnershipSummary>;
+
+/// The functions giving up the ownership of their first argument without freeing it.
+const FORGET_APIS: &[&str] = &["core::mem::forget"];
+
+/// The functions converting their owned first argument into a raw pointer.
+const INTO_RAW_APIS: &[&str] = &[
+    "core::boxed::Box::<*>::into_raw",
+    "core::rc::Rc::<*>::into_raw",
+    "core::sync::Arc::<*>::into_raw",
+    "core::ffi::*CString::into_raw",
+];
+
+/// The functions taking back the ownership of the raw pointer in their first argument.
+const FROM_RAW_APIS: &[&str] = &[
+    "core::boxed::Box::<*>::from_raw",
+    "core::rc::Rc::<*>::from_raw",
+    "core::sync::Arc::<*>::from_raw",
+    "core::ffi::*CString::from_raw",
+    "core::vec::Vec::<*>::from_raw_parts",
+    "core::string::String::from_raw_parts",
+];
+
+fn is_forget(path: &str) -> bool {
+    static PATTERNS: PathPatterns<&str> = PathPatterns::new(FORGET_APIS);
+    PATTERNS.matches(path)
+}
+
+fn is_into_raw(path: &str) -> bool {
+    static PATTERNS: PathPatterns<&str> = PathPatterns::new(INTO_RAW_APIS);
+    PATTERNS.matches(path)
+}
+
+fn is_from_raw(path: &str) -> bool {
+    static PATTERNS: PathPatterns<&str> = PathPatterns::new(FROM_RAW_APIS);
+    PATTERNS.matches(path)
+}
+
+/// The summary of a function of the standard library modeled by rCanary, if any.
+pub fn builtin_summary(path: &str) -> Option<OwnershipSummary> {
+    if is_forget(path) {
+        return Some(vec![ParamFate::Leaked]);
+    }
+    if is_into_raw(path) {
+        return Some(vec![ParamFate::ReturnedRaw]);
+    }
+    None
+}
+
+/// The function called, resolved to the implementation if the instance is known.
+pub fn resolve_callee<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    caller: DefId,
+    callee: DefId,
+    generic_args: GenericArgsRef<'tcx>,
+) -> DefId {
+    let typing_env = TypingEnv::post_analysis(tcx, caller);
+    match Instance::try_resolve(tcx, typing_env, callee, generic_args) {
+        Ok(Some(instance)) => instance.def_id(),
+        _ => callee,
+    }
+}
+
+/// The summary of the callee, either modeled or computed for a local function.
+pub fn callee_summary(
+    tcx: TyCtxt<'_>,
+    summaries: &OwnershipSummaryMap,
+    callee: DefId,
+) -> Option<OwnershipSummary> {
+    builtin_summary(&model_path(tcx, callee)).or_else(|| summaries.get(&callee).cloned())
+}
 
 impl<'tcx, 'a> FlowAnalysis<'tcx, 'a> {
     pub fn inter_run(&mut self) {
         let tcx = self.tcx();
-        let mir_keys = tcx.mir_keys(());
-
-        for each_mir in mir_keys {
-            //let sw = Stopwatch::start_new();
-            let def_id = each_mir.to_def_id();
-            let body = tcx.instance_mir(Item(def_id));
-            if graph::is_cyclic(&body.basic_blocks) {
-                continue;
+        let def_ids: Vec<DefId> = tcx
+            .mir_keys(())
+            .iter()
+            .map(|local_def_id| local_def_id.to_def_id())
+            .collect();
+
+        let mut summaries = OwnershipSummaryMap::default();
+        BottomUpScheduler::new(tcx, def_ids).run(&mut summaries, |scc, summaries| {
+            let callees = callee_summaries(tcx, scc, summaries);
+            let new_summaries = scc
+                .iter()
+                .map(|def_id| {
+                    let summary = InterFlowAnalysis::new(tcx, *def_id, &callees).summarize();
+                    rap_debug!("ownership summary of {:?}: {:?}", def_id, summary);
+                    (*def_id, summary)
+                })
+                .collect();
+            (new_summaries, ())
+        });
+        *self.rcx_mut().ownership_summaries_mut() = summaries;
+    }
+}
+
+/// The summary of one function, computed by following the locals that take over the ownership
+/// of each argument, flow-insensitively.
+struct InterFlowAnalysis<'tcx, 'a> {
+    tcx: TyCtxt<'tcx>,
+    def_id: DefId,
+    body: &'tcx Body<'tcx>,
+    summaries: &'a OwnershipSummaryMap,
+}
+
+/// The locals holding the ownership of an argument, either as an owned value or as a raw pointer.
+#[derive(Default)]
+struct Holders {
+    owned: FxHashSet<Local>,
+    raw: FxHashSet<Local>,
+    returned: bool,
+    returned_raw: bool,
+    forgotten: bool,
+    /// A raw pointer is freed, stored somewhere or passed on.
+    raw_released: bool,
+}
+
+impl Holders {
+    fn len(&self) -> usize {
+        self.owned.len() + self.raw.len()
+    }
+
+    /// The value of a holder flows into `to`.
+    fn flow(&mut self, to: &Place<'_>, raw: bool) {
+        if to.is_indirect() {
+            // Stored behind a reference or a pointer, which is out of sight.
+            self.raw_released |= raw;
+            return;
+        }
+        match (to.local == RETURN_PLACE, raw) {
+            (true, false) => self.returned = true,
+            (true, true) => self.returned_raw = true,
+            (false, false) => {
+                self.owned.insert(to.local);
             }
+            (false, true) => {
+                self.raw.insert(to.local);
+            }
+        }
+    }
+
+    fn fate(&self) -> ParamFate {
+        if self.forgotten || !self.raw.is_empty() && !self.raw_released && !self.returned_raw {
+            ParamFate::Leaked
+        } else if self.returned_raw {
+            ParamFate::ReturnedRaw
+        } else if self.returned {
+            ParamFate::Returned
+        } else {
+            ParamFate::Consumed
+        }
+    }
+}
+
+impl<'tcx, 'a> InterFlowAnalysis<'tcx, 'a> {
+    fn new(tcx: TyCtxt<'tcx>, def_id: DefId, summaries: &'a OwnershipSummaryMap) -> Self {
+        Self {
+            tcx,
+            def_id,
+            body: tcx.instance_mir(Item(def_id)),
+            summaries,
+        }
+    }
 
-            let mut cfg = z3::Config::new();
-            cfg.set_model_generation(true);
-            cfg.set_timeout_msec(1000);
+    fn summarize(&self) -> OwnershipSummary {
+        let typing_env = TypingEnv::post_analysis(self.tcx, self.def_id);
+        self.body
+            .args_iter()
+            .map(|arg| {
+                if self.body.local_decls[arg]
+                    .ty
+                    .needs_drop(self.tcx, typing_env)
+                {
+                    self.fate(arg)
+                } else {
+                    ParamFate::Consumed
+                }
+            })
+            .collect()
+    }
 
-            //let ctx = z3::Context::new(&cfg);
-            //let goal = z3::Goal::new(&ctx, true, false, false);
-            //let solver = z3::Solver::new(&ctx);
-            //let inter_visitor = InterFlowAnalysis::new(self.rcx);
+    fn fate(&self, arg: Local) -> ParamFate {
+        let mut holders = Holders::default();
+        holders.owned.insert(arg);
+        loop {
+            let len = holders.len();
+            for data in self.body.basic_blocks.iter() {
+                if data.is_cleanup {
+                    continue;
+                }
+                for stmt in &data.statements {
+                    let StatementKind::Assign(box (place, rvalue)) = &stmt.kind else {
+                        continue;
+                    };
+                    for operand in rvalue_operands(rvalue) {
+                        match holder_of(&holders, operand) {
+                            Some(raw) => holders.flow(place, raw),
+                            None => continue,
+                        }
+                    }
+                }
+                if let TerminatorKind::Call {
+                    func,
+                    args,
+                    destination,
+                    ..
+                } = &data.terminator().kind
+                {
+                    let args: Vec<&Operand<'tcx>> = args.iter().map(|arg| &arg.node).collect();
+                    self.visit_call(&mut holders, func, &args, destination);
+                }
+            }
+            if holders.len() == len {
+                break;
+            }
+        }
+        holders.fate()
+    }
+
+    fn visit_call(
+        &self,
+        holders: &mut Holders,
+        func: &Operand<'tcx>,
+        args: &[&Operand<'tcx>],
+        destination: &Place<'tcx>,
+    ) {
+        let callee = func.const_fn_def();
+        let path = callee.map(|(callee, _)| model_path(self.tcx, callee));
+        let summary = callee.and_then(|(callee, generic_args)| {
+            let callee = resolve_callee(self.tcx, self.def_id, callee, generic_args);
+            callee_summary(self.tcx, self.summaries, callee)
+        });
+        for (index, arg) in args.iter().enumerate() {
+            match (holder_of(holders, arg), arg) {
+                (Some(false), Operand::Move(_)) => {
+                    match summary.as_ref().and_then(|summary| summary.get(index)) {
+                        Some(ParamFate::Leaked) => holders.forgotten = true,
+                        Some(ParamFate::Returned) => holders.flow(destination, false),
+                        Some(ParamFate::ReturnedRaw) => holders.flow(destination, true),
+                        _ if path.as_deref().is_some_and(is_pass_through) => {
+                            holders.flow(destination, false)
+                        }
+                        _ => {}
+                    }
+                }
+                (Some(true), _) => match path.as_deref() {
+                    Some(path) if is_from_raw(path) => {
+                        holders.raw_released = true;
+                        holders.flow(destination, false);
+                    }
+                    Some(path) if is_pass_through(path) => holders.flow(destination, true),
+                    _ => holders.raw_released = true,
+                },
+                _ => {}
+            }
         }
     }
 }
+
+/// Whether the operand moves an owned holder (`false`) or uses a raw pointer holder (`true`).
+fn holder_of(holders: &Holders, operand: &Operand<'_>) -> Option<bool> {
+    let place = operand.place()?;
+    if place
+        .projection
+        .iter()
+        .any(|elem| elem == ProjectionElem::Deref)
+    {
+        return None;
+    }
+    if holders.raw.contains(&place.local) {
+        Some(true)
+    } else if matches!(operand, Operand::Move(_)) && holders.owned.contains(&place.local) {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+fn rvalue_operands<'a, 'tcx>(rvalue: &'a Rvalue<'tcx>) -> Vec<&'a Operand<'tcx>> {
+    match rvalue {
+        Rvalue::Use(operand) | Rvalue::Cast(_, operand, _) => vec![operand],
+        Rvalue::Aggregate(_, operands) => operands.iter().collect(),
+        _ => Vec::new(),
+    }
+}
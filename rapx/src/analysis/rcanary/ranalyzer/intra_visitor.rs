@@ -1,5 +1,4 @@
 use rustc_abi::VariantIdx;
-use rustc_data_structures::graph;
 use rustc_hir::def_id::DefId;
 use rustc_middle::{
     mir::{
@@ -15,12 +14,15 @@ use std::ops::Add;
 use z3::ast::{self, Ast};
 
 use super::super::{IcxMut, IcxSliceMut, Rcx, RcxMut};
+use super::inter_visitor::{OwnershipSummary, ParamFate, callee_summary, resolve_callee};
 use super::is_z3_goal_verbose;
 use super::ownership::IntraVar;
 use super::{FlowAnalysis, IcxSliceFroBlock, IntraFlowAnalysis};
 use crate::{
     analysis::core::ownedheap_analysis::{default::*, *},
     utils::{
+        budget::{self, Exhausted},
+        diagnostics::{Finding, FindingKind},
         log::{
             are_spans_in_same_file, relative_pos_range, span_to_filename, span_to_line_number,
             span_to_source_code,
@@ -49,22 +51,20 @@ impl<'tcx, 'a> FlowAnalysis<'tcx, 'a> {
         for each_mir in mir_keys {
             let def_id = each_mir.to_def_id();
             let body = tcx.instance_mir(Item(def_id));
-            if graph::is_cyclic(&body.basic_blocks) {
-                continue;
-            }
             if format!("{:?}", def_id).contains("syscall_dispatch") {
                 continue;
             }
 
             let mut cfg = z3::Config::new();
             cfg.set_model_generation(true);
-            cfg.set_timeout_msec(1000);
+            cfg.set_timeout_msec(budget::config().solver_timeout);
             let ctx = z3::Context::new(&cfg);
             let goal = z3::Goal::new(&ctx, true, false, false);
             let solver = z3::Solver::new(&ctx);
 
             let mut intra_visitor = IntraFlowAnalysis::new(self.rcx, def_id);
             intra_visitor.visit_body(&ctx, &goal, &solver, body);
+            intra_visitor.budget.record(tcx, "mleak", def_id);
         }
     }
 }
@@ -78,9 +78,41 @@ impl<'tcx, 'ctx, 'a> IntraFlowAnalysis<'tcx, 'ctx, 'a> {
         body: &'tcx Body<'tcx>,
     ) {
         let topo: Vec<usize> = self.graph.get_topo().iter().map(|id| *id).collect();
-        for bidx in topo {
-            let data = &body.basic_blocks[BasicBlock::from(bidx)];
-            self.visit_block_data(ctx, goal, solver, data, bidx);
+        for (pos, bidx) in topo.iter().enumerate() {
+            if self.budget.is_exhausted() {
+                self.collect_taint_without_solver(body, &topo[pos..]);
+                break;
+            }
+            let data = &body.basic_blocks[BasicBlock::from(*bidx)];
+            self.visit_block_data(ctx, goal, solver, data, *bidx);
+        }
+        // The solver cannot tell whether the heaps of the candidates are released at the returns
+        // once the budget is exhausted, so they are taken as unreleased.
+        if self.budget.exhausted().is_some() && self.taint_flag && !self.leak_reported {
+            self.report_leak(false);
+        }
+    }
+
+    /// Collect the leak candidates in the blocks that are not visited within the budget, i.e.,
+    /// the calls taking the ownership of the heap and returning a pointer.
+    fn collect_taint_without_solver(&mut self, body: &'tcx Body<'tcx>, blocks: &[usize]) {
+        for bidx in blocks {
+            let term = body.basic_blocks[BasicBlock::from(*bidx)].terminator();
+            let TerminatorKind::Call {
+                func,
+                args,
+                destination,
+                ..
+            } = &term.kind
+            else {
+                continue;
+            };
+            let fates = self.callee_fates(func);
+            if self.check_fn_source(args, destination)
+                || self.check_fn_returned_raw(args, destination, &fates)
+            {
+                self.add_taint(term.clone());
+            }
         }
     }
 
@@ -1976,6 +2008,63 @@ impl<'tcx, 'ctx, 'a> IntraFlowAnalysis<'tcx, 'ctx, 'a> {
         }
     }
 
+    /// The fates of the args in the summary of the callee, empty if the callee is unknown.
+    pub(crate) fn callee_fates(&self, func: &Operand<'tcx>) -> OwnershipSummary {
+        let Some((callee, generic_args)) = func.const_fn_def() else {
+            return OwnershipSummary::default();
+        };
+        let callee = resolve_callee(self.tcx(), self.def_id, callee, generic_args);
+        callee_summary(self.tcx(), self.rcx.ownership_summaries(), callee).unwrap_or_default()
+    }
+
+    pub(crate) fn check_fn_returned_raw(
+        &mut self,
+        args: &[Spanned<Operand<'tcx>>],
+        dest: &Place<'tcx>,
+        fates: &OwnershipSummary,
+    ) -> bool {
+        let l_place_ty = dest.ty(&self.body.local_decls, self.tcx());
+        if !is_place_containing_ptr(&l_place_ty.ty) {
+            return false;
+        }
+
+        for (arg, fate) in args.iter().zip(fates) {
+            if *fate != ParamFate::ReturnedRaw {
+                continue;
+            }
+            if let Operand::Move(aplace) = arg.node {
+                let a_place_ty = aplace.ty(&self.body.local_decls, self.tcx());
+                let default_layout =
+                    self.extract_default_ty_layout(a_place_ty.ty, a_place_ty.variant_index);
+                if default_layout.is_owned() {
+                    self.taint_flag = true;
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    pub(crate) fn check_fn_leak(
+        &mut self,
+        args: &[Spanned<Operand<'tcx>>],
+        fates: &OwnershipSummary,
+    ) -> bool {
+        for (arg, fate) in args.iter().zip(fates) {
+            if *fate != ParamFate::Leaked {
+                continue;
+            }
+            if let Operand::Move(aplace) = arg.node {
+                let au: usize = aplace.local.as_usize();
+                if self.icx_slice().len()[au] != 0 && self.icx_slice().var()[au].is_init() {
+                    self.taint_flag = true;
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
     pub(crate) fn check_fn_recovery(
         &mut self,
         //args: &Vec<Operand<'tcx>>,
@@ -2069,18 +2158,25 @@ impl<'tcx, 'ctx, 'a> IntraFlowAnalysis<'tcx, 'ctx, 'a> {
         let llocal = dest.local;
         let lu: usize = llocal.as_usize();
 
+        // the summary tells what the callee does with the ownership of the args
+        let fates = self.callee_fates(func);
+
         // the source flag is for fn(self) -> */&
         // we will tag the lvalue as tainted and change the default ctor to modified one
-        let source_flag = self.check_fn_source(args, dest);
+        let source_flag =
+            self.check_fn_source(args, dest) || self.check_fn_returned_raw(args, dest, &fates);
+        // the leak flag is for fn(self) that forgets self or turns it into a pointer never freed
+        // the heap of the arg is still held by the caller
+        let leak_flag = self.check_fn_leak(args, &fates);
         // the recovery flag is for fn(*) -> Self
         // the return value should have the same layout as tainted one
         // we will take the heap of the args if the arg is a pointer
         let recovery_flag = self.check_fn_recovery(args, dest);
-        if source_flag {
+        if source_flag || leak_flag {
             self.add_taint(term);
         }
 
-        for arg in args {
+        for (index, arg) in args.iter().enumerate() {
             match arg.node {
                 Operand::Move(aplace) => {
                     let alocal = aplace.local;
@@ -2134,6 +2230,17 @@ impl<'tcx, 'ctx, 'a> IntraFlowAnalysis<'tcx, 'ctx, 'a> {
                                 solver.assert(&a_ori_non_owing);
                                 solver.assert(&update_a);
 
+                                self.icx_slice_mut().var_mut()[au] = IntraVar::Init(a_new_bv);
+                            } else if fates.get(index) == Some(&ParamFate::Leaked) {
+                                // if the aplace is a instance leaked by callee (move i => still hold)
+                                // the exact constraint is a'=a
+                                let a_name = new_local_name(au, bidx, 0).add("_param_leak");
+                                let a_new_bv = ast::BV::new_const(ctx, a_name, alen as u32);
+                                let update_a = a_new_bv._safe_eq(&a_ori_bv).unwrap();
+
+                                goal.assert(&update_a);
+                                solver.assert(&update_a);
+
                                 self.icx_slice_mut().var_mut()[au] = IntraVar::Init(a_new_bv);
                             } else {
                                 // if the aplace is a instance (move i => drop)
@@ -2455,6 +2562,10 @@ impl<'tcx, 'ctx, 'a> IntraFlowAnalysis<'tcx, 'ctx, 'a> {
 
         let result = solver.check();
         let model = solver.get_model();
+        if result == z3::SatResult::Unknown {
+            self.budget
+                .exhaust(Exhausted::Solver(budget::config().solver_timeout));
+        }
 
         if is_z3_goal_verbose() {
             let g = format!("{}", goal);
@@ -2470,40 +2581,70 @@ impl<'tcx, 'ctx, 'a> IntraFlowAnalysis<'tcx, 'ctx, 'a> {
         // rap_debug!("{}\n", g.color(Color::LightGray).bold());
 
         if result == z3::SatResult::Unsat && self.taint_flag {
-            let fn_name = get_name(self.tcx(), self.def_id)
-                .unwrap_or_else(|| Symbol::intern("no symbol available"));
-
-            rap_warn!("Memory Leak detected in function {:}", fn_name);
-            let source = span_to_source_code(self.body.span);
-            let file = span_to_filename(self.body.span);
-            let mut snippet = Snippet::source(&source)
-                .line_start(span_to_line_number(self.body.span))
-                .origin(&file)
-                .fold(false);
-
-            for source in self.taint_source.iter() {
-                if are_spans_in_same_file(self.body.span, source.source_info.span) {
-                    snippet = snippet.annotation(
-                        Level::Warning
-                            .span(relative_pos_range(self.body.span, source.source_info.span))
-                            .label("Memory Leak Candidates."),
-                    );
-                }
-                // rap_warn!(
-                //     "{}",
-                //     format!(
-                //         "RCanary: LeakItem Candidates: {:?}, {:?}",
-                //         source.kind, source.source_info.span
-                //     )
-                // );
-            }
+            self.report_leak(true);
+        }
+    }
 
-            let message = Level::Warning
-                .title("Memory Leak detected.")
-                .snippet(snippet);
-            let renderer = Renderer::styled();
-            println!("{}", renderer.render(message));
+    /// Report the leak candidates of the function; they are not verified by the solver if the
+    /// budget is exhausted.
+    fn report_leak(&mut self, verified: bool) {
+        let fn_name = get_name(self.tcx(), self.def_id)
+            .unwrap_or_else(|| Symbol::intern("no symbol available"));
+        let label = if verified {
+            format!("Memory Leak detected in function {}", fn_name)
+        } else {
+            format!(
+                "Memory Leak candidates not verified within the budget in function {}",
+                fn_name
+            )
+        };
+        let mut finding = Finding::new(
+            FindingKind::MemoryLeak,
+            self.def_id,
+            "Memory Leak detected.",
+        )
+        .primary(self.body.span, label.as_str());
+        if !verified {
+            finding = finding.confidence(50);
+        }
+        for source in self.taint_source.iter() {
+            finding = finding.related(source.source_info.span, "Memory Leak Candidates.");
+        }
+        self.leak_reported = true;
+        if !finding.emit() {
+            return;
         }
+
+        rap_warn!("{}", label);
+        let source = span_to_source_code(self.body.span);
+        let file = span_to_filename(self.body.span);
+        let mut snippet = Snippet::source(&source)
+            .line_start(span_to_line_number(self.body.span))
+            .origin(&file)
+            .fold(false);
+
+        for source in self.taint_source.iter() {
+            if are_spans_in_same_file(self.body.span, source.source_info.span) {
+                snippet = snippet.annotation(
+                    Level::Warning
+                        .span(relative_pos_range(self.body.span, source.source_info.span))
+                        .label("Memory Leak Candidates."),
+                );
+            }
+            // rap_warn!(
+            //     "{}",
+            //     format!(
+            //         "RCanary: LeakItem Candidates: {:?}, {:?}",
+            //         source.kind, source.source_info.span
+            //     )
+            // );
+        }
+
+        let message = Level::Warning
+            .title("Memory Leak detected.")
+            .snippet(snippet);
+        let renderer = Renderer::styled();
+        println!("{}", renderer.render(message));
     }
 
     pub(crate) fn handle_drop(
@@ -1,10 +1,10 @@
 use rustc_middle::{mir::TerminatorKind, ty::InstanceKind::Item};
 
-use std::collections::BinaryHeap;
+use std::collections::{BinaryHeap, HashSet};
 //use stopwatch::Stopwatch;
 
 use super::super::RcxMut;
-use super::super::ranalyzer::{FlowAnalysis, NodeOrder};
+use super::super::ranalyzer::{Edges, FlowAnalysis, NodeOrder};
 
 impl<'tcx, 'a> FlowAnalysis<'tcx, 'a> {
     pub fn order(&mut self) {
@@ -35,9 +35,13 @@ impl<'tcx, 'a> FlowAnalysis<'tcx, 'a> {
 
 impl<'tcx> NodeOrder<'tcx> {
     /// !Note: this function does not collect the edges that belongs to unwind paths.
+    /// The back edges of the loops are not collected either. Instead, the end of the loop body
+    /// goes to the exits of the loop, so that the body is visited once, as if the loop were
+    /// unrolled once, and its state reaches the exits.
     pub(crate) fn collect_edges(&mut self, lev: &mut Vec<usize>) {
         let bbs = &self.body().basic_blocks;
-        for (block, data) in bbs.iter().enumerate() {
+        let mut edges: Edges = Vec::with_capacity(bbs.len());
+        for data in bbs.iter() {
             let mut result: Vec<usize> = vec![];
             match &data.terminator().kind {
                 TerminatorKind::Goto { target } => result.push(target.as_usize()),
@@ -69,6 +73,28 @@ impl<'tcx> NodeOrder<'tcx> {
                 }
                 TerminatorKind::TailCall { .. } => todo!(),
             }
+            edges.push(result);
+        }
+
+        let back_edges = back_edges(&edges);
+        let mut loop_exits: Vec<(usize, usize)> = Vec::new();
+        for (latch, header) in back_edges.iter() {
+            let body = loop_body(&edges, &back_edges, *latch, *header);
+            for block in (0..edges.len()).filter(|block| body.contains(block)) {
+                for exit in edges[block].iter() {
+                    if !body.contains(exit) {
+                        loop_exits.push((*latch, *exit));
+                    }
+                }
+            }
+        }
+        for (latch, exit) in loop_exits {
+            if !edges[latch].contains(&exit) {
+                edges[latch].push(exit);
+            }
+        }
+        for (block, mut result) in edges.into_iter().enumerate() {
+            result.retain(|next| !back_edges.contains(&(block, *next)));
             // Update the lev for generating topo order.
             for index in result.iter() {
                 lev[*index] = lev[*index] + 1;
@@ -94,3 +120,63 @@ impl<'tcx> NodeOrder<'tcx> {
         }
     }
 }
+
+/// The edges to the blocks on the path of the depth-first search from the entry, i.e., those
+/// closing the loops.
+fn back_edges(edges: &Edges) -> HashSet<(usize, usize)> {
+    let mut back_edges = HashSet::new();
+    let mut visited = vec![false; edges.len()];
+    let mut on_stack = vec![false; edges.len()];
+    // The block and the index of its next successor to visit.
+    let mut stack: Vec<(usize, usize)> = vec![(0, 0)];
+    visited[0] = true;
+    on_stack[0] = true;
+    while let Some((block, next)) = stack.last_mut() {
+        let block = *block;
+        let Some(&succ) = edges[block].get(*next) else {
+            on_stack[block] = false;
+            stack.pop();
+            continue;
+        };
+        *next += 1;
+        if on_stack[succ] {
+            back_edges.insert((block, succ));
+        } else if !visited[succ] {
+            visited[succ] = true;
+            on_stack[succ] = true;
+            stack.push((succ, 0));
+        }
+    }
+    back_edges
+}
+
+/// The blocks of the loop closed by the back edge, i.e., those reaching the latch without
+/// passing the header.
+fn loop_body(
+    edges: &Edges,
+    back_edges: &HashSet<(usize, usize)>,
+    latch: usize,
+    header: usize,
+) -> HashSet<usize> {
+    let mut pre: Edges = vec![Vec::new(); edges.len()];
+    for (block, succs) in edges.iter().enumerate() {
+        for succ in succs {
+            if !back_edges.contains(&(block, *succ)) {
+                pre[*succ].push(block);
+            }
+        }
+    }
+    let mut body = HashSet::from([header, latch]);
+    let mut stack = vec![latch];
+    while let Some(block) = stack.pop() {
+        if block == header {
+            continue;
+        }
+        for pred in &pre[block] {
+            if body.insert(*pred) {
+                stack.push(*pred);
+            }
+        }
+    }
+    body
+}
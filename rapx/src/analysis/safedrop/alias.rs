@@ -5,8 +5,11 @@ use rustc_middle::{
 
 use super::graph::*;
 use crate::analysis::{
-    core::alias_analysis::default::{
-        MopAAFact, MopAAResultMap, assign::*, block::Term, corner_case::*, types::*, value::*,
+    core::{
+        alias_analysis::default::{
+            MopAAFact, MopAAResultMap, assign::*, block::Term, types::*, value::*,
+        },
+        ownership_model,
     },
     utils::fn_info::convert_alias_to_sets,
 };
@@ -92,12 +95,20 @@ impl<'tcx> SafeDropGraph<'tcx> {
                     if self.mop_graph.values[lv].may_drop {
                         may_drop_flag += 1;
                     }
-                    for arg in args {
+                    // The arguments leaked by the callee, e.g., by `mem::forget`, are not used.
+                    let leaked_args = func
+                        .const_fn_def()
+                        .and_then(|(def_id, _)| ownership_model::lookup(self.mop_graph.tcx, def_id))
+                        .map_or(&[][..], |model| model.leaks.as_slice());
+                    for (arg_idx, arg) in args.iter().enumerate() {
+                        let is_used = !leaked_args.contains(&(arg_idx + 1));
                         match arg.node {
                             Operand::Copy(ref p) => {
                                 let rv = self.projection(true, p.clone());
                                 //self.uaf_check(rv, call.source_info.span, p.local.as_usize(), true);
-                                self.uaf_check(bb_index, rv, call.source_info.span, true);
+                                if is_used {
+                                    self.uaf_check(bb_index, rv, call.source_info.span, true);
+                                }
                                 merge_vec.push(rv);
                                 if self.mop_graph.values[rv].may_drop {
                                     may_drop_flag += 1;
@@ -105,7 +116,9 @@ impl<'tcx> SafeDropGraph<'tcx> {
                             }
                             Operand::Move(ref p) => {
                                 let rv = self.projection(true, p.clone());
-                                self.uaf_check(bb_index, rv, call.source_info.span, true);
+                                if is_used {
+                                    self.uaf_check(bb_index, rv, call.source_info.span, true);
+                                }
                                 merge_vec.push(rv);
                                 if self.mop_graph.values[rv].may_drop {
                                     may_drop_flag += 1;
@@ -118,38 +131,43 @@ impl<'tcx> SafeDropGraph<'tcx> {
                     }
                     if let ty::FnDef(target_id, _) = constant.const_.ty().kind() {
                         if may_drop_flag > 1 {
-                            // This function does not introduce new aliases.
-
-                            if is_corner_case(*target_id) {
+                            // The modeled functions only introduce the aliases in the model.
+                            if let Some(model) =
+                                ownership_model::lookup(self.mop_graph.tcx, *target_id)
+                            {
+                                for assign in model.alias_facts() {
+                                    self.merge(&assign, &merge_vec);
+                                }
                                 return;
                             }
-                            if self.mop_graph.tcx.is_mir_available(*target_id) {
-                                if fn_map.contains_key(&target_id) {
-                                    let assignments = fn_map.get(&target_id).unwrap();
-                                    for assign in assignments.aliases().iter() {
-                                        if !assign.valuable() {
-                                            continue;
-                                        }
-                                        self.merge(assign, &merge_vec);
+                            // The summaries of dependencies are available without MIR.
+                            if let Some(assignments) = fn_map.get(target_id) {
+                                for assign in assignments.aliases().iter() {
+                                    if !assign.valuable() {
+                                        continue;
                                     }
+                                    self.merge(assign, &merge_vec);
                                 }
-                            } else {
-                                if self.mop_graph.values[lv].may_drop {
-                                    let mut right_set = Vec::new();
-                                    for rv in &merge_vec {
-                                        if self.mop_graph.values[*rv].may_drop
-                                            && lv != *rv
-                                            && self.mop_graph.values[lv].is_ptr()
-                                        {
-                                            right_set.push(*rv);
-                                        }
-                                    }
-                                    if right_set.len() == 1 {
-                                        self.merge_alias(lv, right_set[0], 0);
+                            } else if !self.mop_graph.tcx.is_mir_available(*target_id)
+                                && self.mop_graph.values[lv].may_drop
+                            {
+                                let mut right_set = Vec::new();
+                                for rv in &merge_vec {
+                                    if self.mop_graph.values[*rv].may_drop
+                                        && lv != *rv
+                                        && self.mop_graph.values[lv].is_ptr()
+                                    {
+                                        right_set.push(*rv);
                                     }
                                 }
+                                if right_set.len() == 1 {
+                                    self.merge_alias(lv, right_set[0], 0);
+                                }
                             }
                         }
+                        if let Some(summary) = self.drop_summaries.get(target_id).cloned() {
+                            self.apply_drop_summary(bb_index, &summary, args, &call.source_info);
+                        }
                     }
                 }
             }
@@ -233,15 +251,7 @@ impl<'tcx> SafeDropGraph<'tcx> {
         }
         self.mop_graph.union_merge(lv, rv);
 
-        let max_field_depth = match std::env::var_os("SAFEDROP") {
-            Some(val) if val == "0" => 10,
-            Some(val) if val == "1" => 20,
-            Some(val) if val == "2" => 30,
-            Some(val) if val == "3" => 50,
-            _ => 15,
-        };
-
-        if depth > max_field_depth {
+        if depth > self.mop_graph.config.field_depth {
             return;
         }
 
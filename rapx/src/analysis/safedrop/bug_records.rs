@@ -2,11 +2,20 @@ use annotate_snippets::{Level, Renderer, Snippet};
 use rustc_data_structures::fx::FxHashMap;
 use rustc_span::{Span, symbol::Symbol};
 
-use crate::utils::log::{
-    are_spans_in_same_file, get_basic_block_span, get_variable_name, relative_pos_range,
-    span_to_filename, span_to_line_number, span_to_source_code,
+use crate::utils::{
+    diagnostics::{Finding, FindingKind},
+    log::{
+        are_spans_in_same_file, get_basic_block_span, get_variable_name, relative_pos_range,
+        span_to_filename, span_to_line_number, span_to_source_code,
+    },
+    source::{get_filename, get_name},
 };
-use rustc_middle::mir::{Body, HasLocalDecls, Local};
+use rustc_hir::def_id::DefId;
+use rustc_middle::{
+    mir::{BasicBlock, Body, HasLocalDecls, Local},
+    ty::TyCtxt,
+};
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug)]
 pub struct TyBug {
@@ -18,6 +27,80 @@ pub struct TyBug {
     pub confidence: usize,
 }
 
+impl TyBug {
+    /// Replace the span by the statement or terminator that it belongs to, which stays the same
+    /// while the function is unchanged, unlike the span.
+    fn to_cached(&self, body: &Body<'_>) -> CachedBug {
+        let location = (self.span != body.span)
+            .then(|| {
+                body.basic_blocks.iter_enumerated().find_map(|(bb, data)| {
+                    data.statements
+                        .iter()
+                        .map(|statement| statement.source_info.span)
+                        .chain(std::iter::once(data.terminator().source_info.span))
+                        .position(|span| span == self.span)
+                        .map(|index| (bb.as_usize(), index))
+                })
+            })
+            .flatten();
+        CachedBug {
+            drop_bb: self.drop_bb,
+            drop_id: self.drop_id,
+            trigger_bb: self.trigger_bb,
+            trigger_id: self.trigger_id,
+            location,
+            confidence: self.confidence,
+        }
+    }
+
+    fn from_cached(cached: &CachedBug, body: &Body<'_>) -> Self {
+        let span = cached
+            .location
+            .and_then(|(bb, index)| {
+                let data = body.basic_blocks.get(BasicBlock::from_usize(bb))?;
+                Some(match data.statements.get(index) {
+                    Some(statement) => statement.source_info.span,
+                    None => data.terminator().source_info.span,
+                })
+            })
+            .unwrap_or(body.span);
+        TyBug {
+            drop_bb: cached.drop_bb,
+            drop_id: cached.drop_id,
+            trigger_bb: cached.trigger_bb,
+            trigger_id: cached.trigger_id,
+            span,
+            confidence: cached.confidence,
+        }
+    }
+}
+
+/// A bug cached by `-cache`, located by the MIR statement instead of the span.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedBug {
+    pub drop_bb: usize,
+    pub drop_id: usize,
+    pub trigger_bb: usize,
+    pub trigger_id: usize,
+    /// The basic block and the statement index, where the terminator follows the statements;
+    /// the whole function if None.
+    pub location: Option<(usize, usize)>,
+    pub confidence: usize,
+}
+
+/// The bugs of a function cached by `-cache`, which are reported again without checking the
+/// function if it is unchanged.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CachedBugRecords {
+    pub df_bugs: Vec<(usize, CachedBug)>,
+    pub df_bugs_unwind: Vec<(usize, CachedBug)>,
+    pub uaf_bugs: Vec<(usize, CachedBug)>,
+    pub dp_bugs: Vec<(usize, CachedBug)>,
+    pub dp_bugs_unwind: Vec<(usize, CachedBug)>,
+    pub invalid_free_bugs: Vec<(usize, CachedBug)>,
+    pub invalid_free_causes: Vec<(usize, String)>,
+}
+
 /*
  * For each bug in the HashMap, the key is local of the value.
  */
@@ -28,6 +111,10 @@ pub struct BugRecords {
     pub uaf_bugs: FxHashMap<usize, TyBug>,
     pub dp_bugs: FxHashMap<usize, TyBug>,
     pub dp_bugs_unwind: FxHashMap<usize, TyBug>,
+    /// The freed pointer is the drop id, and its allocation is the trigger.
+    pub invalid_free_bugs: FxHashMap<usize, TyBug>,
+    /// Why each freed pointer cannot be freed by the API, e.g., the API that it comes from.
+    pub invalid_free_causes: FxHashMap<usize, String>,
 }
 
 impl BugRecords {
@@ -38,20 +125,76 @@ impl BugRecords {
             uaf_bugs: FxHashMap::default(),
             dp_bugs: FxHashMap::default(),
             dp_bugs_unwind: FxHashMap::default(),
+            invalid_free_bugs: FxHashMap::default(),
+            invalid_free_causes: FxHashMap::default(),
+        }
+    }
+
+    pub fn to_cached(&self, body: &Body<'_>) -> CachedBugRecords {
+        let to_cached = |bugs: &FxHashMap<usize, TyBug>| {
+            bugs.iter()
+                .map(|(local, bug)| (*local, bug.to_cached(body)))
+                .collect()
+        };
+        CachedBugRecords {
+            df_bugs: to_cached(&self.df_bugs),
+            df_bugs_unwind: to_cached(&self.df_bugs_unwind),
+            uaf_bugs: to_cached(&self.uaf_bugs),
+            dp_bugs: to_cached(&self.dp_bugs),
+            dp_bugs_unwind: to_cached(&self.dp_bugs_unwind),
+            invalid_free_bugs: to_cached(&self.invalid_free_bugs),
+            invalid_free_causes: self
+                .invalid_free_causes
+                .iter()
+                .map(|(local, cause)| (*local, cause.clone()))
+                .collect(),
+        }
+    }
+
+    pub fn from_cached(cached: &CachedBugRecords, body: &Body<'_>) -> BugRecords {
+        let from_cached = |bugs: &[(usize, CachedBug)]| {
+            bugs.iter()
+                .map(|(local, bug)| (*local, TyBug::from_cached(bug, body)))
+                .collect()
+        };
+        BugRecords {
+            df_bugs: from_cached(&cached.df_bugs),
+            df_bugs_unwind: from_cached(&cached.df_bugs_unwind),
+            uaf_bugs: from_cached(&cached.uaf_bugs),
+            dp_bugs: from_cached(&cached.dp_bugs),
+            dp_bugs_unwind: from_cached(&cached.dp_bugs_unwind),
+            invalid_free_bugs: from_cached(&cached.invalid_free_bugs),
+            invalid_free_causes: cached.invalid_free_causes.iter().cloned().collect(),
         }
     }
 
+    /// Report the bugs of the function, except those in the dependencies under `.cargo`.
+    pub fn report(&self, tcx: TyCtxt<'_>, def_id: DefId) {
+        if self.is_bug_free()
+            || get_filename(tcx, def_id).is_some_and(|filename| filename.contains(".cargo"))
+        {
+            return;
+        }
+        let fn_name = get_name(tcx, def_id).unwrap_or(Symbol::intern("no symbol available"));
+        let body = tcx.optimized_mir(def_id);
+        self.df_bugs_output(body, fn_name, body.span);
+        self.uaf_bugs_output(body, fn_name, body.span);
+        self.dp_bug_output(body, fn_name, body.span);
+        self.invalid_free_output(body, fn_name, body.span);
+    }
+
     pub fn is_bug_free(&self) -> bool {
         self.df_bugs.is_empty()
             && self.df_bugs_unwind.is_empty()
             && self.uaf_bugs.is_empty()
             && self.dp_bugs.is_empty()
             && self.dp_bugs_unwind.is_empty()
+            && self.invalid_free_bugs.is_empty()
     }
 
     pub fn df_bugs_output<'tcx>(&self, body: &Body<'tcx>, fn_name: Symbol, span: Span) {
         self.emit_bug_reports(
-            body, &self.df_bugs, fn_name, span,
+            body, &self.df_bugs, FindingKind::DoubleFree, fn_name, span,
             "Double free detected",
             "Double free detected.",
             |bug, drop_name, trigger_name, drop_bb_str, trigger_bb_str| {
@@ -68,7 +211,7 @@ impl BugRecords {
         );
 
         self.emit_bug_reports(
-            body, &self.df_bugs_unwind, fn_name, span,
+            body, &self.df_bugs_unwind, FindingKind::DoubleFree, fn_name, span,
             "Double free detected",
             "Double free detected during unwinding.",
             |bug, drop_name, trigger_name, drop_bb_str, trigger_bb_str| {
@@ -87,7 +230,7 @@ impl BugRecords {
 
     pub fn uaf_bugs_output<'tcx>(&self, body: &Body<'tcx>, fn_name: Symbol, span: Span) {
         self.emit_bug_reports(
-            body, &self.uaf_bugs, fn_name, span,
+            body, &self.uaf_bugs, FindingKind::UseAfterFree, fn_name, span,
             "Use-after-free detected",
             "Use-after-free detected.",
             |bug, drop_name, trigger_name, drop_bb_str, trigger_bb_str| {
@@ -106,7 +249,7 @@ impl BugRecords {
 
     pub fn dp_bug_output<'tcx>(&self, body: &Body<'tcx>, fn_name: Symbol, span: Span) {
         self.emit_bug_reports(
-            body, &self.dp_bugs, fn_name, span,
+            body, &self.dp_bugs, FindingKind::DanglingPointer, fn_name, span,
             "Dangling pointer detected",
             "Dangling pointer detected.",
             |bug, drop_name, trigger_name, drop_bb_str, _trigger_bb_str| {
@@ -123,7 +266,7 @@ impl BugRecords {
         );
 
         self.emit_bug_reports(
-            body, &self.dp_bugs_unwind, fn_name, span,
+            body, &self.dp_bugs_unwind, FindingKind::DanglingPointer, fn_name, span,
             "Dangling pointer detected during unwinding",
             "Dangling pointer detected during unwinding.",
             |bug, drop_name, trigger_name, drop_bb_str, _trigger_bb_str| {
@@ -140,10 +283,31 @@ impl BugRecords {
         );
     }
 
+    pub fn invalid_free_output<'tcx>(&self, body: &Body<'tcx>, fn_name: Symbol, span: Span) {
+        self.emit_bug_reports(
+            body, &self.invalid_free_bugs, FindingKind::InvalidFree, fn_name, span,
+            "Invalid free detected",
+            "Invalid free detected.",
+            |bug, drop_name, trigger_name, drop_bb_str, trigger_bb_str| {
+                format!(
+                    "Invalid free (confidence {}%): Location in file {} line {}.\n    | MIR detail: {} {}.\n    | MIR detail: {} is allocated at {}; {} is freed at {}.",
+                    bug.confidence,
+                    span_to_filename(bug.span),
+                    span_to_line_number(bug.span),
+                    drop_name,
+                    self.invalid_free_causes.get(&bug.drop_id).map_or("is freed by an incompatible API", String::as_str),
+                    trigger_name, trigger_bb_str,
+                    drop_name, drop_bb_str
+                )
+            }
+        );
+    }
+
     fn emit_bug_reports<'tcx, F>(
         &self,
         body: &Body<'tcx>,
         bugs: &FxHashMap<usize, TyBug>,
+        kind: FindingKind,
         fn_name: Symbol,
         span: Span,
         log_msg: &str,
@@ -152,7 +316,71 @@ impl BugRecords {
     ) where
         F: Fn(&TyBug, &str, &str, &str, &str) -> String,
     {
-        if bugs.is_empty() {
+        let format_debug_info = |id: usize| -> String {
+            if id >= body.local_decls().len() {
+                return format!("UNKNWON(_{}) in {}", id, fn_name.as_str());
+            }
+            let local = Local::from_usize(id);
+            let name_opt = get_variable_name(body, id);
+            let decl_span = body.local_decls[local].source_info.span;
+            let location = format!(
+                "{}:{}",
+                span_to_filename(decl_span),
+                span_to_line_number(decl_span)
+            );
+            match name_opt {
+                Some(name) => format!("_{}({}, {})", id, name, location),
+                None => format!("_{}(_, {})", id, location),
+            }
+        };
+
+        let format_bb_info = |bb_id: usize| -> String {
+            let bb_span = get_basic_block_span(body, bb_id);
+            let location = format!(
+                "{}:{}",
+                span_to_filename(bb_span),
+                span_to_line_number(bb_span)
+            );
+            format!("BB{}({})", bb_id, location)
+        };
+
+        // Suppressed findings and those recorded in the baseline are not reported.
+        let mut reports = Vec::new();
+        for bug in bugs.values() {
+            if !are_spans_in_same_file(span, bug.span) {
+                continue;
+            }
+            let drop_name = format_debug_info(bug.drop_id);
+            let trigger_name = format_debug_info(bug.trigger_id);
+            let drop_bb_str = format_bb_info(bug.drop_bb);
+            let trigger_bb_str = format_bb_info(bug.trigger_bb);
+
+            let detail = detail_formatter(
+                bug,
+                &drop_name,
+                &trigger_name,
+                &drop_bb_str,
+                &trigger_bb_str,
+            );
+
+            let is_new = Finding::new(kind, body.source.def_id(), title)
+                .confidence(bug.confidence)
+                .primary(bug.span, detail.as_str())
+                .related(
+                    get_basic_block_span(body, bug.drop_bb),
+                    format!("{} is dropped here", drop_name),
+                )
+                .related(
+                    get_basic_block_span(body, bug.trigger_bb),
+                    format!("{} triggers the bug here", trigger_name),
+                )
+                .emit();
+            if is_new {
+                reports.push((bug, detail));
+            }
+        }
+
+        if reports.is_empty() {
             return;
         }
 
@@ -162,64 +390,21 @@ impl BugRecords {
         let filename = span_to_filename(span);
         let renderer = Renderer::styled();
 
-        for bug in bugs.values() {
-            if are_spans_in_same_file(span, bug.span) {
-                let format_debug_info = |id: usize| -> String {
-                    if id >= body.local_decls().len() {
-                        return format!("UNKNWON(_{}) in {}", id, fn_name.as_str());
-                    }
-                    let local = Local::from_usize(id);
-                    let name_opt = get_variable_name(body, id);
-                    let decl_span = body.local_decls[local].source_info.span;
-                    let location = format!(
-                        "{}:{}",
-                        span_to_filename(decl_span),
-                        span_to_line_number(decl_span)
-                    );
-                    match name_opt {
-                        Some(name) => format!("_{}({}, {})", id, name, location),
-                        None => format!("_{}(_, {})", id, location),
-                    }
-                };
-
-                let format_bb_info = |bb_id: usize| -> String {
-                    let bb_span = get_basic_block_span(body, bb_id);
-                    let location = format!(
-                        "{}:{}",
-                        span_to_filename(bb_span),
-                        span_to_line_number(bb_span)
-                    );
-                    format!("BB{}({})", bb_id, location)
-                };
-
-                let drop_name = format_debug_info(bug.drop_id);
-                let trigger_name = format_debug_info(bug.trigger_id);
-                let drop_bb_str = format_bb_info(bug.drop_bb);
-                let trigger_bb_str = format_bb_info(bug.trigger_bb);
-
-                let detail = detail_formatter(
-                    bug,
-                    &drop_name,
-                    &trigger_name,
-                    &drop_bb_str,
-                    &trigger_bb_str,
-                );
-
-                let mut snippet = Snippet::source(&code_source)
-                    .line_start(span_to_line_number(span))
-                    .origin(&filename)
-                    .fold(false);
-
-                snippet = snippet.annotation(
-                    Level::Warning
-                        .span(relative_pos_range(span, bug.span))
-                        .label(&detail),
-                );
-
-                let message = Level::Warning.title(title).snippet(snippet);
-
-                println!("{}", renderer.render(message));
-            }
+        for (bug, detail) in reports {
+            let mut snippet = Snippet::source(&code_source)
+                .line_start(span_to_line_number(span))
+                .origin(&filename)
+                .fold(false);
+
+            snippet = snippet.annotation(
+                Level::Warning
+                    .span(relative_pos_range(span, bug.span))
+                    .label(&detail),
+            );
+
+            let message = Level::Warning.title(title).snippet(snippet);
+
+            println!("{}", renderer.render(message));
         }
     }
 }
@@ -1,13 +1,16 @@
 use super::{bug_records::TyBug, graph::*};
 use crate::{
     analysis::{
-        core::alias_analysis::default::{types::TyKind, value::*},
+        core::{
+            alias_analysis::default::{types::TyKind, value::*},
+            ownership_model,
+        },
         utils::fn_info::{convert_alias_to_sets, generate_mir_cfg_dot},
     },
     utils::source::*,
 };
 use rustc_middle::mir::SourceInfo;
-use rustc_span::{Span, symbol::Symbol};
+use rustc_span::Span;
 
 impl<'tcx> SafeDropGraph<'tcx> {
     pub fn report_bugs(&self) {
@@ -28,17 +31,8 @@ impl<'tcx> SafeDropGraph<'tcx> {
         if self.bug_records.is_bug_free() {
             return;
         }
-        let fn_name = match get_name(self.mop_graph.tcx, self.mop_graph.def_id) {
-            Some(name) => name,
-            None => Symbol::intern("no symbol available"),
-        };
-        let body = self.mop_graph.tcx.optimized_mir(self.mop_graph.def_id);
-        self.bug_records
-            .df_bugs_output(body, fn_name, self.mop_graph.span);
-        self.bug_records
-            .uaf_bugs_output(body, fn_name, self.mop_graph.span);
         self.bug_records
-            .dp_bug_output(body, fn_name, self.mop_graph.span);
+            .report(self.mop_graph.tcx, self.mop_graph.def_id);
         let _ = generate_mir_cfg_dot(
             self.mop_graph.tcx,
             self.mop_graph.def_id,
@@ -96,13 +90,19 @@ impl<'tcx> SafeDropGraph<'tcx> {
             confidence,
         };
         rap_debug!("Find use-after-free bug {:?}; add to records", bug);
-        if self.bug_records.uaf_bugs.contains_key(&local) {
+        if self.bug_records.uaf_bugs.contains_key(&local) || !self.is_on_feasible_path() {
             return;
         }
         self.bug_records.uaf_bugs.insert(local, bug);
         rap_debug!("Find use-after-free bug {:?}; add to records", local);
     }
 
+    /// Whether the conditions of the current path may hold, so that the bugs found on it are real.
+    fn is_on_feasible_path(&mut self) -> bool {
+        let path = self.mop_graph.path.clone();
+        self.mop_graph.is_feasible(&path)
+    }
+
     pub fn sync_drop_record(&mut self, idx: usize) {
         if idx >= self.mop_graph.values.len() {
             return;
@@ -136,6 +136,10 @@ impl<'tcx> SafeDropGraph<'tcx> {
         if self.mop_graph.values[idx].is_alive() {
             return false;
         }
+        // The value is still taken as dropped, so that it is not dropped again.
+        if !self.is_on_feasible_path() {
+            return true;
+        }
         let confidence = match self.mop_graph.values[idx].kind {
             TyKind::CornerCase => 0,
             _ => 99,
@@ -167,9 +171,18 @@ impl<'tcx> SafeDropGraph<'tcx> {
     }
 
     pub fn dp_check(&mut self, flag_cleanup: bool) {
+        // The arguments that the function is modeled to free, e.g., by `Drop::drop`, are expected
+        // to dangle after it returns.
+        let freed_args = ownership_model::freed_args(self.mop_graph.tcx, self.mop_graph.def_id);
+        if !self.is_on_feasible_path() {
+            return;
+        }
         if flag_cleanup {
             for arg_idx in 1..self.mop_graph.arg_size + 1 {
-                if self.mop_graph.values[arg_idx].is_ptr() && self.drop_record[arg_idx].is_dropped {
+                if self.mop_graph.values[arg_idx].is_ptr()
+                    && self.drop_record[arg_idx].is_dropped
+                    && !freed_args.contains(&arg_idx)
+                {
                     let confidence = match self.mop_graph.values[arg_idx].kind {
                         TyKind::CornerCase => 0,
                         _ => 99,
@@ -209,6 +222,7 @@ impl<'tcx> SafeDropGraph<'tcx> {
                 for arg_idx in 0..self.mop_graph.arg_size + 1 {
                     if self.mop_graph.values[arg_idx].is_ptr()
                         && self.drop_record[arg_idx].is_dropped
+                        && !freed_args.contains(&arg_idx)
                     {
                         let confidence = match self.mop_graph.values[arg_idx].kind {
                             TyKind::CornerCase => 0,
@@ -1,4 +1,4 @@
-use super::bug_records::*;
+use super::{bug_records::*, summary::DropSummaryMap};
 use crate::analysis::core::{
     alias_analysis::default::graph::MopGraph, ownedheap_analysis::OHAResultMap,
 };
@@ -39,6 +39,10 @@ pub struct SafeDropGraph<'tcx> {
     pub drop_record: Vec<DropRecord>,
     // analysis of heap item
     pub adt_owner: OHAResultMap,
+    // the drop summaries of the callees
+    pub drop_summaries: DropSummaryMap,
+    // the arguments dropped on all the paths to return that have been visited
+    pub dropped_args: Option<Vec<usize>>,
 }
 
 impl<'tcx> SafeDropGraph<'tcx> {
@@ -54,6 +58,8 @@ impl<'tcx> SafeDropGraph<'tcx> {
             bug_records: BugRecords::new(),
             drop_record,
             adt_owner,
+            drop_summaries: DropSummaryMap::default(),
+            dropped_args: None,
         }
     }
 }
@@ -1,72 +1,217 @@
 pub mod alias;
 pub mod bug_records;
 pub mod check_bugs;
-pub mod corner_case;
 pub mod graph;
+pub mod provenance;
 pub mod safedrop;
+pub mod summary;
 
+use rustc_data_structures::fx::FxHashMap;
 use rustc_hir::def_id::DefId;
 use rustc_middle::ty::TyCtxt;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
 
 use crate::analysis::{
     core::{
-        alias_analysis::default::{AliasAnalyzer, MopAAResultMap},
+        alias_analysis::{
+            AliasBackend,
+            andersen::AndersenAnalyzer,
+            default::{AliasAnalyzer, MopAAResultMap, MopConfig},
+        },
         ownedheap_analysis::{OHAResultMap, OwnedHeapAnalysis, default::OwnedHeapAnalyzer},
+        ownership_model,
+        scheduler::{BottomUpScheduler, callee_summaries},
+        summary_cache::SummaryCache,
     },
     graphs::scc::Scc,
 };
+use crate::utils::budget;
+use bug_records::{BugRecords, CachedBugRecords};
 use graph::SafeDropGraph;
-use safedrop::*;
+use summary::{DropSummary, DropSummaryMap};
 
 use super::Analysis;
 
+/// The results of a function cached by `-cache`.
+#[derive(Serialize, Deserialize)]
+struct CachedFn {
+    dropped_args: Vec<usize>,
+    bugs: CachedBugRecords,
+}
+
 pub struct SafeDrop<'tcx> {
     pub tcx: TyCtxt<'tcx>,
+    /// The thresholds used for both the alias summaries and the detection.
+    pub config: MopConfig,
+    /// The folder of the summary cache, if enabled.
+    pub cache_dir: Option<PathBuf>,
+    /// The alias analysis that summarizes the local functions.
+    pub alias_backend: AliasBackend,
 }
 
 impl<'tcx> SafeDrop<'tcx> {
-    pub fn new(tcx: TyCtxt<'tcx>) -> Self {
-        Self { tcx }
+    pub fn new(tcx: TyCtxt<'tcx>, config: MopConfig) -> Self {
+        Self {
+            tcx,
+            config,
+            cache_dir: None,
+            alias_backend: AliasBackend::default(),
+        }
+    }
+
+    /// Summarize the aliases of the local functions with the backend.
+    pub fn with_alias_backend(mut self, alias_backend: AliasBackend) -> Self {
+        self.alias_backend = alias_backend;
+        self
+    }
+
+    /// Reuse the summaries of the unchanged functions cached in the folder.
+    pub fn with_cache(mut self, cache_dir: Option<&Path>) -> Self {
+        self.cache_dir = cache_dir.map(Path::to_path_buf);
+        self
     }
+
     pub fn start(&self) {
-        let mut mop = AliasAnalyzer::new(self.tcx);
-        mop.run();
-        let fn_map = mop.get_all_fn_alias_raw();
+        self.analyze(true);
+    }
+
+    /// Only persist the summaries of the crate for the crates depending on it.
+    pub fn summarize(&self) {
+        self.analyze(false);
+    }
+
+    fn analyze(&self, report: bool) {
+        let (dep_fn_map, mut drop_map) = summary::load(self.tcx);
+        let fn_map = match self.alias_backend {
+            AliasBackend::Mop => {
+                let mut mop = AliasAnalyzer::with_config(self.tcx, self.config)
+                    .with_cache(self.cache_dir.as_deref());
+                mop.fn_map.extend(dep_fn_map);
+                mop.run();
+                mop.get_all_fn_alias_raw()
+            }
+            AliasBackend::Andersen => {
+                let mut andersen = AndersenAnalyzer::with_config(self.tcx, self.config);
+                andersen.run();
+                let mut fn_map = dep_fn_map;
+                fn_map.extend(andersen.get_all_fn_alias_raw());
+                fn_map
+            }
+        };
 
         let mut heap = OwnedHeapAnalyzer::new(self.tcx);
         heap.run();
         let adt_owner = heap.get_all_items();
 
-        let mir_keys = self.tcx.mir_keys(());
-        for local_def_id in mir_keys {
-            query_safedrop(
+        // The bugs of the unchanged functions are reported from the cache.
+        let mut cache = self.cache_dir.as_ref().map(|dir| {
+            SummaryCache::<CachedFn>::load(
                 self.tcx,
-                &fn_map,
-                local_def_id.to_def_id(),
-                adt_owner.clone(),
-            );
+                dir,
+                "safedrop",
+                &(
+                    self.config,
+                    budget::config(),
+                    self.alias_backend,
+                    ownership_model::user_entries(),
+                ),
+            )
+        });
+        let mut to_check = Vec::new();
+        for local_def_id in self.tcx.mir_keys(()) {
+            let def_id = local_def_id.to_def_id();
+            match cache.as_ref().and_then(|cache| cache.get(def_id)) {
+                Some(cached) => {
+                    if !cached.dropped_args.is_empty() {
+                        let dropped_args = cached.dropped_args.clone();
+                        drop_map.insert(def_id, DropSummary { dropped_args });
+                    }
+                    if report {
+                        let body = self.tcx.optimized_mir(def_id);
+                        BugRecords::from_cached(&cached.bugs, body).report(self.tcx, def_id);
+                    }
+                }
+                None => to_check.push(def_id),
+            }
         }
+        // Callees are checked before their callers, so that their drop summaries are available.
+        let (tcx, config) = (self.tcx, self.config);
+        let buggy_graphs = BottomUpScheduler::new(tcx, to_check.iter().copied()).run(
+            &mut drop_map,
+            |scc, drop_map| {
+                let mut drop_map = callee_summaries(tcx, scc, drop_map);
+                let mut buggy_graphs = Vec::new();
+                for def_id in scc {
+                    let Some(graph) =
+                        query_safedrop(tcx, &fn_map, &mut drop_map, *def_id, &adt_owner, config)
+                    else {
+                        continue;
+                    };
+                    if let Some(drop_summary) = graph.drop_summary() {
+                        drop_map.insert(*def_id, drop_summary);
+                    }
+                    // The bugs on the paths visited within the budget are still reported.
+                    graph.mop_graph.budget.record(tcx, "uaf", *def_id);
+                    // The bugs are reported in the order of the components afterwards, and cached
+                    // even if they are not reported.
+                    if !graph.bug_records.is_bug_free() {
+                        buggy_graphs.push(graph);
+                    }
+                }
+                (drop_map.into_iter().collect(), buggy_graphs)
+            },
+        );
+        let mut bugs = FxHashMap::default();
+        for graph in buggy_graphs.into_iter().flatten() {
+            if report {
+                graph.report_bugs();
+            }
+            if cache.is_some() {
+                let body = self.tcx.optimized_mir(graph.mop_graph.def_id);
+                bugs.insert(graph.mop_graph.def_id, graph.bug_records.to_cached(body));
+            }
+        }
+        if let Some(cache) = &mut cache {
+            for def_id in to_check {
+                let dropped_args = drop_map.get(&def_id).map(|s| s.dropped_args.clone());
+                let cached = CachedFn {
+                    dropped_args: dropped_args.unwrap_or_default(),
+                    bugs: bugs.remove(&def_id).unwrap_or_default(),
+                };
+                cache.insert(def_id, cached);
+            }
+            cache.store();
+        }
+        summary::store(self.tcx, &fn_map, &drop_map);
     }
 }
 
-pub fn query_safedrop(
-    tcx: TyCtxt,
+/// Check the function for the bugs and its drop summary; None if it has no MIR to check.
+pub fn query_safedrop<'tcx>(
+    tcx: TyCtxt<'tcx>,
     fn_map: &MopAAResultMap,
+    drop_map: &mut DropSummaryMap,
     def_id: DefId,
-    adt_owner: OHAResultMap,
-) {
+    adt_owner: &OHAResultMap,
+    config: MopConfig,
+) -> Option<SafeDropGraph<'tcx>> {
     /* filter const mir */
     if let Some(_other) = tcx.hir_body_const_context(def_id.expect_local()) {
-        return;
+        return None;
     }
-    if tcx.is_mir_available(def_id) {
-        let mut safedrop_graph = SafeDropGraph::new(tcx, def_id, adt_owner);
-        rap_debug!("safedrop grah (raw): {}", safedrop_graph);
-        safedrop_graph.mop_graph.find_scc();
-        rap_debug!("safedrop graph (scc): {}", safedrop_graph);
-        safedrop_graph.check(0, fn_map);
-        if safedrop_graph.mop_graph.visit_times <= VISIT_LIMIT {
-            safedrop_graph.report_bugs();
-        }
+    if !tcx.is_mir_available(def_id) {
+        return None;
     }
+    let mut safedrop_graph = SafeDropGraph::new(tcx, def_id, adt_owner.clone());
+    safedrop_graph.mop_graph.config = config;
+    // Lend the summaries to the graph instead of cloning them for every function.
+    safedrop_graph.drop_summaries = std::mem::take(drop_map);
+    rap_debug!("safedrop grah (raw): {}", safedrop_graph);
+    safedrop_graph.mop_graph.find_scc();
+    rap_debug!("safedrop graph (scc): {}", safedrop_graph);
+    safedrop_graph.check(0, fn_map);
+    safedrop_graph.invalid_free_check();
+    *drop_map = std::mem::take(&mut safedrop_graph.drop_summaries);
+    Some(safedrop_graph)
 }
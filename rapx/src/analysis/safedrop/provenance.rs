@@ -0,0 +1,471 @@
+//! The allocation provenance of raw pointers, used to detect invalid frees.
+//!
+//! The tracker records which allocator API produced the memory that each local points to, e.g.,
+//! `Vec::as_mut_ptr` or `alloc::alloc` with a `Layout` of 16 bytes, and follows the pointer through
+//! copies, casts and unwrapping within the function. A release of the pointer through an API that
+//! does not own such memory, e.g., `Box::from_raw` on the buffer of a `Vec`, or `dealloc` with a
+//! layout different from the one used in `alloc`, is reported as an invalid free.
+
+use super::{bug_records::TyBug, graph::SafeDropGraph};
+use crate::analysis::core::ownership_model::{PathPatterns, model_path};
+use rustc_data_structures::fx::FxHashMap;
+use rustc_middle::{
+    mir::{Body, Operand, Place, ProjectionElem, Rvalue, StatementKind, TerminatorKind},
+    ty::{GenericArgsRef, TyCtxt, TypingEnv},
+};
+use rustc_span::def_id::DefId;
+use std::fmt;
+
+/// The owner of the memory that a pointer points to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Allocator {
+    /// The memory of a `Box`.
+    Box,
+    /// The buffer of a `Vec` or `String`.
+    Vec,
+    /// The buffer of a `CString`.
+    CString,
+    /// The memory allocated by `alloc::alloc` and its variants.
+    Global,
+    /// A local variable on the stack.
+    Stack,
+    /// The memory borrowed from its owner, e.g., by `CStr::as_ptr`, which must not be freed
+    /// through the pointer.
+    Borrowed,
+}
+
+/// The size and alignment of a `Layout` whose value is known.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemLayout {
+    pub size: u64,
+    pub align: u64,
+}
+
+impl fmt::Display for MemLayout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "size {} and align {}", self.size, self.align)
+    }
+}
+
+/// Where the memory that a local points to comes from.
+#[derive(Debug, Clone, Copy)]
+pub struct Origin {
+    pub allocator: Allocator,
+    pub layout: Option<MemLayout>,
+    /// The API that produced the pointer.
+    pub api: &'static str,
+    pub bb: usize,
+    pub local: usize,
+}
+
+/// An API that produces or releases pointers of an allocator.
+struct AllocApi {
+    path: &'static str,
+    name: &'static str,
+    allocator: Allocator,
+    /// The argument (from 0) giving the layout of the memory, if any.
+    layout_arg: Option<usize>,
+}
+
+const fn api(path: &'static str, name: &'static str, allocator: Allocator) -> AllocApi {
+    AllocApi {
+        path,
+        name,
+        allocator,
+        layout_arg: None,
+    }
+}
+
+const fn api_with_layout(
+    path: &'static str,
+    name: &'static str,
+    allocator: Allocator,
+    layout_arg: usize,
+) -> AllocApi {
+    AllocApi {
+        path,
+        name,
+        allocator,
+        layout_arg: Some(layout_arg),
+    }
+}
+
+/// The functions whose return values point to the memory of an allocator.
+const PRODUCERS: &[AllocApi] = &[
+    api(
+        "core::boxed::Box::<*>::into_raw",
+        "Box::into_raw",
+        Allocator::Box,
+    ),
+    api("core::boxed::Box::<*>::leak", "Box::leak", Allocator::Box),
+    api(
+        "core::vec::Vec::<*>::as_mut_ptr",
+        "Vec::as_mut_ptr",
+        Allocator::Vec,
+    ),
+    api("core::vec::Vec::<*>::as_ptr", "Vec::as_ptr", Allocator::Vec),
+    api("core::vec::Vec::<*>::leak", "Vec::leak", Allocator::Vec),
+    api(
+        "core::string::String::as_mut_ptr",
+        "String::as_mut_ptr",
+        Allocator::Vec,
+    ),
+    api(
+        "core::ffi::*CString::into_raw",
+        "CString::into_raw",
+        Allocator::CString,
+    ),
+    api(
+        "core::ffi::*CStr::as_ptr",
+        "CStr::as_ptr",
+        Allocator::Borrowed,
+    ),
+    api(
+        "core::ffi::*CString::as_ptr",
+        "CString::as_ptr",
+        Allocator::Borrowed,
+    ),
+    api_with_layout("core::alloc::alloc", "alloc", Allocator::Global, 0),
+    api_with_layout(
+        "core::alloc::alloc_zeroed",
+        "alloc_zeroed",
+        Allocator::Global,
+        0,
+    ),
+];
+
+/// The functions that free their first argument, which must point to the memory of the
+/// allocator.
+const RELEASERS: &[AllocApi] = &[
+    api(
+        "core::boxed::Box::<*>::from_raw",
+        "Box::from_raw",
+        Allocator::Box,
+    ),
+    api(
+        "core::vec::Vec::<*>::from_raw_parts",
+        "Vec::from_raw_parts",
+        Allocator::Vec,
+    ),
+    api(
+        "core::string::String::from_raw_parts",
+        "String::from_raw_parts",
+        Allocator::Vec,
+    ),
+    api(
+        "core::ffi::*CString::from_raw",
+        "CString::from_raw",
+        Allocator::CString,
+    ),
+    api_with_layout("core::alloc::dealloc", "dealloc", Allocator::Global, 1),
+    api_with_layout("core::alloc::realloc", "realloc", Allocator::Global, 1),
+];
+
+/// The functions whose return value carries the provenance or the layout of the first argument.
+const PASS_THROUGH: &[&str] = &[
+    "core::option::Option::<*>::unwrap",
+    "core::option::Option::<*>::expect",
+    "core::result::Result::<*>::unwrap",
+    "core::result::Result::<*>::expect",
+    "core::ptr::NonNull::<*>::as_ptr",
+    "core::ptr::NonNull::<*>::new",
+    "core::ptr::NonNull::<*>::new_unchecked",
+    "core::ptr::NonNull::<*>::cast",
+    "core::ptr::mut_ptr::<impl *mut *>::cast",
+    "core::ptr::const_ptr::<impl *const *>::cast",
+];
+
+static PRODUCER_APIS: PathPatterns<AllocApi> = PathPatterns::with_key(PRODUCERS, |api| api.path);
+static RELEASER_APIS: PathPatterns<AllocApi> = PathPatterns::with_key(RELEASERS, |api| api.path);
+static PASS_THROUGH_APIS: PathPatterns<&str> = PathPatterns::new(PASS_THROUGH);
+
+/// Whether the function returns its first argument unchanged as far as the memory it points to
+/// is concerned, e.g., `Option::unwrap` and `NonNull::as_ptr`.
+pub fn is_pass_through(path: &str) -> bool {
+    PASS_THROUGH_APIS.matches(path)
+}
+
+/// The provenance of the pointers and the values of the layouts in a function.
+pub struct ProvenanceTracker<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    typing_env: TypingEnv<'tcx>,
+    body: &'tcx Body<'tcx>,
+    pub origins: FxHashMap<usize, Origin>,
+    pub layouts: FxHashMap<usize, MemLayout>,
+}
+
+impl<'tcx> ProvenanceTracker<'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>, def_id: DefId) -> Self {
+        Self {
+            tcx,
+            typing_env: TypingEnv::post_analysis(tcx, def_id),
+            body: tcx.optimized_mir(def_id),
+            origins: FxHashMap::default(),
+            layouts: FxHashMap::default(),
+        }
+    }
+
+    /// Propagate the provenance until no local gets a new one. The first provenance found for a
+    /// local is kept, so that the propagation terminates on loops.
+    pub fn run(&mut self) {
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (bb, data) in self.body.basic_blocks.iter_enumerated() {
+                for stmt in &data.statements {
+                    if let StatementKind::Assign(box (place, rvalue)) = &stmt.kind
+                        && let Some(lv) = place.as_local()
+                    {
+                        changed |= self.assign(bb.as_usize(), lv.as_usize(), rvalue);
+                    }
+                }
+                if let TerminatorKind::Call {
+                    func,
+                    args,
+                    destination,
+                    ..
+                } = &data.terminator().kind
+                    && let Some((callee, generic_args)) = func.const_fn_def()
+                    && let Some(lv) = destination.as_local()
+                {
+                    let args: Vec<&Operand<'tcx>> = args.iter().map(|arg| &arg.node).collect();
+                    changed |= self.call(bb.as_usize(), lv.as_usize(), callee, generic_args, &args);
+                }
+            }
+        }
+    }
+
+    /// The releases of pointers through an API that does not own their memory, with the cause.
+    pub fn invalid_frees(&self) -> Vec<(TyBug, String)> {
+        let mut bugs = Vec::new();
+        for (bb, data) in self.body.basic_blocks.iter_enumerated() {
+            let terminator = data.terminator();
+            let TerminatorKind::Call { func, args, .. } = &terminator.kind else {
+                continue;
+            };
+            let Some((callee, _)) = func.const_fn_def() else {
+                continue;
+            };
+            let path = model_path(self.tcx, callee);
+            let Some(releaser) = RELEASER_APIS.find(&path) else {
+                continue;
+            };
+            let Some(ptr) = args.first().and_then(|arg| operand_local(&arg.node)) else {
+                continue;
+            };
+            let Some(origin) = self.origins.get(&ptr) else {
+                continue;
+            };
+            let layout = releaser
+                .layout_arg
+                .and_then(|arg| args.get(arg))
+                .and_then(|arg| operand_local(&arg.node))
+                .and_then(|local| self.layouts.get(&local));
+            let Some(cause) = mismatch(origin, releaser, layout) else {
+                continue;
+            };
+            let bug = TyBug {
+                drop_bb: bb.as_usize(),
+                drop_id: ptr,
+                trigger_bb: origin.bb,
+                trigger_id: origin.local,
+                span: terminator.source_info.span,
+                confidence: 99,
+            };
+            bugs.push((bug, cause));
+        }
+        bugs
+    }
+
+    fn assign(&mut self, bb: usize, lv: usize, rvalue: &Rvalue<'tcx>) -> bool {
+        match rvalue {
+            Rvalue::Use(op) | Rvalue::Cast(_, op, _) => match operand_local(op) {
+                Some(rv) => self.copy(lv, rv),
+                None => false,
+            },
+            Rvalue::Ref(_, _, place) | Rvalue::RawPtr(_, place) => {
+                if place.projection.is_empty() {
+                    let origin = Origin {
+                        allocator: Allocator::Stack,
+                        layout: None,
+                        api: "a local variable",
+                        bb,
+                        local: place.local.as_usize(),
+                    };
+                    self.insert_origin(lv, origin)
+                } else if is_deref(place) {
+                    // A reborrow of the pointee, e.g., `&mut *ptr`.
+                    self.copy(lv, place.local.as_usize())
+                } else {
+                    false
+                }
+            }
+            _ => false,
+        }
+    }
+
+    fn call(
+        &mut self,
+        bb: usize,
+        lv: usize,
+        callee: DefId,
+        generic_args: GenericArgsRef<'tcx>,
+        args: &[&Operand<'tcx>],
+    ) -> bool {
+        let path = model_path(self.tcx, callee);
+        if let Some(producer) = PRODUCER_APIS.find(&path) {
+            let layout = producer
+                .layout_arg
+                .and_then(|arg| args.get(arg))
+                .and_then(|arg| operand_local(arg))
+                .and_then(|local| self.layouts.get(&local).copied());
+            let origin = Origin {
+                allocator: producer.allocator,
+                layout,
+                api: producer.name,
+                bb,
+                local: lv,
+            };
+            return self.insert_origin(lv, origin);
+        }
+        if is_pass_through(&path) {
+            return match args.first().and_then(|arg| operand_local(arg)) {
+                Some(rv) => self.copy(lv, rv),
+                None => false,
+            };
+        }
+        match self.layout_of_call(&path, generic_args, args) {
+            Some(layout) if !self.layouts.contains_key(&lv) => {
+                self.layouts.insert(lv, layout);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// The value of the layout built by a constructor of `Layout` with known arguments.
+    fn layout_of_call(
+        &self,
+        path: &str,
+        generic_args: GenericArgsRef<'tcx>,
+        args: &[&Operand<'tcx>],
+    ) -> Option<MemLayout> {
+        let name = path.strip_prefix("core::alloc::")?;
+        let name = name.strip_prefix("layout::").unwrap_or(name);
+        match name {
+            "Layout::new" => self.layout_of_type(generic_args),
+            "Layout::array" => {
+                let elem = self.layout_of_type(generic_args)?;
+                let len = self.const_usize(args.first()?)?;
+                Some(MemLayout {
+                    size: elem.size.checked_mul(len)?,
+                    align: elem.align,
+                })
+            }
+            "Layout::from_size_align" | "Layout::from_size_align_unchecked" => Some(MemLayout {
+                size: self.const_usize(args.first()?)?,
+                align: self.const_usize(args.get(1)?)?,
+            }),
+            _ => None,
+        }
+    }
+
+    fn layout_of_type(&self, generic_args: GenericArgsRef<'tcx>) -> Option<MemLayout> {
+        let ty = generic_args.types().next()?;
+        let layout = self
+            .tcx
+            .layout_of(self.typing_env.as_query_input(ty))
+            .ok()?;
+        Some(MemLayout {
+            size: layout.size.bytes(),
+            align: layout.align.abi.bytes(),
+        })
+    }
+
+    fn const_usize(&self, op: &Operand<'tcx>) -> Option<u64> {
+        op.constant()?
+            .const_
+            .try_eval_target_usize(self.tcx, self.typing_env)
+    }
+
+    fn copy(&mut self, lv: usize, rv: usize) -> bool {
+        let mut changed = false;
+        if let Some(origin) = self.origins.get(&rv).copied() {
+            changed |= self.insert_origin(lv, origin);
+        }
+        if let Some(layout) = self.layouts.get(&rv).copied()
+            && !self.layouts.contains_key(&lv)
+        {
+            self.layouts.insert(lv, layout);
+            changed = true;
+        }
+        changed
+    }
+
+    fn insert_origin(&mut self, lv: usize, origin: Origin) -> bool {
+        if self.origins.contains_key(&lv) {
+            return false;
+        }
+        self.origins.insert(lv, origin);
+        true
+    }
+}
+
+/// Why the memory of the origin cannot be released by the API; None if it can or if it is
+/// unknown.
+fn mismatch(origin: &Origin, releaser: &AllocApi, layout: Option<&MemLayout>) -> Option<String> {
+    let compatible = match (releaser.allocator, origin.allocator) {
+        (_, Allocator::Stack | Allocator::Borrowed) => false,
+        (Allocator::CString, allocator) => allocator == Allocator::CString,
+        // The memory of `Box` and `Vec` is allocated by the global allocator.
+        (Allocator::Global, Allocator::Box | Allocator::Vec) => true,
+        (Allocator::Global, Allocator::Global) => {
+            if let (Some(alloc_layout), Some(free_layout)) = (origin.layout, layout)
+                && alloc_layout != *free_layout
+            {
+                return Some(format!(
+                    "comes from `{}` with {} but is freed by `{}` with {}",
+                    origin.api, alloc_layout, releaser.name, free_layout
+                ));
+            }
+            true
+        }
+        (expected, allocator) => allocator == expected || allocator == Allocator::Global,
+    };
+    if compatible {
+        None
+    } else {
+        Some(format!(
+            "comes from `{}` but is freed by `{}`",
+            origin.api, releaser.name
+        ))
+    }
+}
+
+fn operand_local(op: &Operand<'_>) -> Option<usize> {
+    match op {
+        Operand::Copy(place) | Operand::Move(place) => {
+            place.as_local().map(|local| local.as_usize())
+        }
+        _ => None,
+    }
+}
+
+fn is_deref(place: &Place<'_>) -> bool {
+    matches!(place.projection.as_slice(), [ProjectionElem::Deref])
+}
+
+impl<'tcx> SafeDropGraph<'tcx> {
+    /// Check whether the pointers freed in the function come from a compatible allocation.
+    pub fn invalid_free_check(&mut self) {
+        let mut tracker = ProvenanceTracker::new(self.mop_graph.tcx, self.mop_graph.def_id);
+        tracker.run();
+        for (bug, cause) in tracker.invalid_frees() {
+            rap_debug!("Find invalid free {:?}: {}", bug, cause);
+            self.bug_records
+                .invalid_free_causes
+                .insert(bug.drop_id, cause);
+            self.bug_records.invalid_free_bugs.insert(bug.drop_id, bug);
+        }
+    }
+}
@@ -1,5 +1,8 @@
-use super::{corner_case::*, graph::*};
-use crate::analysis::core::alias_analysis::default::{MopAAResultMap, block::Term};
+use super::graph::*;
+use crate::analysis::core::{
+    alias_analysis::default::{MopAAResultMap, block::Term},
+    ownership_model,
+};
 use rustc_data_structures::fx::{FxHashMap, FxHashSet};
 use rustc_middle::{
     mir::{
@@ -10,8 +13,6 @@ use rustc_middle::{
 };
 use std::vec;
 
-pub const VISIT_LIMIT: usize = 1000;
-
 impl<'tcx> SafeDropGraph<'tcx> {
     // analyze the drop statement and update the liveness for nodes.
     pub fn drop_check(&mut self, bb_idx: usize) {
@@ -37,20 +38,28 @@ impl<'tcx> SafeDropGraph<'tcx> {
                     self.add_to_drop_record(local, local, birth, &info, false, bb_idx, is_cleanup);
                 }
                 TerminatorKind::Call {
-                    func: _, ref args, ..
+                    ref func, ref args, ..
                 } => {
-                    if args.len() > 0 {
+                    let Some((target_id, _)) = func.const_fn_def() else {
+                        return;
+                    };
+                    let tcx = self.mop_graph.tcx;
+                    // The arguments freed by the callee according to the ownership model.
+                    for arg in ownership_model::freed_args(tcx, target_id) {
+                        let Some(arg) = arg.checked_sub(1).and_then(|arg| args.get(arg)) else {
+                            continue;
+                        };
                         let birth = self.mop_graph.blocks[bb_idx].scc.enter;
-                        let place = match args[0].node {
+                        let place = match arg.node {
                             Operand::Copy(place) => place,
                             Operand::Move(place) => place,
                             _ => {
-                                rap_error!("Constant operand exists: {:?}", args[0]);
-                                return;
+                                rap_error!("Constant operand exists: {:?}", arg);
+                                continue;
                             }
                         };
                         if !self.drop_heap_item_check(&place) {
-                            return;
+                            continue;
                         }
                         let local = self.projection(false, place.clone());
                         let info = drop.source_info.clone();
@@ -127,7 +136,7 @@ impl<'tcx> SafeDropGraph<'tcx> {
     // the core function of the safedrop.
     pub fn check(&mut self, bb_idx: usize, fn_map: &MopAAResultMap) {
         self.mop_graph.visit_times += 1;
-        if self.mop_graph.visit_times > VISIT_LIMIT {
+        if self.mop_graph.is_over_budget() {
             return;
         }
         let scc_idx = self.mop_graph.blocks[bb_idx].scc.enter;
@@ -139,6 +148,8 @@ impl<'tcx> SafeDropGraph<'tcx> {
             cur_block.scc.clone(),
         );
 
+        self.mop_graph.path.push(scc_idx);
+
         if bb_idx == scc_idx && !cur_block.scc.nodes.is_empty() {
             rap_debug!("check {:?} as a scc", bb_idx);
             self.check_scc(bb_idx, fn_map);
@@ -146,6 +157,7 @@ impl<'tcx> SafeDropGraph<'tcx> {
             self.check_single_node(bb_idx, fn_map);
             self.handle_nexts(bb_idx, fn_map, None, None);
         }
+        self.mop_graph.path.pop();
     }
 
     pub fn check_scc(&mut self, bb_idx: usize, fn_map: &MopAAResultMap) {
@@ -217,9 +229,8 @@ impl<'tcx> SafeDropGraph<'tcx> {
         // For dangling pointer check;
         // Since a node within an SCC cannot be an exit, we only check for non-scc nodes;
         if cur_block.next.is_empty() {
-            if should_check(self.mop_graph.def_id) {
-                self.dp_check(cur_block.is_cleanup);
-            }
+            self.dp_check(cur_block.is_cleanup);
+            self.record_dropped_args(bb_idx);
         }
     }
 
@@ -344,7 +355,7 @@ impl<'tcx> SafeDropGraph<'tcx> {
             // Other cases in switchInt terminators
             if let Some(targets) = sw_targets {
                 for iter in targets.iter() {
-                    if self.mop_graph.visit_times > VISIT_LIMIT {
+                    if self.mop_graph.is_over_budget() {
                         continue;
                     }
                     let next = iter.1.as_usize();
@@ -366,7 +377,7 @@ impl<'tcx> SafeDropGraph<'tcx> {
                 self.split_check_with_cond(next_idx, path_discr_id, path_discr_val, fn_map);
             } else {
                 for next in &cur_block.next {
-                    if self.mop_graph.visit_times > VISIT_LIMIT {
+                    if self.mop_graph.is_over_budget() {
                         continue;
                     }
 
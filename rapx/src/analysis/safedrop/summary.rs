@@ -0,0 +1,224 @@
+//! Alias and drop summaries persisted per crate, so that the functions of dependencies are not
+//! treated as black boxes when their MIR is not available.
+//!
+//! When use-after-free detection is enabled, `cargo rapx` compiles the dependencies with
+//! `rapx -summary`, which writes `rapx-summaries/{crate}-{stable crate id}.json` into the output
+//! folder of rustc, e.g., `target/debug/deps`. The crates depending on them load the summaries
+//! from their own output folder and match the functions by `DefPathHash`, which is stable across
+//! compilation sessions.
+
+use super::graph::SafeDropGraph;
+use crate::{
+    analysis::core::alias_analysis::default::{MopAAResult, MopAAResultJson, MopAAResultMap},
+    utils::{
+        fs::rap_write_atomically,
+        json::{def_id_of_hash_pair, def_path_hash_pair},
+    },
+};
+use rustc_data_structures::fx::FxHashMap;
+use rustc_hir::def_id::{CrateNum, DefId, LOCAL_CRATE};
+use rustc_middle::{
+    mir::{BasicBlock, Operand, SourceInfo, TerminatorKind},
+    ty::TyCtxt,
+};
+use rustc_span::source_map::Spanned;
+use serde::{Deserialize, Serialize};
+use std::{fs, io, path::PathBuf};
+
+/// Bump this version whenever the layout of the summary file is changed incompatibly.
+pub const SUMMARY_VERSION: u32 = 1;
+
+/// The arguments (numbered from 1) that are dropped on every path to the return of a function,
+/// e.g., the raw pointer passed to a function that reconstructs and drops the box.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DropSummary {
+    pub dropped_args: Vec<usize>,
+}
+
+pub type DropSummaryMap = FxHashMap<DefId, DropSummary>;
+
+impl<'tcx> SafeDropGraph<'tcx> {
+    /// Intersect the arguments dropped at the exit block with those of the other paths, if the
+    /// block returns normally.
+    pub fn record_dropped_args(&mut self, bb_idx: usize) {
+        let body = self.mop_graph.tcx.optimized_mir(self.mop_graph.def_id);
+        if !matches!(
+            body.basic_blocks[BasicBlock::from_usize(bb_idx)]
+                .terminator()
+                .kind,
+            TerminatorKind::Return
+        ) {
+            return;
+        }
+        let dropped: Vec<usize> = (1..self.mop_graph.arg_size + 1)
+            .filter(|arg| self.mop_graph.values[*arg].may_drop && self.drop_record[*arg].is_dropped)
+            .collect();
+        self.dropped_args = Some(match self.dropped_args.take() {
+            Some(prev) => prev
+                .into_iter()
+                .filter(|arg| dropped.contains(arg))
+                .collect(),
+            None => dropped,
+        });
+    }
+
+    /// The summary is unavailable if no argument is dropped or not all the paths are visited.
+    pub fn drop_summary(&self) -> Option<DropSummary> {
+        if self.mop_graph.budget.exhausted().is_some() {
+            return None;
+        }
+        match &self.dropped_args {
+            Some(dropped_args) if !dropped_args.is_empty() => Some(DropSummary {
+                dropped_args: dropped_args.clone(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Mark the arguments dropped by the callee as dropped, together with their aliases.
+    pub fn apply_drop_summary(
+        &mut self,
+        bb_idx: usize,
+        summary: &DropSummary,
+        args: &[Spanned<Operand<'tcx>>],
+        info: &SourceInfo,
+    ) {
+        let birth = self.mop_graph.blocks[bb_idx].scc.enter;
+        let is_cleanup = self.mop_graph.blocks[bb_idx].is_cleanup;
+        for arg in &summary.dropped_args {
+            let Some(place) = arg
+                .checked_sub(1)
+                .and_then(|arg| args.get(arg))
+                .and_then(|arg| arg.node.place())
+            else {
+                continue;
+            };
+            let idx = self.projection(true, place);
+            if !self.mop_graph.values[idx].may_drop {
+                continue;
+            }
+            self.add_to_drop_record(idx, idx, birth, info, false, bb_idx, is_cleanup);
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CrateSummary {
+    version: u32,
+    #[serde(rename = "crate")]
+    crate_name: String,
+    stable_crate_id: u64,
+    functions: Vec<FnSummary>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct FnSummary {
+    def_path_hash: (u64, u64),
+    /// Only for human readers; the function is identified by `def_path_hash`.
+    function: String,
+    #[serde(flatten)]
+    aliases: MopAAResultJson,
+    dropped_args: Vec<usize>,
+}
+
+fn summary_path(tcx: TyCtxt<'_>, cnum: CrateNum) -> PathBuf {
+    // The output folder given by cargo, e.g., `target/debug/deps`, is shared by all the crates.
+    tcx.sess
+        .io
+        .output_dir
+        .clone()
+        .unwrap_or_default()
+        .join("rapx-summaries")
+        .join(format!(
+            "{}-{:016x}.json",
+            tcx.crate_name(cnum),
+            tcx.stable_crate_id(cnum).as_u64()
+        ))
+}
+
+/// Write the summaries of the local functions for the crates depending on this one.
+pub fn store(tcx: TyCtxt<'_>, fn_map: &MopAAResultMap, drop_map: &DropSummaryMap) {
+    let mut functions: Vec<FnSummary> = fn_map
+        .iter()
+        .filter(|(def_id, _)| def_id.is_local())
+        .map(|(def_id, result)| {
+            let mut aliases = MopAAResultJson::from(result);
+            aliases
+                .aliases
+                .retain(|alias| alias.lhs_may_drop && alias.rhs_may_drop);
+            FnSummary {
+                def_path_hash: def_path_hash_pair(tcx, *def_id),
+                function: tcx.def_path_str(*def_id),
+                aliases,
+                dropped_args: drop_map
+                    .get(def_id)
+                    .map(|summary| summary.dropped_args.clone())
+                    .unwrap_or_default(),
+            }
+        })
+        .collect();
+    functions.sort_by(|a, b| a.function.cmp(&b.function));
+    let summary = CrateSummary {
+        version: SUMMARY_VERSION,
+        crate_name: tcx.crate_name(LOCAL_CRATE).to_string(),
+        stable_crate_id: tcx.stable_crate_id(LOCAL_CRATE).as_u64(),
+        functions,
+    };
+    let path = summary_path(tcx, LOCAL_CRATE);
+    match serde_json::to_vec(&summary)
+        .map_err(io::Error::from)
+        .and_then(|content| rap_write_atomically(&path, &content))
+    {
+        Ok(()) => rap_debug!("Write the summaries to {}", path.display()),
+        Err(err) => rap_warn!(
+            "Failed to write the summaries to {}: {}",
+            path.display(),
+            err
+        ),
+    }
+}
+
+/// Load the summaries of the dependencies that have been compiled by `rapx -summary`; the
+/// dependencies without summaries are handled as before.
+pub fn load(tcx: TyCtxt<'_>) -> (MopAAResultMap, DropSummaryMap) {
+    let mut fn_map = MopAAResultMap::default();
+    let mut drop_map = DropSummaryMap::default();
+    for &cnum in tcx.crates(()) {
+        let path = summary_path(tcx, cnum);
+        let Ok(content) = fs::read(&path) else {
+            continue;
+        };
+        let summary: CrateSummary = match serde_json::from_slice(&content) {
+            Ok(summary) => summary,
+            Err(err) => {
+                rap_warn!("Failed to read the summaries {}: {}", path.display(), err);
+                continue;
+            }
+        };
+        if summary.version != SUMMARY_VERSION
+            || summary.stable_crate_id != tcx.stable_crate_id(cnum).as_u64()
+        {
+            rap_debug!("Skip the outdated summaries {}", path.display());
+            continue;
+        }
+        for function in summary.functions {
+            let Some(def_id) = def_id_of_hash_pair(tcx, function.def_path_hash)
+                .filter(|def_id| def_id.krate == cnum)
+            else {
+                continue;
+            };
+            let result = MopAAResult::from(function.aliases);
+            fn_map.insert(def_id, result);
+            if !function.dropped_args.is_empty() {
+                drop_map.insert(
+                    def_id,
+                    DropSummary {
+                        dropped_args: function.dropped_args,
+                    },
+                );
+            }
+        }
+        rap_debug!("Load the summaries of crate {}", summary.crate_name);
+    }
+    (fn_map, drop_map)
+}
@@ -0,0 +1,54 @@
+pub mod variance;
+
+use crate::utils::{
+    diagnostics::{Finding, FindingKind},
+    log::{span_to_filename, span_to_line_number},
+};
+use rustc_middle::ty::TyCtxt;
+use variance::{VarianceAnalysis, VarianceBug};
+
+/// Detect the `unsafe impl Send/Sync` whose generic parameters lack the `Send` or `Sync` bounds
+/// required by how the type holds and hands out their values.
+pub struct SendSyncCheck<'tcx> {
+    pub tcx: TyCtxt<'tcx>,
+}
+
+impl<'tcx> SendSyncCheck<'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>) -> Self {
+        Self { tcx }
+    }
+
+    pub fn start(&self) {
+        let mut analysis = VarianceAnalysis::new(self.tcx);
+        analysis.run();
+        for bug in &analysis.bugs {
+            self.report(bug);
+        }
+    }
+
+    fn report(&self, bug: &VarianceBug) {
+        let impl_span = self.tcx.def_span(bug.impl_def_id);
+        let header = format!("unsafe impl {} for {}", bug.auto_trait, bug.self_ty);
+        for missing in &bug.missing {
+            let detail = format!(
+                "Send/Sync variance: Location in file {} line {}.\n    | MIR detail: `{}` lacks the bound `{}: {}`; {}.",
+                span_to_filename(impl_span),
+                span_to_line_number(impl_span),
+                header,
+                missing.param,
+                missing.bound,
+                missing.reason,
+            );
+            Finding::new(
+                FindingKind::SendSyncVariance,
+                bug.impl_def_id,
+                "Send/Sync variance bug detected.",
+            )
+            .location(format!("{}: {}", missing.param, missing.bound))
+            .primary(impl_span, detail.as_str())
+            .related(missing.span, "the bound is required here")
+            .subject(format!("`{}`", header))
+            .report(impl_span);
+        }
+    }
+}
@@ -0,0 +1,402 @@
+//! The bounds required by the generic parameters of an `unsafe impl Send/Sync`.
+//!
+//! The requirement of a parameter follows how the type uses it. A value owned by a field, e.g.,
+//! `T`, `Box<T>` or `PhantomData<T>`, requires `T: Send` to send the type and `T: Sync` to share
+//! it, like the auto traits do; `&T` requires `T: Sync` in both cases, and `UnsafeCell<T>`
+//! requires `T: Send` to share the type since `T` may be moved out through `&self`, e.g., by a
+//! lock. Raw pointers, e.g., `*mut T` or `NonNull<T>`, require nothing by themselves, so the
+//! methods of the type are inspected instead: moving `T` into or out of the type requires
+//! `T: Send` to send it, and moving `T` out of or returning `&T` from `&self` requires `T: Send`
+//! or `T: Sync` to share it.
+
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_hir::def::DefKind;
+use rustc_infer::traits::util::elaborate;
+use rustc_middle::ty::{self, AdtDef, GenericArgs, Ty, TyCtxt};
+use rustc_span::{Span, def_id::DefId, sym};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AutoTrait {
+    Send,
+    Sync,
+}
+
+impl fmt::Display for AutoTrait {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AutoTrait::Send => write!(f, "Send"),
+            AutoTrait::Sync => write!(f, "Sync"),
+        }
+    }
+}
+
+/// How a type holds the values of a generic parameter `T`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Usage {
+    /// `T` itself, or inside an owning type, e.g., `Box<T>` or `PhantomData<T>`.
+    Owned,
+    /// `&T`.
+    Shared,
+    /// `&mut T`.
+    Mut,
+    /// `UnsafeCell<T>`, which is mutable through a shared reference.
+    Cell,
+    /// `*const T`, `*mut T`, `NonNull<T>` or `AtomicPtr<T>`.
+    Raw,
+}
+
+impl Usage {
+    fn behind_ref(self, mutbl: ty::Mutability) -> Usage {
+        match (self, mutbl) {
+            (Usage::Raw, _) => Usage::Raw,
+            (_, ty::Mutability::Not) => Usage::Shared,
+            (Usage::Owned, ty::Mutability::Mut) => Usage::Mut,
+            (usage, ty::Mutability::Mut) => usage,
+        }
+    }
+}
+
+/// The occurrences of the generic parameters in the type, keyed by the parameter index.
+fn usages<'tcx>(tcx: TyCtxt<'tcx>, ty: Ty<'tcx>, usage: Usage, out: &mut Vec<(u32, Usage)>) {
+    match ty.kind() {
+        ty::Param(param) => out.push((param.index, usage)),
+        ty::Ref(_, inner, mutbl) => usages(tcx, *inner, usage.behind_ref(*mutbl), out),
+        ty::RawPtr(inner, _) => usages(tcx, *inner, Usage::Raw, out),
+        ty::Adt(adt_def, args) => {
+            let did = adt_def.did();
+            let usage = if tcx.is_diagnostic_item(sym::NonNull, did)
+                || tcx.is_diagnostic_item(sym::AtomicPtr, did)
+            {
+                Usage::Raw
+            } else if adt_def.is_unsafe_cell() && usage == Usage::Owned {
+                Usage::Cell
+            } else {
+                usage
+            };
+            for ty in args.types() {
+                usages(tcx, ty, usage, out);
+            }
+        }
+        ty::Array(inner, _) | ty::Slice(inner) | ty::Pat(inner, _) => {
+            usages(tcx, *inner, usage, out)
+        }
+        ty::Tuple(tys) => {
+            for ty in tys.iter() {
+                usages(tcx, ty, usage, out);
+            }
+        }
+        // Function pointers, closures and trait objects do not hold the values of `T`.
+        _ => {}
+    }
+}
+
+/// A bound missing in the impl.
+#[derive(Debug, Clone)]
+pub struct MissingBound {
+    /// The name of the generic parameter.
+    pub param: String,
+    pub bound: AutoTrait,
+    /// Why the bound is required.
+    pub reason: String,
+    /// The field or method that requires the bound.
+    pub span: Span,
+}
+
+/// An `unsafe impl Send/Sync` missing the bounds of its generic parameters.
+#[derive(Debug, Clone)]
+pub struct VarianceBug {
+    pub impl_def_id: DefId,
+    pub auto_trait: AutoTrait,
+    pub self_ty: String,
+    pub missing: Vec<MissingBound>,
+}
+
+/// How the methods of a type move the values of a generic parameter.
+#[derive(Debug, Default, Clone)]
+struct ApiUsage {
+    /// `T` is moved into or out of the type.
+    moves: Option<(String, Span)>,
+    /// `T` is moved out through `&self`.
+    moves_out_shared: Option<(String, Span)>,
+    /// `&T` is returned from `&self`.
+    exposes_ref_shared: Option<(String, Span)>,
+}
+
+pub struct VarianceAnalysis<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    pub bugs: Vec<VarianceBug>,
+}
+
+impl<'tcx> VarianceAnalysis<'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>) -> Self {
+        Self {
+            tcx,
+            bugs: Vec::new(),
+        }
+    }
+
+    pub fn run(&mut self) {
+        let send = self.tcx.get_diagnostic_item(sym::Send);
+        let sync = self.tcx.lang_items().sync_trait();
+        for local_def_id in self.tcx.hir_crate_items(()).definitions() {
+            let impl_def_id = local_def_id.to_def_id();
+            if !matches!(
+                self.tcx.def_kind(impl_def_id),
+                DefKind::Impl { of_trait: true }
+            ) {
+                continue;
+            }
+            let header = self.tcx.impl_trait_header(impl_def_id);
+            if header.safety.is_safe() || header.polarity != ty::ImplPolarity::Positive {
+                continue;
+            }
+            let trait_def_id = header.trait_ref.skip_binder().def_id;
+            let auto_trait = if Some(trait_def_id) == send {
+                AutoTrait::Send
+            } else if Some(trait_def_id) == sync {
+                AutoTrait::Sync
+            } else {
+                continue;
+            };
+            if let Some(bug) = self.check_impl(impl_def_id, auto_trait) {
+                self.bugs.push(bug);
+            }
+        }
+    }
+
+    fn check_impl(&self, impl_def_id: DefId, auto_trait: AutoTrait) -> Option<VarianceBug> {
+        let self_ty = self.tcx.type_of(impl_def_id).instantiate_identity();
+        let ty::Adt(adt_def, args) = self_ty.kind() else {
+            return None;
+        };
+        let bounds = self.bounds(impl_def_id);
+        let apis = self.api_usages(*adt_def);
+        let mut field_usages: FxHashMap<usize, Vec<(Usage, String, Span)>> = FxHashMap::default();
+        let identity = GenericArgs::identity_for_item(self.tcx, adt_def.did());
+        for field in adt_def.all_fields() {
+            let mut out = Vec::new();
+            usages(
+                self.tcx,
+                field.ty(self.tcx, identity),
+                Usage::Owned,
+                &mut out,
+            );
+            for (index, usage) in out {
+                field_usages.entry(index as usize).or_default().push((
+                    usage,
+                    field.name.to_string(),
+                    self.tcx.def_span(field.did),
+                ));
+            }
+        }
+
+        let mut missing = Vec::new();
+        // The type arguments of the impl are matched with the parameters of the ADT by position.
+        for (position, arg) in args.iter().enumerate() {
+            let Some(arg_ty) = arg.as_type() else {
+                continue;
+            };
+            let ty::Param(param) = arg_ty.kind() else {
+                continue;
+            };
+            let api = apis.get(&position).cloned().unwrap_or_default();
+            let fields = field_usages
+                .get(&position)
+                .map(Vec::as_slice)
+                .unwrap_or(&[]);
+            let mut required: Vec<(AutoTrait, String, Span)> = Vec::new();
+            for (usage, field, span) in fields {
+                for (bound, reason) in
+                    field_requirements(auto_trait, *usage, param.name, field, &api)
+                {
+                    required.push((bound, reason, *span));
+                }
+            }
+            let mut reported = FxHashSet::default();
+            for (bound, reason, span) in required {
+                if bounds.contains(&(param.index, bound)) || !reported.insert(bound) {
+                    continue;
+                }
+                missing.push(MissingBound {
+                    param: param.name.to_string(),
+                    bound,
+                    reason,
+                    span,
+                });
+            }
+        }
+        if missing.is_empty() {
+            return None;
+        }
+        Some(VarianceBug {
+            impl_def_id,
+            auto_trait,
+            self_ty: self_ty.to_string(),
+            missing,
+        })
+    }
+
+    /// The `Send` and `Sync` bounds of the generic parameters of the impl, including those
+    /// implied by the supertraits.
+    fn bounds(&self, impl_def_id: DefId) -> FxHashSet<(u32, AutoTrait)> {
+        let send = self.tcx.get_diagnostic_item(sym::Send);
+        let sync = self.tcx.lang_items().sync_trait();
+        let predicates = self
+            .tcx
+            .predicates_of(impl_def_id)
+            .instantiate_identity(self.tcx)
+            .predicates;
+        let mut bounds = FxHashSet::default();
+        for clause in elaborate(self.tcx, predicates) {
+            let Some(trait_clause) = clause.as_trait_clause() else {
+                continue;
+            };
+            let trait_pred = trait_clause.skip_binder();
+            let ty::Param(param) = trait_pred.self_ty().kind() else {
+                continue;
+            };
+            if Some(trait_pred.def_id()) == send {
+                bounds.insert((param.index, AutoTrait::Send));
+            } else if Some(trait_pred.def_id()) == sync {
+                bounds.insert((param.index, AutoTrait::Sync));
+            }
+        }
+        bounds
+    }
+
+    /// How the methods in the local impls of the ADT move the values of its generic parameters,
+    /// keyed by the parameter position.
+    fn api_usages(&self, adt_def: AdtDef<'tcx>) -> FxHashMap<usize, ApiUsage> {
+        let mut apis: FxHashMap<usize, ApiUsage> = FxHashMap::default();
+        for local_def_id in self.tcx.hir_crate_items(()).definitions() {
+            let impl_def_id = local_def_id.to_def_id();
+            if !matches!(self.tcx.def_kind(impl_def_id), DefKind::Impl { .. }) {
+                continue;
+            }
+            let self_ty = self.tcx.type_of(impl_def_id).instantiate_identity();
+            let ty::Adt(impl_adt, args) = self_ty.kind() else {
+                continue;
+            };
+            if *impl_adt != adt_def {
+                continue;
+            }
+            // The parameters of the impl that stand for those of the ADT.
+            let positions: FxHashMap<u32, usize> = args
+                .iter()
+                .enumerate()
+                .filter_map(|(position, arg)| match arg.as_type()?.kind() {
+                    ty::Param(param) => Some((param.index, position)),
+                    _ => None,
+                })
+                .collect();
+            for item in self.tcx.associated_items(impl_def_id).in_definition_order() {
+                if !matches!(item.kind, ty::AssocKind::Fn { .. }) {
+                    continue;
+                }
+                let sig = self
+                    .tcx
+                    .fn_sig(item.def_id)
+                    .instantiate_identity()
+                    .skip_binder();
+                let name = self.tcx.item_name(item.def_id).to_string();
+                let span = self.tcx.def_span(item.def_id);
+                let shared_self = item.is_method()
+                    && matches!(
+                        sig.inputs().first().map(|ty| ty.kind()),
+                        Some(ty::Ref(_, inner, ty::Mutability::Not)) if *inner == self_ty
+                    );
+                let skip = usize::from(item.is_method());
+                let mut inputs = Vec::new();
+                for ty in sig.inputs().iter().skip(skip) {
+                    usages(self.tcx, *ty, Usage::Owned, &mut inputs);
+                }
+                let mut outputs = Vec::new();
+                usages(self.tcx, sig.output(), Usage::Owned, &mut outputs);
+                for (index, usage) in inputs.iter().chain(&outputs) {
+                    if *usage == Usage::Owned
+                        && let Some(position) = positions.get(index)
+                    {
+                        let api = apis.entry(*position).or_default();
+                        api.moves.get_or_insert((name.clone(), span));
+                    }
+                }
+                if !shared_self {
+                    continue;
+                }
+                for (index, usage) in &outputs {
+                    let Some(position) = positions.get(index) else {
+                        continue;
+                    };
+                    let api = apis.entry(*position).or_default();
+                    match usage {
+                        Usage::Owned => {
+                            api.moves_out_shared.get_or_insert((name.clone(), span));
+                        }
+                        Usage::Shared => {
+                            api.exposes_ref_shared.get_or_insert((name.clone(), span));
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+        apis
+    }
+}
+
+/// The bounds of `param` required by its usage in a field, with the reasons.
+fn field_requirements(
+    auto_trait: AutoTrait,
+    usage: Usage,
+    param: impl fmt::Display,
+    field: &str,
+    api: &ApiUsage,
+) -> Vec<(AutoTrait, String)> {
+    let owned = format!("`{}` is owned by the field `{}`", param, field);
+    let shared = format!("`&{}` is held by the field `{}`", param, field);
+    let mutable = format!("`&mut {}` is held by the field `{}`", param, field);
+    let cell = format!(
+        "`{}` is mutable through `&self` in the field `{}`",
+        param, field
+    );
+    let by_api = |(method, _): &(String, Span), what: &str| {
+        format!(
+            "`{}` is held by the raw pointer in the field `{}`, and {} by `{}`",
+            param, field, what, method
+        )
+    };
+    let mut required = Vec::new();
+    match (auto_trait, usage) {
+        (AutoTrait::Send, Usage::Owned | Usage::Cell) => required.push((AutoTrait::Send, owned)),
+        (AutoTrait::Send, Usage::Mut) => required.push((AutoTrait::Send, mutable)),
+        (AutoTrait::Send, Usage::Shared) => required.push((AutoTrait::Sync, shared)),
+        (AutoTrait::Send, Usage::Raw) => {
+            if let Some(moves) = &api.moves {
+                required.push((AutoTrait::Send, by_api(moves, "moved in or out")));
+            }
+        }
+        (AutoTrait::Sync, Usage::Owned) => required.push((AutoTrait::Sync, owned)),
+        (AutoTrait::Sync, Usage::Shared) => required.push((AutoTrait::Sync, shared)),
+        (AutoTrait::Sync, Usage::Mut) => required.push((AutoTrait::Sync, mutable)),
+        (AutoTrait::Sync, Usage::Cell) => {
+            required.push((AutoTrait::Send, cell));
+            if let Some(exposes) = &api.exposes_ref_shared {
+                let reason = format!("`&{}` is returned from `&self` by `{}`", param, exposes.0);
+                required.push((AutoTrait::Sync, reason));
+            }
+        }
+        (AutoTrait::Sync, Usage::Raw) => {
+            if let Some(moves) = &api.moves_out_shared {
+                required.push((AutoTrait::Send, by_api(moves, "moved out through `&self`")));
+            }
+            if let Some(exposes) = &api.exposes_ref_shared {
+                required.push((
+                    AutoTrait::Sync,
+                    by_api(exposes, "returned as a reference from `&self`"),
+                ));
+            }
+        }
+    }
+    required
+}
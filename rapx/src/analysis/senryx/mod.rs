@@ -17,22 +17,32 @@ use rustc_middle::{
     mir::{BasicBlock, Operand, TerminatorKind},
     ty::{self, TyCtxt},
 };
+use serde::Deserialize;
 use std::collections::HashSet;
 use visitor::{BodyVisitor, CheckResult};
 
-use crate::analysis::{
-    Analysis,
-    core::alias_analysis::{AAResult, AliasAnalysis, default::AliasAnalyzer},
-    upg::{fn_collector::FnCollector, hir_visitor::ContainsUnsafe},
-    utils::fn_info::*,
+use crate::{
+    analysis::{
+        Analysis,
+        core::alias_analysis::{
+            AAResult, AliasAnalysis, AliasBackend, andersen::AndersenAnalyzer,
+            default::AliasAnalyzer,
+        },
+        upg::{fn_collector::FnCollector, hir_visitor::ContainsUnsafe},
+        utils::fn_info::*,
+    },
+    utils::diagnostics::{Finding, FindingKind},
 };
 
 macro_rules! cond_print {
     ($cond:expr, $($t:tt)*) => {if $cond {rap_warn!($($t)*)} else {rap_info!($($t)*)}};
 }
 
+#[derive(Debug, Clone, Copy, Default, Hash, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum CheckLevel {
     High,
+    #[default]
     Medium,
     Low,
 }
@@ -40,6 +50,7 @@ pub enum CheckLevel {
 pub struct SenryxCheck<'tcx> {
     pub tcx: TyCtxt<'tcx>,
     pub threshhold: usize,
+    pub alias_backend: AliasBackend,
 }
 
 impl<'tcx> SenryxCheck<'tcx> {
@@ -49,7 +60,17 @@ impl<'tcx> SenryxCheck<'tcx> {
     /// - `tcx`: compiler TyCtxt for querying types/definitions.
     /// - `threshhold`: a numeric threshold used by checks.
     pub fn new(tcx: TyCtxt<'tcx>, threshhold: usize) -> Self {
-        Self { tcx, threshhold }
+        Self {
+            tcx,
+            threshhold,
+            alias_backend: AliasBackend::default(),
+        }
+    }
+
+    /// Use the alias analysis of the backend for the checks.
+    pub fn with_alias_backend(mut self, alias_backend: AliasBackend) -> Self {
+        self.alias_backend = alias_backend;
+        self
     }
 
     /// Start the checking pass over the collected functions.
@@ -59,9 +80,18 @@ impl<'tcx> SenryxCheck<'tcx> {
     pub fn start(&mut self, check_level: CheckLevel, is_verify: bool) {
         let tcx = self.tcx;
         // Build alias information for all functions first.
-        let mut analyzer = AliasAnalyzer::new(self.tcx);
-        analyzer.run(); // populate alias results
-        let fn_map = &analyzer.get_all_fn_alias();
+        let fn_map = &match self.alias_backend {
+            AliasBackend::Mop => {
+                let mut analyzer = AliasAnalyzer::new(self.tcx);
+                analyzer.run(); // populate alias results
+                analyzer.get_all_fn_alias()
+            }
+            AliasBackend::Andersen => {
+                let mut analyzer = AndersenAnalyzer::new(self.tcx);
+                analyzer.run();
+                analyzer.get_all_fn_alias()
+            }
+        };
 
         // Collect functions of interest (e.g. from UPG/collector)
         let related_items = FnCollector::collect(tcx);
@@ -347,17 +377,35 @@ impl<'tcx> SenryxCheck<'tcx> {
                 }
             }
 
+            let mut failed_sorted: Vec<&String> = all_failed.into_iter().collect();
+            failed_sorted.sort();
+
+            // Skip the API if its failed contracts are suppressed or recorded in the baseline
+            if !failed_sorted.is_empty()
+                && !Finding::new(
+                    FindingKind::UnsafeContract,
+                    def_id,
+                    format!(
+                        "Use unsafe api {:?}; failed contracts: {:?}",
+                        check_result.func_name, failed_sorted
+                    ),
+                )
+                .location(format!("call:{}", check_result.func_name))
+                .primary(check_result.func_span, check_result.func_name.as_str())
+                .emit()
+            {
+                continue;
+            }
+
             // Print the API name with conditional coloring
             cond_print!(
-                !all_failed.is_empty(),
+                !failed_sorted.is_empty(),
                 "  Use unsafe api {:?}.",
                 check_result.func_name
             );
 
             // Print aggregated Failed set
-            if !all_failed.is_empty() {
-                let mut failed_sorted: Vec<&String> = all_failed.into_iter().collect();
-                failed_sorted.sort();
+            if !failed_sorted.is_empty() {
                 cond_print!(true, "      Failed: {:?}", failed_sorted);
             }
 
@@ -23,6 +23,7 @@ use crate::{
         },
     },
     rap_debug, rap_warn,
+    utils::budget::{self, Exhausted, FnBudget},
 };
 use rustc_middle::ty::GenericParamDefKind;
 use serde::de;
@@ -116,6 +117,7 @@ pub struct BodyVisitor<'tcx> {
     pub chains: DominatedGraph<'tcx>,
     pub value_domains: HashMap<usize, ValueDomain<'tcx>>,
     pub path_constraints: Vec<SymbolicDef<'tcx>>,
+    pub budget: FnBudget,
 }
 
 // === Partition: Initialization & state ===
@@ -143,6 +145,7 @@ impl<'tcx> BodyVisitor<'tcx> {
             chains,
             value_domains: HashMap::new(),
             path_constraints: Vec::new(),
+            budget: FnBudget::new(),
         }
     }
 }
@@ -176,7 +179,15 @@ impl<'tcx> BodyVisitor<'tcx> {
 
         // Iterate all the paths. Paths have been handled by tarjan.
         let tmp_chain = self.chains.clone();
+        let path_limit = budget::config().paths;
         for (index, (path, constraint)) in paths.iter().enumerate() {
+            // The results of the paths checked within the budget are kept.
+            if index >= path_limit {
+                self.budget.exhaust(Exhausted::Paths(path_limit));
+            }
+            if self.budget.is_exhausted() {
+                break;
+            }
             // Init three data structures in every path
             self.value_domains.clear();
             for (arg_index, _) in body.args_iter().enumerate() {
@@ -227,6 +238,7 @@ impl<'tcx> BodyVisitor<'tcx> {
                 InterResultNode::construct_from_var_node(self.chains.clone(), 0);
             inter_return_value.merge(curr_path_inter_return_value);
         }
+        self.budget.record(self.tcx, "verify", self.def_id);
 
         inter_return_value
     }
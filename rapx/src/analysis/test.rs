@@ -8,7 +8,11 @@ use crate::analysis::{
     },
 };
 
-use rustc_middle::ty::TyCtxt;
+use crate::utils::source::get_fn_name;
+use rustc_middle::{
+    mir::{Location, RETURN_PLACE, TerminatorKind},
+    ty::TyCtxt,
+};
 
 pub struct Test<'tcx> {
     pub tcx: TyCtxt<'tcx>,
@@ -24,6 +28,7 @@ impl<'tcx> Test<'tcx> {
         alias_analysis.run();
         let result = alias_analysis.get_local_fn_alias();
         rap_info!("{}", AAResultMapWrapper(result));
+        self.query_alias_points(&alias_analysis);
 
         let mut dataflow_analysis = DataFlowAnalyzer::new(self.tcx, false);
         dataflow_analysis.run();
@@ -34,10 +39,61 @@ impl<'tcx> Test<'tcx> {
         heap_analysis.run();
         let result = heap_analysis.get_all_items();
         rap_info!("{}", OHAResultMapWrapper(result));
+        self.query_heap_trees(&mut heap_analysis);
 
         let mut range_analysis = RangeAnalyzer::<i64>::new(self.tcx, false);
         range_analysis.run();
         let path_constraint = range_analysis.get_all_path_constraints();
         rap_info!("{}", PathConstraintMapWrapper(path_constraint));
     }
+
+    /// Query the heap ownership of the types of the arguments of each function.
+    fn query_heap_trees(&self, heap_analysis: &mut impl OwnedHeapAnalysis<'tcx>) {
+        for local_def_id in self.tcx.mir_keys(()) {
+            let def_id = local_def_id.to_def_id();
+            if self.tcx.hir_body_const_context(*local_def_id).is_some() {
+                continue;
+            }
+            let body = self.tcx.optimized_mir(def_id);
+            for arg in body.args_iter() {
+                let tree = heap_analysis.query(body.local_decls[arg].ty);
+                rap_info!(
+                    "Heap of {:?} in {:?}: {}",
+                    arg,
+                    get_fn_name(self.tcx, def_id),
+                    tree
+                );
+            }
+        }
+    }
+
+    /// Query the aliases of the return value at the entry and the returns of each function.
+    fn query_alias_points(&self, alias_analysis: &impl AliasAnalysis<'tcx>) {
+        for local_def_id in self.tcx.mir_keys(()) {
+            let def_id = local_def_id.to_def_id();
+            if self.tcx.hir_body_const_context(*local_def_id).is_some() {
+                continue;
+            }
+            let body = self.tcx.optimized_mir(def_id);
+            let returns = body
+                .basic_blocks
+                .iter_enumerated()
+                .filter(|(_, bb)| matches!(bb.terminator().kind, TerminatorKind::Return))
+                .map(|(block, _)| body.terminator_loc(block));
+            for location in std::iter::once(Location::START).chain(returns) {
+                let mut aliases: Vec<String> = alias_analysis
+                    .alias_set(def_id, location, RETURN_PLACE.into())
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect();
+                aliases.sort();
+                rap_info!(
+                    "Aliases of 0 in {:?} at {:?}: {{{}}}",
+                    get_fn_name(self.tcx, def_id),
+                    location,
+                    aliases.join(", ")
+                );
+            }
+        }
+    }
 }
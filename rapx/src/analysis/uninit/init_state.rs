@@ -0,0 +1,913 @@
+//! The initialization state of the memory allocated in a function.
+//!
+//! Each allocation site is an object, e.g., `MaybeUninit::uninit()`, `Vec::with_capacity(n)` or
+//! `alloc(layout)`, whose bytes are tracked as initialized or not. The locals pointing into an
+//! object are resolved once for the whole function, with the byte offset when it is a constant.
+//! The states are then propagated over the control flow graph, where a byte is initialized at a
+//! join point only if it is initialized on all the incoming edges. A read of a byte that may be
+//! uninitialized is reported with the allocation site.
+
+use crate::analysis::{
+    core::ownership_model::{PathPatterns, model_path},
+    safedrop::provenance::is_pass_through,
+};
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_middle::{
+    mir::{
+        BasicBlock, Body, Operand, Place, PlaceElem, PlaceTy, ProjectionElem, Rvalue,
+        StatementKind, TerminatorKind,
+    },
+    ty::{self, GenericArgsRef, Ty, TyCtxt, TypingEnv},
+};
+use rustc_span::{Span, def_id::DefId};
+use std::collections::VecDeque;
+
+/// The bytes beyond the limit are not tracked, and the writes to them initialize the object.
+const MAX_TRACKED_BYTES: u64 = 4096;
+
+/// The APIs that allocate, access or initialize the tracked memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InitApi {
+    Alloc,
+    MaybeUninitNew,
+    VecWithCapacity,
+    /// `MaybeUninit::as_ptr` and `as_mut_ptr`.
+    MaybeUninitAsPtr,
+    MaybeUninitWrite,
+    /// `MaybeUninit::assume_init` and its variants.
+    MaybeUninitRead,
+    VecAsPtr,
+    VecSetLen,
+    VecPush,
+    VecIndex,
+    VecIndexMut,
+    VecDeref,
+    /// The methods of `Vec` that neither read nor write the elements.
+    VecNoAccess,
+    PtrWrite,
+    WriteBytes,
+    PtrRead,
+    /// The copies of `count` elements from the source to the destination argument.
+    Copy {
+        src: usize,
+        dst: usize,
+    },
+    /// The pointer arithmetic by elements, negated for `sub`.
+    Offset {
+        negative: bool,
+    },
+    ByteOffset,
+    SliceFromRawParts,
+    SliceWrite,
+}
+
+const INIT_APIS: &[(&str, InitApi)] = &[
+    ("core::alloc::alloc", InitApi::Alloc),
+    (
+        "core::mem::MaybeUninit::<*>::uninit",
+        InitApi::MaybeUninitNew,
+    ),
+    (
+        "core::mem::MaybeUninit::<*>::as_ptr",
+        InitApi::MaybeUninitAsPtr,
+    ),
+    (
+        "core::mem::MaybeUninit::<*>::as_mut_ptr",
+        InitApi::MaybeUninitAsPtr,
+    ),
+    (
+        "core::mem::MaybeUninit::<*>::write",
+        InitApi::MaybeUninitWrite,
+    ),
+    (
+        "core::mem::MaybeUninit::<*>::assume_init*",
+        InitApi::MaybeUninitRead,
+    ),
+    (
+        "core::vec::Vec::<*>::with_capacity",
+        InitApi::VecWithCapacity,
+    ),
+    ("core::vec::Vec::<*>::as_ptr", InitApi::VecAsPtr),
+    ("core::vec::Vec::<*>::as_mut_ptr", InitApi::VecAsPtr),
+    ("core::vec::Vec::<*>::set_len", InitApi::VecSetLen),
+    ("core::vec::Vec::<*>::push", InitApi::VecPush),
+    // The trait methods only count when called on a `Vec`.
+    ("core::ops::Index::index", InitApi::VecIndex),
+    ("core::ops::IndexMut::index_mut", InitApi::VecIndexMut),
+    ("core::ops::Deref::deref", InitApi::VecDeref),
+    ("core::vec::Vec::<*>::capacity", InitApi::VecNoAccess),
+    ("core::vec::Vec::<*>::len", InitApi::VecNoAccess),
+    ("core::vec::Vec::<*>::is_empty", InitApi::VecNoAccess),
+    ("core::vec::Vec::<*>::reserve*", InitApi::VecNoAccess),
+    (
+        "core::vec::Vec::<*>::spare_capacity_mut",
+        InitApi::VecNoAccess,
+    ),
+    ("core::ptr::write_bytes", InitApi::WriteBytes),
+    ("core::intrinsics::write_bytes", InitApi::WriteBytes),
+    (
+        "core::ptr::mut_ptr::<impl *mut *>::write_bytes",
+        InitApi::WriteBytes,
+    ),
+    ("core::ptr::write*", InitApi::PtrWrite),
+    (
+        "core::ptr::mut_ptr::<impl *mut *>::write*",
+        InitApi::PtrWrite,
+    ),
+    ("core::ptr::NonNull::<*>::write*", InitApi::PtrWrite),
+    ("core::ptr::read*", InitApi::PtrRead),
+    ("core::ptr::*_ptr::<impl *>::read*", InitApi::PtrRead),
+    ("core::ptr::NonNull::<*>::read*", InitApi::PtrRead),
+    ("core::ptr::copy*", InitApi::Copy { src: 0, dst: 1 }),
+    ("core::intrinsics::copy*", InitApi::Copy { src: 0, dst: 1 }),
+    (
+        "core::ptr::*_ptr::<impl *>::copy_to*",
+        InitApi::Copy { src: 0, dst: 1 },
+    ),
+    (
+        "core::ptr::mut_ptr::<impl *mut *>::copy_from*",
+        InitApi::Copy { src: 1, dst: 0 },
+    ),
+    (
+        "core::ptr::*_ptr::<impl *>::add",
+        InitApi::Offset { negative: false },
+    ),
+    (
+        "core::ptr::*_ptr::<impl *>::offset",
+        InitApi::Offset { negative: false },
+    ),
+    (
+        "core::ptr::*_ptr::<impl *>::wrapping_add",
+        InitApi::Offset { negative: false },
+    ),
+    (
+        "core::ptr::*_ptr::<impl *>::sub",
+        InitApi::Offset { negative: true },
+    ),
+    (
+        "core::ptr::*_ptr::<impl *>::wrapping_sub",
+        InitApi::Offset { negative: true },
+    ),
+    (
+        "core::ptr::NonNull::<*>::add",
+        InitApi::Offset { negative: false },
+    ),
+    ("core::ptr::*_ptr::<impl *>::byte_add", InitApi::ByteOffset),
+    ("core::slice::from_raw_parts*", InitApi::SliceFromRawParts),
+    (
+        "core::slice::<impl [*]>::copy_from_slice",
+        InitApi::SliceWrite,
+    ),
+    (
+        "core::slice::<impl [*]>::clone_from_slice",
+        InitApi::SliceWrite,
+    ),
+    ("core::slice::<impl [*]>::fill*", InitApi::SliceWrite),
+];
+
+fn init_api(path: &str) -> Option<InitApi> {
+    static APIS: PathPatterns<(&str, InitApi)> = PathPatterns::keyed(INIT_APIS);
+    APIS.find(path).map(|(_, api)| *api)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjectKind {
+    MaybeUninit,
+    Vec,
+    Alloc,
+}
+
+/// The memory allocated at a site.
+#[derive(Debug, Clone)]
+pub struct Object<'tcx> {
+    pub kind: ObjectKind,
+    /// The API that allocates the memory.
+    pub api: &'static str,
+    pub bb: usize,
+    pub span: Span,
+    /// The local holding the object or the pointer returned by the allocation.
+    pub local: usize,
+    /// The type of the value in `MaybeUninit`, or of the elements of `Vec`.
+    pub ty: Option<Ty<'tcx>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RefKind {
+    /// The local holds the object itself, e.g., the `MaybeUninit` or the `Vec`.
+    Value,
+    /// A reference to a `Vec`, rather than into its buffer.
+    Handle,
+    /// A pointer into the memory of the object.
+    Ptr,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ObjRef {
+    obj: usize,
+    /// The offset in bytes, if it is a constant.
+    offset: Option<u64>,
+    kind: RefKind,
+}
+
+/// The initialization state of an object at a program point.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+struct ObjState {
+    /// Whether each byte is initialized; the bytes beyond are not.
+    init: Vec<bool>,
+    /// The object has been written at unknown bytes, so any byte may be initialized.
+    unknown_write: bool,
+    /// The length of a `Vec`, if it is a constant.
+    len: Option<u64>,
+}
+
+impl ObjState {
+    fn write(&mut self, offset: Option<u64>, size: Option<u64>) {
+        let (Some(offset), Some(size)) = (offset, size) else {
+            self.unknown_write = true;
+            return;
+        };
+        let end = offset.saturating_add(size);
+        if end > MAX_TRACKED_BYTES {
+            self.unknown_write = true;
+            return;
+        }
+        if self.init.len() < end as usize {
+            self.init.resize(end as usize, false);
+        }
+        self.init[offset as usize..end as usize].fill(true);
+    }
+
+    /// The first range of the bytes that may be uninitialized, or None if all are initialized.
+    fn uninit_range(&self, offset: u64, ranges: &[(u64, u64)]) -> Option<(u64, u64)> {
+        if self.unknown_write {
+            return None;
+        }
+        ranges
+            .iter()
+            .map(|(start, size)| (offset + start, offset + start + size))
+            .find(|(start, end)| (*start..*end).any(|byte| !self.is_init(byte)))
+    }
+
+    fn is_init(&self, byte: u64) -> bool {
+        byte >= MAX_TRACKED_BYTES || self.init.get(byte as usize).copied().unwrap_or(false)
+    }
+
+    fn is_untouched(&self) -> bool {
+        !self.unknown_write && !self.init.contains(&true)
+    }
+
+    /// The bytes are initialized only if they are on both paths.
+    fn join(&mut self, other: &ObjState) {
+        let len = self.init.len().min(other.init.len());
+        self.init.truncate(len);
+        for (byte, init) in self.init.iter_mut().enumerate() {
+            *init &= other.init[byte];
+        }
+        self.unknown_write |= other.unknown_write;
+        if self.len != other.len {
+            self.len = None;
+        }
+    }
+}
+
+type State = Vec<ObjState>;
+/// The reference read by a call, and the byte ranges of the data it reads if known.
+type CallRead = (ObjRef, Option<Vec<(u64, u64)>>);
+
+/// A read of the bytes of an object that may be uninitialized.
+#[derive(Debug, Clone)]
+pub struct UninitRead {
+    pub obj: usize,
+    pub bb: usize,
+    pub span: Span,
+    /// The bytes of the object that may be uninitialized, if known.
+    pub bytes: Option<(u64, u64)>,
+}
+
+pub struct InitAnalysis<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    typing_env: TypingEnv<'tcx>,
+    body: &'tcx Body<'tcx>,
+    pub objects: Vec<Object<'tcx>>,
+    refs: FxHashMap<usize, ObjRef>,
+    /// The object allocated by each call, indexed by the basic block.
+    allocations: FxHashMap<usize, usize>,
+    pub reads: Vec<UninitRead>,
+}
+
+impl<'tcx> InitAnalysis<'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>, def_id: DefId) -> Self {
+        Self {
+            tcx,
+            typing_env: TypingEnv::post_analysis(tcx, def_id),
+            body: tcx.optimized_mir(def_id),
+            objects: Vec::new(),
+            refs: FxHashMap::default(),
+            allocations: FxHashMap::default(),
+            reads: Vec::new(),
+        }
+    }
+
+    pub fn run(&mut self) {
+        self.find_objects();
+        if self.objects.is_empty() {
+            return;
+        }
+        self.resolve_refs();
+        let entries = self.propagate();
+        // The reads are only checked with the states at the fixed point.
+        let mut reported = FxHashSet::default();
+        for (bb, entry) in entries.into_iter().enumerate() {
+            let Some(mut state) = entry else {
+                continue;
+            };
+            let mut reads = Vec::new();
+            self.transfer(BasicBlock::from_usize(bb), &mut state, Some(&mut reads));
+            for read in reads {
+                if reported.insert((read.obj, read.bb, read.span)) {
+                    self.reads.push(read);
+                }
+            }
+        }
+    }
+
+    fn find_objects(&mut self) {
+        for (bb, data) in self.body.basic_blocks.iter_enumerated() {
+            let terminator = data.terminator();
+            let TerminatorKind::Call {
+                func, destination, ..
+            } = &terminator.kind
+            else {
+                continue;
+            };
+            let (Some((callee, generic_args)), Some(local)) =
+                (func.const_fn_def(), destination.as_local())
+            else {
+                continue;
+            };
+            let (kind, api, ty) = match init_api(&model_path(self.tcx, callee)) {
+                Some(InitApi::MaybeUninitNew) => (
+                    ObjectKind::MaybeUninit,
+                    "MaybeUninit::uninit",
+                    generic_args.types().next(),
+                ),
+                Some(InitApi::VecWithCapacity) => (
+                    ObjectKind::Vec,
+                    "Vec::with_capacity",
+                    generic_args.types().next(),
+                ),
+                Some(InitApi::Alloc) => (ObjectKind::Alloc, "alloc", None),
+                _ => continue,
+            };
+            let obj = self.objects.len();
+            self.objects.push(Object {
+                kind,
+                api,
+                bb: bb.as_usize(),
+                span: terminator.source_info.span,
+                local: local.as_usize(),
+                ty,
+            });
+            self.allocations.insert(bb.as_usize(), obj);
+            let kind = match kind {
+                ObjectKind::Alloc => RefKind::Ptr,
+                _ => RefKind::Value,
+            };
+            self.refs.insert(
+                local.as_usize(),
+                ObjRef {
+                    obj,
+                    offset: Some(0),
+                    kind,
+                },
+            );
+        }
+    }
+
+    /// Find the locals pointing into the objects until no more is found.
+    fn resolve_refs(&mut self) {
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for data in self.body.basic_blocks.iter() {
+                for stmt in &data.statements {
+                    if let StatementKind::Assign(box (place, rvalue)) = &stmt.kind
+                        && let Some(lv) = place.as_local()
+                        && !self.refs.contains_key(&lv.as_usize())
+                        && let Some(obj_ref) = self.rvalue_ref(rvalue)
+                    {
+                        self.refs.insert(lv.as_usize(), obj_ref);
+                        changed = true;
+                    }
+                }
+                if let TerminatorKind::Call {
+                    func,
+                    args,
+                    destination,
+                    ..
+                } = &data.terminator().kind
+                    && let Some((callee, generic_args)) = func.const_fn_def()
+                    && let Some(lv) = destination.as_local()
+                    && !self.refs.contains_key(&lv.as_usize())
+                {
+                    let args: Vec<&Operand<'tcx>> = args.iter().map(|arg| &arg.node).collect();
+                    if let Some(obj_ref) = self.call_ref(callee, generic_args, &args) {
+                        self.refs.insert(lv.as_usize(), obj_ref);
+                        changed = true;
+                    }
+                }
+            }
+        }
+    }
+
+    fn rvalue_ref(&self, rvalue: &Rvalue<'tcx>) -> Option<ObjRef> {
+        match rvalue {
+            Rvalue::Use(op) | Rvalue::Cast(_, op, _) => self.operand_ref(op),
+            Rvalue::Ref(_, _, place) | Rvalue::RawPtr(_, place) => {
+                let base = *self.refs.get(&place.local.as_usize())?;
+                if place.projection.is_empty() {
+                    let kind = match self.objects[base.obj].kind {
+                        ObjectKind::Vec => RefKind::Handle,
+                        _ => RefKind::Ptr,
+                    };
+                    return (base.kind == RefKind::Value).then_some(ObjRef { kind, ..base });
+                }
+                match base.kind {
+                    RefKind::Ptr => {
+                        let (offset, _) = self.deref_offset(place)?;
+                        Some(ObjRef {
+                            offset: base.offset.zip(offset).map(|(base, delta)| base + delta),
+                            ..base
+                        })
+                    }
+                    // A reborrow of the reference to a `Vec`.
+                    RefKind::Handle if is_deref(place) => Some(base),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn call_ref(
+        &self,
+        callee: DefId,
+        generic_args: GenericArgsRef<'tcx>,
+        args: &[&Operand<'tcx>],
+    ) -> Option<ObjRef> {
+        let path = model_path(self.tcx, callee);
+        let arg = self.operand_ref(args.first()?)?;
+        if is_pass_through(&path) {
+            return Some(arg);
+        }
+        match init_api(&path)? {
+            InitApi::MaybeUninitAsPtr if arg.kind == RefKind::Ptr => Some(arg),
+            InitApi::VecAsPtr if arg.kind == RefKind::Handle => Some(ObjRef {
+                offset: Some(0),
+                kind: RefKind::Ptr,
+                ..arg
+            }),
+            InitApi::VecIndex | InitApi::VecIndexMut if arg.kind == RefKind::Handle => {
+                let elem = self.size_of(self.objects[arg.obj].ty?);
+                let index = self.const_usize(args.get(1)?);
+                Some(ObjRef {
+                    offset: elem.zip(index).map(|(elem, index)| elem * index),
+                    kind: RefKind::Ptr,
+                    ..arg
+                })
+            }
+            InitApi::SliceFromRawParts if arg.kind == RefKind::Ptr => Some(arg),
+            InitApi::Offset { negative } if arg.kind == RefKind::Ptr => {
+                let elem = self.size_of(generic_args.types().next()?);
+                let count = self.const_usize(args.get(1)?);
+                let delta = elem.zip(count).map(|(elem, count)| elem * count);
+                Some(ObjRef {
+                    offset: offset_by(arg.offset, delta, negative),
+                    ..arg
+                })
+            }
+            InitApi::ByteOffset if arg.kind == RefKind::Ptr => {
+                let delta = self.const_usize(args.get(1)?);
+                Some(ObjRef {
+                    offset: offset_by(arg.offset, delta, false),
+                    ..arg
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// The states at the entry of each basic block reachable from an allocation.
+    fn propagate(&self) -> Vec<Option<State>> {
+        let blocks = self.body.basic_blocks.len();
+        let mut entries: Vec<Option<State>> = vec![None; blocks];
+        entries[0] = Some(vec![ObjState::default(); self.objects.len()]);
+        let mut worklist = VecDeque::from([BasicBlock::from_usize(0)]);
+        while let Some(bb) = worklist.pop_front() {
+            let Some(mut state) = entries[bb.as_usize()].clone() else {
+                continue;
+            };
+            self.transfer(bb, &mut state, None);
+            for succ in self.body.basic_blocks[bb].terminator().successors() {
+                let entry = &mut entries[succ.as_usize()];
+                let joined = match entry {
+                    None => state.clone(),
+                    Some(old) => {
+                        let mut joined = old.clone();
+                        for (obj, obj_state) in joined.iter_mut().enumerate() {
+                            obj_state.join(&state[obj]);
+                        }
+                        joined
+                    }
+                };
+                if entry.as_ref() != Some(&joined) {
+                    *entry = Some(joined);
+                    worklist.push_back(succ);
+                }
+            }
+        }
+        entries
+    }
+
+    /// Apply the effects of the basic block to the state, and collect the reads that may be
+    /// uninitialized if `reads` is given.
+    fn transfer(&self, bb: BasicBlock, state: &mut State, mut reads: Option<&mut Vec<UninitRead>>) {
+        let data = &self.body.basic_blocks[bb];
+        for stmt in &data.statements {
+            let StatementKind::Assign(box (place, rvalue)) = &stmt.kind else {
+                continue;
+            };
+            for read in rvalue_places(rvalue) {
+                if let Some(read) = self.check_place_read(state, bb, stmt.source_info.span, read)
+                    && let Some(reads) = reads.as_deref_mut()
+                {
+                    reads.push(read);
+                }
+            }
+            self.write_place(state, place);
+        }
+        let terminator = data.terminator();
+        let TerminatorKind::Call { func, args, .. } = &terminator.kind else {
+            return;
+        };
+        let Some((callee, generic_args)) = func.const_fn_def() else {
+            return;
+        };
+        if let Some(obj) = self.allocations.get(&bb.as_usize()) {
+            state[*obj] = ObjState::default();
+            if self.objects[*obj].kind == ObjectKind::Vec {
+                state[*obj].len = Some(0);
+            }
+            return;
+        }
+        let args: Vec<&Operand<'tcx>> = args.iter().map(|arg| &arg.node).collect();
+        let span = terminator.source_info.span;
+        let mut checked = Vec::new();
+        self.call(state, callee, generic_args, &args, &mut checked);
+        for (obj_ref, ranges) in checked {
+            if let Some(read) = self.check_read(state, bb, span, obj_ref, ranges.as_deref())
+                && let Some(reads) = reads.as_deref_mut()
+            {
+                reads.push(read);
+            }
+        }
+    }
+
+    /// Apply the effects of the call, and collect the bytes it reads.
+    fn call(
+        &self,
+        state: &mut State,
+        callee: DefId,
+        generic_args: GenericArgsRef<'tcx>,
+        args: &[&Operand<'tcx>],
+        reads: &mut Vec<CallRead>,
+    ) {
+        let path = model_path(self.tcx, callee);
+        let refs: Vec<Option<ObjRef>> = args.iter().map(|arg| self.operand_ref(arg)).collect();
+        let elem = generic_args.types().next();
+        let api = init_api(&path);
+        // The source of a copy is not necessarily tracked, e.g., a local array.
+        if let Some(InitApi::Copy { src, dst }) = api {
+            let count = args.get(2).and_then(|arg| self.const_usize(arg));
+            let ranges = elem
+                .and_then(|ty| self.data_ranges(ty))
+                .zip(elem.and_then(|ty| self.size_of(ty)))
+                .zip(count)
+                .map(|((ranges, size), count)| repeat_ranges(&ranges, size, count));
+            if let Some(src) = refs.get(src).copied().flatten()
+                && src.kind == RefKind::Ptr
+            {
+                reads.push((src, ranges));
+            }
+            if let Some(dst) = refs.get(dst).copied().flatten()
+                && dst.kind == RefKind::Ptr
+            {
+                let size = elem.and_then(|ty| self.size_of(ty));
+                let bytes = size.zip(count).map(|(size, count)| size * count);
+                state[dst.obj].write(dst.offset, bytes);
+            }
+            return;
+        }
+        let Some(first) = refs.first().copied().flatten() else {
+            return;
+        };
+        match api {
+            Some(InitApi::MaybeUninitWrite) => {
+                let size = self.objects[first.obj].ty.and_then(|ty| self.size_of(ty));
+                state[first.obj].write(Some(0), size);
+            }
+            Some(InitApi::MaybeUninitRead) => {
+                let ranges = self.objects[first.obj]
+                    .ty
+                    .and_then(|ty| self.data_ranges(ty));
+                reads.push((first, ranges));
+            }
+            Some(InitApi::PtrWrite) if first.kind == RefKind::Ptr => {
+                state[first.obj].write(first.offset, elem.and_then(|ty| self.size_of(ty)));
+            }
+            Some(InitApi::WriteBytes) if first.kind == RefKind::Ptr => {
+                let size = elem.and_then(|ty| self.size_of(ty));
+                let count = args.get(2).and_then(|arg| self.const_usize(arg));
+                let bytes = size.zip(count).map(|(size, count)| size * count);
+                state[first.obj].write(first.offset, bytes);
+            }
+            Some(InitApi::PtrRead) if first.kind == RefKind::Ptr => {
+                reads.push((first, elem.and_then(|ty| self.data_ranges(ty))));
+            }
+            Some(InitApi::VecSetLen) if first.kind == RefKind::Handle => {
+                state[first.obj].len = args.get(1).and_then(|arg| self.const_usize(arg));
+            }
+            Some(InitApi::VecPush) if first.kind == RefKind::Handle => {
+                let obj_state = &mut state[first.obj];
+                let size = self.objects[first.obj].ty.and_then(|ty| self.size_of(ty));
+                let offset = obj_state.len.zip(size).map(|(len, size)| len * size);
+                obj_state.write(offset, size);
+                obj_state.len = obj_state.len.map(|len| len + 1);
+            }
+            Some(InitApi::VecIndex) if first.kind == RefKind::Handle => {
+                if let Some(obj_ref) = self.call_ref(callee, generic_args, args) {
+                    let ty = self.objects[first.obj].ty;
+                    reads.push((obj_ref, ty.and_then(|ty| self.data_ranges(ty))));
+                }
+            }
+            Some(InitApi::VecDeref) if first.kind == RefKind::Handle => {
+                let ty = self.objects[first.obj].ty;
+                let ranges = ty
+                    .and_then(|ty| self.data_ranges(ty))
+                    .zip(ty.and_then(|ty| self.size_of(ty)))
+                    .zip(state[first.obj].len)
+                    .map(|((ranges, size), len)| repeat_ranges(&ranges, size, len));
+                if let Some(ranges) = ranges {
+                    let obj_ref = ObjRef {
+                        offset: Some(0),
+                        kind: RefKind::Ptr,
+                        ..first
+                    };
+                    reads.push((obj_ref, Some(ranges)));
+                }
+            }
+            Some(
+                InitApi::VecAsPtr
+                | InitApi::VecIndexMut
+                | InitApi::VecNoAccess
+                | InitApi::MaybeUninitAsPtr
+                | InitApi::SliceFromRawParts
+                | InitApi::Offset { .. }
+                | InitApi::ByteOffset,
+            ) => {}
+            _ if is_pass_through(&path) => {}
+            // The unknown callees may initialize the memory passed to them.
+            _ => {
+                for obj_ref in refs.into_iter().flatten() {
+                    if obj_ref.kind != RefKind::Value {
+                        state[obj_ref.obj].unknown_write = true;
+                        state[obj_ref.obj].len = None;
+                    }
+                }
+            }
+        }
+    }
+
+    fn write_place(&self, state: &mut State, place: &Place<'tcx>) {
+        let Some(obj_ref) = self.refs.get(&place.local.as_usize()) else {
+            return;
+        };
+        if obj_ref.kind != RefKind::Ptr || place.projection.first() != Some(&ProjectionElem::Deref)
+        {
+            return;
+        }
+        match self.deref_offset(place) {
+            Some((delta, ty)) => {
+                let offset = obj_ref.offset.zip(delta).map(|(base, delta)| base + delta);
+                state[obj_ref.obj].write(offset, self.size_of(ty));
+            }
+            None => state[obj_ref.obj].unknown_write = true,
+        }
+    }
+
+    fn check_place_read(
+        &self,
+        state: &State,
+        bb: BasicBlock,
+        span: Span,
+        place: &Place<'tcx>,
+    ) -> Option<UninitRead> {
+        let obj_ref = *self.refs.get(&place.local.as_usize())?;
+        if obj_ref.kind != RefKind::Ptr || place.projection.first() != Some(&ProjectionElem::Deref)
+        {
+            return None;
+        }
+        let (delta, ty) = self.deref_offset(place)?;
+        let obj_ref = ObjRef {
+            offset: obj_ref.offset.zip(delta).map(|(base, delta)| base + delta),
+            ..obj_ref
+        };
+        self.check_read(state, bb, span, obj_ref, self.data_ranges(ty).as_deref())
+    }
+
+    fn check_read(
+        &self,
+        state: &State,
+        bb: BasicBlock,
+        span: Span,
+        obj_ref: ObjRef,
+        ranges: Option<&[(u64, u64)]>,
+    ) -> Option<UninitRead> {
+        let obj_state = &state[obj_ref.obj];
+        let bytes = match (obj_ref.offset, ranges) {
+            (Some(offset), Some(ranges)) => Some(obj_state.uninit_range(offset, ranges)?),
+            // Only the reads of the objects never written are known to be uninitialized.
+            _ if obj_state.is_untouched() => None,
+            _ => return None,
+        };
+        Some(UninitRead {
+            obj: obj_ref.obj,
+            bb: bb.as_usize(),
+            span,
+            bytes,
+        })
+    }
+
+    fn operand_ref(&self, op: &Operand<'tcx>) -> Option<ObjRef> {
+        match op {
+            Operand::Copy(place) | Operand::Move(place) => {
+                self.refs.get(&place.as_local()?.as_usize()).copied()
+            }
+            _ => None,
+        }
+    }
+
+    /// The offset of the place from the pointee of its local, e.g., of the field `(*p).1`, and
+    /// the type of the place. The offset is None if it is not a constant.
+    fn deref_offset(&self, place: &Place<'tcx>) -> Option<(Option<u64>, Ty<'tcx>)> {
+        let (first, rest) = place.projection.split_first()?;
+        if *first != ProjectionElem::Deref {
+            return None;
+        }
+        let local_ty = self.body.local_decls[place.local].ty;
+        let mut place_ty = PlaceTy::from_ty(local_ty).projection_ty(self.tcx, *first);
+        let mut offset = Some(0);
+        for elem in rest {
+            offset = offset
+                .zip(self.elem_offset(place_ty.ty, elem))
+                .map(|(a, b)| a + b);
+            if matches!(elem, ProjectionElem::Downcast(..)) {
+                return Some((None, place_ty.ty));
+            }
+            place_ty = place_ty.projection_ty(self.tcx, *elem);
+        }
+        Some((offset, place_ty.ty))
+    }
+
+    fn elem_offset(&self, ty: Ty<'tcx>, elem: &PlaceElem<'tcx>) -> Option<u64> {
+        match elem {
+            ProjectionElem::Field(field, _) => {
+                if matches!(ty.kind(), ty::Adt(adt, _) if adt.is_enum()) {
+                    return None;
+                }
+                let layout = self
+                    .tcx
+                    .layout_of(self.typing_env.as_query_input(ty))
+                    .ok()?;
+                Some(layout.fields.offset(field.as_usize()).bytes())
+            }
+            ProjectionElem::ConstantIndex {
+                offset,
+                from_end: false,
+                ..
+            } => {
+                let elem_ty = ty.builtin_index()?;
+                Some(self.size_of(elem_ty)? * offset)
+            }
+            ProjectionElem::OpaqueCast(_) => Some(0),
+            _ => None,
+        }
+    }
+
+    fn size_of(&self, ty: Ty<'tcx>) -> Option<u64> {
+        let layout = self
+            .tcx
+            .layout_of(self.typing_env.as_query_input(ty))
+            .ok()?;
+        Some(layout.size.bytes())
+    }
+
+    /// The ranges of the bytes that hold data rather than padding, as (offset, size). A union,
+    /// e.g., `MaybeUninit`, has no bytes required to be initialized.
+    fn data_ranges(&self, ty: Ty<'tcx>) -> Option<Vec<(u64, u64)>> {
+        let layout = self
+            .tcx
+            .layout_of(self.typing_env.as_query_input(ty))
+            .ok()?;
+        let size = layout.size.bytes();
+        if size == 0 {
+            return Some(Vec::new());
+        }
+        match ty.kind() {
+            ty::Adt(adt, _) if adt.is_union() => Some(Vec::new()),
+            ty::Adt(adt, args) if adt.is_struct() => {
+                let mut ranges = Vec::new();
+                for (index, field) in adt.non_enum_variant().fields.iter().enumerate() {
+                    let offset = layout.fields.offset(index).bytes();
+                    for (start, len) in self.data_ranges(field.ty(self.tcx, args))? {
+                        ranges.push((offset + start, len));
+                    }
+                }
+                Some(ranges)
+            }
+            ty::Tuple(tys) => {
+                let mut ranges = Vec::new();
+                for (index, field_ty) in tys.iter().enumerate() {
+                    let offset = layout.fields.offset(index).bytes();
+                    for (start, len) in self.data_ranges(field_ty)? {
+                        ranges.push((offset + start, len));
+                    }
+                }
+                Some(ranges)
+            }
+            ty::Array(elem_ty, _) => {
+                let elem_size = self.size_of(*elem_ty)?;
+                let count = size.checked_div(elem_size)?;
+                Some(repeat_ranges(
+                    &self.data_ranges(*elem_ty)?,
+                    elem_size,
+                    count,
+                ))
+            }
+            _ => Some(vec![(0, size)]),
+        }
+    }
+
+    fn const_usize(&self, op: &Operand<'tcx>) -> Option<u64> {
+        op.constant()?
+            .const_
+            .try_eval_target_usize(self.tcx, self.typing_env)
+    }
+}
+
+/// The ranges of `count` consecutive elements of `size` bytes, merged into one range if there are
+/// too many to track.
+fn repeat_ranges(ranges: &[(u64, u64)], size: u64, count: u64) -> Vec<(u64, u64)> {
+    if size.saturating_mul(count) > MAX_TRACKED_BYTES {
+        return vec![(0, MAX_TRACKED_BYTES)];
+    }
+    (0..count)
+        .flat_map(|index| {
+            ranges
+                .iter()
+                .map(move |(start, len)| (index * size + start, *len))
+        })
+        .collect()
+}
+
+fn offset_by(offset: Option<u64>, delta: Option<u64>, negative: bool) -> Option<u64> {
+    let (offset, delta) = (offset?, delta?);
+    if negative {
+        offset.checked_sub(delta)
+    } else {
+        offset.checked_add(delta)
+    }
+}
+
+fn is_deref(place: &Place<'_>) -> bool {
+    matches!(place.projection.as_slice(), [ProjectionElem::Deref])
+}
+
+/// The places read by the rvalue.
+fn rvalue_places<'a, 'tcx>(rvalue: &'a Rvalue<'tcx>) -> Vec<&'a Place<'tcx>> {
+    let operands: Vec<&Operand<'tcx>> = match rvalue {
+        Rvalue::Use(op) | Rvalue::Cast(_, op, _) | Rvalue::UnaryOp(_, op) => vec![op],
+        Rvalue::Repeat(op, _) => vec![op],
+        Rvalue::BinaryOp(_, box (lhs, rhs)) => vec![lhs, rhs],
+        Rvalue::Aggregate(_, ops) => ops.iter().collect(),
+        Rvalue::Discriminant(place) | Rvalue::CopyForDeref(place) => return vec![place],
+        _ => Vec::new(),
+    };
+    operands
+        .into_iter()
+        .filter_map(|op| match op {
+            Operand::Copy(place) | Operand::Move(place) => Some(place),
+            _ => None,
+        })
+        .collect()
+}
@@ -0,0 +1,66 @@
+pub mod init_state;
+
+use crate::utils::{
+    diagnostics::{Finding, FindingKind},
+    log::{span_to_filename, span_to_line_number},
+};
+use init_state::{InitAnalysis, Object, UninitRead};
+use rustc_middle::ty::TyCtxt;
+use rustc_span::def_id::DefId;
+
+/// Detect the reads of uninitialized memory, e.g., `MaybeUninit::uninit().assume_init()`, or
+/// `Vec::with_capacity` followed by `set_len` without writing the elements.
+pub struct UninitCheck<'tcx> {
+    pub tcx: TyCtxt<'tcx>,
+}
+
+impl<'tcx> UninitCheck<'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>) -> Self {
+        Self { tcx }
+    }
+
+    pub fn start(&self) {
+        for local_def_id in self.tcx.mir_keys(()) {
+            let def_id = local_def_id.to_def_id();
+            if self.tcx.hir_body_const_context(*local_def_id).is_some()
+                || !self.tcx.is_mir_available(def_id)
+            {
+                continue;
+            }
+            let mut analysis = InitAnalysis::new(self.tcx, def_id);
+            analysis.run();
+            if !analysis.reads.is_empty() {
+                self.report(def_id, &analysis.objects, &analysis.reads);
+            }
+        }
+    }
+
+    fn report(&self, def_id: DefId, objects: &[Object<'tcx>], reads: &[UninitRead]) {
+        let fn_span = self.tcx.optimized_mir(def_id).span;
+        for read in reads {
+            let object = &objects[read.obj];
+            let bytes = match read.bytes {
+                Some((start, end)) => format!("bytes {}..{} of ", start, end),
+                None => String::new(),
+            };
+            let detail = format!(
+                "Uninitialized read: Location in file {} line {}.\n    | MIR detail: {}the memory allocated by `{}` at {}:{} may be uninitialized; it is read at bb{}.",
+                span_to_filename(read.span),
+                span_to_line_number(read.span),
+                bytes,
+                object.api,
+                span_to_filename(object.span),
+                span_to_line_number(object.span),
+                read.bb,
+            );
+            Finding::new(
+                FindingKind::UninitRead,
+                def_id,
+                "Uninitialized read detected.",
+            )
+            .primary(read.span, detail.as_str())
+            .related(object.span, format!("allocated by `{}` here", object.api))
+            .report(fn_span);
+        }
+    }
+}
@@ -1,3 +1,7 @@
+use rapx::{
+    config::{RapxConfig, Recursive, find_config},
+    utils::log::rap_error_and_exit,
+};
 use std::{
     env,
     path::{Path, PathBuf},
@@ -12,7 +16,6 @@ struct Arguments {
     /// options as second half after -- in args
     args_group2: Vec<String>,
     current_exe_path: PathBuf,
-    rap_clean: bool,
 }
 
 impl Arguments {
@@ -40,28 +43,17 @@ impl Arguments {
     }
 
     fn new() -> Self {
-        fn rap_clean() -> bool {
-            match env::var("RAP_CLEAN")
-                .ok()
-                .map(|s| s.trim().to_ascii_lowercase())
-                .as_deref()
-            {
-                Some("false") => false,
-                _ => true, // clean is the preferred behavior
-            }
-        }
-
         let args: Vec<_> = env::args().collect();
         let path = env::current_exe().expect("Current executable path invalid.");
         rap_trace!("Current exe: {path:?}\tReceived args: {args:?}");
-        let [args_group1, args_group2] = split_args_by_double_dash(&args);
+        let [mut args_group1, args_group2] = split_args_by_double_dash(&args);
+        absolutize_path_args(&mut args_group1);
 
         Arguments {
             args,
             args_group1,
             args_group2,
             current_exe_path: path,
-            rap_clean: rap_clean(),
         }
     }
 
@@ -81,8 +73,34 @@ impl Arguments {
     }
 }
 
+/// `RAP_CLEAN` takes precedence over `clean` in rapx.toml.
 pub fn rap_clean() -> bool {
-    ARGS.rap_clean
+    match env::var("RAP_CLEAN")
+        .ok()
+        .map(|s| s.trim().to_ascii_lowercase())
+        .as_deref()
+    {
+        Some("false") => false,
+        Some(_) => true,
+        None => CONFIG.cargo.clean,
+    }
+}
+
+/// `RAP_RECURSIVE` takes precedence over `recursive` in rapx.toml.
+pub fn recursive() -> Recursive {
+    match env::var("RAP_RECURSIVE")
+        .ok()
+        .map(|s| s.trim().to_ascii_lowercase())
+        .as_deref()
+    {
+        Some("none") => Recursive::None,
+        Some("deep") => Recursive::Deep,
+        Some("shallow") => Recursive::Shallow,
+        None => CONFIG.cargo.recursive,
+        _ => rap_error_and_exit(
+            "`recursive` should only accept one the values: none, shallow or deep.",
+        ),
+    }
 }
 
 fn split_args_by_double_dash(args: &[String]) -> [Vec<String>; 2] {
@@ -92,8 +110,49 @@ fn split_args_by_double_dash(args: &[String]) -> [Vec<String>; 2] {
     [rap_args, cargo_args]
 }
 
+/// rapx is launched by cargo in other working directories, so the paths of output, baseline and
+/// cache files are resolved against the folder where `cargo rapx` is invoked. If `-format=sarif` is
+/// specified without `-output=`, the SARIF file is written to `./rapx.sarif`.
+fn absolutize_path_args(rap_args: &mut Vec<String>) {
+    let cwd = env::current_dir().expect("Current directory invalid.");
+    for arg in rap_args.iter_mut() {
+        for flag in [
+            "-output=",
+            "-baseline=",
+            "-write-baseline=",
+            "-config=",
+            "-cache=",
+        ] {
+            if let Some(path) = arg.strip_prefix(flag) {
+                *arg = format!("{}{}", flag, cwd.join(path).display());
+                break;
+            }
+        }
+    }
+    if rap_args.iter().any(|arg| arg == "-format=sarif")
+        && !rap_args.iter().any(|arg| arg.starts_with("-output="))
+    {
+        rap_args.push(format!("-output={}", cwd.join("rapx.sarif").display()));
+    }
+}
+
 static ARGS: LazyLock<Arguments> = LazyLock::new(Arguments::new);
 
+/// The rapx.toml in use: `-config=<path>`, or the first one found from the current folder upwards.
+static CONFIG_PATH: LazyLock<Option<PathBuf>> =
+    LazyLock::new(|| match ARGS.get_arg_flag_value("-config") {
+        Some(path) => Some(PathBuf::from(path)),
+        None => find_config(&env::current_dir().expect("Current directory invalid.")),
+    });
+
+/// The options of rapx.toml that apply to all packages.
+static CONFIG: LazyLock<RapxConfig> = LazyLock::new(|| match CONFIG_PATH.as_deref() {
+    Some(path) => RapxConfig::load(path, None).unwrap_or_else(|err| {
+        rap_error_and_exit(format!("Failed to load {}: {}", path.display(), err))
+    }),
+    None => RapxConfig::default(),
+});
+
 pub fn get_arg_flag_value(name: &str) -> Option<&'static str> {
     ARGS.get_arg_flag_value(name)
 }
@@ -106,6 +165,24 @@ pub fn rap_and_cargo_args() -> [&'static [String]; 2] {
     [&ARGS.args_group1, &ARGS.args_group2]
 }
 
+/// The rapx.toml to be forwarded to rapx via `-config=`, since rapx runs in other folders.
+pub fn config_path() -> Option<&'static Path> {
+    CONFIG_PATH.as_deref()
+}
+
+/// The dependencies are compiled by rapx to write their summaries only if use-after-free
+/// detection is enabled, either on the command line or in rapx.toml.
+pub fn needs_summaries(rap_args: &[String]) -> bool {
+    const UAF_OPTIONS: [&str; 5] = ["-F", "-F0", "-F1", "-F2", "-uaf"];
+    rap_args
+        .iter()
+        .any(|arg| UAF_OPTIONS.contains(&arg.as_str()))
+        || CONFIG
+            .analyses
+            .iter()
+            .any(|name| UAF_OPTIONS.contains(&format!("-{name}").as_str()))
+}
+
 /// If a crate being compiled is local in rustc phase.
 pub fn is_current_compile_crate() -> bool {
     ARGS.is_current_compile_crate()
@@ -138,6 +215,16 @@ pub fn current_exe_path() -> &'static Path {
     &ARGS.current_exe_path
 }
 
+/// The SARIF file to be written, if `-format=sarif` is specified.
+pub fn sarif_output() -> Option<&'static str> {
+    ARGS.get_arg_flag_value("-output")
+}
+
+/// The baseline file to be written, if `-write-baseline=` is specified.
+pub fn write_baseline() -> Option<&'static str> {
+    ARGS.get_arg_flag_value("-write-baseline")
+}
+
 /// NOTE: for simplicify in rapx argument forwarding, only `-timeout=` is correctly handled,
 /// even though both flavors are accepted here.
 pub fn timeout() -> Option<u64> {
@@ -1,23 +1,16 @@
 use crate::args;
 use cargo_metadata::camino::Utf8Path;
-use rapx::utils::log::rap_error_and_exit;
-use std::{env, process::Command, time::Duration};
+use rapx::{config::Recursive, utils::log::rap_error_and_exit};
+use std::{process::Command, time::Duration};
 use wait_timeout::ChildExt;
 
 mod workspace;
 
 pub fn run() {
-    match env::var("RAP_RECURSIVE")
-        .ok()
-        .map(|s| s.trim().to_ascii_lowercase())
-        .as_deref()
-    {
-        Some("none") | None => default_run(),
-        Some("deep") => workspace::deep_run(),
-        Some("shallow") => workspace::shallow_run(),
-        _ => rap_error_and_exit(
-            "`recursive` should only accept one the values: none, shallow or deep.",
-        ),
+    match args::recursive() {
+        Recursive::None => default_run(),
+        Recursive::Deep => workspace::deep_run(),
+        Recursive::Shallow => workspace::shallow_run(),
     }
 }
 
@@ -28,6 +21,12 @@ fn cargo_check(dir: &Utf8Path) {
 
     rap_trace!("cargo check in package folder {dir}");
     let [rap_args, cargo_args] = args::rap_and_cargo_args();
+    let mut rap_args = rap_args.to_vec();
+    if let Some(path) = args::config_path()
+        && !rap_args.iter().any(|arg| arg.starts_with("-config="))
+    {
+        rap_args.push(format!("-config={}", path.display()));
+    }
     rap_trace!("rap_args={rap_args:?}\tcargo_args={cargo_args:?}");
 
     /*Here we prepare the cargo command as cargo check, which is similar to build, but much faster*/
@@ -44,8 +43,11 @@ fn cargo_check(dir: &Utf8Path) {
 
     cmd.env(
         "RAP_ARGS",
-        serde_json::to_string(rap_args).expect("Failed to serialize args."),
+        serde_json::to_string(&rap_args).expect("Failed to serialize args."),
     );
+    if args::needs_summaries(&rap_args) {
+        cmd.env("RAP_SUMMARY", "1");
+    }
 
     // Invoke actual cargo for the job, but with different flags.
     let cargo_rap_path = args::current_exe_path();
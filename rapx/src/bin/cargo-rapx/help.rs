@@ -5,29 +5,96 @@ Usage:
 RAPx Options:
 
 Application:
-    -F or -uaf      use-after-free/double free detection.
+    -F or -uaf      use-after-free/double free/invalid free detection. The dependencies are also
+                    compiled by rapx to summarize how their functions alias and drop the arguments.
     -M or -mleak    memory leakage detection.
+    -uninit         uninitialized memory read detection, e.g., of `MaybeUninit` or `Vec::set_len`.
+    -panic          panic safety bug detection, i.e., a broken invariant when user code panics.
+    -sendsync       detect `unsafe impl Send/Sync` missing the bounds of its generic parameters.
+    -lifetime       detect references derived from raw pointers or `transmute` and returned with
+                    lifetimes not tied to any input.
+    -lock           detect double locks, lock order inversions and lock guards held across `.await`.
+    -rccycle        detect strong reference cycles of `Rc` and `Arc`, which are never freed.
+    -overflow       detect integer overflows, truncating casts and `usize` underflows used as lengths.
     -O or -opt      automatically detect code optimization chances.
     -I or -infer    (under development) infer the safety properties required by unsafe APIs.
     -V or -verify   (under development) verify if the safety requirements of unsafe API are satisfied.
 
 Analysis:
     -alias          perform alias analysis (meet-over-paths by default)
+    -alias=<backend>
+                    use the backend `mop` or `andersen` (a points-to analysis of the whole crate)
+                    for alias analysis and its clients, e.g., -F and -V; add -alias to dump the
+                    alias results
     -adg            generate API dependency graphs
     -upg            generate unsafety propagation graphs for each module.
     -upg-std        generate unsafety propagation graphs for each module of the Rust standard library
     -callgraph      generate callgraphs
+    -callgraph=<def path>
+                    only output the callers and callees of the function, also in DOT format
     -dataflow       generate dataflow graphs
     -ownedheap      analyze if the type holds a piece of memory on heap
     -pathcond       extract path constraints
     -range          perform range analysis
     -scan           print basic information of the crate, e.g., the number of APIs.
+    -json           also dump the results of the analyses above in JSON format, e.g.,
+                    `cargo rapx -alias -json` writes alias_<crate>_<type>.json.
 
 General command: 
     -help                        show help information
     -version                     show the version of RAPx
     -test-crate=<package_name>   specify the tested package in the workspace
     -timeout=<seconds>           specify the timeout seconds in running rapx
+    -format=sarif                also write the findings of bug detectors in SARIF 2.1.0 format
+    -output=<path>               specify the SARIF file to write (default: rapx.sarif)
+    -write-baseline=<path>       record the findings of this run in a baseline file
+    -baseline=<path>             only report the findings not recorded in the baseline file
+    -cache=<dir>                 reuse the summaries of unchanged functions cached in the folder,
+                                 e.g., `cargo rapx -F -cache=rapx-cache`
+    -jobs=<N>                    analyze the functions of a crate with N threads (default: 1);
+                                 the results are the same as with one thread
+    -config=<path>               specify the configuration file (default: the first rapx.toml
+                                 found from the current folder upwards)
+
+Configuration:
+    Options on the command line take precedence over those in rapx.toml:
+        analyses = ["uaf", "mleak"] # analyses to run if none is given on the command line
+        include = ["src/**"]        # only report findings in the matched source files
+        exclude = ["src/ffi/**"]    # do not report findings in the matched source files
+        [alias]                     # thresholds of alias analysis
+        field_depth = 20
+        visit_limit = 1000
+        [safedrop]                  # thresholds of use-after-free detection
+        field_depth = 20
+        visit_limit = 1000
+        [opt]
+        level = "default"           # report, default, all
+        [verify]
+        check_level = "medium"      # high, medium, low
+        [budget]                    # per-function budgets; functions over them are listed as incomplete
+        time = 60                   # seconds per function and analysis; unlimited if absent
+        scc_paths = 1000            # paths enumerated in a loop
+        paths = 1000                # paths checked by -verify
+        solver_timeout = 1000       # milliseconds per Z3 query of -mleak
+        [callgraph]
+        rta = false                 # prune the targets of virtual calls by the constructed types
+        slice_depth = 2             # depth of the slice by -callgraph=<def path>; unlimited if absent
+        [cargo]
+        recursive = "none"          # same as RAP_RECURSIVE
+        clean = true                # same as RAP_CLEAN
+        [[model]]                   # ownership effects of a function, numbering the arguments from 1
+        path = "my_crate::Pool::release"
+        frees = [2]                 # freed args; also moves (into the return value), aliases, leaks
+        [crates.my_crate]           # overrides for the package my_crate
+        analyses = ["mleak"]
+
+Suppression:
+    Findings can be suppressed with `#[rapx::allow(<kind>, ...)]` on the function or its enclosing
+    items (requires `#![feature(register_tool)]` and `#![register_tool(rapx)]`), or with an
+    allowlist in rapx.toml:
+        [[allow]]
+        kind = "uaf"                # uaf, mleak, opt, verify, all, or a rule id in the SARIF file
+        path = "my_crate::ffi::*"   # optional def path pattern
 
 NOTE: multiple detections can be processed in single run by 
 appending the options to the arguments. Like `cargo rapx -F -M`
@@ -48,10 +115,11 @@ Environment Variables (Values are case insensitive):
                      warn: show bugs detected only.
 
     RAP_CLEAN        run cargo clean before check: true, false
-                     * true is the default value except that false is set
+                     * overrides `clean` in rapx.toml, which is true by default
 
     RAP_RECURSIVE    scope of packages to check: none, shallow, deep
-                     * none or the variable not set: check for current folder
+                     * overrides `recursive` in rapx.toml
+                     * none: check for current folder (default)
                      * shallow: check for current workpace members
                      * deep: check for all workspaces from current folder
                       
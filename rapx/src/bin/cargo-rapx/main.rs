@@ -8,6 +8,7 @@
 extern crate rapx;
 
 use rapx::utils::log::{init_log, rap_error_and_exit};
+use std::env;
 
 mod args;
 mod help;
@@ -37,6 +38,14 @@ fn phase_cargo_rap() {
         _ => {}
     }
 
+    // Findings of all checked crates are appended to the output files, so start from clean ones.
+    for path in [args::sarif_output(), args::write_baseline()]
+        .into_iter()
+        .flatten()
+    {
+        let _ = std::fs::remove_file(path);
+    }
+
     cargo_check::run();
 }
 
@@ -50,6 +59,12 @@ fn phase_rustc_wrapper() {
         return;
     }
 
+    // dependencies are summarized for use-after-free detection of the local crates
+    if !is_direct && args::filter_crate_type() && env::var_os("RAP_SUMMARY").is_some() {
+        run_rap_summary();
+        return;
+    }
+
     // for dependencies and some special crate types, run rustc as usual
     run_rustc();
 }
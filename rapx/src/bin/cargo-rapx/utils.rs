@@ -32,9 +32,35 @@ pub fn run_rustc() {
 pub fn run_rap() {
     let mut cmd = Command::new(find_rap());
     cmd.args(args::skip2());
-    let magic = env::var("RAP_ARGS").expect("Missing RAP_ARGS.");
-    let rap_args: Vec<String> =
-        serde_json::from_str(&magic).expect("Failed to deserialize RAP_ARGS.");
-    cmd.args(rap_args);
+    cmd.args(rap_args());
     run_cmd(cmd);
 }
+
+fn rap_args() -> Vec<String> {
+    let magic = env::var("RAP_ARGS").expect("Missing RAP_ARGS.");
+    serde_json::from_str(&magic).expect("Failed to deserialize RAP_ARGS.")
+}
+
+/// Compile the dependency with rapx to write its summaries; as the summaries are optional, fall
+/// back to rustc if rapx fails on it.
+pub fn run_rap_summary() {
+    let mut cmd = Command::new(find_rap());
+    cmd.args(args::skip2());
+    cmd.arg("-summary");
+    cmd.args(
+        rap_args()
+            .into_iter()
+            .filter(|arg| arg.starts_with("-cache=") || arg.starts_with("-jobs=")),
+    );
+    rap_trace!("Command is: {:?}.", cmd);
+    match cmd.status() {
+        Ok(status) if status.success() => {}
+        _ => {
+            rap_warn!(
+                "Failed to summarize crate {}; compile it without summaries.",
+                args::get_arg_flag_value("--crate-name").unwrap_or("unknown")
+            );
+            run_rustc();
+        }
+    }
+}
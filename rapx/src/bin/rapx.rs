@@ -3,11 +3,17 @@
 extern crate rustc_driver;
 extern crate rustc_session;
 
-use rapx::{RAP_DEFAULT_ARGS, RapCallback, rap_info, rap_trace, utils::log::init_log};
+use rapx::{
+    RAP_DEFAULT_ARGS, RapCallback,
+    analysis::opt::OptLevel,
+    config::{CONFIG_FILE, RapxConfig, find_config},
+    rap_info, rap_trace, rap_warn,
+    utils::log::{init_log, rap_error_and_exit},
+};
 use regex::Regex;
 use rustc_session::EarlyDiagCtxt;
 use rustc_session::config::ErrorOutputType;
-use std::env;
+use std::{env, path::PathBuf};
 
 fn run_complier(args: &mut Vec<String>, callback: &mut RapCallback) {
     // Finally, add the default flags all the way in the beginning, but after the binary name.
@@ -21,11 +27,76 @@ fn run_complier(args: &mut Vec<String>, callback: &mut RapCallback) {
     rap_trace!("The arg for compilation is {:?}", args);
 }
 
+/// Load `-config=<path>`, or find `rapx.toml` from the package folder if rapx is not launched
+/// by `cargo rapx`, with the overrides of the package being compiled.
+fn load_config() -> RapxConfig {
+    let path = env::args()
+        .find_map(|arg| arg.strip_prefix("-config=").map(PathBuf::from))
+        .or_else(|| find_config(&PathBuf::from(env::var("CARGO_MANIFEST_DIR").ok()?)));
+    let Some(path) = path else {
+        return RapxConfig::default();
+    };
+    let package_name = env::var("CARGO_PKG_NAME").ok();
+    match RapxConfig::load(&path, package_name.as_deref()) {
+        Ok(config) => config,
+        Err(err) => rap_error_and_exit(format!("Failed to load {}: {}", path.display(), err)),
+    }
+}
+
+/// Enable the analysis specified by the option; returns false if it is not an analysis.
+fn enable_analysis(compiler: &mut RapCallback, arg: &str) -> bool {
+    match arg {
+        "-alias" | "-alias0" | "-alias1" | "-alias2" => compiler.enable_alias(arg.to_owned()),
+        x if x.starts_with("-alias=") => compiler.set_alias_backend(x),
+        "-adg" => compiler.enable_api_dependency(), // api dependency graph
+        "-callgraph" => compiler.enable_callgraph(),
+        x if x.starts_with("-callgraph=") => {
+            compiler.set_callgraph_slice(&x["-callgraph=".len()..])
+        }
+        "-dataflow" => compiler.enable_dataflow(1),
+        "-dataflow=debug" => compiler.enable_dataflow(2),
+        "-ownedheap" => compiler.enable_ownedheap(),
+        "-range" => compiler.enable_range_analysis(1),
+        "-range=print_mir" => compiler.enable_range_analysis(2),
+        "-pathcond" => compiler.enable_range_analysis(3),
+        "-test" => compiler.enable_test(),
+        "-F" | "-F0" | "-F1" | "-F2" | "-uaf" => compiler.enable_safedrop(arg.to_owned()),
+        // used by cargo-rapx for the dependencies of the crates checked by safedrop
+        "-summary" => compiler.enable_summary(),
+        "-I" | "-infer" => compiler.enable_infer(),
+        "-M" | "-mleak" => compiler.enable_rcanary(),
+        "-uninit" => compiler.enable_uninit(),
+        "-panic" => compiler.enable_panic_safety(),
+        "-sendsync" => compiler.enable_send_sync(),
+        "-lifetime" => compiler.enable_lifetime(),
+        "-lock" => compiler.enable_lock(),
+        "-rccycle" => compiler.enable_rc_cycle(),
+        "-overflow" => compiler.enable_overflow(),
+        "-V" | "-verify" => compiler.enable_verify(),
+        "-O" | "-opt" => compiler.enable_opt(None),
+        "-opt=all" => compiler.enable_opt(Some(OptLevel::All)),
+        "-opt=report" => compiler.enable_opt(Some(OptLevel::Report)),
+        "-scan" => compiler.enable_scan(),
+        "-ssa" => compiler.enable_ssa_transform(),
+        "-upg" => compiler.enable_upg(1),
+        "-upg-std" => compiler.enable_upg(2),
+        "-verify-std" => compiler.enable_verify_std(),
+        "-mir" => compiler.enable_show_mir(),
+        "-dotmir" => compiler.enable_show_mir_dot(),
+        _ => return false,
+    }
+    true
+}
+
 fn main() {
+    _ = init_log().inspect_err(|err| eprintln!("Failed to init log: {err}"));
+
     // Parse the arguments from env.
     let mut args = vec![];
     let mut compiler = RapCallback::default();
     let re_test_crate = Regex::new(r"-test-crate=(\S*)").unwrap();
+    let mut has_analysis = false;
+    compiler.set_config(load_config());
 
     for arg in env::args() {
         if let Some((_full, [test_crate_name])) =
@@ -34,37 +105,39 @@ fn main() {
             compiler.set_test_crate(test_crate_name.to_owned());
             continue;
         }
+        if enable_analysis(&mut compiler, &arg) {
+            has_analysis = true;
+            continue;
+        }
         match arg.as_str() {
-            "-alias" | "-alias0" | "-alias1" | "-alias2" => compiler.enable_alias(arg),
-            "-adg" => compiler.enable_api_dependency(), // api dependency graph
-            "-callgraph" => compiler.enable_callgraph(),
-            "-dataflow" => compiler.enable_dataflow(1),
-            "-dataflow=debug" => compiler.enable_dataflow(2),
-            "-ownedheap" => compiler.enable_ownedheap(),
-            "-range" => compiler.enable_range_analysis(1),
-            "-range=print_mir" => compiler.enable_range_analysis(2),
-            "-pathcond" => compiler.enable_range_analysis(3),
-            "-test" => compiler.enable_test(),
-            "-F" | "-F0" | "-F1" | "-F2" | "-uaf" => compiler.enable_safedrop(arg),
-            "-I" | "-infer" => compiler.enable_infer(),
-            "-M" | "-mleak" => compiler.enable_rcanary(),
-            "-V" | "-verify" => compiler.enable_verify(),
-            "-O" | "-opt" => compiler.enable_opt(1),
-            "-opt=all" => compiler.enable_opt(2),
-            "-opt=report" => compiler.enable_opt(0),
-            "-scan" => compiler.enable_scan(),
-            "-ssa" => compiler.enable_ssa_transform(),
-            "-upg" => compiler.enable_upg(1),
-            "-upg-std" => compiler.enable_upg(2),
-            "-verify-std" => compiler.enable_verify_std(),
-            "-mir" => compiler.enable_show_mir(),
-            "-dotmir" => compiler.enable_show_mir_dot(),
+            "-json" => compiler.enable_json(),
+            "-format=sarif" => compiler.enable_sarif(),
+            x if x.starts_with("-output=") => compiler.set_output(&x["-output=".len()..]),
+            x if x.starts_with("-baseline=") => compiler.set_baseline(&x["-baseline=".len()..]),
+            x if x.starts_with("-write-baseline=") => {
+                compiler.set_write_baseline(&x["-write-baseline=".len()..])
+            }
+            x if x.starts_with("-cache=") => compiler.set_cache_dir(&x["-cache=".len()..]),
+            // The per-function analyses run on the thread pool of rustc.
+            x if x.starts_with("-jobs=") => match x["-jobs=".len()..].parse::<usize>() {
+                Ok(jobs) if jobs > 0 => args.push(format!("-Zthreads={}", jobs)),
+                _ => rap_error_and_exit(format!("Invalid number of jobs: {}", x)),
+            },
+            // -config has been loaded before parsing other arguments
+            x if x.starts_with("-config=") => (),
             // -timeout has been handled in cargo-rapx
             x if x.starts_with("-timeout=") => (),
             _ => args.push(arg),
         }
     }
-    _ = init_log().inspect_err(|err| eprintln!("Failed to init log: {err}"));
+    // The analyses in rapx.toml are used only if none is specified on the command line.
+    if !has_analysis {
+        for name in compiler.config().analyses.clone() {
+            if !enable_analysis(&mut compiler, &format!("-{}", name)) {
+                rap_warn!("Unknown analysis `{}` in {}.", name, CONFIG_FILE);
+            }
+        }
+    }
     rap_info!("Start analysis with RAPx.");
     rap_trace!("rap received arguments: {:#?}", env::args());
     rap_trace!("arguments to rustc: {:?}", &args);
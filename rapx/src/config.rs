@@ -0,0 +1,172 @@
+//! Project configuration loaded from `rapx.toml`.
+//!
+//! `cargo rapx` looks for `rapx.toml` from the current folder up to the root, and forwards the
+//! first one found to rapx via `-config=<path>`. Options given on the command line take
+//! precedence over the file. For example:
+//! ```toml
+//! # The analyses to run if none is given on the command line, named as the options without `-`.
+//! analyses = ["uaf", "mleak", "opt=all"]
+//! # Only report findings in the source files matching the globs, relative to the package folder.
+//! include = ["src/**"]
+//! exclude = ["src/ffi/**"]
+//!
+//! [alias]
+//! field_depth = 20        # -alias0: 10, -alias1: 20, -alias2: 30
+//! visit_limit = 1000
+//!
+//! [safedrop]
+//! field_depth = 20        # -F0: 10, -F1: 20, -F2: 30
+//! visit_limit = 1000
+//!
+//! [opt]
+//! level = "default"       # report, default, all
+//!
+//! [verify]
+//! check_level = "medium"  # high, medium, low; used by -verify and -infer
+//!
+//! [budget]
+//! time = 60               # seconds per function and analysis; unlimited if absent
+//! scc_paths = 1000        # paths enumerated in a loop
+//! paths = 1000            # paths checked by -verify
+//! solver_timeout = 1000   # milliseconds per Z3 query of -mleak
+//!
+//! [callgraph]
+//! rta = false             # prune the targets of virtual calls with rapid type analysis
+//! slice_depth = 2         # the depth of the slice by -callgraph=<def path>; unlimited if absent
+//!
+//! [cargo]
+//! recursive = "none"      # none, shallow, deep; overridden by RAP_RECURSIVE
+//! clean = true            # overridden by RAP_CLEAN
+//!
+//! [[allow]]
+//! kind = "uaf"
+//! path = "my_crate::ffi::*"
+//!
+//! # The ownership effects of a function for -uaf; the arguments are numbered from 1.
+//! [[model]]
+//! path = "my_crate::pool::Pool::release"
+//! frees = [2]             # also moves, aliases and leaks
+//!
+//! # Overrides for the package `foo`, merged into the options above.
+//! [crates.foo]
+//! analyses = ["mleak"]
+//! safedrop.field_depth = 10
+//! ```
+
+use crate::{
+    analysis::{
+        core::{
+            alias_analysis::default::MopConfig, callgraph::CallGraphConfig,
+            ownership_model::FnModel,
+        },
+        opt::OptLevel,
+        senryx::CheckLevel,
+    },
+    utils::{budget::BudgetConfig, suppression::AllowEntry},
+};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+pub const CONFIG_FILE: &str = "rapx.toml";
+
+#[derive(Debug, Clone, Default, Hash, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RapxConfig {
+    /// The analyses to run if none is specified on the command line.
+    pub analyses: Vec<String>,
+    /// Path globs of the source files whose findings are reported; all files if empty.
+    pub include: Vec<String>,
+    /// Path globs of the source files whose findings are not reported.
+    pub exclude: Vec<String>,
+    pub alias: MopConfig,
+    pub safedrop: MopConfig,
+    pub opt: OptConfig,
+    pub verify: VerifyConfig,
+    pub budget: BudgetConfig,
+    pub callgraph: CallGraphConfig,
+    pub cargo: CargoConfig,
+    pub allow: Vec<AllowEntry>,
+    /// The ownership effects of functions, in addition to the model shipped with RAPx.
+    pub model: Vec<FnModel>,
+}
+
+#[derive(Debug, Clone, Default, Hash, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OptConfig {
+    pub level: OptLevel,
+}
+
+#[derive(Debug, Clone, Default, Hash, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct VerifyConfig {
+    pub check_level: CheckLevel,
+}
+
+/// Options of `cargo rapx` itself, which are ignored by rapx.
+#[derive(Debug, Clone, Hash, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CargoConfig {
+    pub recursive: Recursive,
+    /// Run `cargo clean` before checking the package.
+    pub clean: bool,
+}
+
+impl Default for CargoConfig {
+    fn default() -> Self {
+        Self {
+            recursive: Recursive::default(),
+            clean: true,
+        }
+    }
+}
+
+/// The scope of packages to check.
+#[derive(Debug, Clone, Copy, Default, Hash, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Recursive {
+    /// Check the package in the current folder.
+    #[default]
+    None,
+    /// Check the members of the workspace in the current folder.
+    Shallow,
+    /// Check all the workspaces under the current folder.
+    Deep,
+}
+
+impl RapxConfig {
+    /// Load the configuration file with the overrides of the package `crate_name` applied.
+    pub fn load(path: impl AsRef<Path>, crate_name: Option<&str>) -> Result<Self, String> {
+        let content = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
+        Self::parse(&content, crate_name).map_err(|err| err.to_string())
+    }
+
+    pub fn parse(content: &str, crate_name: Option<&str>) -> Result<Self, toml::de::Error> {
+        let mut table: toml::Table = toml::from_str(content)?;
+        if let Some(toml::Value::Table(mut crates)) = table.remove("crates")
+            && let Some(toml::Value::Table(overrides)) =
+                crate_name.and_then(|name| crates.remove(name))
+        {
+            merge_table(&mut table, overrides);
+        }
+        toml::Value::Table(table).try_into()
+    }
+}
+
+/// Find `rapx.toml` in the folder or its ancestors.
+pub fn find_config(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .map(|dir| dir.join(CONFIG_FILE))
+        .find(|path| path.is_file())
+}
+
+/// Tables are merged recursively; other values in `overrides` replace those in `base`.
+fn merge_table(base: &mut toml::Table, overrides: toml::Table) {
+    for (key, value) in overrides {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(value)) => merge_table(base, value),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
@@ -5,6 +5,7 @@
 #[macro_use]
 pub mod utils;
 pub mod analysis;
+pub mod config;
 pub mod def_id;
 pub mod preprocess;
 extern crate intervals;
@@ -28,40 +29,57 @@ extern crate rustc_trait_selection;
 extern crate rustc_traits;
 extern crate rustc_type_ir;
 extern crate thin_vec;
-use crate::analysis::scan::ScanAnalysis;
+use crate::{
+    analysis::{opt::OptLevel, scan::ScanAnalysis},
+    config::RapxConfig,
+    utils::{budget, diagnostics, json, log::rap_error_and_exit, suppression},
+};
 use analysis::{
     Analysis,
     core::{
-        alias_analysis::{AAResultMapWrapper, AliasAnalysis, default::AliasAnalyzer},
+        alias_analysis::{
+            AAResultMapWrapper, AliasAnalysis, AliasBackend, andersen::AndersenAnalyzer,
+            default::AliasAnalyzer,
+        },
         api_dependency::ApiDependencyAnalyzer,
         callgraph::{CallGraphAnalysis, CallGraphDisplay, default::CallGraphAnalyzer},
         dataflow::{
             Arg2RetMapWrapper, DataFlowAnalysis, DataFlowGraphMapWrapper, default::DataFlowAnalyzer,
         },
         ownedheap_analysis::{OHAResultMapWrapper, OwnedHeapAnalysis, default::OwnedHeapAnalyzer},
+        ownership_model,
         range_analysis::{
-            PathConstraintMapWrapper, RAResultMapWrapper, RangeAnalysis, default::RangeAnalyzer,
+            PathConstraintMapWrapper, RangeAnalysis, TypedRAResultMapWrapper,
+            default::{RangeAnalyzer, TypedRangeAnalyzer},
         },
         ssa_transform::SSATrans,
     },
+    lifetime::UnboundedLifetimeCheck,
+    lock::LockCheck,
     opt::Opt,
+    overflow::OverflowCheck,
+    panic_safety::PanicSafetyCheck,
+    rc_cycle::RcCycleCheck,
     rcanary::rCanary,
     safedrop::SafeDrop,
-    senryx::{CheckLevel, SenryxCheck},
+    send_sync::SendSyncCheck,
+    senryx::SenryxCheck,
     test::Test,
+    uninit::UninitCheck,
     upg::{TargetCrate, UPGAnalysis},
     utils::show_mir::ShowMir,
 };
 use rustc_ast::ast;
 use rustc_driver::{Callbacks, Compilation};
+use rustc_hir::def_id::LOCAL_CRATE;
 use rustc_interface::{
     Config,
     interface::{self, Compiler},
 };
 use rustc_middle::{ty::TyCtxt, util::Providers};
 use rustc_session::search_paths::PathKind;
-use std::path::PathBuf;
-use std::{env, sync::Arc};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 // Insert rustc arguments at the beginning of the argument list that RAP wants to be
 // set per default, for maximal validation power.
@@ -78,24 +96,41 @@ pub static RAP_DEFAULT_ARGS: &[&str] = &[
 #[derive(Debug, Clone, Hash)]
 pub struct RapCallback {
     alias: bool,
+    alias_backend: AliasBackend,
     api_dependency: bool,
     callgraph: bool,
+    callgraph_slice: Option<String>,
     dataflow: usize,
     ownedheap: bool,
     range: usize,
     ssa: bool,
     test: bool,
     infer: bool,
-    opt: usize,
+    lifetime: bool,
+    lock: bool,
+    rc_cycle: bool,
+    overflow: bool,
+    opt: bool,
+    panic_safety: bool,
     rcanary: bool,
     safedrop: bool,
+    summary: bool,
+    uninit: bool,
     show_mir: bool,
     show_mir_dot: bool,
     upg: usize,
     verify: bool,
     verify_std: bool,
     scan: bool,
+    send_sync: bool,
+    sarif: bool,
+    output: Option<String>,
+    json: bool,
+    baseline: Option<String>,
+    write_baseline: Option<String>,
+    cache: Option<String>,
     test_crate: Option<String>,
+    config: RapxConfig,
 }
 
 #[allow(clippy::derivable_impls)]
@@ -103,24 +138,41 @@ impl Default for RapCallback {
     fn default() -> Self {
         Self {
             alias: false,
+            alias_backend: AliasBackend::Mop,
             api_dependency: false,
             callgraph: false,
+            callgraph_slice: None,
             dataflow: 0,
             ownedheap: false,
             range: 0,
             ssa: false,
             test: false,
             infer: false,
-            opt: usize::MAX,
+            lifetime: false,
+            lock: false,
+            rc_cycle: false,
+            overflow: false,
+            opt: false,
+            panic_safety: false,
             rcanary: false,
             safedrop: false,
+            summary: false,
+            uninit: false,
             show_mir: false,
             show_mir_dot: false,
             upg: 0,
             verify: false,
             verify_std: false,
             scan: false,
+            send_sync: false,
+            sarif: false,
+            output: None,
+            json: false,
+            baseline: None,
+            write_baseline: None,
+            cache: None,
             test_crate: None,
+            config: RapxConfig::default(),
         }
     }
 }
@@ -183,31 +235,44 @@ impl RapCallback {
         }
     }
 
+    /// Set the options loaded from `rapx.toml`; the options from the command line should be
+    /// applied afterwards to take precedence.
+    pub fn set_config(&mut self, config: RapxConfig) {
+        self.config = config;
+    }
+
+    pub fn config(&self) -> &RapxConfig {
+        &self.config
+    }
+
     /// Enable alias analysis. The parameter is used to config the threshold of alias analysis.
     /// Currently, we mainly use it to control the depth of field-sensitive analysis.
-    /// -alias0: set field depth limit to 10; do not distinguish different flows within a each
-    /// strongly-connected component.
+    /// -alias0: set field depth limit to 10.
     /// -alias1: set field depth limit to 20 (this is default setting).
     /// -alias2: set field depth limit to 30.
+    /// -alias: use the field depth limit in `rapx.toml`.
     pub fn enable_alias(&mut self, arg: String) {
         self.alias = true;
-        match arg.as_str() {
-            "-alias" => unsafe {
-                env::set_var("ALIAS", "1");
-            },
-            "-alias0" => unsafe {
-                env::set_var("ALIAS", "0");
-            },
-            "-alias1" => unsafe {
-                env::set_var("ALIAS", "1");
-            },
-            "-alias2" => unsafe {
-                env::set_var("ALIAS", "2");
-            },
-            _ => {}
+        if let Some(depth) = field_depth_of(arg.strip_prefix("-alias")) {
+            self.config.alias.field_depth = depth;
+        }
+    }
+
+    /// Use the backend `mop` or `andersen` given by `-alias=<backend>`, both for the alias analysis
+    /// and its clients such as SafeDrop and senryx. It does not enable the alias analysis itself.
+    pub fn set_alias_backend(&mut self, arg: &str) {
+        let name = arg.strip_prefix("-alias=").unwrap_or(arg);
+        match AliasBackend::from_name(name) {
+            Some(backend) => self.alias_backend = backend,
+            None => rap_error_and_exit(format!("Unknown alias analysis backend: {}", name)),
         }
     }
 
+    /// The backend of alias analysis used by the analyses.
+    pub fn get_alias_backend(&self) -> AliasBackend {
+        self.alias_backend
+    }
+
     /// Test if alias analysis is enabled.
     pub fn is_alias_enabled(&self) -> bool {
         self.alias
@@ -233,6 +298,16 @@ impl RapCallback {
         self.callgraph
     }
 
+    /// Only output the call graph around the function, which is also written in the DOT format.
+    pub fn set_callgraph_slice(&mut self, def_path: &str) {
+        self.callgraph = true;
+        self.callgraph_slice = Some(def_path.to_owned());
+    }
+
+    pub fn get_callgraph_slice(&self) -> Option<&str> {
+        self.callgraph_slice.as_deref()
+    }
+
     /// Enable owned heap analysis.
     pub fn enable_ownedheap(&mut self) {
         self.ownedheap = true;
@@ -283,13 +358,17 @@ impl RapCallback {
         self.ssa
     }
 
-    /// Enable optimization analysis for performance bug detection.
-    pub fn enable_opt(&mut self, x: usize) {
-        self.opt = x;
+    /// Enable optimization analysis for performance bug detection. The level in `rapx.toml` is
+    /// used if not specified.
+    pub fn enable_opt(&mut self, level: Option<OptLevel>) {
+        self.opt = true;
+        if let Some(level) = level {
+            self.config.opt.level = level;
+        }
     }
 
     /// Test if optimization analysis is enabled.
-    pub fn is_opt_enabled(&self) -> usize {
+    pub fn is_opt_enabled(&self) -> bool {
         self.opt
     }
 
@@ -304,52 +383,12 @@ impl RapCallback {
     }
 
     /// Enable safedrop for use-after-free bug detection.
-    /// Similar to alias analysis, the second parameter is to control the depth threshold for
-    /// field-sensitive analysis.
+    /// Similar to alias analysis, the parameter is to control the depth threshold for
+    /// field-sensitive analysis: -F0, -F1 and -F2 set it to 10, 20 and 30 respectively.
     pub fn enable_safedrop(&mut self, arg: String) {
         self.safedrop = true;
-        match arg.as_str() {
-            "-F" => {
-                unsafe {
-                    env::set_var("SAFEDROP", "1");
-                }
-                unsafe {
-                    env::set_var("MOP", "1");
-                }
-            }
-            "-F0" => {
-                unsafe {
-                    env::set_var("SAFEDROP", "0");
-                }
-                unsafe {
-                    env::set_var("MOP", "0");
-                }
-            }
-            "-F1" => {
-                unsafe {
-                    env::set_var("SAFEDROP", "1");
-                }
-                unsafe {
-                    env::set_var("MOP", "1");
-                }
-            }
-            "-F2" => {
-                unsafe {
-                    env::set_var("SAFEDROP", "2");
-                }
-                unsafe {
-                    env::set_var("MOP", "2");
-                }
-            }
-            "-uaf" => {
-                unsafe {
-                    env::set_var("SAFEDROP", "1");
-                }
-                unsafe {
-                    env::set_var("MOP", "1");
-                }
-            }
-            _ => {}
+        if let Some(depth) = field_depth_of(arg.strip_prefix("-F")) {
+            self.config.safedrop.field_depth = depth;
         }
     }
 
@@ -358,6 +397,89 @@ impl RapCallback {
         self.safedrop
     }
 
+    /// Enable the detection of uninitialized memory reads.
+    pub fn enable_uninit(&mut self) {
+        self.uninit = true;
+    }
+
+    /// Test if the detection of uninitialized memory reads is enabled.
+    pub fn is_uninit_enabled(&self) -> bool {
+        self.uninit
+    }
+
+    /// Enable the detection of panic safety bugs.
+    pub fn enable_panic_safety(&mut self) {
+        self.panic_safety = true;
+    }
+
+    /// Test if the detection of panic safety bugs is enabled.
+    pub fn is_panic_safety_enabled(&self) -> bool {
+        self.panic_safety
+    }
+
+    /// Enable the detection of unsound `unsafe impl Send/Sync`.
+    pub fn enable_send_sync(&mut self) {
+        self.send_sync = true;
+    }
+
+    /// Test if the detection of unsound `unsafe impl Send/Sync` is enabled.
+    pub fn is_send_sync_enabled(&self) -> bool {
+        self.send_sync
+    }
+
+    /// Enable the detection of references returned with unbounded lifetimes.
+    pub fn enable_lifetime(&mut self) {
+        self.lifetime = true;
+    }
+
+    /// Test if the detection of references returned with unbounded lifetimes is enabled.
+    pub fn is_lifetime_enabled(&self) -> bool {
+        self.lifetime
+    }
+
+    /// Enable the detection of double locks, lock order inversions and guards across `.await`.
+    pub fn enable_lock(&mut self) {
+        self.lock = true;
+    }
+
+    /// Test if the detection of double locks, lock order inversions and guards across `.await`
+    /// is enabled.
+    pub fn is_lock_enabled(&self) -> bool {
+        self.lock
+    }
+
+    /// Enable the detection of strong reference cycles of `Rc` and `Arc`.
+    pub fn enable_rc_cycle(&mut self) {
+        self.rc_cycle = true;
+    }
+
+    /// Test if the detection of strong reference cycles of `Rc` and `Arc` is enabled.
+    pub fn is_rc_cycle_enabled(&self) -> bool {
+        self.rc_cycle
+    }
+
+    /// Enable the detection of integer overflows, truncating casts and `usize` underflows.
+    pub fn enable_overflow(&mut self) {
+        self.overflow = true;
+    }
+
+    /// Test if the detection of integer overflows, truncating casts and `usize` underflows is
+    /// enabled.
+    pub fn is_overflow_enabled(&self) -> bool {
+        self.overflow
+    }
+
+    /// Only write the alias and drop summaries of the crate, which is a dependency of the crates
+    /// checked by safedrop.
+    pub fn enable_summary(&mut self) {
+        self.summary = true;
+    }
+
+    /// Test if summary generation is enabled.
+    pub fn is_summary_enabled(&self) -> bool {
+        self.summary
+    }
+
     /// Enable mir display.
     pub fn enable_show_mir(&mut self) {
         self.show_mir = true;
@@ -416,18 +538,100 @@ impl RapCallback {
         self.scan
     }
 
+    /// Enable writing the findings of bug detectors in SARIF format.
+    pub fn enable_sarif(&mut self) {
+        self.sarif = true;
+    }
+
+    pub fn is_sarif_enabled(&self) -> bool {
+        self.sarif
+    }
+
+    /// Set the path of the SARIF file; `rapx.sarif` is used if not set.
+    pub fn set_output(&mut self, path: impl ToString) {
+        self.output = Some(path.to_string());
+    }
+
+    pub fn get_output(&self) -> &str {
+        self.output.as_deref().unwrap_or("rapx.sarif")
+    }
+
+    /// Enable dumping the results of core analyses in JSON format.
+    pub fn enable_json(&mut self) {
+        self.json = true;
+    }
+
+    pub fn is_json_enabled(&self) -> bool {
+        self.json
+    }
+
+    /// Set the baseline file; findings recorded in it are not reported.
+    pub fn set_baseline(&mut self, path: impl ToString) {
+        self.baseline = Some(path.to_string());
+    }
+
+    pub fn get_baseline(&self) -> Option<&str> {
+        self.baseline.as_deref()
+    }
+
+    /// Set the file to record the findings of this run as a baseline.
+    pub fn set_write_baseline(&mut self, path: impl ToString) {
+        self.write_baseline = Some(path.to_string());
+    }
+
+    pub fn get_write_baseline(&self) -> Option<&str> {
+        self.write_baseline.as_deref()
+    }
+
+    /// Set the folder to cache the summaries of functions across runs.
+    pub fn set_cache_dir(&mut self, path: impl ToString) {
+        self.cache = Some(path.to_string());
+    }
+
+    pub fn get_cache_dir(&self) -> Option<&Path> {
+        self.cache.as_deref().map(Path::new)
+    }
+
     pub fn set_test_crate(&mut self, crate_name: impl ToString) {
         self.test_crate = Some(crate_name.to_string())
     }
 }
 
+/// Map the level suffix of `-alias` and `-F` to the depth limit of field-sensitive analysis.
+fn field_depth_of(level: Option<&str>) -> Option<usize> {
+    match level? {
+        "0" => Some(10),
+        "1" => Some(20),
+        "2" => Some(30),
+        _ => None,
+    }
+}
+
 /// Start the analysis with the features enabled.
 pub fn start_analyzer(tcx: TyCtxt, callback: &RapCallback) {
+    let config = callback.config();
+    if let Err(err) = suppression::init(config, callback.get_baseline()) {
+        rap_error_and_exit(format!("Failed to load the suppression rules: {}", err));
+    }
+    budget::init(config.budget);
+    ownership_model::init(config.model.clone());
+
     if callback.is_alias_enabled() {
-        let mut analyzer = AliasAnalyzer::new(tcx);
-        analyzer.run();
-        let alias = analyzer.get_local_fn_alias();
-        rap_info!("{}", AAResultMapWrapper(alias));
+        let alias = match callback.get_alias_backend() {
+            AliasBackend::Mop => {
+                let mut analyzer = AliasAnalyzer::with_config(tcx, config.alias)
+                    .with_cache(callback.get_cache_dir());
+                analyzer.run();
+                AAResultMapWrapper(analyzer.get_local_fn_alias())
+            }
+            AliasBackend::Andersen => {
+                let mut analyzer = AndersenAnalyzer::with_config(tcx, config.alias);
+                analyzer.run();
+                AAResultMapWrapper(analyzer.get_local_fn_alias())
+            }
+        };
+        rap_info!("{}", alias);
+        dump_json(tcx, callback, "alias", &alias);
     }
 
     if callback.is_api_dependency_enabled() {
@@ -443,16 +647,39 @@ pub fn start_analyzer(tcx: TyCtxt, callback: &RapCallback) {
     }
 
     if callback.is_callgraph_enabled() {
-        let mut analyzer = CallGraphAnalyzer::new(tcx);
+        let mut analyzer = CallGraphAnalyzer::with_config(tcx, config.callgraph);
         analyzer.run();
-        let callgraph = analyzer.get_callgraph();
-        rap_info!(
-            "{}",
-            CallGraphDisplay {
-                graph: &callgraph,
-                tcx
+        rap_debug!("{}", analyzer.get_instance_callgraph());
+        let mut callgraph = analyzer.get_callgraph();
+        if let Some(def_path) = callback.get_callgraph_slice() {
+            match callgraph.find(tcx, def_path) {
+                Some(def_id) => {
+                    callgraph = callgraph.slice(def_id, config.callgraph.slice_depth);
+                }
+                None => rap_warn!("Function {} is not found in the call graph.", def_path),
             }
-        );
+        }
+        let display = CallGraphDisplay {
+            graph: &callgraph,
+            tcx,
+        };
+        rap_info!("{}", display);
+        for scc in callgraph.recursive_sccs() {
+            let scc: Vec<_> = scc.iter().map(|def_id| tcx.def_path_str(*def_id)).collect();
+            rap_info!("Recursion: {}", scc.join(", "));
+        }
+        if callback.get_callgraph_slice().is_some() {
+            let path = format!(
+                "callgraph_{}_{}.dot",
+                tcx.crate_name(LOCAL_CRATE),
+                tcx.crate_types()[0]
+            );
+            match std::fs::write(&path, display.to_dot()) {
+                Ok(()) => rap_info!("Dump call graph to {}", path),
+                Err(err) => rap_error!("Failed to write {}: {}", path, err),
+            }
+        }
+        dump_json(tcx, callback, "callgraph", &display);
         //analyzer.display();
     }
 
@@ -460,8 +687,9 @@ pub fn start_analyzer(tcx: TyCtxt, callback: &RapCallback) {
         1 => {
             let mut analyzer = DataFlowAnalyzer::new(tcx, false);
             analyzer.run();
-            let result = analyzer.get_all_arg2ret();
-            rap_info!("{}", Arg2RetMapWrapper(result));
+            let result = Arg2RetMapWrapper(analyzer.get_all_arg2ret());
+            rap_info!("{}", result);
+            dump_json(tcx, callback, "dataflow", &result);
         }
         2 => {
             let mut analyzer = DataFlowAnalyzer::new(tcx, true);
@@ -475,29 +703,33 @@ pub fn start_analyzer(tcx: TyCtxt, callback: &RapCallback) {
     if callback.is_ownedheap_enabled() {
         let mut analyzer = OwnedHeapAnalyzer::new(tcx);
         analyzer.run();
-        let result = analyzer.get_all_items();
-        rap_info!("{}", OHAResultMapWrapper(result));
+        let result = OHAResultMapWrapper(analyzer.get_all_items());
+        rap_info!("{}", result);
+        dump_json(tcx, callback, "ownedheap", &result);
     }
 
     if callback.is_range_analysis_enabled() {
         match callback.range {
             1 => {
-                let mut analyzer = RangeAnalyzer::<i64>::new(tcx, false);
+                let mut analyzer = TypedRangeAnalyzer::new(tcx, false);
                 analyzer.run();
-                let result = analyzer.get_all_fn_ranges();
-                rap_info!("{}", RAResultMapWrapper(result));
+                let result = TypedRAResultMapWrapper(analyzer.get_all_fn_ranges());
+                rap_info!("{}", result);
+                dump_json(tcx, callback, "range", &result);
             }
             2 => {
-                let mut analyzer = RangeAnalyzer::<i64>::new(tcx, true);
+                let mut analyzer = TypedRangeAnalyzer::new(tcx, true);
                 analyzer.run();
-                let result = analyzer.get_all_fn_ranges();
-                rap_info!("{}", RAResultMapWrapper(result));
+                let result = TypedRAResultMapWrapper(analyzer.get_all_fn_ranges());
+                rap_info!("{}", result);
+                dump_json(tcx, callback, "range", &result);
             }
             3 => {
                 let mut analyzer = RangeAnalyzer::<i64>::new(tcx, false);
                 analyzer.start_path_constraints_analysis();
-                let result = analyzer.get_all_path_constraints();
-                rap_info!("{}", PathConstraintMapWrapper(result));
+                let result = PathConstraintMapWrapper(analyzer.get_all_path_constraints());
+                rap_info!("{}", result);
+                dump_json(tcx, callback, "pathcond", &result);
             }
             _ => {}
         }
@@ -508,11 +740,8 @@ pub fn start_analyzer(tcx: TyCtxt, callback: &RapCallback) {
         test.start();
     }
 
-    match callback.is_opt_enabled() {
-        0 => Opt::new(tcx, 0).start(),
-        1 => Opt::new(tcx, 1).start(),
-        2 => Opt::new(tcx, 2).start(),
-        _ => {}
+    if callback.is_opt_enabled() {
+        Opt::new(tcx, config.opt.level as usize).start();
     }
 
     let _rcanary: Option<rCanary> = if callback.is_rcanary_enabled() {
@@ -527,7 +756,43 @@ pub fn start_analyzer(tcx: TyCtxt, callback: &RapCallback) {
     };
 
     if callback.is_safedrop_enabled() {
-        SafeDrop::new(tcx).start();
+        SafeDrop::new(tcx, config.safedrop)
+            .with_cache(callback.get_cache_dir())
+            .with_alias_backend(callback.get_alias_backend())
+            .start();
+    } else if callback.is_summary_enabled() {
+        SafeDrop::new(tcx, config.safedrop)
+            .with_cache(callback.get_cache_dir())
+            .with_alias_backend(callback.get_alias_backend())
+            .summarize();
+    }
+
+    if callback.is_uninit_enabled() {
+        UninitCheck::new(tcx).start();
+    }
+
+    if callback.is_panic_safety_enabled() {
+        PanicSafetyCheck::new(tcx).start();
+    }
+
+    if callback.is_send_sync_enabled() {
+        SendSyncCheck::new(tcx).start();
+    }
+
+    if callback.is_lifetime_enabled() {
+        UnboundedLifetimeCheck::new(tcx).start();
+    }
+
+    if callback.is_lock_enabled() {
+        LockCheck::new(tcx).start();
+    }
+
+    if callback.is_rc_cycle_enabled() {
+        RcCycleCheck::new(tcx).start();
+    }
+
+    if callback.is_overflow_enabled() {
+        OverflowCheck::new(tcx).start();
     }
 
     if callback.is_show_mir_enabled() {
@@ -550,8 +815,9 @@ pub fn start_analyzer(tcx: TyCtxt, callback: &RapCallback) {
     }
 
     if callback.is_verify_enabled() {
-        let check_level = CheckLevel::Medium;
-        SenryxCheck::new(tcx, 2).start(check_level, true);
+        SenryxCheck::new(tcx, 2)
+            .with_alias_backend(callback.get_alias_backend())
+            .start(config.verify.check_level, true);
     }
 
     if callback.is_verify_std_enabled() {
@@ -560,11 +826,36 @@ pub fn start_analyzer(tcx: TyCtxt, callback: &RapCallback) {
     }
 
     if callback.is_infer_enabled() {
-        let check_level = CheckLevel::Medium;
-        SenryxCheck::new(tcx, 2).start(check_level, false);
+        SenryxCheck::new(tcx, 2)
+            .with_alias_backend(callback.get_alias_backend())
+            .start(config.verify.check_level, false);
     }
 
     if callback.is_scan_enabled() {
         ScanAnalysis::new(tcx).run();
     }
+
+    let findings = diagnostics::take_findings();
+    let incomplete = budget::take_incomplete();
+    budget::report_incomplete(&incomplete);
+    if callback.is_sarif_enabled() {
+        let output = callback.get_output();
+        if let Err(err) = diagnostics::write_sarif(tcx, output, &findings, &incomplete) {
+            rap_error!("Failed to write SARIF file {}: {}", output, err);
+        }
+    }
+    if let Some(path) = callback.get_write_baseline()
+        && let Err(err) = suppression::write_baseline(path, &findings)
+    {
+        rap_error!("Failed to write baseline {}: {}", path, err);
+    }
+}
+
+/// Dump the results of a core analysis to a JSON file if `-json` is specified.
+fn dump_json<T: serde::Serialize>(tcx: TyCtxt, callback: &RapCallback, analysis: &str, result: &T) {
+    if callback.is_json_enabled()
+        && let Err(err) = json::dump_to_json(tcx, analysis, result)
+    {
+        rap_error!("Failed to dump {} results to JSON: {}", analysis, err);
+    }
 }
@@ -0,0 +1,202 @@
+//! Per-function budgets shared by the path-sensitive analyses.
+//!
+//! Each analysis explores a function within a budget of time and paths, configured by the
+//! `[budget]` table of `rapx.toml`. Once the budget runs out, the analysis stops exploring the
+//! function and keeps what it has found on the paths visited so far, and the function is
+//! recorded as incomplete with the reason. The incomplete functions are listed at the end of the
+//! run and in the SARIF output, which tells where the coverage is lost.
+
+use super::json::stable_def_path;
+use rustc_hir::def_id::DefId;
+use rustc_middle::ty::TyCtxt;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::{
+    fmt,
+    sync::{Mutex, OnceLock},
+    time::{Duration, Instant},
+};
+
+static CONFIG: OnceLock<BudgetConfig> = OnceLock::new();
+static INCOMPLETE: Mutex<Vec<IncompleteFn>> = Mutex::new(Vec::new());
+
+/// The options in the `[budget]` table of `rapx.toml`. The paths visited by MoP and SafeDrop are
+/// bounded by `visit_limit` of `[alias]` and `[safedrop]` instead.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BudgetConfig {
+    /// The seconds an analysis may spend on a function; unlimited if absent.
+    pub time: Option<u64>,
+    /// The number of paths enumerated in a loop of a function.
+    pub scc_paths: usize,
+    /// The number of paths of a function checked by senryx.
+    pub paths: usize,
+    /// The milliseconds of each Z3 query of rCanary.
+    pub solver_timeout: u64,
+}
+
+impl Default for BudgetConfig {
+    fn default() -> Self {
+        Self {
+            time: None,
+            scc_paths: 1000,
+            paths: 1000,
+            solver_timeout: 1000,
+        }
+    }
+}
+
+/// Why the analysis of a function is incomplete.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Exhausted {
+    /// The seconds of the time budget.
+    Time(u64),
+    /// The function has more paths to visit than the limit.
+    Paths(usize),
+    /// A loop of the function has more paths than the limit.
+    SccPaths(usize),
+    /// The solver gave up on the constraints within the milliseconds.
+    Solver(u64),
+}
+
+impl fmt::Display for Exhausted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Exhausted::Time(secs) => write!(f, "time budget of {}s exhausted", secs),
+            Exhausted::Paths(limit) => write!(f, "more than {} paths", limit),
+            Exhausted::SccPaths(limit) => write!(f, "a loop with more than {} paths", limit),
+            Exhausted::Solver(ms) => write!(f, "solver gave up within {}ms", ms),
+        }
+    }
+}
+
+/// The budget of one analysis on one function, whose time starts when it is created.
+#[derive(Debug, Clone)]
+pub struct FnBudget {
+    deadline: Option<Instant>,
+    exhausted: Option<Exhausted>,
+}
+
+impl Default for FnBudget {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FnBudget {
+    pub fn new() -> Self {
+        Self {
+            deadline: config()
+                .time
+                .map(|secs| Instant::now() + Duration::from_secs(secs)),
+            exhausted: None,
+        }
+    }
+
+    /// Mark the budget as run out, unless it has run out for another reason before.
+    pub fn exhaust(&mut self, reason: Exhausted) {
+        self.exhausted.get_or_insert(reason);
+    }
+
+    /// Whether the budget has run out, checking the time as well.
+    pub fn is_exhausted(&mut self) -> bool {
+        if self.exhausted.is_none()
+            && let Some(deadline) = self.deadline
+            && Instant::now() >= deadline
+        {
+            self.exhausted = config().time.map(Exhausted::Time);
+        }
+        self.exhausted.is_some()
+    }
+
+    pub fn exhausted(&self) -> Option<Exhausted> {
+        self.exhausted
+    }
+
+    /// Record the function as incomplete for the analysis if the budget has run out.
+    pub fn record(&self, tcx: TyCtxt<'_>, analysis: &'static str, def_id: DefId) {
+        if let Some(reason) = self.exhausted {
+            record_incomplete(tcx, analysis, def_id, reason);
+        }
+    }
+}
+
+/// Set the budgets for this run; must be called before any function is analyzed.
+pub fn init(config: BudgetConfig) {
+    let _ = CONFIG.set(config);
+}
+
+pub fn config() -> BudgetConfig {
+    CONFIG.get().copied().unwrap_or_default()
+}
+
+/// A function whose analysis is incomplete.
+#[derive(Debug, Clone)]
+pub struct IncompleteFn {
+    /// The name of the analysis, e.g., `uaf`.
+    pub analysis: &'static str,
+    pub def_id: DefId,
+    pub def_path: String,
+    pub reason: Exhausted,
+}
+
+impl IncompleteFn {
+    /// A SARIF notification of the tool execution.
+    pub fn to_sarif(&self) -> Value {
+        json!({
+            "level": "note",
+            "message": {
+                "text": format!("The {} analysis is incomplete: {}.", self.analysis, self.reason),
+            },
+            "locations": [{
+                "logicalLocations": [{
+                    "fullyQualifiedName": self.def_path,
+                    "kind": "function",
+                }]
+            }],
+        })
+    }
+}
+
+pub fn record_incomplete(
+    tcx: TyCtxt<'_>,
+    analysis: &'static str,
+    def_id: DefId,
+    reason: Exhausted,
+) {
+    let incomplete = IncompleteFn {
+        analysis,
+        def_id,
+        def_path: stable_def_path(tcx, def_id),
+        reason,
+    };
+    INCOMPLETE.lock().unwrap().push(incomplete);
+}
+
+/// Take the incomplete functions recorded so far, ordered by their def paths, leaving the record
+/// empty. A function is listed once for each analysis.
+pub fn take_incomplete() -> Vec<IncompleteFn> {
+    let mut incomplete = std::mem::take(&mut *INCOMPLETE.lock().unwrap());
+    incomplete.sort_by(|a, b| (&a.def_path, a.analysis).cmp(&(&b.def_path, b.analysis)));
+    incomplete.dedup_by(|a, b| a.def_id == b.def_id && a.analysis == b.analysis);
+    incomplete
+}
+
+/// List the incomplete functions, whose bugs may be missed.
+pub fn report_incomplete(incomplete: &[IncompleteFn]) {
+    if incomplete.is_empty() {
+        return;
+    }
+    rap_warn!(
+        "The analysis of {} functions is incomplete:",
+        incomplete.len()
+    );
+    for function in incomplete {
+        rap_warn!(
+            "  {} of {}: {}",
+            function.analysis,
+            function.def_path,
+            function.reason
+        );
+    }
+}
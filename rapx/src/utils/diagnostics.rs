@@ -0,0 +1,586 @@
+//! A unified sink for the findings of all bug detectors.
+//!
+//! Detectors hand each finding to this module, which also renders its human-readable report to
+//! the log. At the end of a run, the collected findings can be written to a
+//! SARIF 2.1.0 file so that CI code-scanning dashboards can ingest them directly.
+//!
+//! Each finding is identified by a fingerprint of its kind, the stable def path of the function
+//! and a normalized location inside the function, which is used by baselines and suppressions.
+
+use super::{
+    budget::IncompleteFn,
+    json::stable_def_path,
+    log::{
+        are_spans_in_same_file, relative_pos_range, span_to_filename, span_to_line_number,
+        span_to_source_code,
+    },
+    source::get_name,
+    suppression,
+};
+use annotate_snippets::{Level, Renderer, Snippet};
+use rustc_hir::def_id::DefId;
+use rustc_middle::ty::{self, TyCtxt};
+use rustc_span::{FileName, Span, source_map::get_source_map, symbol::Symbol};
+use serde_json::{Value, json};
+use std::{
+    fs::OpenOptions,
+    io::{Read, Seek, SeekFrom, Write},
+    path::Path,
+    sync::Mutex,
+};
+
+static FINDINGS: Mutex<Vec<Finding>> = Mutex::new(Vec::new());
+
+pub const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+pub const SARIF_VERSION: &str = "2.1.0";
+
+/// The kinds of findings that RAPx detectors may report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum FindingKind {
+    DoubleFree,
+    UseAfterFree,
+    DanglingPointer,
+    InvalidFree,
+    UninitRead,
+    PanicSafety,
+    SendSyncVariance,
+    UnboundedLifetime,
+    DoubleLock,
+    LockOrderInversion,
+    GuardAcrossAwait,
+    RcCycle,
+    IntOverflow,
+    TruncatingCast,
+    UsizeUnderflow,
+    MemoryLeak,
+    UnsafeContract,
+    BoundsChecking,
+    EncodingChecking,
+    Suboptimal,
+    Initialization,
+    Reallocation,
+    Iterator,
+    Cloning,
+}
+
+impl FindingKind {
+    /// The stable rule id used in machine-readable outputs.
+    pub fn id(&self) -> &'static str {
+        match self {
+            FindingKind::DoubleFree => "double-free",
+            FindingKind::UseAfterFree => "use-after-free",
+            FindingKind::DanglingPointer => "dangling-pointer",
+            FindingKind::InvalidFree => "invalid-free",
+            FindingKind::UninitRead => "uninit-read",
+            FindingKind::PanicSafety => "panic-safety",
+            FindingKind::SendSyncVariance => "send-sync-variance",
+            FindingKind::UnboundedLifetime => "unbounded-lifetime",
+            FindingKind::DoubleLock => "double-lock",
+            FindingKind::LockOrderInversion => "lock-order-inversion",
+            FindingKind::GuardAcrossAwait => "guard-across-await",
+            FindingKind::RcCycle => "rc-cycle",
+            FindingKind::IntOverflow => "int-overflow",
+            FindingKind::TruncatingCast => "truncating-cast",
+            FindingKind::UsizeUnderflow => "usize-underflow",
+            FindingKind::MemoryLeak => "memory-leak",
+            FindingKind::UnsafeContract => "unsafe-contract",
+            FindingKind::BoundsChecking => "opt-bounds-checking",
+            FindingKind::EncodingChecking => "opt-encoding-checking",
+            FindingKind::Suboptimal => "opt-suboptimal",
+            FindingKind::Initialization => "opt-initialization",
+            FindingKind::Reallocation => "opt-reallocation",
+            FindingKind::Iterator => "opt-iterator",
+            FindingKind::Cloning => "opt-cloning",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            FindingKind::DoubleFree => "A heap object may be freed twice.",
+            FindingKind::UseAfterFree => "A value may be used after it has been freed.",
+            FindingKind::DanglingPointer => "A pointer may outlive the object it points to.",
+            FindingKind::InvalidFree => {
+                "A heap object may be freed through an API or layout other than its allocation."
+            }
+            FindingKind::UninitRead => "Memory may be read before it is initialized.",
+            FindingKind::PanicSafety => {
+                "An invariant broken by unsafe code may be observed when user code panics."
+            }
+            FindingKind::SendSyncVariance => {
+                "An unsafe impl of Send or Sync lacks the bounds of its generic parameters."
+            }
+            FindingKind::UnboundedLifetime => {
+                "A reference is returned with a lifetime not tied to any input."
+            }
+            FindingKind::DoubleLock => "A lock may be acquired again while it is held.",
+            FindingKind::LockOrderInversion => {
+                "Two locks may be acquired in different orders, which can deadlock."
+            }
+            FindingKind::GuardAcrossAwait => "A lock guard may be held across an `.await`.",
+            FindingKind::RcCycle => {
+                "Strong references of `Rc` or `Arc` may form a cycle that is never freed."
+            }
+            FindingKind::IntOverflow => "An integer operation may overflow its type.",
+            FindingKind::TruncatingCast => {
+                "An `as` cast may truncate the value or change its sign."
+            }
+            FindingKind::UsizeUnderflow => {
+                "A `usize` subtraction may underflow before being used as an index or length."
+            }
+            FindingKind::MemoryLeak => "A heap object may never be freed.",
+            FindingKind::UnsafeContract => {
+                "The safety requirements of an unsafe API may be violated."
+            }
+            FindingKind::BoundsChecking => "Unnecessary bounds checking.",
+            FindingKind::EncodingChecking => "Unnecessary encoding checking.",
+            FindingKind::Suboptimal => "Suboptimal data collection.",
+            FindingKind::Initialization => "Unnecessary data collection initialization.",
+            FindingKind::Reallocation => "Avoidable data collection reallocation.",
+            FindingKind::Iterator => "Inefficient iterator.",
+            FindingKind::Cloning => "Unnecessary memory cloning.",
+        }
+    }
+
+    /// Memory-safety bugs are reported as warnings; optimization chances are notes.
+    pub fn level(&self) -> &'static str {
+        match self {
+            FindingKind::DoubleFree
+            | FindingKind::UseAfterFree
+            | FindingKind::DanglingPointer
+            | FindingKind::InvalidFree
+            | FindingKind::UninitRead
+            | FindingKind::PanicSafety
+            | FindingKind::SendSyncVariance
+            | FindingKind::UnboundedLifetime
+            | FindingKind::DoubleLock
+            | FindingKind::LockOrderInversion
+            | FindingKind::GuardAcrossAwait
+            | FindingKind::RcCycle
+            | FindingKind::IntOverflow
+            | FindingKind::TruncatingCast
+            | FindingKind::UsizeUnderflow
+            | FindingKind::MemoryLeak
+            | FindingKind::UnsafeContract => "warning",
+            _ => "note",
+        }
+    }
+
+    /// Whether the name given in `#[rapx::allow(...)]` or `rapx.toml` covers this kind.
+    /// Besides the rule id, the names of detectors (`uaf`, `mleak`, `uninit`, `panic`, `sendsync`,
+    /// `lifetime`, `lock`, `rccycle`, `overflow`, `opt` and `verify`) stand for all kinds they
+    /// report, and `all` stands for every kind. `_` and `-` are interchangeable.
+    pub fn is_named(&self, name: &str) -> bool {
+        let name = name.trim().trim_matches('"').replace('_', "-");
+        match name.as_str() {
+            "all" => true,
+            "uaf" | "safedrop" => matches!(
+                self,
+                FindingKind::DoubleFree
+                    | FindingKind::UseAfterFree
+                    | FindingKind::DanglingPointer
+                    | FindingKind::InvalidFree
+            ),
+            "mleak" | "rcanary" => *self == FindingKind::MemoryLeak,
+            "uninit" => *self == FindingKind::UninitRead,
+            "panic" => *self == FindingKind::PanicSafety,
+            "sendsync" => *self == FindingKind::SendSyncVariance,
+            "lifetime" => *self == FindingKind::UnboundedLifetime,
+            "lock" => matches!(
+                self,
+                FindingKind::DoubleLock
+                    | FindingKind::LockOrderInversion
+                    | FindingKind::GuardAcrossAwait
+            ),
+            "rccycle" => *self == FindingKind::RcCycle,
+            "overflow" => matches!(
+                self,
+                FindingKind::IntOverflow
+                    | FindingKind::TruncatingCast
+                    | FindingKind::UsizeUnderflow
+            ),
+            "verify" | "senryx" => *self == FindingKind::UnsafeContract,
+            "opt" => self.id().starts_with("opt-"),
+            _ => name == self.id(),
+        }
+    }
+
+    pub fn all() -> &'static [FindingKind] {
+        &[
+            FindingKind::DoubleFree,
+            FindingKind::UseAfterFree,
+            FindingKind::DanglingPointer,
+            FindingKind::InvalidFree,
+            FindingKind::UninitRead,
+            FindingKind::PanicSafety,
+            FindingKind::SendSyncVariance,
+            FindingKind::UnboundedLifetime,
+            FindingKind::DoubleLock,
+            FindingKind::LockOrderInversion,
+            FindingKind::GuardAcrossAwait,
+            FindingKind::RcCycle,
+            FindingKind::IntOverflow,
+            FindingKind::TruncatingCast,
+            FindingKind::UsizeUnderflow,
+            FindingKind::MemoryLeak,
+            FindingKind::UnsafeContract,
+            FindingKind::BoundsChecking,
+            FindingKind::EncodingChecking,
+            FindingKind::Suboptimal,
+            FindingKind::Initialization,
+            FindingKind::Reallocation,
+            FindingKind::Iterator,
+            FindingKind::Cloning,
+        ]
+    }
+}
+
+/// A source region resolved from a `Span` while the source map is still alive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceLocation {
+    pub file: String,
+    pub start_line: usize,
+    pub start_column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+    pub label: String,
+}
+
+impl SourceLocation {
+    /// Resolve the span; returns None for dummy spans or spans without a real file.
+    pub fn from_span(span: Span, label: impl Into<String>) -> Option<Self> {
+        if span.is_dummy() {
+            return None;
+        }
+        let source_map = get_source_map()?;
+        let FileName::Real(realname) = source_map.span_to_filename(span) else {
+            return None;
+        };
+        let file = realname.local_path()?.to_string_lossy().into_owned();
+        let lo = source_map.lookup_char_pos(span.lo());
+        let hi = source_map.lookup_char_pos(span.hi());
+        // SARIF lines and columns are 1-based.
+        Some(SourceLocation {
+            file,
+            start_line: lo.line,
+            start_column: lo.col.0 + 1,
+            end_line: hi.line,
+            end_column: hi.col.0 + 1,
+            label: label.into(),
+        })
+    }
+
+    fn to_sarif(&self) -> Value {
+        let artifact = if Path::new(&self.file).is_absolute() {
+            json!({ "uri": format!("file://{}", self.file) })
+        } else {
+            json!({ "uri": self.file, "uriBaseId": "%SRCROOT%" })
+        };
+        json!({
+            "artifactLocation": artifact,
+            "region": {
+                "startLine": self.start_line,
+                "startColumn": self.start_column,
+                "endLine": self.end_line,
+                "endColumn": self.end_column,
+            }
+        })
+    }
+}
+
+/// A finding reported by a detector.
+#[derive(Debug, Clone)]
+pub struct Finding {
+    pub kind: FindingKind,
+    pub message: String,
+    pub def_id: DefId,
+    pub def_path: String,
+    /// The normalized location inside the function, e.g., the line offsets from the function
+    /// start, which are stable against edits outside the function.
+    pub location: String,
+    /// Whether the finding is not recorded in the baseline.
+    pub is_new: bool,
+    pub confidence: Option<usize>,
+    pub primary: Option<SourceLocation>,
+    pub related: Vec<SourceLocation>,
+    /// The spans of the primary and related locations with their labels, for the report.
+    primary_span: Option<(Span, String)>,
+    related_spans: Vec<(Span, String)>,
+    /// What the finding is reported in, e.g., `function "main"` by default.
+    subject: Option<String>,
+}
+
+impl Finding {
+    /// Create a finding in the function `def_id`; must be called while the type context is alive.
+    pub fn new(kind: FindingKind, def_id: DefId, message: impl Into<String>) -> Self {
+        Finding {
+            kind,
+            message: message.into(),
+            def_id,
+            def_path: ty::tls::with(|tcx| stable_def_path(tcx, def_id)),
+            location: String::new(),
+            is_new: true,
+            confidence: None,
+            primary: None,
+            related: Vec::new(),
+            primary_span: None,
+            related_spans: Vec::new(),
+            subject: None,
+        }
+    }
+
+    pub fn confidence(mut self, confidence: usize) -> Self {
+        self.confidence = Some(confidence);
+        self
+    }
+
+    pub fn primary(mut self, span: Span, label: impl Into<String>) -> Self {
+        let label = label.into();
+        self.primary = SourceLocation::from_span(span, label.clone());
+        self.primary_span = Some((span, label));
+        self
+    }
+
+    pub fn related(mut self, span: Span, label: impl Into<String>) -> Self {
+        let label = label.into();
+        if let Some(location) = SourceLocation::from_span(span, label.clone()) {
+            self.related.push(location);
+        }
+        self.related_spans.push((span, label));
+        self
+    }
+
+    /// Set what the finding is reported in, e.g., ``"`unsafe impl Send for Foo`"``.
+    pub fn subject(mut self, subject: impl Into<String>) -> Self {
+        self.subject = Some(subject.into());
+        self
+    }
+
+    /// Set the normalized location, e.g., the type of a cycle. If not set, the line offsets of the
+    /// primary and related locations from the function start are used, which are stable against
+    /// edits outside the function, unlike basic block indices.
+    pub fn location(mut self, location: impl Into<String>) -> Self {
+        self.location = location.into();
+        self
+    }
+
+    /// The fingerprint as a 64-bit FNV-1a hash, which stays the same across runs and platforms.
+    pub fn fingerprint(&self) -> String {
+        let key = format!("{}|{}|{}", self.kind.id(), self.def_path, self.location);
+        let hash = key.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        });
+        format!("{:016x}", hash)
+    }
+
+    /// Hand the finding over to the sink. Returns false if the finding is suppressed or has been
+    /// recorded in the baseline, in which case the detector should not report it.
+    pub fn emit(mut self) -> bool {
+        if self.location.is_empty() {
+            self.location = self.default_location();
+        }
+        if suppression::is_allowed(&self) {
+            return false;
+        }
+        self.is_new = !suppression::is_in_baseline(&self);
+        let is_new = self.is_new;
+        FINDINGS.lock().unwrap().push(self);
+        is_new
+    }
+
+    /// Emit the finding and, if it is new, log the warning and print the source of `context`,
+    /// e.g., the function, with the primary location labeled as the warning and the related ones
+    /// as the notes. Nothing is printed if the primary location is outside `context`, and the
+    /// related ones outside it are left out. Returns whether the finding is new.
+    pub fn report(self, context: Span) -> bool {
+        let title = self.message.clone();
+        let subject = self.subject.clone().unwrap_or_else(|| {
+            let name = ty::tls::with(|tcx| get_name(tcx, self.def_id))
+                .unwrap_or_else(|| Symbol::intern("no symbol"));
+            format!("function {:?}", name)
+        });
+        let primary = self.primary_span.clone();
+        let related = self.related_spans.clone();
+        if !self.emit() {
+            return false;
+        }
+
+        rap_warn!("{} in {}", title.trim_end_matches('.'), subject);
+        let inside = |span: Span| are_spans_in_same_file(context, span) && context.contains(span);
+        let Some((span, label)) = primary.filter(|(span, _)| inside(*span)) else {
+            return true;
+        };
+        let code_source = span_to_source_code(context);
+        let filename = span_to_filename(context);
+        let mut snippet = Snippet::source(&code_source)
+            .line_start(span_to_line_number(context))
+            .origin(&filename)
+            .fold(false)
+            .annotation(
+                Level::Warning
+                    .span(relative_pos_range(context, span))
+                    .label(&label),
+            );
+        for (span, label) in related.iter().filter(|(span, _)| inside(*span)) {
+            snippet = snippet.annotation(
+                Level::Info
+                    .span(relative_pos_range(context, *span))
+                    .label(label),
+            );
+        }
+        let message = Level::Warning.title(&title).snippet(snippet);
+        println!("{}", Renderer::styled().render(message));
+        true
+    }
+
+    /// The line offsets of the primary location and then the related ones from the function
+    /// start, e.g., `line+3->line+7` for a use after the drop on line 3.
+    fn default_location(&self) -> String {
+        let Some(primary) = &self.primary else {
+            return String::new();
+        };
+        let fn_span = ty::tls::with(|tcx| tcx.def_span(self.def_id));
+        let Some(fn_loc) = SourceLocation::from_span(fn_span, "") else {
+            return String::new();
+        };
+        std::iter::once(primary)
+            .chain(&self.related)
+            .map(|location| {
+                format!(
+                    "line+{}",
+                    location.start_line.saturating_sub(fn_loc.start_line)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("->")
+    }
+
+    fn to_sarif(&self, rule_index: usize) -> Value {
+        let mut location = json!({
+            "logicalLocations": [{
+                "fullyQualifiedName": self.def_path,
+                "kind": "function",
+            }]
+        });
+        if let Some(primary) = &self.primary {
+            location["physicalLocation"] = primary.to_sarif();
+        }
+        let related: Vec<Value> = self
+            .related
+            .iter()
+            .enumerate()
+            .map(|(id, loc)| {
+                json!({
+                    "id": id,
+                    "physicalLocation": loc.to_sarif(),
+                    "message": { "text": loc.label },
+                })
+            })
+            .collect();
+        let mut result = json!({
+            "ruleId": self.kind.id(),
+            "partialFingerprints": { "rapx/v1": self.fingerprint() },
+            "ruleIndex": rule_index,
+            "level": self.kind.level(),
+            "message": { "text": self.message },
+            "locations": [location],
+        });
+        if !related.is_empty() {
+            result["relatedLocations"] = Value::Array(related);
+        }
+        if let Some(confidence) = self.confidence {
+            result["properties"] = json!({ "confidence": confidence });
+        }
+        result
+    }
+}
+
+/// Take all findings collected so far, leaving the sink empty.
+pub fn take_findings() -> Vec<Finding> {
+    std::mem::take(&mut *FINDINGS.lock().unwrap())
+}
+
+/// Build a SARIF run object for the given findings, with the incomplete functions as the
+/// notifications of the invocation.
+pub fn sarif_run(crate_name: &str, findings: &[Finding], incomplete: &[IncompleteFn]) -> Value {
+    let rules: Vec<Value> = FindingKind::all()
+        .iter()
+        .map(|kind| {
+            json!({
+                "id": kind.id(),
+                "shortDescription": { "text": kind.description() },
+                "defaultConfiguration": { "level": kind.level() },
+            })
+        })
+        .collect();
+    let results: Vec<Value> = findings
+        .iter()
+        .filter(|finding| finding.is_new)
+        .map(|finding| {
+            let rule_index = FindingKind::all()
+                .iter()
+                .position(|kind| *kind == finding.kind)
+                .unwrap();
+            finding.to_sarif(rule_index)
+        })
+        .collect();
+    let notifications: Vec<Value> = incomplete.iter().map(IncompleteFn::to_sarif).collect();
+    json!({
+        "tool": {
+            "driver": {
+                "name": "RAPx",
+                "version": env!("CARGO_PKG_VERSION"),
+                "informationUri": "https://github.com/Artisan-Lab/RAPx",
+                "rules": rules,
+            }
+        },
+        "automationDetails": { "id": format!("rapx/{}", crate_name) },
+        "invocations": [{
+            "executionSuccessful": true,
+            "toolExecutionNotifications": notifications,
+        }],
+        "results": results,
+    })
+}
+
+/// Write the collected findings as one SARIF run into `path`.
+///
+/// Cargo may invoke rapx for several crates of a workspace concurrently, so the file is locked
+/// and each crate appends its own run to the log.
+pub fn write_sarif(
+    tcx: TyCtxt<'_>,
+    path: impl AsRef<Path>,
+    findings: &[Finding],
+    incomplete: &[IncompleteFn],
+) -> std::io::Result<()> {
+    let crate_name = tcx.crate_name(rustc_span::def_id::LOCAL_CRATE);
+    let run = sarif_run(crate_name.as_str(), findings, incomplete);
+
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path.as_ref())?;
+    file.lock()?;
+    let mut content = String::new();
+    file.read_to_string(&mut content)?;
+    let mut log = match serde_json::from_str::<Value>(&content) {
+        Ok(log) if log["runs"].is_array() => log,
+        _ => json!({
+            "$schema": SARIF_SCHEMA,
+            "version": SARIF_VERSION,
+            "runs": [],
+        }),
+    };
+    log["runs"].as_array_mut().unwrap().push(run);
+    file.set_len(0)?;
+    file.seek(SeekFrom::Start(0))?;
+    file.write_all(serde_json::to_string_pretty(&log)?.as_bytes())?;
+    file.unlock()?;
+    rap_info!(
+        "Write {} findings in SARIF format to {}",
+        findings.iter().filter(|finding| finding.is_new).count(),
+        path.as_ref().display()
+    );
+    Ok(())
+}
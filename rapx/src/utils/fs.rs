@@ -1,7 +1,7 @@
 use crate::utils::log::rap_error_and_exit;
 
 use std::fs::{self, File};
-use std::io::Write;
+use std::io::{self, Write};
 use std::path::Path;
 
 use rustc_demangle::try_demangle;
@@ -50,6 +50,19 @@ pub fn rap_write(mut file: File, buf: &[u8], msg: impl AsRef<str>) -> usize {
         .unwrap_or_else(|e| rap_error_and_exit(format!("{}: {}", msg.as_ref(), e)))
 }
 
+/// Write the file via a temporary one renamed into place, so that the processes reading it
+/// concurrently, e.g., rustc of the other crates, never see a partial file.
+pub fn rap_write_atomically<P: AsRef<Path>>(path: P, contents: &[u8]) -> io::Result<()> {
+    let path = path.as_ref();
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(format!(".{}", std::process::id()));
+    fs::write(&tmp, contents)?;
+    fs::rename(&tmp, path)
+}
+
 pub fn rap_demangle(name: &str) -> String {
     match try_demangle(name) {
         Ok(d) => format!("{:#}", d),
@@ -0,0 +1,82 @@
+//! Helpers for dumping analysis results in a machine-readable JSON format.
+//!
+//! Every file shares the same envelope, so that tools can check `schema_version` before reading
+//! the analysis-specific `results`:
+//! `{ "schema_version": 1, "analysis": "alias", "crate": "foo", "results": ... }`
+
+use rustc_data_structures::fingerprint::Fingerprint;
+use rustc_hir::def_id::{DefId, DefPathHash, LOCAL_CRATE};
+use rustc_middle::ty::{self, TyCtxt};
+use serde::Serialize;
+use std::path::Path;
+
+/// Bump this version whenever the layout of any result is changed incompatibly.
+pub const JSON_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Serialize)]
+struct JsonReport<'a, T: Serialize> {
+    schema_version: u32,
+    analysis: &'a str,
+    #[serde(rename = "crate")]
+    crate_name: String,
+    results: &'a T,
+}
+
+/// The def path that is stable across compilation sessions, e.g., `foo::bar::{impl#0}::new`.
+/// Unlike `DefId`, it does not depend on the crate numbering and the definition order.
+pub fn stable_def_path(tcx: TyCtxt<'_>, def_id: DefId) -> String {
+    format!(
+        "{}{}",
+        tcx.crate_name(def_id.krate),
+        tcx.def_path(def_id).to_string_no_crate_verbose()
+    )
+}
+
+/// Same as `stable_def_path`, used where the type context is not at hand, e.g., in `Serialize`.
+pub fn stable_def_path_tls(def_id: DefId) -> String {
+    ty::tls::with(|tcx| stable_def_path(tcx, def_id))
+}
+
+/// The `DefPathHash` of the definition as a pair of integers, which identifies the definition in
+/// the files read by later compilation sessions, e.g., the persisted summaries.
+pub fn def_path_hash_pair(tcx: TyCtxt<'_>, def_id: DefId) -> (u64, u64) {
+    let (hash0, hash1) = tcx.def_path_hash(def_id).0.split();
+    (hash0.as_u64(), hash1.as_u64())
+}
+
+/// The definition of the hash pair, if it is still defined in the crates being compiled.
+pub fn def_id_of_hash_pair(tcx: TyCtxt<'_>, (hash0, hash1): (u64, u64)) -> Option<DefId> {
+    let hash = DefPathHash(Fingerprint::new(hash0, hash1));
+    let krate = hash.stable_crate_id();
+    if krate != tcx.stable_crate_id(LOCAL_CRATE)
+        && !tcx
+            .crates(())
+            .iter()
+            .any(|cnum| tcx.stable_crate_id(*cnum) == krate)
+    {
+        return None;
+    }
+    tcx.def_path_hash_to_def_id(hash)
+}
+
+/// Dump the results of an analysis into `{analysis}_{crate}_{crate_type}.json` in the current
+/// folder, following the naming of the API dependency graph JSON.
+pub fn dump_to_json<T: Serialize>(
+    tcx: TyCtxt<'_>,
+    analysis: &str,
+    results: &T,
+) -> std::io::Result<()> {
+    let crate_name = tcx.crate_name(LOCAL_CRATE);
+    let crate_type = tcx.crate_types()[0];
+    let path = format!("{}_{}_{}.json", analysis, crate_name, crate_type);
+    let report = JsonReport {
+        schema_version: JSON_SCHEMA_VERSION,
+        analysis,
+        crate_name: crate_name.to_string(),
+        results,
+    };
+    let file = std::fs::File::create(Path::new(&path))?;
+    serde_json::to_writer_pretty(file, &report)?;
+    rap_info!("Dump {} results to {}", analysis, path);
+    Ok(())
+}
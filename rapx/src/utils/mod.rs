@@ -1,4 +1,8 @@
 pub mod fs;
 #[macro_use]
 pub mod log;
+pub mod budget;
+pub mod diagnostics;
+pub mod json;
 pub mod source;
+pub mod suppression;
@@ -0,0 +1,271 @@
+//! Suppression of known findings.
+//!
+//! A finding is not reported if any of the following holds:
+//! - the function or one of its enclosing items is annotated with `#[rapx::allow(<kind>, ...)]`,
+//!   which requires `#![feature(register_tool)]` and `#![register_tool(rapx)]` in the crate root;
+//! - it matches an `[[allow]]` entry of `rapx.toml`, e.g.,
+//!   ```toml
+//!   [[allow]]
+//!   kind = "uaf"
+//!   path = "my_crate::ffi::*"
+//!   ```
+//! - the function is defined in a source file out of the `include` and `exclude` globs of
+//!   `rapx.toml`;
+//! - its fingerprint is recorded in the baseline file specified by `-baseline=<path>`.
+//!
+//! Findings silenced by the baseline are still recorded when a new baseline is written with
+//! `-write-baseline=<path>`.
+
+use super::diagnostics::{Finding, FindingKind, SourceLocation};
+use crate::{
+    analysis::core::ownership_model::{glob_to_regex, normalize_path, path_pattern_to_regex},
+    config::RapxConfig,
+};
+use regex::Regex;
+use rustc_hir::{Attribute, def_id::DefId};
+use rustc_middle::ty::{self, TyCtxt};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashSet,
+    fs::OpenOptions,
+    io::{Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+static SUPPRESSION: OnceLock<Suppression> = OnceLock::new();
+
+pub const BASELINE_VERSION: u32 = 1;
+const REGISTER_TOOL: &str = "rapx";
+/// Unlike def path patterns, `*` does not match `/` in path globs, while `**` matches any folders.
+const PATH_GLOB_WILDCARDS: &[(&str, &str)] = &[
+    (r"\*\*/", "(.*/)?"),
+    (r"\*\*", ".*"),
+    (r"\*", "[^/]*"),
+    (r"\?", "[^/]"),
+];
+
+/// An entry of the allowlist in `rapx.toml`.
+#[derive(Debug, Clone, Hash, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AllowEntry {
+    /// A finding kind name accepted by `FindingKind::is_named`.
+    pub kind: String,
+    /// A def path pattern where `*` matches any sequence of characters; all paths if absent.
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct BaselineEntry {
+    pub fingerprint: String,
+    pub kind: String,
+    pub def_path: String,
+    pub location: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Baseline {
+    pub version: u32,
+    pub findings: Vec<BaselineEntry>,
+}
+
+#[derive(Debug, Default)]
+pub struct Suppression {
+    allowlist: Vec<(AllowEntry, Option<Regex>)>,
+    include: Vec<Regex>,
+    exclude: Vec<Regex>,
+    baseline: HashSet<String>,
+}
+
+impl Suppression {
+    /// Take the allowlist and path globs from the configuration and the fingerprints from the
+    /// baseline. Returns an error if a pattern is malformed.
+    pub fn new(config: &RapxConfig, baseline: Option<&str>) -> Result<Self, String> {
+        let mut suppression = Suppression::default();
+        suppression.set_allowlist(config.allow.clone())?;
+        suppression.include = path_globs_to_regexes(&config.include)?;
+        suppression.exclude = path_globs_to_regexes(&config.exclude)?;
+        if let Some(path) = baseline {
+            match read_baseline(path) {
+                Ok(baseline) => {
+                    suppression.baseline = baseline
+                        .findings
+                        .into_iter()
+                        .map(|entry| entry.fingerprint)
+                        .collect();
+                }
+                Err(err) => rap_warn!("Failed to read baseline {}: {}", path, err),
+            }
+        }
+        Ok(suppression)
+    }
+
+    pub fn set_allowlist(&mut self, allowlist: Vec<AllowEntry>) -> Result<(), String> {
+        self.allowlist = allowlist
+            .into_iter()
+            .map(|entry| {
+                let pattern = entry
+                    .path
+                    .as_deref()
+                    .map(|path| {
+                        path_pattern_to_regex(path)
+                            .map_err(|err| format!("Invalid allow path `{}`: {}", path, err))
+                    })
+                    .transpose()?;
+                Ok((entry, pattern))
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(())
+    }
+
+    pub fn is_allowed(&self, tcx: TyCtxt<'_>, finding: &Finding) -> bool {
+        self.is_allowed_by_config(finding)
+            || !self.is_in_scope(tcx, finding.def_id)
+            || is_allowed_by_attr(tcx, finding.def_id, finding.kind)
+    }
+
+    pub fn is_in_baseline(&self, finding: &Finding) -> bool {
+        self.baseline.contains(&finding.fingerprint())
+    }
+
+    fn is_allowed_by_config(&self, finding: &Finding) -> bool {
+        self.allowlist.iter().any(|(entry, pattern)| {
+            finding.kind.is_named(&entry.kind)
+                && pattern
+                    .as_ref()
+                    .is_none_or(|pattern| pattern.is_match(&normalize_path(&finding.def_path)))
+        })
+    }
+
+    /// Whether the file of the function matches the `include` globs and not the `exclude` ones.
+    fn is_in_scope(&self, tcx: TyCtxt<'_>, def_id: DefId) -> bool {
+        if self.include.is_empty() && self.exclude.is_empty() {
+            return true;
+        }
+        let Some(location) = SourceLocation::from_span(tcx.def_span(def_id), "") else {
+            return true;
+        };
+        let file = Path::new(&location.file);
+        let file = file.strip_prefix(package_dir()).unwrap_or(file);
+        let file = file.to_string_lossy();
+        (self.include.is_empty() || self.include.iter().any(|glob| glob.is_match(&file)))
+            && !self.exclude.iter().any(|glob| glob.is_match(&file))
+    }
+}
+
+/// Initialize the suppression rules for this run; must be called before any finding is emitted.
+pub fn init(config: &RapxConfig, baseline: Option<&str>) -> Result<(), String> {
+    let _ = SUPPRESSION.set(Suppression::new(config, baseline)?);
+    Ok(())
+}
+
+/// Whether the finding is silenced by `#[rapx::allow(...)]` or the options of `rapx.toml`.
+pub fn is_allowed(finding: &Finding) -> bool {
+    match SUPPRESSION.get() {
+        Some(suppression) => ty::tls::with(|tcx| suppression.is_allowed(tcx, finding)),
+        None => false,
+    }
+}
+
+/// Whether the finding has been recorded in the baseline, i.e., it is not a new one.
+pub fn is_in_baseline(finding: &Finding) -> bool {
+    SUPPRESSION
+        .get()
+        .is_some_and(|suppression| suppression.is_in_baseline(finding))
+}
+
+/// Check `#[rapx::allow(...)]` on the item and all its enclosing items.
+pub fn is_allowed_by_attr(tcx: TyCtxt<'_>, def_id: DefId, kind: FindingKind) -> bool {
+    let mut cur = Some(def_id);
+    while let Some(def_id) = cur {
+        for attr in tcx.get_all_attrs(def_id) {
+            let Attribute::Unparsed(tool_attr) = attr else {
+                continue;
+            };
+            let segments = &tool_attr.path.segments;
+            if segments.len() != 2
+                || segments[0].as_str() != REGISTER_TOOL
+                || segments[1].as_str() != "allow"
+            {
+                continue;
+            }
+            let attr_str = rustc_hir_pretty::attribute_to_string(&tcx, attr);
+            let Some(args) = attr_str
+                .split_once('(')
+                .and_then(|(_, rest)| rest.rsplit_once(')'))
+                .map(|(args, _)| args)
+            else {
+                continue;
+            };
+            if args.split(',').any(|name| kind.is_named(name.trim())) {
+                return true;
+            }
+        }
+        cur = tcx.opt_parent(def_id);
+    }
+    false
+}
+
+/// Cargo runs rustc in the workspace root, so the package folder is taken from cargo.
+fn package_dir() -> PathBuf {
+    std::env::var("CARGO_MANIFEST_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from("."))
+}
+
+fn path_globs_to_regexes(globs: &[String]) -> Result<Vec<Regex>, String> {
+    globs
+        .iter()
+        .map(|glob| {
+            glob_to_regex(glob, PATH_GLOB_WILDCARDS)
+                .map_err(|err| format!("Invalid path glob `{}`: {}", glob, err))
+        })
+        .collect()
+}
+
+pub fn read_baseline(path: impl AsRef<Path>) -> std::io::Result<Baseline> {
+    let content = std::fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+/// Append the findings to the baseline file. Like the SARIF output, the file is shared by all
+/// crates checked in one `cargo rapx` run, so it is locked while updating.
+pub fn write_baseline(path: impl AsRef<Path>, findings: &[Finding]) -> std::io::Result<()> {
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path.as_ref())?;
+    file.lock()?;
+    let mut content = String::new();
+    file.read_to_string(&mut content)?;
+    let mut baseline = serde_json::from_str::<Baseline>(&content).unwrap_or(Baseline {
+        version: BASELINE_VERSION,
+        findings: Vec::new(),
+    });
+    for finding in findings {
+        let entry = BaselineEntry {
+            fingerprint: finding.fingerprint(),
+            kind: finding.kind.id().to_string(),
+            def_path: finding.def_path.clone(),
+            location: finding.location.clone(),
+        };
+        if !baseline.findings.contains(&entry) {
+            baseline.findings.push(entry);
+        }
+    }
+    baseline.findings.sort_by(|a, b| {
+        (&a.def_path, &a.kind, &a.location).cmp(&(&b.def_path, &b.kind, &b.location))
+    });
+    file.set_len(0)?;
+    file.seek(SeekFrom::Start(0))?;
+    file.write_all(serde_json::to_string_pretty(&baseline)?.as_bytes())?;
+    file.unlock()?;
+    rap_info!(
+        "Write {} findings to baseline {}",
+        findings.len(),
+        path.as_ref().display()
+    );
+    Ok(())
+}
@@ -0,0 +1,27 @@
+/*
+ * The loop of `choose` has more paths than the budget in rapx.toml, so that its summary
+ * assumes that both arguments may alias the return value.
+ */
+
+fn choose<'a>(a: &'a mut String, b: &'a mut String, n: usize) -> &'a mut String {
+    let mut r = a;
+    let mut i = 0;
+    while i < n {
+        if i % 2 == 0 {
+            r.push('a');
+        } else {
+            r.push('b');
+        }
+        i += 1;
+    }
+    if n > 10 {
+        r = b;
+    }
+    r
+}
+
+fn main() {
+    let mut a = String::new();
+    let mut b = String::new();
+    choose(&mut a, &mut b, 3).push('c');
+}
@@ -0,0 +1,58 @@
+trait Shape {
+    fn area(&self) -> f64;
+}
+
+struct Square(f64);
+impl Shape for Square {
+    fn area(&self) -> f64 {
+        self.0 * self.0
+    }
+}
+
+struct Circle(f64);
+impl Shape for Circle {
+    fn area(&self) -> f64 {
+        3.14 * self.0 * self.0
+    }
+}
+
+// Never coerced into `dyn Shape`, so it is pruned by rapid type analysis.
+struct Unused;
+impl Shape for Unused {
+    fn area(&self) -> f64 {
+        0.0
+    }
+}
+
+fn total(shapes: &[Box<dyn Shape>]) -> f64 {
+    let mut sum = 0.0;
+    for shape in shapes {
+        sum += shape.area();
+    }
+    sum
+}
+
+fn double(x: i32) -> i32 {
+    x * 2
+}
+
+fn apply(f: fn(i32) -> i32, x: i32) -> i32 {
+    f(x)
+}
+
+fn apply_dyn(f: &dyn Fn(i32) -> i32, x: i32) -> i32 {
+    f(x)
+}
+
+fn apply_generic<F: Fn(i32) -> i32>(f: F, x: i32) -> i32 {
+    f(x)
+}
+
+fn main() {
+    let offset = 3;
+    let a = apply(double, 1);
+    let b = apply_dyn(&|x| x + offset, 2);
+    let c = apply_generic(|x| x - 1, 3);
+    let shapes: Vec<Box<dyn Shape>> = vec![Box::new(Square(1.0)), Box::new(Circle(2.0))];
+    println!("{} {} {} {} {}", a, b, c, total(&shapes), Unused.area());
+}
@@ -0,0 +1,8 @@
+// Polymorphic recursion: each call instantiates `f` with a longer type.
+fn f<T: Clone>(x: T, n: usize) -> usize {
+    if n == 0 { 0 } else { f(vec![x], n - 1) + 1 }
+}
+
+fn main() {
+    println!("{}", f(1u8, 3));
+}
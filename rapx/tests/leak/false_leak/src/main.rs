@@ -0,0 +1,43 @@
+fn consume(b: Box<i32>) {
+    drop(b);
+}
+
+// The caller takes over the raw pointer.
+fn to_raw(b: Box<i32>, _tag: u8) -> *mut i32 {
+    Box::into_raw(b)
+}
+
+fn pass(b: Box<i32>) -> Box<i32> {
+    b
+}
+
+pub fn free_consumed() {
+    let b = Box::new(1);
+    consume(b);
+}
+
+pub fn free_raw() {
+    let p = to_raw(Box::new(2), 0);
+    unsafe {
+        drop(Box::from_raw(p));
+    }
+}
+
+pub fn free_passed() {
+    let b = pass(Box::new(3));
+    drop(b);
+}
+
+pub fn free_loop(n: i32) {
+    for i in 0..n {
+        let v = vec![i];
+        drop(v);
+    }
+}
+
+fn main() {
+    free_consumed();
+    free_raw();
+    free_passed();
+    free_loop(3);
+}
@@ -0,0 +1,9 @@
+/*
+ * The time budget in rapx.toml runs out before the solver checks `main`, so that the leak is
+ * reported without being verified.
+ */
+
+fn main() {
+    let buf = Box::new("buffer");
+    let _ptr = Box::into_raw(buf);
+}
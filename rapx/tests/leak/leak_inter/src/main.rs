@@ -0,0 +1,31 @@
+// The helpers take the ownership of the boxes without freeing them.
+fn forget_box(b: Box<i32>) {
+    std::mem::forget(b);
+}
+
+fn forward(b: Box<i32>) {
+    forget_box(b);
+}
+
+pub fn leak_forget() {
+    let b = Box::new(1);
+    forget_box(b);
+}
+
+pub fn leak_nested() {
+    let b = Box::new(2);
+    forward(b);
+}
+
+pub fn leak_loop(n: i32) {
+    for i in 0..n {
+        let v = Box::new(i);
+        std::mem::forget(v);
+    }
+}
+
+fn main() {
+    leak_forget();
+    leak_nested();
+    leak_loop(3);
+}
@@ -0,0 +1,59 @@
+pub struct Buf {
+    ptr: *const u8,
+    len: usize,
+}
+
+impl Buf {
+    pub fn as_slice(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.ptr, self.len) }
+    }
+}
+
+pub fn deref_bounded<'a, T>(_owner: &'a [T], p: *const T) -> &'a T {
+    unsafe { &*p }
+}
+
+// `'b` is bounded by `'a`, which is tied to the input.
+pub fn shorten<'a: 'b, 'b>(s: &'a str) -> &'b str {
+    unsafe { std::mem::transmute::<&'a str, &'b str>(s) }
+}
+
+pub fn get<'a>(v: &'a [u8], i: usize) -> &'a u8 {
+    unsafe { &*v.as_ptr().add(i % v.len()) }
+}
+
+pub fn name() -> &'static str {
+    "rapx"
+}
+
+pub fn to_bits(x: f32) -> u32 {
+    unsafe { std::mem::transmute(x) }
+}
+
+// The caller of an unsafe function is responsible for the lifetime.
+pub unsafe fn deref_unchecked<'a, T>(p: *const T) -> &'a T {
+    unsafe { &*p }
+}
+
+fn private_deref<'a>(p: *const u8) -> &'a u8 {
+    unsafe { &*p }
+}
+
+fn main() {
+    let data = vec![1u8, 2, 3];
+    let buf = Buf {
+        ptr: data.as_ptr(),
+        len: data.len(),
+    };
+    println!(
+        "{:?} {} {} {} {} {} {} {}",
+        buf.as_slice(),
+        deref_bounded(&data, &data[0]),
+        shorten("x"),
+        get(&data, 1),
+        name(),
+        to_bits(1.0),
+        unsafe { deref_unchecked(&data[0]) },
+        private_deref(&data[2]),
+    );
+}
@@ -0,0 +1,40 @@
+pub struct Buf {
+    ptr: *const u8,
+    len: usize,
+}
+
+impl Buf {
+    // The slice outlives `self` if the caller chooses so.
+    pub fn as_slice<'a>(&self) -> &'a [u8] {
+        unsafe { std::slice::from_raw_parts(self.ptr, self.len) }
+    }
+}
+
+pub fn deref_unbounded<'a, T>(p: *const T) -> &'a T {
+    unsafe { &*p }
+}
+
+pub fn extend<'a, 'b>(s: &'a str) -> &'b str {
+    unsafe { std::mem::transmute::<&'a str, &'b str>(s) }
+}
+
+pub fn leak_static(p: *mut String) -> &'static mut String {
+    unsafe { &mut *p }
+}
+
+fn main() {
+    let data = vec![1u8, 2, 3];
+    let buf = Buf {
+        ptr: data.as_ptr(),
+        len: data.len(),
+    };
+    let mut s = String::from("rapx");
+    let r = leak_static(&mut s);
+    println!(
+        "{:?} {} {} {}",
+        buf.as_slice(),
+        deref_unbounded(&data[0]),
+        extend("x"),
+        r
+    );
+}
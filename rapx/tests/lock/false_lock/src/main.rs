@@ -0,0 +1,80 @@
+use std::sync::{Arc, Mutex, RwLock};
+
+pub struct Account {
+    balance: Mutex<i64>,
+    log: Mutex<Vec<String>>,
+}
+
+impl Account {
+    // The temporary guards are dropped at the end of the statements.
+    pub fn temporaries(&self) -> i64 {
+        let first = *self.balance.lock().unwrap();
+        let second = *self.balance.lock().unwrap();
+        first + second
+    }
+
+    pub fn explicit_drop(&self) {
+        let balance = self.balance.lock().unwrap();
+        drop(balance);
+        let _balance = self.balance.lock().unwrap();
+    }
+
+    fn record(&self, msg: &str) {
+        self.log.lock().unwrap().push(msg.to_string());
+    }
+
+    // `balance` and then `log` in both functions.
+    pub fn deposit(&self, n: i64) {
+        let mut balance = self.balance.lock().unwrap();
+        *balance += n;
+        self.record("deposit");
+    }
+
+    pub fn withdraw(&self, n: i64) {
+        let mut balance = self.balance.lock().unwrap();
+        *balance -= n;
+        let mut log = self.log.lock().unwrap();
+        log.push(format!("withdraw {}", n));
+    }
+}
+
+// The locks of two accounts are different.
+pub fn transfer(from: &Account, to: &Account, n: i64) {
+    let mut a = from.balance.lock().unwrap();
+    let mut b = to.balance.lock().unwrap();
+    *a -= n;
+    *b += n;
+}
+
+pub fn read_twice(lock: &RwLock<i32>) -> i32 {
+    let a = lock.read().unwrap();
+    let b = lock.read().unwrap();
+    *a + *b
+}
+
+async fn ready() {}
+
+pub async fn release_before_await(m: Arc<Mutex<i32>>) {
+    {
+        let mut guard = m.lock().unwrap();
+        *guard += 1;
+    }
+    ready().await;
+}
+
+fn main() {
+    let account = Account {
+        balance: Mutex::new(0),
+        log: Mutex::new(Vec::new()),
+    };
+    let other = Account {
+        balance: Mutex::new(0),
+        log: Mutex::new(Vec::new()),
+    };
+    account.deposit(1);
+    account.withdraw(1);
+    account.explicit_drop();
+    transfer(&account, &other, 1);
+    println!("{} {}", account.temporaries(), read_twice(&RwLock::new(0)));
+    let _future = release_before_await(Arc::new(Mutex::new(0)));
+}
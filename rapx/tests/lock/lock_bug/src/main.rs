@@ -0,0 +1,66 @@
+use std::sync::{Arc, Mutex, RwLock};
+
+pub struct Account {
+    balance: Mutex<i64>,
+    log: Mutex<Vec<String>>,
+}
+
+impl Account {
+    // The first guard lives until the end of the function.
+    pub fn double_lock(&self) -> i64 {
+        let first = self.balance.lock().unwrap();
+        let second = self.balance.lock().unwrap();
+        *first + *second
+    }
+
+    fn record(&self, msg: &str) {
+        self.log.lock().unwrap().push(msg.to_string());
+    }
+
+    // `record` locks `log` again.
+    pub fn log_twice(&self) {
+        let _log = self.log.lock().unwrap();
+        self.record("twice");
+    }
+
+    // `balance` and then `log`.
+    pub fn deposit(&self, n: i64) {
+        let mut balance = self.balance.lock().unwrap();
+        *balance += n;
+        let mut log = self.log.lock().unwrap();
+        log.push(format!("deposit {}", n));
+    }
+
+    // `log` and then `balance`.
+    pub fn audit(&self) -> usize {
+        let log = self.log.lock().unwrap();
+        let balance = self.balance.lock().unwrap();
+        log.len() + *balance as usize
+    }
+}
+
+pub fn upgrade(lock: &RwLock<i32>) {
+    let r = lock.read().unwrap();
+    let mut w = lock.write().unwrap();
+    *w += *r;
+}
+
+async fn ready() {}
+
+pub async fn hold_across_await(m: Arc<Mutex<i32>>) {
+    let mut guard = m.lock().unwrap();
+    ready().await;
+    *guard += 1;
+}
+
+fn main() {
+    let account = Account {
+        balance: Mutex::new(0),
+        log: Mutex::new(Vec::new()),
+    };
+    account.deposit(1);
+    println!("{} {}", account.audit(), account.double_lock());
+    account.log_twice();
+    upgrade(&RwLock::new(0));
+    let _future = hold_across_await(Arc::new(Mutex::new(0)));
+}
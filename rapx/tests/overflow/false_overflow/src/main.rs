@@ -0,0 +1,46 @@
+fn add_in_range(flag: bool) -> u8 {
+    let x: u8 = if flag { 100 } else { 200 };
+    x + 50
+}
+
+fn widening_cast(x: u8) -> u64 {
+    x as u64
+}
+
+fn fitting_cast(flag: bool) -> u8 {
+    let x: u32 = if flag { 10 } else { 255 };
+    x as u8
+}
+
+fn guarded_index(v: &[u8], flag: bool) -> u8 {
+    let i: usize = if flag { 0 } else { 3 };
+    if i > 0 { v[i - 1] } else { 0 }
+}
+
+fn unknown_add(a: u32, b: u32) -> u32 {
+    a + b
+}
+
+fn wrapping(flag: bool) -> u8 {
+    let x: u8 = if flag { 100 } else { 250 };
+    x.wrapping_add(10)
+}
+
+fn abs_diff(a: u32, b: u32) -> u32 {
+    if a > b { a - b } else { b - a }
+}
+
+fn main() {
+    let flag = std::env::args().count() > 1;
+    let v = [1, 2, 3, 4];
+    println!(
+        "{} {} {} {} {} {} {}",
+        add_in_range(flag),
+        widening_cast(v[0]),
+        fitting_cast(flag),
+        guarded_index(&v, flag),
+        unknown_add(v[1] as u32, v[2] as u32),
+        wrapping(flag),
+        abs_diff(1, 2)
+    );
+}
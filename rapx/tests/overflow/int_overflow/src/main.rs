@@ -0,0 +1,45 @@
+fn add_overflow(flag: bool) -> u8 {
+    let x: u8 = if flag { 100 } else { 250 };
+    x + 10
+}
+
+fn mul_overflow(flag: bool) -> i32 {
+    let x: i32 = if flag { 1 } else { 100_000 };
+    x * 100_000
+}
+
+fn shl_overflow(flag: bool) -> u32 {
+    let n: u32 = if flag { 4 } else { 40 };
+    1 << n
+}
+
+fn truncating_cast(flag: bool) -> u8 {
+    let x: u32 = if flag { 10 } else { 300 };
+    x as u8
+}
+
+fn sign_cast(flag: bool) -> usize {
+    let x: i64 = if flag { 10 } else { -1 };
+    x as usize
+}
+
+fn underflow_index(v: &[u8], flag: bool) -> u8 {
+    let i: usize = if flag { 0 } else { 3 };
+    v[i - 1]
+}
+
+fn underflow_len(flag: bool) -> Vec<u8> {
+    let n: usize = if flag { 2 } else { 8 };
+    Vec::with_capacity(n - 4)
+}
+
+fn main() {
+    let flag = std::env::args().count() > 1;
+    add_overflow(flag);
+    mul_overflow(flag);
+    shl_overflow(flag);
+    truncating_cast(flag);
+    sign_cast(flag);
+    underflow_index(&[1, 2, 3], flag);
+    underflow_len(flag);
+}
@@ -0,0 +1,51 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+
+pub struct Record {
+    id: u32,
+    name: String,
+    tags: Option<Vec<u8>>,
+}
+
+pub enum Shape {
+    Circle(f64),
+    Path(Vec<(i32, i32)>),
+}
+
+pub struct Wrapper<T> {
+    inner: T,
+    count: usize,
+}
+
+pub struct List {
+    value: i32,
+    next: Option<Box<List>>,
+}
+
+pub fn tuple(_pair: (i32, String)) {}
+
+pub fn array(_names: [String; 2], _ids: [u8; 4]) {}
+
+pub fn record(_record: Record, _shape: Shape) {}
+
+pub fn external(_map: HashMap<u32, i32>, _opt: Option<i32>, _opt_heap: Option<String>) {}
+
+pub fn generic<T>(_wrapper: Wrapper<T>, _concrete: Wrapper<Vec<i32>>) {}
+
+pub fn dynamic(_obj: Box<dyn Debug>, _r: &dyn Debug) {}
+
+pub fn list(_list: List) {}
+
+pub fn call<F: FnOnce() -> usize>(f: F) -> usize {
+    f()
+}
+
+fn main() {
+    let name = String::from("rapx");
+    let id = 1;
+    // The closure takes itself by value, as it moves `name` out.
+    call(move || {
+        let name = name;
+        name.len() + id
+    });
+}
@@ -0,0 +1,71 @@
+use std::ptr;
+
+// The length is set only after all the clones are written.
+fn extend_from<T: Clone>(v: &mut Vec<T>, items: &[T]) {
+    let len = v.len();
+    v.reserve(items.len());
+    unsafe {
+        let p = v.as_mut_ptr().add(len);
+        for (i, item) in items.iter().enumerate() {
+            ptr::write(p.add(i), item.clone());
+        }
+        v.set_len(len + items.len());
+    }
+}
+
+// Each element is written before the length covers it.
+fn push_with<T, F: FnMut() -> T>(v: &mut Vec<T>, n: usize, mut f: F) {
+    let len = v.len();
+    v.reserve(n);
+    for i in 0..n {
+        unsafe {
+            ptr::write(v.as_mut_ptr().add(len + i), f());
+            v.set_len(len + i + 1);
+        }
+    }
+}
+
+// The elements are leaked rather than dropped twice if `f` panics.
+fn map_in_place<T, F: FnMut(T) -> T>(v: &mut Vec<T>, mut f: F) {
+    let len = v.len();
+    unsafe {
+        v.set_len(0);
+        let p = v.as_mut_ptr();
+        for i in 0..len {
+            let x = ptr::read(p.add(i));
+            let y = f(x);
+            ptr::write(p.add(i), y);
+        }
+        v.set_len(len);
+    }
+}
+
+// The duplicate is written back before the closure is called.
+fn swap_then_call<T, F: Fn()>(a: &mut T, b: &mut T, f: F) {
+    unsafe {
+        let tmp = ptr::read(a);
+        ptr::copy_nonoverlapping(b, a, 1);
+        ptr::write(b, tmp);
+    }
+    f();
+}
+
+// The values without destructors cannot be dropped twice.
+fn map_copy<T: Copy, F: Fn(T) -> T>(dest: &mut T, f: F) {
+    unsafe {
+        let old = ptr::read(dest);
+        ptr::write(dest, f(old));
+    }
+}
+
+fn main() {
+    let mut v = vec![String::from("a")];
+    extend_from(&mut v, &[String::from("b")]);
+    map_in_place(&mut v, |s| s + "!");
+    push_with(&mut v, 2, || String::from("c"));
+    let (mut a, mut b) = (String::from("a"), String::from("b"));
+    swap_then_call(&mut a, &mut b, || {});
+    let mut n = 1;
+    map_copy(&mut n, |n| n + 1);
+    println!("{:?} {} {} {}", v, a, b, n);
+}
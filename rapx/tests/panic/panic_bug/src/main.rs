@@ -0,0 +1,42 @@
+use std::ptr;
+
+// The length covers the clones not yet written, which are dropped if `T::clone` panics.
+fn extend_from<T: Clone>(v: &mut Vec<T>, items: &[T]) {
+    let len = v.len();
+    v.reserve(items.len());
+    unsafe {
+        v.set_len(len + items.len());
+        let p = v.as_mut_ptr().add(len);
+        for (i, item) in items.iter().enumerate() {
+            ptr::write(p.add(i), item.clone());
+        }
+    }
+}
+
+// The element read out is dropped twice if `f` panics.
+fn map_in_place<T, F: FnMut(T) -> T>(v: &mut [T], mut f: F) {
+    let p = v.as_mut_ptr();
+    for i in 0..v.len() {
+        unsafe {
+            let x = ptr::read(p.add(i));
+            let y = f(x);
+            ptr::write(p.add(i), y);
+        }
+    }
+}
+
+fn replace_with<T>(dest: &mut T, f: &dyn Fn(T) -> T) {
+    unsafe {
+        let old = ptr::read(dest);
+        ptr::write(dest, f(old));
+    }
+}
+
+fn main() {
+    let mut v = vec![String::from("a")];
+    extend_from(&mut v, &[String::from("b")]);
+    map_in_place(&mut v, |s| s + "!");
+    let mut s = String::new();
+    replace_with(&mut s, &|s| s + "?");
+    println!("{:?} {}", v, s);
+}
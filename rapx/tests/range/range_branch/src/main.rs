@@ -0,0 +1,50 @@
+// The variable is narrowed by the comparison with a constant in both branches.
+fn gt(x: i32) -> i32 {
+    if x > 10 { x } else { -x }
+}
+
+fn ge(x: i32) -> i32 {
+    if x >= 10 { x } else { -x }
+}
+
+// The constant in the left is compared as if it were in the right, i.e., `x > 10`.
+fn lt_reversed(x: i32) -> i32 {
+    if 10 < x { x } else { -x }
+}
+
+fn eq(x: i32) -> i32 {
+    if x == 7 { x * 2 } else { 0 }
+}
+
+// The variables compared with each other are narrowed by the ranges of both.
+fn abs_diff(a: u32, b: u32) -> u32 {
+    if a > b { a - b } else { b - a }
+}
+
+// The operations of a constant first operand are left unconstrained.
+fn shl(n: u32) -> u32 {
+    1 << n
+}
+
+// The ranges of a callee whose return value is not modeled are kept.
+fn count() -> u8 {
+    let mut i: u32 = 0;
+    while i < 5 {
+        i += 1;
+    }
+    i as u8
+}
+
+fn main() {
+    let n = std::env::args().count() as i32;
+    println!(
+        "{} {} {} {} {} {} {}",
+        gt(n),
+        ge(n),
+        lt_reversed(n),
+        eq(n),
+        abs_diff(1, 2),
+        shl(n as u32),
+        count()
+    );
+}
@@ -0,0 +1,51 @@
+struct Counter;
+
+impl Counter {
+    // The ranges of the methods are analyzed as those of the functions.
+    fn count(&self) -> u32 {
+        let mut i = 0;
+        while i < 10 {
+            i += 1;
+        }
+        i
+    }
+}
+
+// The values above `i64::MAX` are modeled in the `u64` domain.
+fn big() -> u64 {
+    let mut x: u64 = 10_000_000_000_000_000_000;
+    while x < 10_000_000_000_000_000_100 {
+        x += 1;
+    }
+    x
+}
+
+// The values above `u64::MAX` are modeled in the `u128` domain.
+fn huge() -> u128 {
+    let mut x: u128 = 100_000_000_000_000_000_000;
+    while x < 100_000_000_000_000_000_005 {
+        x += 1;
+    }
+    x
+}
+
+// The result wraps around at the bounds of the `u8` domain.
+fn wrap(x: u8) -> u8 {
+    x.wrapping_add(10)
+}
+
+fn main() {
+    let counter = Counter;
+    counter.count();
+    big();
+    huge();
+    wrap(250);
+    let closure = || {
+        let mut j: i8 = -5;
+        while j < 5 {
+            j += 1;
+        }
+        j
+    };
+    closure();
+}
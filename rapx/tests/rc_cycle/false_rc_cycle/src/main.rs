@@ -0,0 +1,57 @@
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+
+pub struct Node {
+    value: i32,
+    next: Option<Rc<RefCell<Node>>>,
+}
+
+pub struct Parent {
+    children: Vec<Rc<RefCell<Child>>>,
+}
+
+pub struct Child {
+    parent: Option<Weak<RefCell<Parent>>>,
+}
+
+pub struct Leaf {
+    value: i32,
+}
+
+// The nodes form a list, not a cycle.
+pub fn list() -> i32 {
+    let a = Rc::new(RefCell::new(Node {
+        value: 1,
+        next: None,
+    }));
+    let b = Rc::new(RefCell::new(Node {
+        value: 2,
+        next: None,
+    }));
+    a.borrow_mut().next = Some(b.clone());
+    a.borrow().value + b.borrow().value
+}
+
+// The child holds its parent weakly.
+pub fn parent_child() {
+    let parent = Rc::new(RefCell::new(Parent {
+        children: Vec::new(),
+    }));
+    let child = Rc::new(RefCell::new(Child { parent: None }));
+    parent.borrow_mut().children.push(child.clone());
+    child.borrow_mut().parent = Some(Rc::downgrade(&parent));
+}
+
+// The leaf cannot hold any `Rc`.
+pub fn shared_leaf() -> i32 {
+    let leaf = Rc::new(Leaf { value: 1 });
+    let other = Rc::clone(&leaf);
+    let leaves = vec![leaf, other];
+    leaves.iter().map(|leaf| leaf.value).sum()
+}
+
+fn main() {
+    list();
+    parent_child();
+    shared_leaf();
+}
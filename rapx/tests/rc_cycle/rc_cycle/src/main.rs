@@ -0,0 +1,84 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+
+pub struct Node {
+    value: i32,
+    next: Option<Rc<RefCell<Node>>>,
+}
+
+pub struct Parent {
+    children: Vec<Rc<RefCell<Child>>>,
+}
+
+pub struct Child {
+    parent: Option<Rc<RefCell<Parent>>>,
+}
+
+pub struct Observer {
+    callback: Option<Box<dyn Fn() -> i32>>,
+    count: i32,
+}
+
+pub struct Shared {
+    peer: Mutex<Option<Arc<Shared>>>,
+}
+
+// The node points to itself.
+pub fn self_loop() {
+    let node = Rc::new(RefCell::new(Node {
+        value: 1,
+        next: None,
+    }));
+    node.borrow_mut().next = Some(Rc::clone(&node));
+}
+
+// Two nodes point to each other.
+pub fn two_nodes() -> i32 {
+    let a = Rc::new(RefCell::new(Node {
+        value: 1,
+        next: None,
+    }));
+    let b = Rc::new(RefCell::new(Node {
+        value: 2,
+        next: Some(a.clone()),
+    }));
+    a.borrow_mut().next = Some(b.clone());
+    a.borrow().value + b.borrow().value
+}
+
+// The child holds its parent strongly.
+pub fn parent_child() {
+    let parent = Rc::new(RefCell::new(Parent {
+        children: Vec::new(),
+    }));
+    let child = Rc::new(RefCell::new(Child { parent: None }));
+    parent.borrow_mut().children.push(child.clone());
+    child.borrow_mut().parent = Some(parent.clone());
+}
+
+// The callback captures the observer it is stored in.
+pub fn observer() {
+    let observer = Rc::new(RefCell::new(Observer {
+        callback: None,
+        count: 0,
+    }));
+    let this = Rc::clone(&observer);
+    observer.borrow_mut().callback = Some(Box::new(move || this.borrow().count));
+}
+
+// The same with `Arc`.
+pub fn arc_loop() {
+    let shared = Arc::new(Shared {
+        peer: Mutex::new(None),
+    });
+    *shared.peer.lock().unwrap() = Some(Arc::clone(&shared));
+}
+
+fn main() {
+    self_loop();
+    two_nodes();
+    parent_child();
+    observer();
+    arc_loop();
+}
@@ -0,0 +1,91 @@
+use std::cell::UnsafeCell;
+use std::marker::PhantomData;
+use std::ptr::NonNull;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+pub struct Wrapper<T> {
+    value: T,
+}
+
+unsafe impl<T: Send> Send for Wrapper<T> {}
+
+pub struct Lock<T> {
+    locked: AtomicBool,
+    data: UnsafeCell<T>,
+}
+
+unsafe impl<T: Send> Sync for Lock<T> {}
+
+impl<T> Lock<T> {
+    pub fn new(data: T) -> Self {
+        Lock {
+            locked: AtomicBool::new(false),
+            data: UnsafeCell::new(data),
+        }
+    }
+
+    pub fn with<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        while self.locked.swap(true, Ordering::Acquire) {}
+        let result = f(unsafe { &mut *self.data.get() });
+        self.locked.store(false, Ordering::Release);
+        result
+    }
+}
+
+pub struct RawBox<T> {
+    ptr: NonNull<T>,
+}
+
+unsafe impl<T: Send> Send for RawBox<T> {}
+unsafe impl<T: Sync> Sync for RawBox<T> {}
+
+impl<T> RawBox<T> {
+    pub fn new(value: T) -> Self {
+        RawBox {
+            ptr: NonNull::from(Box::leak(Box::new(value))),
+        }
+    }
+
+    pub fn get(&self) -> &T {
+        unsafe { self.ptr.as_ref() }
+    }
+}
+
+// No value of `T` is ever held.
+pub struct Token<T> {
+    _marker: PhantomData<fn() -> T>,
+}
+
+unsafe impl<T> Send for Token<T> {}
+unsafe impl<T> Sync for Token<T> {}
+
+// The bound is implied by the supertrait.
+pub trait Job: Send {}
+
+pub struct Worker<J> {
+    job: J,
+}
+
+unsafe impl<J: Job> Send for Worker<J> {}
+
+pub struct Counter {
+    count: *mut u64,
+}
+
+unsafe impl Send for Counter {}
+
+fn main() {
+    let w = Wrapper { value: 1 };
+    let lock = Lock::new(w.value);
+    let b = RawBox::new(lock.with(|n| *n));
+    let _token: Token<u8> = Token {
+        _marker: PhantomData,
+    };
+    struct Noop;
+    impl Job for Noop {}
+    let worker = Worker { job: Noop };
+    let _ = worker.job;
+    let mut n = 0;
+    let counter = Counter { count: &mut n };
+    println!("{} {:?}", b.get(), counter.count);
+}
@@ -0,0 +1,67 @@
+use std::cell::UnsafeCell;
+use std::ptr::NonNull;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// `Wrapper<Rc<_>>` could be sent to another thread.
+pub struct Wrapper<T> {
+    value: T,
+}
+
+unsafe impl<T> Send for Wrapper<T> {}
+
+impl<T> Wrapper<T> {
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+// A lock moves `T` across threads, so `T: Send` is required.
+pub struct Lock<T> {
+    locked: AtomicBool,
+    data: UnsafeCell<T>,
+}
+
+unsafe impl<T> Sync for Lock<T> {}
+
+impl<T> Lock<T> {
+    pub fn new(data: T) -> Self {
+        Lock {
+            locked: AtomicBool::new(false),
+            data: UnsafeCell::new(data),
+        }
+    }
+
+    pub fn with<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        while self.locked.swap(true, Ordering::Acquire) {}
+        let result = f(unsafe { &mut *self.data.get() });
+        self.locked.store(false, Ordering::Release);
+        result
+    }
+}
+
+// `T` is moved in by `new`, and `&T` is handed out by `get`.
+pub struct RawBox<T> {
+    ptr: NonNull<T>,
+}
+
+unsafe impl<T> Send for RawBox<T> {}
+unsafe impl<T> Sync for RawBox<T> {}
+
+impl<T> RawBox<T> {
+    pub fn new(value: T) -> Self {
+        RawBox {
+            ptr: NonNull::from(Box::leak(Box::new(value))),
+        }
+    }
+
+    pub fn get(&self) -> &T {
+        unsafe { self.ptr.as_ref() }
+    }
+}
+
+fn main() {
+    let w = Wrapper { value: 1 };
+    let lock = Lock::new(w.into_inner());
+    let b = RawBox::new(lock.with(|n| *n));
+    println!("{}", b.get());
+}
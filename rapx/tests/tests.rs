@@ -4,12 +4,16 @@ use std::process::Command;
 
 #[inline(always)]
 fn running_tests_with_arg(dir: &str, arg: &str) -> String {
+    running_tests_with_args(dir, &[arg])
+}
+
+fn running_tests_with_args(dir: &str, args: &[&str]) -> String {
     let raw_path = "./tests/".to_owned() + dir;
     let project_path = Path::new(&raw_path);
 
     let output = Command::new("cargo")
         .arg("rapx")
-        .arg(arg)
+        .args(args)
         .current_dir(project_path)
         .output()
         .expect("Failed to execute cargo rapx");
@@ -51,6 +55,47 @@ fn test_dp_lengthy() {
     );
 }
 
+#[test]
+fn test_df_allow() {
+    let output = running_tests_with_arg("uaf/df_allow", "-F");
+    assert_eq!(
+        output.contains("Double free detected in function \"reported\""),
+        true
+    );
+    assert_eq!(output.contains("in function \"allowed\""), false);
+    assert_eq!(output.contains("in function \"allowed_by_config\""), false);
+}
+
+#[test]
+fn test_df_config() {
+    let output = running_tests_with_arg("uaf/df_config", "--");
+    assert_eq!(
+        output.contains("Double free detected in function \"reported\""),
+        true
+    );
+    assert_eq!(output.contains("in function \"not_reported\""), false);
+    assert_eq!(output.contains("code inefficiencies"), false);
+}
+
+#[test]
+fn test_df_min_jobs() {
+    let output = running_tests_with_args("uaf/df_min", &["-F", "-jobs=4"]);
+    assert_eq!(
+        output.contains("Double free detected in function \"main\""),
+        true
+    );
+}
+
+#[test]
+fn test_df_baseline() {
+    let output =
+        running_tests_with_args("uaf/df_unwinding", &["-F", "-write-baseline=baseline.json"]);
+    assert_eq!(output.contains("to baseline"), true);
+    let output = running_tests_with_args("uaf/df_unwinding", &["-F", "-baseline=baseline.json"]);
+    let _ = std::fs::remove_file("./tests/uaf/df_unwinding/baseline.json");
+    assert_eq!(output.contains("Double free detected"), false);
+}
+
 #[test]
 fn test_uaf_drop() {
     let output = running_tests_with_arg("uaf/uaf_drop", "-F");
@@ -78,6 +123,15 @@ fn test_uaf_drop_in_place() {
     );
 }
 
+#[test]
+fn test_uaf_model() {
+    let output = running_tests_with_arg("uaf/uaf_model", "-F");
+    assert_eq!(
+        output.contains("Use-after-free detected in function \"main\""),
+        true
+    );
+}
+
 #[test]
 fn test_uaf_lifetime() {
     let output = running_tests_with_arg("uaf/uaf_lifetime", "-F");
@@ -105,6 +159,50 @@ fn test_uaf_swithint() {
     );
 }
 
+#[test]
+fn test_df_budget() {
+    // The loop of `branchy` is over the budget, while the bugs elsewhere are still reported.
+    let output = running_tests_with_arg("uaf/df_budget", "-F");
+    assert_eq!(
+        output.contains("Double free detected in function \"reported\""),
+        true
+    );
+    assert_eq!(output.contains("functions is incomplete"), true);
+    assert_eq!(
+        output.contains("uaf of df_budget::branchy: a loop with more than 1 paths"),
+        true
+    );
+}
+
+#[test]
+fn test_uaf_cache() {
+    running_tests_with_args("uaf/uaf_drop2", &["-F", "-cache=rapx-cache"]);
+    let output = running_tests_with_args("uaf/uaf_drop2", &["-F", "-cache=rapx-cache"]);
+    let _ = std::fs::remove_dir_all("./tests/uaf/uaf_drop2/rapx-cache");
+    assert_eq!(output.contains("Reuse the cached alias summaries of"), true);
+    assert_eq!(output.contains("0 functions changed"), true);
+    // The bugs of the unchanged functions are reported from the cache.
+    assert_eq!(
+        output.contains("Reuse the cached safedrop summaries of"),
+        true
+    );
+    assert_eq!(
+        output.contains("Use-after-free detected in function \"main\""),
+        true
+    );
+}
+
+#[test]
+fn test_uaf_cross_crate() {
+    // The dependency is only summarized, so its dangling pointer is not reported.
+    let output = running_tests_with_arg("uaf/uaf_cross_crate", "-F");
+    assert_eq!(
+        output.contains("Use-after-free detected in function \"main\""),
+        true
+    );
+    assert_eq!(output.contains("free_buffer"), false);
+}
+
 #[test]
 fn test_false_wrapper() {
     let output = running_tests_with_arg("uaf/false_wrapper", "-F");
@@ -123,6 +221,116 @@ fn test_false_tuple_transitive() {
     assert_eq!(output.contains("detected"), false);
 }
 
+#[test]
+fn test_false_infeasible() {
+    let output = running_tests_with_arg("uaf/false_infeasible", "-F");
+    assert_eq!(output.contains("detected"), false);
+}
+
+#[test]
+fn test_uaf_reassign() {
+    // The second condition holds after `a` is reassigned, so the path is feasible.
+    let output = running_tests_with_arg("uaf/uaf_reassign", "-F");
+    assert_eq!(
+        output.contains("Use-after-free detected in function \"print_if_caught_up\""),
+        true
+    );
+}
+
+#[test]
+fn test_invalid_free() {
+    let output = running_tests_with_arg("uaf/invalid_free", "-F");
+    assert!(output.contains("Invalid free detected in function \"box_from_vec_buffer\""));
+    assert!(output.contains("Invalid free detected in function \"dealloc_with_other_layout\""));
+    assert!(output.contains("Invalid free detected in function \"cstring_from_vec_buffer\""));
+    assert!(output.contains("Invalid free detected in function \"cstring_from_borrowed_ptr\""));
+}
+
+#[test]
+fn test_false_provenance() {
+    let output = running_tests_with_arg("uaf/false_provenance", "-F");
+    assert_eq!(output.contains("detected"), false);
+}
+
+#[test]
+fn test_uninit_read() {
+    let output = running_tests_with_arg("uninit/uninit_read", "-uninit");
+    assert!(
+        output.contains("Uninitialized read detected in function \"assume_init_without_write\"")
+    );
+    assert!(output.contains("Uninitialized read detected in function \"set_len_without_write\""));
+    assert!(output.contains("Uninitialized read detected in function \"partial_field_write\""));
+    assert!(output.contains("Uninitialized read detected in function \"alloc_without_write\""));
+}
+
+#[test]
+fn test_false_uninit() {
+    let output = running_tests_with_arg("uninit/false_uninit", "-uninit");
+    assert_eq!(output.contains("detected"), false);
+}
+
+#[test]
+fn test_panic_safety() {
+    let output = running_tests_with_arg("panic/panic_bug", "-panic");
+    assert!(output.contains("Panic safety bug detected in function \"extend_from\""));
+    assert!(output.contains("Panic safety bug detected in function \"map_in_place\""));
+    assert!(output.contains("Panic safety bug detected in function \"replace_with\""));
+}
+
+#[test]
+fn test_false_panic() {
+    let output = running_tests_with_arg("panic/false_panic", "-panic");
+    assert_eq!(output.contains("detected"), false);
+}
+
+#[test]
+fn test_send_sync_variance() {
+    let output = running_tests_with_arg("send_sync/send_sync_bug", "-sendsync");
+    assert!(output.contains("detected in `unsafe impl Send for Wrapper<T>`"));
+    assert!(output.contains("detected in `unsafe impl Sync for Lock<T>`"));
+    assert!(output.contains("detected in `unsafe impl Send for RawBox<T>`"));
+    assert!(output.contains("detected in `unsafe impl Sync for RawBox<T>`"));
+}
+
+#[test]
+fn test_false_send_sync() {
+    let output = running_tests_with_arg("send_sync/false_send_sync", "-sendsync");
+    assert_eq!(output.contains("detected"), false);
+}
+
+#[test]
+fn test_unbounded_lifetime() {
+    let output = running_tests_with_arg("lifetime/unbounded_lifetime", "-lifetime");
+    assert!(output.contains("Unbounded lifetime detected in function \"as_slice\""));
+    assert!(output.contains("Unbounded lifetime detected in function \"deref_unbounded\""));
+    assert!(output.contains("Unbounded lifetime detected in function \"extend\""));
+    assert!(output.contains("Unbounded lifetime detected in function \"leak_static\""));
+}
+
+#[test]
+fn test_false_lifetime() {
+    let output = running_tests_with_arg("lifetime/false_lifetime", "-lifetime");
+    assert_eq!(output.contains("detected"), false);
+}
+
+#[test]
+fn test_lock() {
+    let output = running_tests_with_arg("lock/lock_bug", "-lock");
+    assert!(output.contains("Double lock detected in function \"double_lock\""));
+    assert!(output.contains("Double lock detected in function \"log_twice\""));
+    assert!(output.contains("Double lock detected in function \"upgrade\""));
+    assert!(
+        output.contains("Lock order inversion detected in functions \"deposit\" and \"audit\"")
+    );
+    assert!(output.contains("Guard held across await detected in function \"hold_across_await\""));
+}
+
+#[test]
+fn test_false_lock() {
+    let output = running_tests_with_arg("lock/false_lock", "-lock");
+    assert_eq!(output.contains("detected"), false);
+}
+
 #[test]
 fn test_false_arc() {
     let output = running_tests_with_arg("uaf/false_arc", "-F");
@@ -200,6 +408,68 @@ fn test_alias_scc() {
     assert_eq!(output.contains("foo\": (0,1)"), true);
 }
 
+#[test]
+fn test_alias_budget() {
+    // The loop of `choose` is over the budget, so that its summary is conservative.
+    let output = running_tests_with_arg("alias/alias_budget", "-alias");
+    let summary = output
+        .lines()
+        .find(|line| line.starts_with("Alias of \"choose\""))
+        .expect("Missing the summary of choose");
+    assert!(
+        summary.contains("(0,1)") && summary.contains("(0,2)"),
+        "{}",
+        output
+    );
+}
+
+#[test]
+fn test_alias_scc_jobs() {
+    // The results do not depend on the number of threads.
+    let aliases = |output: String| -> Vec<String> {
+        output
+            .lines()
+            .filter(|line| line.starts_with("Alias of"))
+            .map(ToOwned::to_owned)
+            .collect()
+    };
+    let sequential = running_tests_with_args("alias/alias_scc", &["-alias", "-jobs=1"]);
+    let parallel = running_tests_with_args("alias/alias_scc", &["-alias", "-jobs=4"]);
+    assert_eq!(parallel.contains("foo\": (0,1)"), true);
+    assert_eq!(aliases(sequential), aliases(parallel));
+}
+
+#[test]
+fn test_alias_andersen() {
+    let output = running_tests_with_args("alias/alias_field", &["-alias", "-alias=andersen"]);
+    assert_eq!(
+        output.contains("\"foo\": (0,1.1), (0,1.0)")
+            || output.contains("\"foo\": (0,1.0), (0,1.1)"),
+        true
+    );
+    let output = running_tests_with_args("alias/not_alias_iter", &["-alias", "-alias=andersen"]);
+    assert_eq!(output.contains("foo\": null"), true);
+}
+
+#[test]
+fn test_uaf_andersen() {
+    let output = running_tests_with_args("uaf/uaf_drop2", &["-F", "-alias=andersen"]);
+    assert_eq!(
+        output.contains("Use-after-free detected in function \"main\""),
+        true
+    );
+}
+
+#[test]
+fn test_alias_point() {
+    let output = running_tests_with_arg("alias/alias_field", "-test");
+    assert_eq!(output.contains("in Some(\"::foo\") at bb0[0]: {}"), true);
+    assert_eq!(
+        output.contains("in Some(\"::foo\") at bb3[1]: {1.0, 1.1"),
+        true
+    );
+}
+
 #[test]
 fn test_alias_switch() {
     let output = running_tests_with_arg("alias/alias_switch", "-alias");
@@ -236,6 +506,18 @@ fn test_leak_orphan() {
     );
 }
 
+#[test]
+fn test_leak_budget() {
+    // The candidates are still reported when the budget runs out before the returns are checked.
+    let output = running_tests_with_arg("leak/leak_budget", "-M");
+    assert!(
+        output.contains("Memory Leak candidates not verified within the budget in function main"),
+        "{}",
+        output
+    );
+    assert!(output.contains("mleak of leak_budget::main: time budget of 0s exhausted"));
+}
+
 #[test]
 fn test_leak_orphan_timeout() {
     let output = running_tests_with_arg("leak/leak_orphan", "-timeout=0");
@@ -251,6 +533,81 @@ fn test_leak_proxy() {
     );
 }
 
+#[test]
+fn test_leak_inter() {
+    let output = running_tests_with_arg("leak/leak_inter", "-M");
+    for name in ["leak_forget", "leak_nested", "leak_loop"] {
+        assert_eq!(
+            output.contains(&format!("Memory Leak detected in function {}", name)),
+            true
+        );
+    }
+}
+
+#[test]
+fn test_false_leak() {
+    let output = running_tests_with_arg("leak/false_leak", "-M");
+    assert_eq!(output.contains("Memory Leak detected"), false);
+}
+
+#[test]
+fn test_rc_cycle() {
+    let output = running_tests_with_arg("rc_cycle/rc_cycle", "-rccycle");
+    for name in [
+        "self_loop",
+        "two_nodes",
+        "parent_child",
+        "observer",
+        "arc_loop",
+    ] {
+        assert_eq!(
+            output.contains(&format!(
+                "Reference cycle detected in function \"{}\"",
+                name
+            )),
+            true
+        );
+    }
+}
+
+#[test]
+fn test_false_rc_cycle() {
+    let output = running_tests_with_arg("rc_cycle/false_rc_cycle", "-rccycle");
+    assert_eq!(output.contains("Reference cycle detected"), false);
+}
+
+#[test]
+fn test_int_overflow() {
+    let output = running_tests_with_arg("overflow/int_overflow", "-overflow");
+    for (kind, name) in [
+        ("Integer overflow", "add_overflow"),
+        ("Integer overflow", "mul_overflow"),
+        ("Integer overflow", "shl_overflow"),
+        ("Truncating cast", "truncating_cast"),
+        ("Truncating cast", "sign_cast"),
+        ("Usize underflow", "underflow_index"),
+        ("Usize underflow", "underflow_len"),
+    ] {
+        assert_eq!(
+            output.contains(&format!("{} detected in function \"{}\"", kind, name)),
+            true
+        );
+    }
+}
+
+#[test]
+fn test_false_overflow() {
+    let output = running_tests_with_arg("overflow/false_overflow", "-overflow");
+    assert_eq!(output.contains("Integer overflow detected"), false);
+    assert_eq!(output.contains("Truncating cast detected"), false);
+    assert_eq!(output.contains("Usize underflow detected"), false);
+    // The constraint graphs are dumped only when debugging the range analysis.
+    assert_eq!(
+        Path::new("./tests/overflow/false_overflow/cg_dot").exists(),
+        false
+    );
+}
+
 #[test]
 fn test_heap_cell() {
     let output = running_tests_with_arg("ownedheap/heap_cell", "-ownedheap");
@@ -306,6 +663,23 @@ fn test_heap_proxy() {
     );
 }
 
+#[test]
+fn test_heap_query() {
+    let output = running_tests_with_arg("ownedheap/heap_query", "-test");
+    assert_eq!(
+        output.contains(
+            "(i32, std::string::String): True { 0 = i32: False, 1 = std::string::String: True }"
+        ) && output.contains("[u8; 4]: False")
+            && output.contains("Circle.0 = f64: False, Path.0 = std::vec::Vec<(i32, i32)>: True")
+            && output.contains("std::option::Option<i32>: False")
+            && output.contains("std::option::Option<std::string::String>: True")
+            && output.contains("Wrapper<T>: Unknown { inner = T: Unknown, count = usize: False }")
+            && output.contains("T = dyn std::fmt::Debug: Unknown")
+            && output.contains("True { name = std::string::String: True, id = usize: False }"),
+        true
+    );
+}
+
 #[test]
 fn test_upg_safe_caller() {
     let output = running_tests_with_arg("upg/safe_caller", "-upg");
@@ -374,6 +748,70 @@ fn test_interprocedual_range_analysis() {
     }
 }
 
+#[test]
+fn test_typed_range_analysis() {
+    let output = running_tests_with_arg("range/range_typed", "-range");
+
+    let expected_ranges = vec![
+        // u64 above i64::MAX
+        "_12 => Regular [10000000000000000001, 10000000000000000100]",
+        // u128 above u64::MAX
+        "_12 => Regular [100000000000000000001, 100000000000000000005]",
+        // 250u8.wrapping_add(10)
+        "_6 => Regular [4, 4]",
+        // the closure
+        "_6 => Regular [-5, 5]",
+        // the method
+        "_13 => Regular [1, 10]",
+    ];
+
+    for expected in expected_ranges {
+        assert!(
+            output.contains(expected),
+            "Missing expected range: '{}'\nFull output:\n{}",
+            expected,
+            output
+        );
+    }
+}
+
+#[test]
+fn test_branch_range_analysis() {
+    let output = running_tests_with_arg("range/range_branch", "-range");
+
+    let expected_ranges = vec![
+        // the false branch of `x > 10`
+        ("gt", "_4 => Regular [Min, 10]"),
+        // the false branch of `x >= 10`
+        ("ge", "_4 => Regular [Min, 9]"),
+        // the false branch of `10 < x`
+        ("lt_reversed", "_4 => Regular [Min, 10]"),
+        // the true branch of `x == 7`, and `x * 2` in it
+        ("eq", "_4 => Regular [7, 7]"),
+        ("eq", "_5 => Regular [14, 14]"),
+        // `b - a` in the false branch of `a > b`, with `a` in [1, 1] and `b` in [2, 2]
+        ("abs_diff", "_9 => Regular [2, 2]"),
+        ("abs_diff", "_11 => Regular [1, 1]"),
+        // the callee returning a cast
+        ("count", "_10 => Regular [5, 5]"),
+    ];
+
+    for (function, expected) in expected_ranges {
+        let ranges = output
+            .split(&format!("Function: \"{}\" =>", function))
+            .nth(1)
+            .and_then(|rest| rest.split("Function: ").next())
+            .unwrap_or_else(|| panic!("Missing function '{}'\nFull output:\n{}", function, output));
+        assert!(
+            ranges.contains(expected),
+            "Missing expected range of {}: '{}'\nFull output:\n{}",
+            function,
+            expected,
+            output
+        );
+    }
+}
+
 #[test]
 fn test_callgraph_dynamic_dispatch() {
     let output = running_tests_with_arg("callgraph/dynamic", "-callgraph");
@@ -389,3 +827,87 @@ fn test_callgraph_dynamic_dispatch() {
         );
     }
 }
+
+#[test]
+fn test_callgraph_indirect() {
+    let output = running_tests_with_arg("callgraph/indirect", "-callgraph");
+
+    let expected_calls = vec![
+        "apply calls:\n    -> double",
+        "apply_dyn calls:\n    -> main::{closure#0}",
+        "apply_generic calls:\n    -> main::{closure#1}",
+        "-> <Circle as Shape>::area",
+        "-> <Square as Shape>::area",
+    ];
+
+    for expected in expected_calls {
+        assert!(
+            output.contains(expected),
+            "Missing indirect call '{}'\nFull output:\n{}",
+            expected,
+            output
+        );
+    }
+    // Unused is never coerced into `dyn Shape`.
+    let virtual_callees = output
+        .split("  Shape::area calls:")
+        .nth(1)
+        .and_then(|rest| rest.split(" calls:").next())
+        .expect("Missing virtual call of Shape::area");
+    assert!(!virtual_callees.contains("<Unused as Shape>::area"));
+}
+
+#[test]
+fn test_callgraph_slice() {
+    let output = running_tests_with_arg("callgraph/indirect", "-callgraph=apply");
+    let dot_path = Path::new("./tests/callgraph/indirect/callgraph_indirect_bin.dot");
+    let dot = std::fs::read_to_string(dot_path).expect("Failed to read the DOT file");
+    let _ = std::fs::remove_file(dot_path);
+
+    assert!(output.contains("apply calls:\n    -> double"), "{}", output);
+    assert!(output.contains("main calls:\n    -> apply\n"), "{}", output);
+    assert!(!output.contains("total calls:"), "{}", output);
+    assert!(dot.contains("label = \"double\""));
+}
+
+#[test]
+fn test_callgraph_recursion() {
+    let output = running_tests_with_arg("callgraph/recursion", "-callgraph");
+    assert!(output.contains("Recursion: factorial"), "{}", output);
+}
+
+#[test]
+fn test_callgraph_polymorphic_recursion() {
+    let output = running_tests_with_arg("callgraph/poly_recursion", "-callgraph");
+    assert!(output.contains("Recursion: f"), "{}", output);
+    // The bottom-up scheduler of safedrop builds the same call graph.
+    let output = running_tests_with_arg("callgraph/poly_recursion", "-F");
+    assert!(!output.contains("panicked"), "{}", output);
+}
+
+// ================Output Format Test=====================
+#[test]
+fn test_sarif_output() {
+    let output = running_tests_with_args("uaf/df_min", &["-F", "-format=sarif"]);
+    assert_eq!(output.contains("findings in SARIF format"), true);
+    let sarif_path = Path::new("./tests/uaf/df_min/rapx.sarif");
+    let sarif = std::fs::read_to_string(sarif_path).expect("Failed to read the SARIF file");
+    let _ = std::fs::remove_file(sarif_path);
+    assert_eq!(sarif.contains("\"version\": \"2.1.0\""), true);
+    assert_eq!(sarif.contains("\"ruleId\": \"double-free\""), true);
+    assert_eq!(
+        sarif.contains("\"fullyQualifiedName\": \"df_min::main\""),
+        true
+    );
+}
+
+#[test]
+fn test_json_output() {
+    let output = running_tests_with_args("alias/alias_field", &["-alias", "-json"]);
+    assert_eq!(output.contains("Dump alias results to"), true);
+    let json_path = Path::new("./tests/alias/alias_field/alias_alias_field_bin.json");
+    let json = std::fs::read_to_string(json_path).expect("Failed to read the JSON file");
+    let _ = std::fs::remove_file(json_path);
+    assert_eq!(json.contains("\"schema_version\": 1"), true);
+    assert_eq!(json.contains("\"function\": \"alias_field::foo\""), true);
+}
@@ -0,0 +1,31 @@
+#![feature(register_tool)]
+#![register_tool(rapx)]
+
+/*
+ * This is a buggy case: double free, which is suppressed by the tool attribute or rapx.toml.
+ */
+
+#[rapx::allow(uaf)]
+fn allowed() {
+    let mut s = String::from("a tmp string");
+    let ptr = s.as_mut_ptr();
+    let _v = unsafe { Vec::from_raw_parts(ptr, s.len(), s.len()) };
+}
+
+fn allowed_by_config() {
+    let mut s = String::from("a tmp string");
+    let ptr = s.as_mut_ptr();
+    let _v = unsafe { Vec::from_raw_parts(ptr, s.len(), s.len()) };
+}
+
+fn reported() {
+    let mut s = String::from("a tmp string");
+    let ptr = s.as_mut_ptr();
+    let _v = unsafe { Vec::from_raw_parts(ptr, s.len(), s.len()) };
+}
+
+fn main() {
+    allowed();
+    allowed_by_config();
+    reported();
+}
@@ -0,0 +1,29 @@
+/*
+ * This is a buggy case: double free, where the loop of `branchy` has more paths than the
+ * budget in rapx.toml, so that its analysis is incomplete.
+ */
+
+fn reported() {
+    let mut s = String::from("a tmp string");
+    let ptr = s.as_mut_ptr();
+    let _v = unsafe { Vec::from_raw_parts(ptr, s.len(), s.len()) };
+}
+
+fn branchy(n: usize) -> Vec<String> {
+    let mut v = Vec::new();
+    let mut i = 0;
+    while i < n {
+        if i % 2 == 0 {
+            v.push(String::from("even"));
+        } else {
+            v.push(String::from("odd"));
+        }
+        i += 1;
+    }
+    v
+}
+
+fn main() {
+    reported();
+    branchy(3);
+}
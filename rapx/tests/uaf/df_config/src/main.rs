@@ -0,0 +1,17 @@
+/*
+ * This is a buggy case: double free, where the analyses and the reported files are
+ * configured by rapx.toml.
+ */
+
+mod excluded;
+
+fn reported() {
+    let mut s = String::from("a tmp string");
+    let ptr = s.as_mut_ptr();
+    let _v = unsafe { Vec::from_raw_parts(ptr, s.len(), s.len()) };
+}
+
+fn main() {
+    reported();
+    excluded::not_reported();
+}
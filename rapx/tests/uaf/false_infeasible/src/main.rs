@@ -0,0 +1,16 @@
+fn print_if_kept(a: usize, b: usize) {
+    let s = String::from("hello");
+    let p = &s as *const String;
+    if a < b {
+        drop(s);
+    }
+    if a >= b {
+        unsafe {
+            println!("{}", *p);
+        }
+    }
+}
+
+fn main() {
+    print_if_kept(1, 2);
+}
@@ -0,0 +1,33 @@
+use std::alloc::{Layout, alloc, dealloc};
+use std::ffi::CString;
+
+fn box_round_trip() {
+    let p = Box::into_raw(Box::new(1u64));
+    let _b = unsafe { Box::from_raw(p) };
+}
+
+fn dealloc_with_same_layout() {
+    unsafe {
+        let p = alloc(Layout::from_size_align(8, 8).unwrap());
+        dealloc(p, Layout::new::<u64>());
+    }
+}
+
+fn cstring_round_trip() {
+    let p = CString::new("hello").unwrap().into_raw();
+    let _s = unsafe { CString::from_raw(p) };
+}
+
+fn vec_round_trip() {
+    let mut v = vec![1u8; 4];
+    let (p, len, cap) = (v.as_mut_ptr(), v.len(), v.capacity());
+    std::mem::forget(v);
+    let _v = unsafe { Vec::from_raw_parts(p, len, cap) };
+}
+
+fn main() {
+    box_round_trip();
+    dealloc_with_same_layout();
+    cstring_round_trip();
+    vec_round_trip();
+}
@@ -0,0 +1,35 @@
+use std::alloc::{Layout, alloc, dealloc};
+use std::ffi::{CString, c_char};
+
+fn box_from_vec_buffer() {
+    let mut v = vec![1u8; 4];
+    let p = v.as_mut_ptr();
+    std::mem::forget(v);
+    let _b = unsafe { Box::from_raw(p) };
+}
+
+fn dealloc_with_other_layout() {
+    unsafe {
+        let p = alloc(Layout::from_size_align(16, 8).unwrap());
+        dealloc(p, Layout::new::<u64>());
+    }
+}
+
+fn cstring_from_vec_buffer() {
+    let mut v = vec![b'a' as c_char, 0];
+    let p = v.as_mut_ptr();
+    std::mem::forget(v);
+    let _s = unsafe { CString::from_raw(p) };
+}
+
+fn cstring_from_borrowed_ptr() {
+    let c = CString::new("borrowed").unwrap();
+    let _s = unsafe { CString::from_raw(c.as_ptr() as *mut _) };
+}
+
+fn main() {
+    box_from_vec_buffer();
+    dealloc_with_other_layout();
+    cstring_from_vec_buffer();
+    cstring_from_borrowed_ptr();
+}
@@ -0,0 +1,10 @@
+/*
+ * This is a buggy case: use-after-free across the crate boundary.
+ * The buffer is freed by the dependency, whose MIR is not available.
+ */
+fn main() {
+    let buf = dep::new_buffer();
+    dep::free_buffer(buf);
+    let v = unsafe { &*buf };
+    println!("{}", v.len());
+}
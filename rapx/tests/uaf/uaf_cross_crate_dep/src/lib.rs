@@ -0,0 +1,7 @@
+pub fn new_buffer() -> *mut Vec<u8> {
+    Box::into_raw(Box::new(vec![1, 2, 3]))
+}
+
+pub fn free_buffer(buf: *mut Vec<u8>) {
+    unsafe { drop(Box::from_raw(buf)) };
+}
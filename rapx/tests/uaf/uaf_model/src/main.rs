@@ -0,0 +1,13 @@
+extern "C" {
+    fn pool_release(v: *mut Vec<i32>);
+}
+
+fn main() {
+    let mut v = vec![1, 2, 3];
+    let p = &mut v as *mut Vec<i32>;
+    unsafe {
+        pool_release(p);
+    }
+    v.push(4);
+    std::mem::forget(v);
+}
@@ -0,0 +1,17 @@
+fn print_if_caught_up(mut a: usize, b: usize) {
+    let s = String::from("hello");
+    let p = &s as *const String;
+    if a < b {
+        drop(s);
+        a = b;
+    }
+    if a >= b {
+        unsafe {
+            println!("{}", *p);
+        }
+    }
+}
+
+fn main() {
+    print_if_caught_up(1, 2);
+}
@@ -0,0 +1,68 @@
+use std::alloc::{Layout, alloc, dealloc};
+use std::mem::MaybeUninit;
+
+struct Pair {
+    a: u32,
+    b: u64,
+}
+
+fn write_then_assume_init() -> u64 {
+    let mut x = MaybeUninit::<u64>::uninit();
+    x.write(1);
+    unsafe { x.assume_init() }
+}
+
+fn fill_then_set_len(n: usize) -> u8 {
+    let mut v: Vec<u8> = Vec::with_capacity(n);
+    let p = v.as_mut_ptr();
+    for i in 0..n {
+        unsafe { p.add(i).write(0) };
+    }
+    unsafe { v.set_len(n) };
+    v[0]
+}
+
+fn copy_then_set_len() -> u8 {
+    let src = [1u8; 4];
+    let mut v: Vec<u8> = Vec::with_capacity(4);
+    unsafe {
+        std::ptr::copy_nonoverlapping(src.as_ptr(), v.as_mut_ptr(), 4);
+        v.set_len(4);
+    }
+    v[3]
+}
+
+fn all_fields_written() -> u64 {
+    let mut p = MaybeUninit::<Pair>::uninit();
+    let ptr = p.as_mut_ptr();
+    unsafe {
+        std::ptr::addr_of_mut!((*ptr).a).write(1);
+        std::ptr::addr_of_mut!((*ptr).b).write(2);
+        let p = p.assume_init();
+        p.a as u64 + p.b
+    }
+}
+
+fn alloc_then_write() -> u32 {
+    unsafe {
+        let layout = Layout::new::<u32>();
+        let p = alloc(layout) as *mut u32;
+        *p = 7;
+        let x = *p;
+        dealloc(p as *mut u8, layout);
+        x
+    }
+}
+
+fn uninit_array() -> [MaybeUninit<u8>; 4] {
+    unsafe { MaybeUninit::<[MaybeUninit<u8>; 4]>::uninit().assume_init() }
+}
+
+fn main() {
+    write_then_assume_init();
+    fill_then_set_len(4);
+    copy_then_set_len();
+    all_fields_written();
+    alloc_then_write();
+    uninit_array();
+}
@@ -0,0 +1,45 @@
+use std::alloc::{Layout, alloc, dealloc};
+use std::mem::MaybeUninit;
+
+struct Pair {
+    a: u32,
+    b: u64,
+}
+
+fn assume_init_without_write() -> u64 {
+    let x = MaybeUninit::<u64>::uninit();
+    unsafe { x.assume_init() }
+}
+
+fn set_len_without_write() -> u8 {
+    let mut v: Vec<u8> = Vec::with_capacity(8);
+    unsafe { v.set_len(8) };
+    v[3]
+}
+
+fn partial_field_write() -> u64 {
+    let mut p = MaybeUninit::<Pair>::uninit();
+    let ptr = p.as_mut_ptr();
+    unsafe {
+        std::ptr::addr_of_mut!((*ptr).a).write(1);
+        let p = p.assume_init();
+        p.a as u64 + p.b
+    }
+}
+
+fn alloc_without_write() -> u32 {
+    unsafe {
+        let layout = Layout::new::<u32>();
+        let p = alloc(layout) as *mut u32;
+        let x = *p;
+        dealloc(p as *mut u8, layout);
+        x
+    }
+}
+
+fn main() {
+    assume_init_without_write();
+    set_len_without_write();
+    partial_field_write();
+    alloc_without_write();
+}